@@ -0,0 +1,80 @@
+/** ===============================================================================
+ * File: ascii_frontend.rs
+ * Author: Scott Stack
+ * Description: Minimal custom frontend. Draws the board as plain ASCII from the snapshot
+ * API and narrates births/kills from the event queue, without touching the environment's
+ * internals. Run with `cargo run --example ascii_frontend`
+ * ===============================================================================*/
+use evolution_sim::prelude::*;
+use std::thread;
+use std::time;
+
+const NUM_FRAMES : usize = 30;          // Number of steps to draw
+const FRAME_DELAY_MS : u64 = 100;       // Pause between frames
+const MAX_EVENT_LINES : usize = 5;      // Max number of events narrated per frame
+const CLEAR_SCREEN_ESCAPE_SEQ : &str = "\x1B[2J\x1B[H";
+
+/// Character a creature is drawn with (it points the way the creature is facing)
+fn get_creature_char(orientation : CreatureOrientation) -> char {
+    return match orientation {
+        CreatureOrientation::Up => '^',
+        CreatureOrientation::Down => 'v',
+        CreatureOrientation::Left => '<',
+        CreatureOrientation::Right => '>',
+    };
+}
+
+/// Draw the board as a block of text
+fn render(snapshot : &EnvSnapshot) -> String {
+    let mut text = String::with_capacity((snapshot.x_size + 3) * (snapshot.y_size + 2));
+    let border = format!("+{}+\n", "-".repeat(snapshot.x_size));
+    text.push_str(&border);
+    for y in 0..snapshot.y_size {
+        text.push('|');
+        for x in 0..snapshot.x_size {
+            text.push(match snapshot.get_cell(x, y) {
+                SnapshotCell::Blank if snapshot.is_beacon_at(x, y) => '@',
+                SnapshotCell::Blank if snapshot.get_decal_at(x, y) == Some(DecalKind::Fight) => 'x',
+                SnapshotCell::Blank => ' ',
+                SnapshotCell::Creature(c_idx) => get_creature_char(snapshot.creatures[c_idx].orientation),
+                SnapshotCell::Food => '.',
+                SnapshotCell::Wall => '#',
+            });
+        }
+        text.push_str("|\n");
+    }
+    text.push_str(&border);
+    return text;
+}
+
+/// Describe an event in a line of text. Returns None for events that aren't worth narrating
+fn describe_event(event : &EnvEvent) -> Option<String> {
+    return match event {
+        EnvEvent::Birth {id, parent_id : Some(parent_id), ..} => Some(format!("creature {} was born to {}", id, parent_id)),
+        EnvEvent::Kill {killer_id, victim_id, position} => Some(format!("creature {} killed {} at ({}, {})", killer_id, victim_id, position.x, position.y)),
+        EnvEvent::Extinction {time_step} => Some(format!("every creature died at step {}", time_step)),
+        _ => None,
+    };
+}
+
+fn main() {
+    let mut params = EnvironmentParams::with_size(48, 20);
+    params.enable_beacon = true;
+    let mut env = EnvironmentV1::new_rand(&params);
+
+    for _ in 0..NUM_FRAMES {
+        env.advance_step();
+
+        let snapshot = env.get_snapshot();
+        print!("{}{}", CLEAR_SCREEN_ESCAPE_SEQ, render(&snapshot));
+        println!("step {}, {} creatures", snapshot.time_step, snapshot.creatures.len());
+        for line in env.events.take_all().iter().filter_map(describe_event).take(MAX_EVENT_LINES) {
+            println!("  {}", line);
+        }
+
+        if env.num_creatures == 0 {
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(FRAME_DELAY_MS));
+    }
+}
@@ -0,0 +1,66 @@
+/** ===============================================================================
+ * File: experiment_sweep.rs
+ * Author: Scott Stack
+ * Description: Sweeps the mutation probability over a few values, running several seeded
+ * simulations for each one, and prints how long the populations survived and how long
+ * their creatures lived. Then scores each value on several objectives and reports which ones are on the
+ * Pareto front (also written to PARETO_CSV_FILE). Run with `cargo run --release --example experiment_sweep`
+ * ===============================================================================*/
+use evolution_sim::prelude::*;
+use evolution_sim::experiments::{ObjectiveScores, ParetoReport};
+
+const MUTATION_PROBS : [f32; 4] = [0.0, 0.01, 0.05, 0.2];  // Values of the mutation probability to try
+const NUM_SEEDS : u64 = 3;                                  // Number of runs per value
+const NUM_STEPS : usize = 500;                              // Max number of steps per run
+const PARETO_CSV_FILE : &str = "experiment_sweep_pareto.csv";  // Where the Pareto report is written
+
+/// Result of one run
+struct RunResult {
+    steps_survived : usize,     // Steps until extinction (or NUM_STEPS if it never happened)
+    final_creatures : usize,    // Number of creatures at the end of the run
+    avg_lifespan : f32,         // Average lifespan of every creature that died (0 if none did)
+    scores : ObjectiveScores,   // Score of the run on each objective
+}
+
+/// Run one seeded simulation to completion
+fn run_once(params : &EnvironmentParams, seed : u64) -> RunResult {
+    let mut env = EnvironmentV1::new_seeded(params, seed);
+    let steps_survived = match env.run_n_steps(NUM_STEPS) {
+        Ok(()) => NUM_STEPS,
+        Err(_) => env.time_step,
+    };
+    return RunResult {
+        steps_survived : steps_survived,
+        final_creatures : env.num_creatures,
+        avg_lifespan : env.stats.summarize_lifetimes().map(|s| s.avg_lifespan).unwrap_or(0.0),
+        scores : ObjectiveScores::from_run(&env),
+    };
+}
+
+fn main() {
+    println!("{:>13} {:>14} {:>15} {:>12}", "mutation prob", "steps survived", "final creatures", "avg lifespan");
+    let mut sweep_scores : Vec<(String, ObjectiveScores)> = Vec::new();
+    for mutation_prob in MUTATION_PROBS {
+        let mut params = EnvironmentParams::with_size(32, 32);
+        params.mutation_prob = mutation_prob;
+
+        let results : Vec<RunResult> = (0..NUM_SEEDS).map(|seed| run_once(&params, seed)).collect();
+        let avg = |get_value : fn(&RunResult) -> f32| results.iter().map(get_value).sum::<f32>() / results.len() as f32;
+        println!("{:>13} {:>14.1} {:>15.1} {:>12.1}", mutation_prob,
+            avg(|r| r.steps_survived as f32), avg(|r| r.final_creatures as f32), avg(|r| r.avg_lifespan));
+
+        let scores : Vec<ObjectiveScores> = results.iter().map(|r| r.scores).collect();
+        sweep_scores.push((format!("mutation_prob={}", mutation_prob), ObjectiveScores::average(&scores)));
+    }
+
+    let report = ParetoReport::new(sweep_scores);
+    println!();
+    println!("{:>22} {:>9} {:>9} {:>13} {:>13} {:>8}", "setting", "longevity", "offspring", "kills avoided", "area explored", "on front");
+    for entry in report.entries.iter() {
+        let s = &entry.scores;
+        println!("{:>22} {:>9.1} {:>9.2} {:>13.2} {:>13.2} {:>8}", entry.label, s.longevity, s.offspring, s.kills_avoided, s.area_explored,
+            if entry.on_front { "*" } else { "" });
+    }
+    #[cfg(feature = "file_io")]
+    report.save_csv(PARETO_CSV_FILE);
+}
@@ -0,0 +1,43 @@
+/** ===============================================================================
+ * File: headless.rs
+ * Author: Scott Stack
+ * Description: Runs a seeded simulation for 10k steps with no display at all, printing
+ * population statistics as it goes. The run restarts from the best creatures if they
+ * go extinct. Run with `cargo run --release --example headless [seed]`
+ * ===============================================================================*/
+use evolution_sim::prelude::*;
+
+const NUM_STEPS : usize = 10_000;       // Number of steps to run for
+const PRINT_INTERVAL : usize = 1_000;   // Number of steps between stats lines
+const DEFAULT_SEED : u64 = 1;           // Seed used if none is given on the command line
+
+fn main() {
+    let seed = std::env::args().nth(1).and_then(|arg| arg.parse::<u64>().ok()).unwrap_or(DEFAULT_SEED);
+
+    let mut params = EnvironmentParams::with_size(40, 40);
+    params.enable_speciation = true;
+    let mut env = EnvironmentV1::new_seeded(&params, seed);
+    let mut run_policy = RunPolicy::new();
+    run_policy.restart_on_extinction = true;
+
+    println!("{:>6} {:>9} {:>5} {:>6} {:>7} {:>8} {:>12}", "step", "creatures", "food", "kills", "species", "restarts", "avg lifespan");
+    for _ in 0..NUM_STEPS {
+        env.advance_step();
+        run_policy.maybe_restart(&mut env);
+
+        if env.time_step % PRINT_INTERVAL == 0 {
+            let avg_lifespan = match env.stats.summarize_lifetimes() {
+                Some(summary) => format!("{:.1}", summary.avg_lifespan),
+                None => String::from("-"),
+            };
+            println!("{:>6} {:>9} {:>5} {:>6} {:>7} {:>8} {:>12}",
+                env.time_step, env.num_creatures, env.num_food, env.num_kills, env.num_species, run_policy.num_restarts, avg_lifespan);
+        }
+    }
+
+    // Show the brain of the oldest creature still alive
+    if let Some(oldest) = env.creatures.iter().max_by_key(|c| c.age) {
+        println!("\nOldest creature: id {}, age {}, generation {}", oldest.id, oldest.age, oldest.generation);
+        println!("{}", oldest.brain.describe());
+    }
+}
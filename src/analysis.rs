@@ -0,0 +1,362 @@
+/** ===============================================================================
+ * File: analysis.rs
+ * Author: Scott Stack
+ * Description: Analysis of creature behavior and genomes. Implements novelty scoring
+ * (how different a creature's behavior is from the rest of the population) and
+ * speciation (clustering creatures by genome distance), plus a spatial index for
+ * counting things near a point on the board and fingerprint glyphs of genomes
+ * ===============================================================================*/
+use crate::creature::*;
+use crate::environment::Fnv1aHasher;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const NOVELTY_NUM_NEIGHBORS : usize = 15;       // Novelty is the mean distance to this many nearest behaviors
+pub const NOVELTY_UPDATE_INTERVAL : usize = 5;      // Novelty scores are recomputed every this many steps
+pub const NOVELTY_ARCHIVE_LEN : usize = 200;        // Max number of behaviors kept in the novelty archive
+pub const NOVELTY_ARCHIVE_MIN_RANK : f32 = 0.9;     // Creatures at or above this novelty rank get archived when they die
+pub const VISIT_GRID_SIZE : usize = 4;              // The board is split into VISIT_GRID_SIZE x VISIT_GRID_SIZE regions for visitation histograms
+pub const SPECIES_HISTORY_LEN : usize = 200;        // Number of past species counts kept for the stats graph
+pub const FINGERPRINT_SIZE : usize = 5;             // Genome fingerprints are FINGERPRINT_SIZE x FINGERPRINT_SIZE glyphs
+
+// Palette used to color creatures by species. Species IDs wrap around the palette so a species always keeps its color
+const SPECIES_PALETTE : [[u8; 3]; 12] = [
+    [230, 25, 75],  // red
+    [60, 180, 75],  // green
+    [255, 225, 25], // yellow
+    [0, 130, 200],  // blue
+    [245, 130, 48], // orange
+    [145, 30, 180], // purple
+    [70, 240, 240], // cyan
+    [240, 50, 230], // magenta
+    [210, 245, 60], // lime
+    [250, 190, 212],// pink
+    [0, 128, 128],  // teal
+    [170, 110, 40], // brown
+];
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Spatial index for counting the spaces of one kind on the board near a point. It's a summed-area table,
+/// so counting the spaces in any square window takes constant time no matter how big the window is
+pub struct GridCounts {
+    sums : Vec<Vec<usize>>,     // sums[x][y] = number of counted spaces with a smaller x and smaller y. Padded with a zero row/column
+    x_size : usize,
+    y_size : usize,
+}
+
+/// Representative genome of a species. New creatures join the first species whose representative
+/// is close enough to them
+#[derive(Clone)]
+pub struct SpeciesRepresentative {
+    pub species_id : usize,     // ID of the species
+    pub brain : Brain,          // Genome that members of the species are compared against
+}
+
+/// Small left-right symmetric glyph made from a hash of a genome, so creatures with the same genome can be told
+/// apart from others at a glance without relying on their evolved color
+#[derive(Copy, Clone, PartialEq)]
+pub struct GenomeFingerprint {
+    pub cells : [[bool; FINGERPRINT_SIZE]; FINGERPRINT_SIZE],   // Filled cells of the glyph, indexed by [row][column]
+    pub color : CreatureColor,                                  // Color the filled cells are drawn in
+}
+
+/// What creatures are grouped by for group stats
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GroupBy {
+    Tribe,      // Lineage ID
+    Species,    // Species ID (only meaningful if speciation is on)
+}
+
+/// Live stats of one tribe or species
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GroupStats {
+    pub id : usize,                 // Lineage or species ID
+    pub num_creatures : usize,      // Number of living members
+    pub mean_energy : f32,          // Average energy of the living members
+    pub num_kills : usize,          // Total kills made by the living members
+}
+
+/// What is used to describe a creature's behavior when comparing it to others
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum NoveltyDescriptor {
+    #[default]
+    ActionDistribution, // Fraction of steps spent on each action
+    Visitation,         // Fraction of steps spent in each region of the board
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+/// Get the behavior descriptor of a creature. Both descriptor types are normalized histograms
+/// so creatures of different ages can be compared
+pub fn get_behavior_descriptor(creature : &CreatureV1, descriptor : NoveltyDescriptor) -> Vec<f32> {
+    let counts : Vec<usize> = match descriptor {
+        NoveltyDescriptor::ActionDistribution => creature.action_counts.to_vec(),
+        NoveltyDescriptor::Visitation => {
+            let mut visits = creature.visit_counts.clone();
+            visits.resize(VISIT_GRID_SIZE * VISIT_GRID_SIZE, 0);
+            visits
+        },
+    };
+
+    let total : usize = counts.iter().sum();
+    if total == 0 {
+        return vec![0.0; counts.len()];
+    }
+    return counts.iter().map(|count| *count as f32 / total as f32).collect();
+}
+
+impl GridCounts {
+    /// Build the index for an `x_size` by `y_size` board. `is_counted(x, y)` says whether the space at (x, y) is counted
+    pub fn new(x_size : usize, y_size : usize, is_counted : impl Fn(usize, usize) -> bool) -> GridCounts {
+        let mut sums = vec![vec![0usize; y_size + 1]; x_size + 1];
+        for x in 0..x_size {
+            for y in 0..y_size {
+                sums[x + 1][y + 1] = is_counted(x, y) as usize + sums[x][y + 1] + sums[x + 1][y] - sums[x][y];
+            }
+        }
+        return GridCounts {
+            sums : sums,
+            x_size : x_size,
+            y_size : y_size,
+        };
+    }
+
+    /// Count the spaces within `radius` of (x, y) (a (2 * radius + 1) square, clipped to the board).
+    /// Also returns the number of board spaces the window covers
+    pub fn count_in_radius(&self, x : usize, y : usize, radius : usize) -> (usize, usize) {
+        let (x_min, x_max) = (x.saturating_sub(radius), (x + radius + 1).min(self.x_size));
+        let (y_min, y_max) = (y.saturating_sub(radius), (y + radius + 1).min(self.y_size));
+        let count = self.sums[x_max][y_max] + self.sums[x_min][y_min] - self.sums[x_min][y_max] - self.sums[x_max][y_min];
+        return (count, (x_max - x_min) * (y_max - y_min));
+    }
+}
+
+/// Get the index of the visitation region that contains (x, y) on a board of the given size
+pub fn get_visit_region(x : usize, y : usize, x_size : usize, y_size : usize) -> usize {
+    let region_x = (x * VISIT_GRID_SIZE / x_size.max(1)).min(VISIT_GRID_SIZE - 1);
+    let region_y = (y * VISIT_GRID_SIZE / y_size.max(1)).min(VISIT_GRID_SIZE - 1);
+    return region_y * VISIT_GRID_SIZE + region_x;
+}
+
+/// Euclidean distance between two behavior descriptors
+fn get_behavior_distance(a : &[f32], b : &[f32]) -> f32 {
+    return a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum::<f32>().sqrt();
+}
+
+/// Novelty of a behavior: the mean distance to its `k` nearest neighbors in `others`.
+/// `skip_idx` can be used to leave the behavior itself out of `others`
+pub fn get_novelty_score(behavior : &[f32], others : &[Vec<f32>], skip_idx : Option<usize>, k : usize) -> f32 {
+    let mut distances : Vec<f32> = others.iter()
+        .enumerate()
+        .filter(|(idx, _)| Some(*idx) != skip_idx)
+        .map(|(_, other)| get_behavior_distance(behavior, other))
+        .collect();
+    if distances.is_empty() || k == 0 {
+        return 0.0;
+    }
+
+    distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let num_neighbors = k.min(distances.len());
+    return distances[..num_neighbors].iter().sum::<f32>() / num_neighbors as f32;
+}
+
+/// Recompute the novelty score and rank of every creature. Creatures are compared against the rest of the
+/// population as well as the archive of past novel behaviors
+pub fn update_novelty_scores(creatures : &mut [CreatureV1], archive : &VecDeque<Vec<f32>>, descriptor : NoveltyDescriptor) {
+    // Everyone is compared against the current population followed by the archive
+    let mut behaviors : Vec<Vec<f32>> = creatures.iter().map(|c| get_behavior_descriptor(c, descriptor)).collect();
+    behaviors.extend(archive.iter().cloned());
+
+    let scores : Vec<f32> = (0..creatures.len())
+        .map(|idx| get_novelty_score(&behaviors[idx], &behaviors, Some(idx), NOVELTY_NUM_NEIGHBORS))
+        .collect();
+
+    // Rank is the fraction of the population that's less novel (0 = least novel, 1 = most novel)
+    let mut order : Vec<usize> = (0..creatures.len()).collect();
+    order.sort_by(|a, b| scores[*a].partial_cmp(&scores[*b]).unwrap_or(std::cmp::Ordering::Equal));
+    let max_rank = (creatures.len().max(2) - 1) as f32;
+    for (rank, idx) in order.iter().enumerate() {
+        creatures[*idx].novelty = scores[*idx];
+        creatures[*idx].novelty_rank = rank as f32 / max_rank;
+    }
+}
+
+/// Get the display color for a species. The same species always gets the same color
+pub fn get_species_color(species_id : usize) -> CreatureColor {
+    return CreatureColor::new_from_vec(SPECIES_PALETTE[species_id % SPECIES_PALETTE.len()]);
+}
+
+/// Get a hash of every weight and bias in a genome. It's the same between runs and platforms
+pub fn get_genome_hash(brain : &Brain) -> u64 {
+    let mut hasher = Fnv1aHasher::new();
+    for val in brain.get_genome() {
+        hasher.write_f32(val);
+    }
+    return hasher.finish();
+}
+
+/// Get the fingerprint glyph of a genome. The left half (and middle column) of the glyph comes from the low bits of
+/// the genome hash and is mirrored onto the right half, and the color is picked from the species palette by the next bits
+pub fn get_genome_fingerprint(brain : &Brain) -> GenomeFingerprint {
+    let hash = get_genome_hash(brain);
+    let half_width = FINGERPRINT_SIZE.div_ceil(2);
+    let mut cells = [[false; FINGERPRINT_SIZE]; FINGERPRINT_SIZE];
+    for (row, row_cells) in cells.iter_mut().enumerate() {
+        for col in 0..half_width {
+            let filled = (hash >> (row * half_width + col)) & 1 == 1;
+            row_cells[col] = filled;
+            row_cells[FINGERPRINT_SIZE - 1 - col] = filled;
+        }
+    }
+    let palette_idx = (hash >> (FINGERPRINT_SIZE * half_width)) as usize % SPECIES_PALETTE.len();
+    return GenomeFingerprint {
+        cells : cells,
+        color : CreatureColor::new_from_vec(SPECIES_PALETTE[palette_idx]),
+    };
+}
+
+/// Get the stats of every tribe/species that has living members, biggest first (ties go to the lowest ID)
+pub fn get_group_stats(creatures : &[CreatureV1], group_by : GroupBy) -> Vec<GroupStats> {
+    let mut groups : Vec<GroupStats> = Vec::new();
+    for creature in creatures.iter() {
+        let id = match group_by {
+            GroupBy::Tribe => creature.lineage_id,
+            GroupBy::Species => creature.species_id,
+        };
+        let group_idx = match groups.iter().position(|g| g.id == id) {
+            Some(group_idx) => group_idx,
+            None => {
+                groups.push(GroupStats {id : id, num_creatures : 0, mean_energy : 0.0, num_kills : 0});
+                groups.len() - 1
+            }
+        };
+
+        // Energy is summed here and divided out at the end
+        groups[group_idx].num_creatures += 1;
+        groups[group_idx].mean_energy += creature.energy as f32;
+        groups[group_idx].num_kills += creature.num_kills;
+    }
+
+    for group in groups.iter_mut() {
+        group.mean_energy /= group.num_creatures as f32;
+    }
+    groups.sort_by(|a, b| b.num_creatures.cmp(&a.num_creatures).then(a.id.cmp(&b.id)));
+    return groups;
+}
+
+/// Cluster creatures into species by genome distance. Each creature joins the first existing species whose
+/// representative is within `threshold`, otherwise it founds a new species (IDs come from `next_species_id`).
+/// Species IDs are stable between calls because the representatives are carried over. Afterwards every species
+/// that still has members is represented by its first member. Returns the number of species
+pub fn assign_species(creatures : &mut [CreatureV1], representatives : &mut Vec<SpeciesRepresentative>, next_species_id : &mut usize, threshold : f32) -> usize {
+    let mut member_counts : Vec<usize> = vec![0; representatives.len()];
+    let mut first_members : Vec<Option<usize>> = vec![None; representatives.len()];
+
+    for (c_idx, creature) in creatures.iter_mut().enumerate() {
+        let found = representatives.iter().position(|rep| rep.brain.genome_distance(&creature.brain) <= threshold);
+        let rep_idx = match found {
+            Some(rep_idx) => rep_idx,
+            None => {
+                representatives.push(SpeciesRepresentative {species_id : *next_species_id, brain : creature.brain.clone()});
+                member_counts.push(0);
+                first_members.push(None);
+                *next_species_id += 1;
+                representatives.len() - 1
+            }
+        };
+
+        creature.species_id = representatives[rep_idx].species_id;
+        member_counts[rep_idx] += 1;
+        if first_members[rep_idx].is_none() {
+            first_members[rep_idx] = Some(c_idx);
+        }
+    }
+
+    // Drop extinct species and let the surviving ones drift with their members
+    let mut new_reps : Vec<SpeciesRepresentative> = Vec::new();
+    for (rep_idx, rep) in representatives.iter().enumerate() {
+        if let Some(c_idx) = first_members[rep_idx] {
+            new_reps.push(SpeciesRepresentative {species_id : rep.species_id, brain : creatures[c_idx].brain.clone()});
+        }
+    }
+    *representatives = new_reps;
+
+    return member_counts.iter().filter(|count| **count > 0).count();
+}
+
+
+#[cfg(test)]
+mod analysis_test {
+    use super::*;
+
+    #[test]
+    fn test_novelty_score() {
+        let others = vec![vec![0.0, 0.0], vec![1.0, 0.0], vec![0.0, 3.0]];
+
+        // Nearest 2 neighbors of the origin (skipping itself) are at distance 1 and 3
+        assert_eq!(get_novelty_score(&others[0], &others, Some(0), 2), 2.0);
+        assert_eq!(get_novelty_score(&[0.0, 0.0], &[], None, 2), 0.0);
+    }
+
+    #[test]
+    fn test_assign_species() {
+        // Two creatures with the same genome and one with a different (random) genome
+        let params = CreatureParams::new();
+        let mut creatures = vec![CreatureV1::new(0, &params), CreatureV1::new(1, &params), CreatureV1::new(2, &params)];
+        creatures[1].brain = creatures[0].brain.clone();
+        let mut representatives : Vec<SpeciesRepresentative> = Vec::new();
+        let mut next_species_id = 0;
+        assert_eq!(assign_species(&mut creatures, &mut representatives, &mut next_species_id, 1.0), 2);
+        assert_eq!(creatures.iter().map(|c| c.species_id).collect::<Vec<usize>>(), vec![0, 0, 1]);
+
+        // Re-clustering in a different order keeps the IDs. A new genome gets a new ID, even after a species dies out
+        creatures.reverse();
+        assert_eq!(assign_species(&mut creatures, &mut representatives, &mut next_species_id, 1.0), 2);
+        assert_eq!(creatures.iter().map(|c| c.species_id).collect::<Vec<usize>>(), vec![1, 0, 0]);
+        creatures.remove(0);
+        creatures.push(CreatureV1::new(3, &params));
+        assert_eq!(assign_species(&mut creatures, &mut representatives, &mut next_species_id, 1.0), 2);
+        assert_eq!(creatures.iter().map(|c| c.species_id).collect::<Vec<usize>>(), vec![0, 0, 2]);
+        assert_eq!(representatives.iter().map(|r| r.species_id).collect::<Vec<usize>>(), vec![0, 2]);
+
+        // Everything within the threshold is one species, but brains of different shapes never are
+        let mut representatives : Vec<SpeciesRepresentative> = Vec::new();
+        assert_eq!(assign_species(&mut creatures, &mut representatives, &mut next_species_id, 1000.0), 1);
+        creatures.push(CreatureV1::new_with_inputs(4, &params, &vec![CreatureInputs::Energy]));
+        assert_eq!(assign_species(&mut creatures, &mut representatives, &mut next_species_id, 1000.0), 2);
+    }
+
+    #[test]
+    fn test_grid_counts() {
+        // Count the spaces on the diagonal of a 5x5 board
+        let counts = GridCounts::new(5, 5, |x, y| x == y);
+        assert_eq!(counts.count_in_radius(2, 2, 1), (3, 9));
+        assert_eq!(counts.count_in_radius(0, 4, 1), (0, 4));
+        assert_eq!(counts.count_in_radius(0, 0, 10), (5, 25));
+    }
+
+    #[test]
+    fn test_genome_fingerprint() {
+        let creature = CreatureV1::new(0, &CreatureParams::new());
+        let fingerprint = get_genome_fingerprint(&creature.brain);
+
+        // Same genome, same glyph, and it's mirrored left to right
+        assert!(get_genome_fingerprint(&creature.clone().brain) == fingerprint);
+        assert!(fingerprint.cells.iter().all(|row| (0..FINGERPRINT_SIZE).all(|col| row[col] == row[FINGERPRINT_SIZE - 1 - col])));
+
+        // Different genomes (almost always) get different glyphs
+        let others : Vec<GenomeFingerprint> = (1..10).map(|id| get_genome_fingerprint(&CreatureV1::new(id, &CreatureParams::new()).brain)).collect();
+        assert!(others.iter().any(|other| other.cells != fingerprint.cells));
+    }
+}
@@ -0,0 +1,93 @@
+/** ===============================================================================
+ * File: catastrophes.rs
+ * Author: Scott Stack
+ * Description: Random disasters (food die-offs, plagues, collapsing walls) that the
+ * environment can throw at the population to test how well it recovers
+ * ===============================================================================*/
+use crate::sim_rng::sim_rng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const CATASTROPHE_RATE_STEPS : f32 = 1000.0;       // Catastrophe rates are given as the average number per this many steps
+pub const MAX_CATASTROPHES_PER_STEP : usize = 10;      // Cap on how many of one kind of catastrophe can hit in a single step
+
+pub const DEFAULT_FOOD_DIE_OFF : CatastropheParams = CatastropheParams {rate : 0.0, magnitude : 0.5};
+pub const DEFAULT_PLAGUE : CatastropheParams = CatastropheParams {rate : 0.0, magnitude : 0.3};
+pub const DEFAULT_WALL_COLLAPSE : CatastropheParams = CatastropheParams {rate : 0.0, magnitude : 0.25};
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Kinds of catastrophe that can hit the environment
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CatastropheKind {
+    FoodDieOff,     // A fraction of the food on the board disappears
+    Plague,         // A fraction of the creatures die
+    WallCollapse,   // A fraction of the walls crumble into blank spaces
+}
+pub const ALL_CATASTROPHE_KINDS : [CatastropheKind; 3] = [CatastropheKind::FoodDieOff, CatastropheKind::Plague, CatastropheKind::WallCollapse];
+
+/// How often one kind of catastrophe happens and how bad it is
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatastropheParams {
+    pub rate : f32,         // Average number of these per CATASTROPHE_RATE_STEPS steps (Poisson distributed). 0 = never
+    pub magnitude : f32,    // Chance [0, 1] of each food piece/creature/wall being hit
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl CatastropheParams {
+
+    /// Decide how many of this catastrophe hit in one step. Catastrophes arrive as a Poisson process, so the count
+    /// is Poisson distributed with a mean of `rate / CATASTROPHE_RATE_STEPS` (capped at MAX_CATASTROPHES_PER_STEP)
+    pub fn sample_num_per_step(&self) -> usize {
+        if self.rate <= 0.0 || self.magnitude <= 0.0 {
+            return 0;
+        }
+        return sample_poisson(self.rate / CATASTROPHE_RATE_STEPS, MAX_CATASTROPHES_PER_STEP);
+    }
+}
+
+/// Draw from a Poisson distribution with the given mean using Knuth's method (fine for the small means used here).
+/// Results are capped at `max_count`
+pub fn sample_poisson(mean : f32, max_count : usize) -> usize {
+    let mut rng = sim_rng();
+    let limit = (-mean).exp();
+    let mut count : usize = 0;
+    let mut product : f32 = rng.gen::<f32>();
+    while product > limit && count < max_count {
+        count += 1;
+        product *= rng.gen::<f32>();
+    }
+    return count;
+}
+
+
+#[cfg(test)]
+mod catastrophes_test {
+    use super::*;
+    use crate::sim_rng::run_seeded;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_sample_poisson() {
+        run_seeded(&mut Some(StdRng::seed_from_u64(8)), || {
+            let num_samples = 20_000;
+            let total : usize = (0..num_samples).map(|_| sample_poisson(0.5, MAX_CATASTROPHES_PER_STEP)).sum();
+            let sample_mean = total as f32 / num_samples as f32;
+            assert!((sample_mean - 0.5).abs() < 0.05, "sample mean {}", sample_mean);
+
+            assert_eq!(DEFAULT_PLAGUE.sample_num_per_step(), 0);
+            assert!(sample_poisson(1000.0, 3) <= 3);
+        });
+    }
+}
@@ -0,0 +1,129 @@
+/** ===============================================================================
+ * File: commands.rs
+ * Author: Scott Stack
+ * Description: Text commands (pause, stats, save, ...) that can be sent to a running
+ * simulation, parsed and carried out the same way no matter which frontend received them
+ * ===============================================================================*/
+use crate::environment::*;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const COMMAND_HELP : &str = "pause | resume | stats | save <file> | set food-rate <food per step> | quit | help";
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// A command for a running simulation
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimCommand {
+    Pause,              // Stop stepping the simulation
+    Resume,             // Start stepping it again
+    Stats,              // Reply with a one line summary of the current state
+    Save(String),       // Save the whole environment to the given file
+    SetFoodRate(f32),   // Change the average number of new food pieces added per step
+    Quit,               // Stop the run (the frontend decides what stopping means)
+    Help,               // Reply with the list of commands
+}
+
+/// State a frontend keeps about its run loop that commands can change
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct CommandState {
+    pub paused : bool,      // The frontend shouldn't step the simulation while this is set
+    pub quit : bool,        // The frontend should stop running the simulation
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl SimCommand {
+
+    /// Parse a command from a line of text (see COMMAND_HELP). Returns the reason if it isn't a valid command
+    pub fn parse(text : &str) -> Result<SimCommand, String> {
+        let words : Vec<&str> = text.split_whitespace().collect();
+        return match words.as_slice() {
+            ["pause"] => Ok(SimCommand::Pause),
+            ["resume"] => Ok(SimCommand::Resume),
+            ["stats"] => Ok(SimCommand::Stats),
+            ["quit"] => Ok(SimCommand::Quit),
+            ["help"] => Ok(SimCommand::Help),
+            ["save", filename] => Ok(SimCommand::Save(filename.to_string())),
+            ["set", "food-rate", rate] => match rate.parse::<f32>() {
+                Ok(rate) if rate.is_finite() && rate >= 0.0 => Ok(SimCommand::SetFoodRate(rate)),
+                _ => Err(format!("Invalid food rate '{}'", rate)),
+            },
+            [] => Err(String::from("Empty command")),
+            _ => Err(format!("Unknown command '{}'. Commands: {}", text.trim(), COMMAND_HELP)),
+        };
+    }
+
+    /// Carry out the command on `env` (and the frontend's `state`). Returns the reply to send back
+    pub fn execute(&self, env : &mut EnvironmentV1, state : &mut CommandState) -> String {
+        return match self {
+            SimCommand::Pause => {
+                state.paused = true;
+                format!("Paused in step {}", env.time_step)
+            },
+            SimCommand::Resume => {
+                state.paused = false;
+                format!("Resumed in step {}", env.time_step)
+            },
+            SimCommand::Stats => format!("step {} creatures {} food {} species {} kills {} natural_deaths {}{}",
+                env.time_step, env.creatures.len(), env.num_food, env.num_species, env.num_kills, env.num_natural_deaths,
+                if state.paused { " (paused)" } else { "" }),
+            SimCommand::Save(filename) => save_env(env, filename),
+            SimCommand::SetFoodRate(rate) => {
+                env.params.avg_new_food_per_day = *rate;
+                format!("Food rate set to {}", rate)
+            },
+            SimCommand::Quit => {
+                state.quit = true;
+                format!("Quitting in step {}", env.time_step)
+            },
+            SimCommand::Help => format!("Commands: {}", COMMAND_HELP),
+        };
+    }
+}
+
+/// Save the whole environment for the save command. Returns the reply
+#[cfg(feature = "file_io")]
+fn save_env(env : &EnvironmentV1, filename : &str) -> String {
+    return match std::fs::write(filename, env.to_json()) {
+        Ok(()) => format!("Saved step {} to {}", env.time_step, filename),
+        Err(e) => format!("Error: could not save to {}. Error = {e}", filename),
+    };
+}
+
+/// Save the whole environment for the save command. Returns the reply
+#[cfg(not(feature = "file_io"))]
+fn save_env(_env : &EnvironmentV1, filename : &str) -> String {
+    return format!("Error: cannot save to {}, built without the `file_io` feature", filename);
+}
+
+
+#[cfg(test)]
+mod commands_test {
+    use super::*;
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(SimCommand::parse(" pause \n"), Ok(SimCommand::Pause));
+        assert_eq!(SimCommand::parse("save run.json"), Ok(SimCommand::Save(String::from("run.json"))));
+        assert_eq!(SimCommand::parse("set food-rate 2.5"), Ok(SimCommand::SetFoodRate(2.5)));
+        assert!(SimCommand::parse("set food-rate -1").is_err());
+        assert!(SimCommand::parse("jump").is_err());
+
+        let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(10, 10), 1);
+        let mut state = CommandState::default();
+        SimCommand::Pause.execute(&mut env, &mut state);
+        assert!(state.paused);
+        SimCommand::SetFoodRate(3.0).execute(&mut env, &mut state);
+        assert_eq!(env.params.avg_new_food_per_day, 3.0);
+        assert!(SimCommand::Stats.execute(&mut env, &mut state).starts_with("step 0 creatures"));
+    }
+}
@@ -8,6 +8,7 @@
 
 // Define external crates to use in this module
 use std::fmt::Debug;
+use std::collections::VecDeque;
 use serde::{Deserialize, Serialize};
 
 //===============================================================================
@@ -26,8 +27,16 @@ pub const DEFAULT_REPRODUCE_ENERGY_COST : usize = DEFAULT_ENERGY_LEVEL;     // D
 pub const DEFAULT_MOVE_ENERGY_COST : usize = 1;             // Default amount of energy it takes to move one space
 pub const DEFAULT_ROTATE_ENERGY_COST : usize = 1;           // Default amount of energy it takes to rotate
 pub const DEFAULT_KILL_ENERGY_COST : usize = 1;             // Default amount of energy it takes to perform a kill action
+pub const DEFAULT_REST_ENERGY_GAIN : usize = 1;             // Default amount of energy a creature recovers by resting for a step
+pub const DEFAULT_KILL_RESTING_ENERGY_COST : usize = 0;     // Default amount of energy it takes to kill a creature that is resting
+pub const DEFAULT_MARK_TERRITORY_ENERGY_COST : usize = 1;   // Default amount of energy it takes to mark territory
+pub const LARGE_BODY_MULTIPLIER : usize = 2;                // Large creatures can store this many times the max energy, and pay this many times the move cost
 
+pub const ENERGY_HISTORY_LEN : usize = 50;                  // Number of past steps of energy level that each creature remembers (for display)
 
+
+pub const DEFAULT_VIEW_DISTANCE : usize = 5;                 // Default max number of spaces a creature can "see"
+pub const MAX_VIEW_DISTANCE : usize = 20;                    // View distance can't be set (or evolve) past this
 pub const VISION_NEURON_INVALID_VAL : f32 = -1e6;           // Value that should be applied to a vision input neuron if there's nothing in view
 
 const DEBUG_LEVEL : usize = 0;  // Debug print level (higher number = more detail)
@@ -45,12 +54,60 @@ pub enum CreatureActions {
     Stay,       // Do nothing
     Reproduce,
     Kill,
+    Rest,       // Recover a little energy, but kills against this creature are cheaper
+    MarkTerritory, // Claim the space the creature is standing on for its lineage (only enabled with territory)
+}
+pub const NUM_CREATURE_ACTIONS : usize = 11; // Number of variants in CreatureActions
+pub const ENABLED_CREATURE_ACTIONS : [CreatureActions; 9] = [Stay, MoveForwards, MoveBackwards, MoveLeft, MoveRight, RotateCCW, RotateCW, Reproduce, Kill];
+
+/// Whether a creature is carrying the disease (see the disease params of the environment)
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum InfectionState {
+    #[default]
+    Healthy,            // Never been infected. Can catch the disease
+    Infected(usize),    // Sick. Argument is the number of steps left until the creature recovers
+    Immune,             // Survived an infection and can't catch it again
+}
+
+/// Outcome of the last action a creature took. The environment reports this back to the creature
+/// after applying the action so it can be sensed on the next step
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ActionResult {
+    #[default]
+    NoEffect,           // Action didn't change anything in the environment (stay, rotate, kill with no target...)
+    Moved,              // Moved to a blank space
+    Blocked,            // Tried to move but a wall/creature was in the way
+    Ate,                // Moved onto a food space and ate it
+    ReachedBeacon,      // Moved onto the beacon
+    Killed,             // Killed another creature
+    Conceived(usize),   // Started a gestation period. Argument is the number of offspring that will be born
+    Reproduced(usize),  // Reproduced. Argument is the number of offspring produced
+}
+
+/// How the size of brain mutations changes over the course of a lineage
+#[derive(Copy, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum MutationSchedule {
+    #[default]
+    Constant,           // Every mutation has full strength
+    AnnealByGeneration, // Mutations get smaller the more generations a lineage has been around
+    SelfAdaptive,       // Each brain carries its own mutation scale gene, which is itself mutated on reproduction
+}
+
+/// Everything that decides how a brain is mutated when it's copied into an offspring
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MutationConfig {
+    pub prob : f32,                 // Chance of each weight/bias being mutated
+    pub mix : MutationMix,          // Relative weights of each mutation operator
+    pub schedule : MutationSchedule,// How mutation strength changes over a lineage
+    pub anneal_rate : f32,          // Rate used by MutationSchedule::AnnealByGeneration
 }
-const ENABLED_CREATURE_ACTIONS : [CreatureActions; 9] = [Stay, MoveForwards, MoveBackwards, MoveLeft, MoveRight, RotateCCW, RotateCW, Reproduce, Kill];
 
 /// Defines input neuron types to a creature. Each one of these has to directly translate into
 /// a single neuron input in the "brain" of the creature. I.e. the number of entries here
 /// defines how many input nodes are in the network.
+/// Categorical senses come in two encodings: a single neuron with an arbitrary number per value (`LastAction`,
+/// `Orientation`), or one-hot, with one neuron per possible value (`LastActionIs(..)`, `OrientationIs(..)`). A saved
+/// brain lists its inputs in `input_node_types`, so one-hot brains have one entry per value there
 #[derive(Copy, Clone, PartialEq, Debug, Deserialize, Serialize)]
 pub enum CreatureInputs {
     Unused,             // Unused
@@ -62,10 +119,24 @@ pub enum CreatureInputs {
     VisionColorBlue,    // Blue component of the color of the object the creature can see [0, 255]
     LastAction,         // The last action that the creature took
     Orientation,        // Which way th creature is facing
+    PositionX,          // X position of the creature normalized to [0, 1] across the board
+    PositionY,          // Y position of the creature normalized to [0, 1] across the board
+    DistanceToWallAhead,// Distance (in spaces) to the nearest wall in the direction the creature is facing
+    BeaconBearing,      // Angle to the beacon relative to the direction the creature is facing [-1, 1]
+    BeaconDistance,     // Distance (in spaces) to the beacon
+    LastMoveBlocked,    // 1 if the creature's last movement failed because something was in the way, 0 otherwise
+    HeardCreatures,     // Number of other creatures within the hearing radius (in any direction)
+    HeardFood,          // Number of food pieces within the hearing radius (in any direction)
+    OnForeignTerritory, // Strength [0, 1] of another lineage's territory mark on the creature's space (0 if unmarked or its own)
+    TribeSeesFood,      // 1 if a creature of the same lineage within the tribe vision radius can see food, 0 otherwise
+    TribeSeesThreat,    // 1 if a creature of the same lineage within the tribe vision radius can see a creature from another lineage, 0 otherwise
+    SeasonPhase,        // How far through the seasonal cycle the environment is [0, 1)
+    LastActionIs(CreatureActions),      // One-hot last action: 1 if the last action the creature took was this one, 0 otherwise
+    OrientationIs(CreatureOrientation), // One-hot orientation: 1 if the creature is facing this way, 0 otherwise
 }
-const ENABLED_CREATURE_INPUTS : [CreatureInputs; 8] = [Age, Energy, VisionDistance, VisionColorRed, VisionColorGreen, VisionColorBlue, Orientation, LastAction];
+pub const ENABLED_CREATURE_INPUTS : [CreatureInputs; 8] = [Age, Energy, VisionDistance, VisionColorRed, VisionColorGreen, VisionColorBlue, Orientation, LastAction];
 
-#[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CreaturePosition {
     pub x : usize, // x position of the creature
     pub y : usize, // y position of the creature
@@ -80,6 +151,7 @@ pub enum CreatureOrientation {
     Right,
 }
 pub const NUM_ORIENTATION_STATES : usize = 4;
+pub const ALL_ORIENTATIONS : [CreatureOrientation; NUM_ORIENTATION_STATES] = [CreatureOrientation::Up, CreatureOrientation::Down, CreatureOrientation::Left, CreatureOrientation::Right];
 
 // This represents the state of a creatures vision in one direction
 #[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
@@ -90,6 +162,29 @@ pub struct CreatureVisionState {
     pub space_type : SpaceStates, // State of the object
 }
 
+/// Extra information about the creature's surroundings that the environment fills in each step for
+/// the optional input neurons (vision has its own state above)
+#[derive(Copy, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct CreatureEnvSenses {
+    pub position_x : f32,       // x position normalized to [0, 1]
+    pub position_y : f32,       // y position normalized to [0, 1]
+    pub wall_dist_ahead : f32,  // distance to the nearest wall straight ahead (VISION_NEURON_INVALID_VAL if none)
+    pub beacon_bearing : f32,   // angle to the beacon relative to the facing direction, normalized to [-1, 1]
+    pub beacon_dist : f32,      // distance to the beacon in spaces
+    #[serde(default)]
+    pub heard_creatures : f32,  // number of other creatures within the hearing radius
+    #[serde(default)]
+    pub heard_food : f32,       // number of food pieces within the hearing radius
+    #[serde(default)]
+    pub foreign_territory : f32,// strength of another lineage's territory mark on the creature's space
+    #[serde(default)]
+    pub tribe_sees_food : f32,  // 1 if a nearby creature of the same lineage sees food
+    #[serde(default)]
+    pub tribe_sees_threat : f32,// 1 if a nearby creature of the same lineage sees a creature from another lineage
+    #[serde(default)]
+    pub season_phase : f32,     // how far through the seasonal cycle the environment is [0, 1)
+}
+
 /// Represents the color of a creature
 #[derive(Copy, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CreatureColor {
@@ -117,7 +212,10 @@ impl CreatureColor {
 use CreatureActions::*;
 use CreatureInputs::*;
 
-use crate::{neural_net::NeuralNet, environment::SpaceStates};
+use crate::{neural_net::{NeuralNet, MutationMix, standard_normal_sample}, environment::SpaceStates};
+use crate::linalg::Matrix;
+use crate::sim_rng::sim_rng;
+use rand::Rng;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct CreatureParams {
@@ -126,6 +224,12 @@ pub struct CreatureParams {
     pub rotate_energy_cost : usize,
     pub kill_energy_cost : usize,
     pub starting_energy : usize,
+    #[serde(default = "default_rest_energy_gain")]
+    pub rest_energy_gain : usize,
+    #[serde(default = "default_kill_resting_energy_cost")]
+    pub kill_resting_energy_cost : usize,
+    #[serde(default = "default_mark_territory_energy_cost")]
+    pub mark_territory_energy_cost : usize,
 }
 impl CreatureParams {
     pub fn new() -> CreatureParams {
@@ -135,6 +239,9 @@ impl CreatureParams {
             rotate_energy_cost : DEFAULT_ROTATE_ENERGY_COST,
             kill_energy_cost : DEFAULT_KILL_ENERGY_COST,
             starting_energy : DEFAULT_ENERGY_LEVEL,
+            rest_energy_gain : DEFAULT_REST_ENERGY_GAIN,
+            kill_resting_energy_cost : DEFAULT_KILL_RESTING_ENERGY_COST,
+            mark_territory_energy_cost : DEFAULT_MARK_TERRITORY_ENERGY_COST,
         }
     }
 }
@@ -165,12 +272,72 @@ pub struct CreatureV1 {
     /// Current energy level
     pub energy : usize,
 
+    /// Energy level at the start of each of the last `ENERGY_HISTORY_LEN` steps (oldest first)
+    #[serde(default)]
+    pub energy_history : VecDeque<usize>,
+
     /// Vision state of the creature (abstract info about what it can "see" in front of it)
     pub vision_state : CreatureVisionState,
 
+    /// Other senses populated by the environment (only used if the matching input neurons are enabled)
+    #[serde(default)]
+    pub env_senses : CreatureEnvSenses,
+
     /// Current age of the creature in time-steps 
     pub age : usize,
 
+    /// Number of ancestors between this creature and the randomly generated first generation (0 = first generation)
+    #[serde(default)]
+    pub generation : usize,
+
+    /// ID of the creature this one was born from (None for randomly generated creatures)
+    #[serde(default)]
+    pub parent_id : Option<usize>,
+
+    /// ID of the randomly generated ancestor this creature's lineage started from (its own ID for randomly generated creatures)
+    #[serde(default)]
+    pub lineage_id : usize,
+
+    /// Number of offspring this creature has had
+    #[serde(default)]
+    pub num_offspring : usize,
+
+    /// Number of creatures this creature has killed
+    #[serde(default)]
+    pub num_kills : usize,
+
+    /// Number of spaces this creature has moved
+    #[serde(default)]
+    pub distance_traveled : usize,
+
+    /// Number of food pieces this creature has eaten
+    #[serde(default)]
+    pub food_eaten : usize,
+
+    /// Number of times this creature has rotated
+    #[serde(default)]
+    pub num_rotations : usize,
+
+    /// Number of times each action has been taken (indexed by `CreatureActions as usize`)
+    #[serde(default, deserialize_with = "deserialize_action_counts")]
+    pub action_counts : [usize; NUM_CREATURE_ACTIONS],
+
+    /// Number of steps spent in each region of the board (only tracked when novelty search uses visitation)
+    #[serde(default)]
+    pub visit_counts : Vec<usize>,
+
+    /// Novelty of this creature's behavior compared to the population (only computed when novelty search is enabled)
+    #[serde(default)]
+    pub novelty : f32,
+
+    /// Rank of `novelty` within the population. 0 = least novel, 1 = most novel
+    #[serde(default)]
+    pub novelty_rank : f32,
+
+    /// Species this creature belongs to (assigned by the environment when speciation is enabled, otherwise inherited)
+    #[serde(default)]
+    pub species_id : usize,
+
     /// Color of the creature
     pub color : CreatureColor,
 
@@ -180,6 +347,58 @@ pub struct CreatureV1 {
     /// Last action that the creature took
     pub last_action : CreatureActions,
 
+    /// Outcome of the last action (reported by the environment)
+    #[serde(default)]
+    pub last_action_result : ActionResult,
+
+    /// Number of steps left until offspring are born (0 if not pregnant)
+    #[serde(default)]
+    pub gestation_steps_remaining : usize,
+
+    /// Number of offspring that will be born at the end of the current gestation
+    #[serde(default)]
+    pub pending_offspring : usize,
+
+    /// Whether the creature is healthy, sick or immune to the disease. Offspring are always born healthy
+    #[serde(default)]
+    pub infection : InfectionState,
+
+    /// How many spaces ahead this creature can see. Inherited (and mutated) when the environment evolves view distance
+    #[serde(default = "default_view_distance")]
+    pub view_distance : usize,
+
+    /// True if this creature can swim across water. Inherited (and occasionally flipped by mutation) when the environment enables swimming
+    #[serde(default)]
+    pub can_swim : bool,
+
+    /// True if this creature has a large (2x1) body. Inherited (and occasionally flipped by mutation) when the environment enables large bodies
+    #[serde(default)]
+    pub is_large : bool,
+
+    /// Second space a large creature takes up on the board. Right behind it when there's room, None if it's squeezed into one space
+    #[serde(default)]
+    pub tail : Option<CreaturePosition>,
+
+    /// Fraction of an energy unit of view cost that hasn't been paid yet (energy is only taken in whole units)
+    #[serde(default)]
+    pub view_cost_owed : f32,
+
+    /// Fraction of an energy unit of metabolic (sensing/thinking) cost that hasn't been paid yet
+    #[serde(default)]
+    pub metabolic_cost_owed : f32,
+
+    /// Action the brain is still "thinking" about when its compute budget is too small to decide in one step
+    #[serde(default)]
+    pub pending_action : Option<CreatureActions>,
+
+    /// Number of steps left until `pending_action` is carried out
+    #[serde(default)]
+    pub thinking_steps_left : usize,
+
+    /// Actions the brain decided on that haven't been carried out yet when actions are delayed, oldest first
+    #[serde(default)]
+    pub delayed_actions : VecDeque<CreatureActions>,
+
     /// Mapping from input neuron number (idx into this vec) to type.
     input_neuron_types : Vec<CreatureInputs>,
 
@@ -192,9 +411,22 @@ impl CreatureV1 {
     // ============= CONSTRUCTORS ================
 
     /// Constructor returns creature instance w/ default values
+    #[allow(dead_code)]
     pub fn new(id : usize, inparams : &CreatureParams) -> CreatureV1 {
-        let input_neuron_types = ENABLED_CREATURE_INPUTS.to_vec();
-        let output_neuron_types = ENABLED_CREATURE_ACTIONS.to_vec();
+        return CreatureV1::new_with_inputs(id, inparams, &ENABLED_CREATURE_INPUTS.to_vec());
+    }
+
+    /// Constructor returns creature instance w/ default values and a brain with one input neuron
+    /// for each entry in `input_neuron_types`
+    pub fn new_with_inputs(id : usize, inparams : &CreatureParams, input_neuron_types : &Vec<CreatureInputs>) -> CreatureV1 {
+        return CreatureV1::new_with_io(id, inparams, input_neuron_types, &ENABLED_CREATURE_ACTIONS.to_vec());
+    }
+
+    /// Constructor returns creature instance w/ default values and a brain with one input neuron for each
+    /// entry in `input_neuron_types` and one output neuron for each entry in `output_neuron_types`
+    pub fn new_with_io(id : usize, inparams : &CreatureParams, input_neuron_types : &Vec<CreatureInputs>, output_neuron_types : &Vec<CreatureActions>) -> CreatureV1 {
+        let input_neuron_types = input_neuron_types.clone();
+        let output_neuron_types = output_neuron_types.clone();
 
         let temp_creature = CreatureV1 {
             params : inparams.clone(),
@@ -205,9 +437,37 @@ impl CreatureV1 {
             position : CreaturePosition {x : 0, y : 0},
             orientation : DEFAULT_ORIENTATION,
             energy : DEFAULT_ENERGY_LEVEL,
+            energy_history : VecDeque::with_capacity(ENERGY_HISTORY_LEN),
             vision_state : CreatureVisionState {obj_in_view : false, dist : 0, color : CreatureColor::new_from_vec([0,0,0]), space_type : SpaceStates::BlankSpace},
+            env_senses : CreatureEnvSenses::default(),
             age : 0,
+            generation : 0,
+            parent_id : None,
+            lineage_id : id,
+            num_offspring : 0,
+            num_kills : 0,
+            distance_traveled : 0,
+            food_eaten : 0,
+            num_rotations : 0,
+            action_counts : [0; NUM_CREATURE_ACTIONS],
+            visit_counts : Vec::new(),
+            novelty : 0.0,
+            novelty_rank : 0.0,
+            species_id : 0,
             last_action : CreatureActions::Stay,
+            last_action_result : ActionResult::NoEffect,
+            gestation_steps_remaining : 0,
+            pending_offspring : 0,
+            infection : InfectionState::Healthy,
+            view_distance : DEFAULT_VIEW_DISTANCE,
+            can_swim : false,
+            is_large : false,
+            tail : None,
+            view_cost_owed : 0.0,
+            metabolic_cost_owed : 0.0,
+            pending_action : None,
+            thinking_steps_left : 0,
+            delayed_actions : VecDeque::new(),
             color : CreatureColor::new_from_vec(DEFAULT_CREATURE_COLOR),
             reproduction_age : DEFAULT_REPRODUCE_AGE,
             input_neuron_types : input_neuron_types,
@@ -218,20 +478,50 @@ impl CreatureV1 {
     }
 
     /// Constructor to create a new creature from a provided parent (genes copied with optional mutations)
-    pub fn new_offspring(id : usize, parent : &CreatureV1, mutation_prob : f32) -> CreatureV1 {
+    /// The offspring starts out with `starting_energy` energy. The brain is mutated as set by `mutation` (see `Brain::new_copy`)
+    pub fn new_offspring(id : usize, parent : &CreatureV1, mutation : &MutationConfig, starting_energy : usize) -> CreatureV1 {
 
+        let generation = parent.generation + 1;
         let mut temp_creature = CreatureV1 {
             params : parent.params.clone(),
-            brain : Brain::new_copy(&parent.brain, mutation_prob),
+            brain : Brain::new_copy(&parent.brain, mutation, generation),
             id : id,
             is_alive : true,
             killed : false,
             position : CreaturePosition {x : parent.position.x, y : parent.position.y},
             orientation : parent.orientation,
-            energy : parent.params.starting_energy,
+            energy : starting_energy,
+            energy_history : VecDeque::with_capacity(ENERGY_HISTORY_LEN),
             vision_state : CreatureVisionState {obj_in_view : false, dist : 0, color : CreatureColor::new_from_vec([0,0,0]), space_type : SpaceStates::BlankSpace},
+            env_senses : CreatureEnvSenses::default(),
             age : 0,
+            generation : generation,
+            parent_id : Some(parent.id),
+            lineage_id : parent.lineage_id,
+            num_offspring : 0,
+            num_kills : 0,
+            distance_traveled : 0,
+            food_eaten : 0,
+            num_rotations : 0,
+            action_counts : [0; NUM_CREATURE_ACTIONS],
+            visit_counts : Vec::new(),
+            novelty : 0.0,
+            novelty_rank : 0.0,
+            species_id : parent.species_id,
             last_action : CreatureActions::Stay,
+            last_action_result : ActionResult::NoEffect,
+            gestation_steps_remaining : 0,
+            pending_offspring : 0,
+            infection : InfectionState::Healthy,
+            view_distance : parent.view_distance,
+            can_swim : parent.can_swim,
+            is_large : parent.is_large,
+            tail : None,
+            view_cost_owed : 0.0,
+            metabolic_cost_owed : 0.0,
+            pending_action : None,
+            thinking_steps_left : 0,
+            delayed_actions : VecDeque::new(),
             color : parent.color.clone(),
             reproduction_age : DEFAULT_REPRODUCE_AGE,
             input_neuron_types : parent.input_neuron_types.clone(),
@@ -245,6 +535,7 @@ impl CreatureV1 {
     }
 
 
+    #[cfg(feature = "json")]
     /// Constructor to create a new creature from a JSON string
     #[allow(dead_code)]
     pub fn new_from_json(id : usize, json_in : &str) -> serde_json::Result<CreatureV1> {
@@ -254,6 +545,7 @@ impl CreatureV1 {
 
         // Set the ID to the new value
         temp_creature.id = id;
+        temp_creature.brain.restore_activations();
 
         return Ok(temp_creature);
     }
@@ -274,18 +566,178 @@ impl CreatureV1 {
 
     /// Eat a piece of food that gives it the specified amount of energy
     pub fn eat_food(&mut self, food_energy : usize) {
-        if self.energy + food_energy > MAX_POSSIBLE_ENERGY {
-            self.energy = MAX_POSSIBLE_ENERGY;
+        let max_energy = self.get_max_energy();
+        if self.energy + food_energy > max_energy {
+            self.energy = max_energy;
         } else {
             self.energy += food_energy;
         }
     }
 
+    /// Take the specified amount of energy away from this creature to give to another (e.g. an offspring)
+    /// Returns the amount actually transferred, which is less than `amount` if the creature doesn't have enough
+    pub fn transfer_energy(&mut self, amount : usize) -> usize {
+        let transferred = amount.min(self.energy);
+        self.energy -= transferred;
+        return transferred;
+    }
+
+    /// Start a gestation period. `num_offspring` will be born after `num_steps` more steps
+    pub fn start_gestation(&mut self, num_offspring : usize, num_steps : usize) {
+        self.pending_offspring = num_offspring;
+        self.gestation_steps_remaining = num_steps;
+    }
+
+    /// Returns true if the creature is currently sick
+    pub fn is_infected(&self) -> bool {
+        return matches!(self.infection, InfectionState::Infected(_));
+    }
+
+    /// Infect the creature for `duration` steps. Only healthy creatures can be infected. Returns true if it caught the disease
+    pub fn infect(&mut self, duration : usize) -> bool {
+        if self.infection != InfectionState::Healthy {
+            return false;
+        }
+        self.infection = InfectionState::Infected(duration.max(1));
+        return true;
+    }
+
+    /// Advance an infection by one step, draining `energy_drain` energy. Returns true if the creature recovered
+    /// (and is now immune) this step
+    pub fn advance_infection(&mut self, energy_drain : usize) -> bool {
+        let steps_left = match self.infection {
+            InfectionState::Infected(steps_left) => steps_left,
+            _ => return false,
+        };
+
+        self.energy = self.energy.saturating_sub(energy_drain);
+        if steps_left <= 1 {
+            self.infection = InfectionState::Immune;
+            return true;
+        }
+        self.infection = InfectionState::Infected(steps_left - 1);
+        return false;
+    }
+
+    /// Returns true if the creature is currently carrying offspring
+    pub fn is_pregnant(&self) -> bool {
+        return self.gestation_steps_remaining > 0;
+    }
+
+    /// Advance the gestation period by one step, paying `upkeep_energy` for it. Returns the number of
+    /// offspring to spawn if the gestation period is over
+    pub fn advance_gestation(&mut self, upkeep_energy : usize) -> Option<usize> {
+        if !self.is_pregnant() {
+            return None;
+        }
+
+        self.energy = self.energy.saturating_sub(upkeep_energy);
+        self.gestation_steps_remaining -= 1;
+        if self.gestation_steps_remaining == 0 {
+            let num_offspring = self.pending_offspring;
+            self.pending_offspring = 0;
+            return Some(num_offspring);
+        }
+        return None;
+    }
+
+    /// Pay the energy cost of seeing `view_distance` spaces for one step, at `cost_per_space` energy per space.
+    /// Fractions of an energy unit are carried over until they add up to a whole unit
+    pub fn pay_view_cost(&mut self, cost_per_space : f32) {
+        self.view_cost_owed += cost_per_space.max(0.0) * self.view_distance as f32;
+        let whole_units = self.view_cost_owed.floor();
+        self.view_cost_owed -= whole_units;
+        self.energy = self.energy.saturating_sub(whole_units as usize);
+    }
+
+    /// Pay the metabolic cost of sensing and thinking for one step, at `cost_per_neuron` energy per neuron in the brain.
+    /// Fractions of an energy unit are carried over like the view cost
+    pub fn pay_metabolic_cost(&mut self, cost_per_neuron : f32) {
+        self.metabolic_cost_owed += cost_per_neuron.max(0.0) * self.brain.get_num_neurons() as f32;
+        let whole_units = self.metabolic_cost_owed.floor();
+        self.metabolic_cost_owed -= whole_units;
+        self.energy = self.energy.saturating_sub(whole_units as usize);
+    }
+
+    /// With probability `mutation_prob`, make the view distance one space longer or shorter (staying within 1 to MAX_VIEW_DISTANCE)
+    pub fn mutate_view_distance(&mut self, mutation_prob : f32) {
+        let mut rng = sim_rng();
+        if rng.gen::<f32>() < mutation_prob {
+            self.view_distance = if rng.gen::<bool>() { self.view_distance + 1 } else { self.view_distance.saturating_sub(1) };
+            self.view_distance = self.view_distance.clamp(1, MAX_VIEW_DISTANCE);
+        }
+    }
+
+    /// With probability `mutation_prob`, switch between a large and a normal body
+    pub fn mutate_body_size(&mut self, mutation_prob : f32) {
+        let mut rng = sim_rng();
+        if rng.gen::<f32>() < mutation_prob {
+            self.is_large = !self.is_large;
+        }
+    }
+
+    /// Get the most energy this creature can store (more for large creatures)
+    pub fn get_max_energy(&self) -> usize {
+        return if self.is_large { MAX_POSSIBLE_ENERGY * LARGE_BODY_MULTIPLIER } else { MAX_POSSIBLE_ENERGY };
+    }
+
+    /// Get the energy it costs this creature to move one space (more for large creatures)
+    pub fn get_move_energy_cost(&self) -> usize {
+        return if self.is_large { self.params.move_energy_cost * LARGE_BODY_MULTIPLIER } else { self.params.move_energy_cost };
+    }
+
+    /// With probability `mutation_prob`, gain or lose the ability to swim
+    pub fn mutate_swimming(&mut self, mutation_prob : f32) {
+        let mut rng = sim_rng();
+        if rng.gen::<f32>() < mutation_prob {
+            self.can_swim = !self.can_swim;
+        }
+    }
+
     /// Set the vision state of the creature based on surroundings
     pub fn set_vision(&mut self, vision : CreatureVisionState) {
         self.vision_state = vision;
     }
 
+    /// Set the other (non-vision) senses of the creature based on surroundings
+    pub fn set_env_senses(&mut self, senses : CreatureEnvSenses) {
+        self.env_senses = senses;
+    }
+
+    /// Report the outcome of the action this creature just took. Called by the environment after the
+    /// action has been applied, and stored for the next sensing phase
+    pub fn apply_action_result(&mut self, result : ActionResult) {
+        self.last_action_result = result;
+
+        // Keep lifetime counters up to date
+        match result {
+            ActionResult::Moved | ActionResult::ReachedBeacon => self.distance_traveled += 1,
+            ActionResult::Ate => {
+                self.distance_traveled += 1;
+                self.food_eaten += 1;
+            },
+            ActionResult::Killed => self.num_kills += 1,
+            _ => (),
+        }
+    }
+
+    /// Returns true if this creature's brain is wired for exactly these input and output neuron types (in this order)
+    pub fn has_io(&self, input_neuron_types : &[CreatureInputs], output_neuron_types : &[CreatureActions]) -> bool {
+        return self.input_neuron_types == input_neuron_types && self.output_neuron_types == output_neuron_types;
+    }
+
+    /// Rewire this creature's brain for a different set of inputs and outputs (see `Brain::adapt_io`)
+    pub fn adapt_io(&mut self, input_neuron_types : &[CreatureInputs], output_neuron_types : &[CreatureActions]) {
+        self.brain.adapt_io(input_neuron_types, output_neuron_types);
+        self.input_neuron_types = input_neuron_types.to_vec();
+        self.output_neuron_types = output_neuron_types.to_vec();
+    }
+
+    /// Returns true if this creature has an input neuron of the specified type
+    pub fn has_input(&self, input_type : CreatureInputs) -> bool {
+        return self.input_neuron_types.contains(&input_type);
+    }
+
     /// Kill this creature (another creature has hunted it)
     pub fn kill(&mut self) {
         self.energy = 0;
@@ -293,6 +745,12 @@ impl CreatureV1 {
         self.killed = true;
     }
 
+    /// Kill this creature off without another creature being responsible (e.g. a plague). Counts as a natural death
+    pub fn die(&mut self) {
+        self.energy = 0;
+        self.is_alive = false;
+    }
+
     /// Mark this creature as a killer (carnivore). This changes it's color a bit to indicate to others that it's dangerous
     pub fn set_killer(&mut self) {
         self.color.red = self.color.red.saturating_add(10);
@@ -320,7 +778,7 @@ impl CreatureV1 {
 
 
     /// Sense surroundings by populating the input neurons to reflect current state
-    /// This should be called before `perform_next_action`
+    /// This should be called before the brain is evaluated (see `decide_action`)
     pub fn sense_surroundings(&mut self) {
         // Get vision state node values first
         let vis_dist : f32;
@@ -353,7 +811,22 @@ impl CreatureV1 {
                 VisionColorBlue => self.brain.set_input(input_neuron_idx, vis_blue),
                 LastAction => self.brain.set_input(input_neuron_idx,  self.action_to_f32(self.last_action)),
                 Orientation => self.brain.set_input(input_neuron_idx, self.orientation_to_f32(self.orientation)),
+                PositionX => self.brain.set_input(input_neuron_idx, self.env_senses.position_x),
+                PositionY => self.brain.set_input(input_neuron_idx, self.env_senses.position_y),
+                DistanceToWallAhead => self.brain.set_input(input_neuron_idx, self.env_senses.wall_dist_ahead),
+                BeaconBearing => self.brain.set_input(input_neuron_idx, self.env_senses.beacon_bearing),
+                BeaconDistance => self.brain.set_input(input_neuron_idx, self.env_senses.beacon_dist),
+                HeardCreatures => self.brain.set_input(input_neuron_idx, self.env_senses.heard_creatures),
+                HeardFood => self.brain.set_input(input_neuron_idx, self.env_senses.heard_food),
+                OnForeignTerritory => self.brain.set_input(input_neuron_idx, self.env_senses.foreign_territory),
+                TribeSeesFood => self.brain.set_input(input_neuron_idx, self.env_senses.tribe_sees_food),
+                TribeSeesThreat => self.brain.set_input(input_neuron_idx, self.env_senses.tribe_sees_threat),
+                SeasonPhase => self.brain.set_input(input_neuron_idx, self.env_senses.season_phase),
+                LastMoveBlocked => self.brain.set_input(input_neuron_idx, if self.last_action_result == ActionResult::Blocked {1.0} else {0.0}),
+                LastActionIs(action) => self.brain.set_input(input_neuron_idx, if self.last_action == *action {1.0} else {0.0}),
+                OrientationIs(orientation) => self.brain.set_input(input_neuron_idx, if self.orientation == *orientation {1.0} else {0.0}),
                 _ => {
+                    #[allow(clippy::absurd_extreme_comparisons)]    // DEBUG_LEVEL is a compile-time switch that's usually 0
                     if DEBUG_LEVEL > 0 {
                         println!("Warning: unused/unpopulated input neuron {:?} at idx {}", neuron_type, input_neuron_idx);
                     }
@@ -377,6 +850,8 @@ impl CreatureV1 {
             CreatureActions::RotateCW => 11.0,
             CreatureActions::Reproduce => 15.0,
             CreatureActions::Kill => 20.0,
+            CreatureActions::Rest => 1.0,
+            CreatureActions::MarkTerritory => 25.0,
         }
     }
 
@@ -390,9 +865,53 @@ impl CreatureV1 {
         }
     }
 
-    // Perform next action (evaluate neural network and decide on next action based on output)
+    /// Sense the surroundings and evaluate the brain to get the action it wants to take. Nothing else about the
+    /// creature changes (see `perform_next_action` for carrying it out)
+    pub fn decide_action(&mut self) -> CreatureActions {
+        self.sense_surroundings();
+        return self.brain.get_next_action();
+    }
+
+    /// Same as `decide_action`, but the brain can only evaluate `budget` connections per step (0 = no limit). A brain that
+    /// costs more takes several steps to decide: the creature senses and decides right away, stays put while it "thinks",
+    /// then carries out the (by then possibly stale) decision on the last step
+    pub fn decide_action_with_budget(&mut self, budget : usize) -> CreatureActions {
+        if self.pending_action.is_none() {
+            let action = self.decide_action();
+            let steps_needed = if budget == 0 { 1 } else { self.brain.get_compute_cost().div_ceil(budget).max(1) };
+            if steps_needed == 1 {
+                return action;
+            }
+            self.pending_action = Some(action);
+            self.thinking_steps_left = steps_needed - 1;
+            return Stay;
+        }
+
+        self.thinking_steps_left = self.thinking_steps_left.saturating_sub(1);
+        if self.thinking_steps_left == 0 {
+            return self.pending_action.take().unwrap_or(Stay);
+        }
+        return Stay;
+    }
+
+    /// Queue up `action` (just decided on by the brain) and get the action to carry out this step, which is the one
+    /// decided on `delay_steps` steps ago. Until the queue fills up (e.g. right after birth) the creature stays put
+    pub fn delay_action(&mut self, action : CreatureActions, delay_steps : usize) -> CreatureActions {
+        self.delayed_actions.push_back(action);
+        while self.delayed_actions.len() > delay_steps + 1 {
+            self.delayed_actions.pop_front();
+        }
+        if self.delayed_actions.len() > delay_steps {
+            return self.delayed_actions.pop_front().unwrap_or(Stay);
+        }
+        return Stay;
+    }
+
+    // Perform next action. `brain_action` is what the brain decided on (see `decide_action`), but the
+    // creature may do something else (e.g. reproduce, or stay put if it dies). If `can_reproduce` is false
+    // (e.g. outside of mating season) the creature won't reproduce and a brain Reproduce becomes Stay
     // 
-    pub fn perform_next_action(&mut self) -> CreatureActions {
+    pub fn perform_next_action(&mut self, brain_action : CreatureActions, can_reproduce : bool) -> CreatureActions {
 
         if self.is_dead() {
             // Creature is dead, just return stay action
@@ -400,6 +919,9 @@ impl CreatureV1 {
             return CreatureActions::Stay;
         }
 
+        // Remember the energy we started this step with
+        self.record_energy();
+
         // Increase age until a max. If we've hit max, then die of old age
         if self.age < MAX_POSSIBLE_AGE {
             self.age += 1;
@@ -409,21 +931,24 @@ impl CreatureV1 {
         }
 
         // Before we even do any action eval, check to see whether creature should reproduce
-        if self.energy > DEFAULT_MIN_REPRODUCE_ENERGY {
+        // (can't start another pregnancy if already carrying offspring)
+        if self.energy > DEFAULT_MIN_REPRODUCE_ENERGY && !self.is_pregnant() && can_reproduce {
             self.energy -= self.params.reproduce_energy_cost;
             self.last_action = Reproduce;
+            self.action_counts[Reproduce as usize] += 1;
             return Reproduce;
         }
 
-        // Otherwise, evaluate the brain network based on the current state of the input neurons
-        // To check what our next action will be
-        let mut action = self.brain.get_next_action();
+        // Otherwise, do what the brain decided on based on the current state of the input neurons
+        let mut action = if brain_action == Reproduce && !can_reproduce { Stay } else { brain_action };
 
         // Get the value of the action to be taken
         // let action = self.brain.get_current_action();
         self.last_action = action;
 
         // Show the state of the brain if debug level high enough
+        #[cfg(feature = "json")]
+        #[allow(clippy::absurd_extreme_comparisons)]
         if DEBUG_LEVEL > 1 {
             print!("{}", self.to_json());
         }
@@ -438,9 +963,11 @@ impl CreatureV1 {
         // Calculate new energy based on which action we decide to take. Different actions cost differing amounts
         self.energy = match action {
             Reproduce => self.energy.saturating_sub(self.params.reproduce_energy_cost),
-            MoveBackwards | MoveForwards | MoveLeft | MoveRight => self.energy.saturating_sub(self.params.move_energy_cost),
+            MoveBackwards | MoveForwards | MoveLeft | MoveRight => self.energy.saturating_sub(self.get_move_energy_cost()),
             RotateCCW | RotateCW => self.energy.saturating_sub(self.params.rotate_energy_cost),
             Kill => self.energy.saturating_sub(self.params.kill_energy_cost),
+            Rest => (self.energy + self.params.rest_energy_gain).min(self.get_max_energy()),
+            MarkTerritory => self.energy.saturating_sub(self.params.mark_territory_energy_cost),
             _ => self.energy,
         };
 
@@ -450,9 +977,19 @@ impl CreatureV1 {
             action = Stay;
         }
 
+        self.action_counts[action as usize] += 1;
         return action; 
     }
 
+    /// Record that the creature spent a step in the board region `region_idx` (out of `num_regions`)
+    pub fn record_visit(&mut self, region_idx : usize, num_regions : usize) {
+        if self.visit_counts.len() < num_regions {
+            self.visit_counts.resize(num_regions, 0);
+        }
+        self.visit_counts[region_idx] += 1;
+    }
+
+    #[cfg(feature = "json")]
     /// Return a JSON string that represents this creature. Allows saving state to
     /// a file for use later
     pub fn to_json(&self) -> String {
@@ -462,6 +999,14 @@ impl CreatureV1 {
 
     // ============= INTERNAL FUNCTIONS ================
 
+    /// Push the current energy level into the energy history ring buffer, dropping the oldest entry if it's full
+    fn record_energy(&mut self) {
+        if self.energy_history.len() >= ENERGY_HISTORY_LEN {
+            self.energy_history.pop_front();
+        }
+        self.energy_history.push_back(self.energy);
+    }
+
     /// Apply rotation to creature - there is definitely a better way to do this...
     /// If the action does not specify a rotation, just do nothing
     fn apply_rotation(&mut self, action : CreatureActions) {
@@ -495,11 +1040,22 @@ impl CreatureV1 {
                 }
             }
         } 
+
+        if action == RotateCCW || action == RotateCW {
+            self.num_rotations += 1;
+        }
     }
 
 }
 
 
+/// A single weight or bias in a brain that can be tweaked by hand
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BrainParam {
+    Weight {layer : usize, to : usize, from : usize},   // Weight from neuron `from` in layer `layer` to neuron `to` in layer `layer + 1`
+    Bias {layer : usize, node : usize},                 // Bias of neuron `node` in layer `layer + 1`
+}
+
 /// Second version of a creature brain that uses more generic neural network
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Brain {
@@ -511,6 +1067,45 @@ pub struct Brain {
 
     // Input types (maps output neuron index to type)
     pub output_node_types : Vec<CreatureActions>,
+
+    // Self-adaptive mutation scale gene. Only used with MutationSchedule::SelfAdaptive
+    #[serde(default = "default_mutation_scale")]
+    pub mutation_scale : f32,
+}
+
+/// View distance for creatures saved before it was a parameter
+fn default_view_distance() -> usize {
+    return DEFAULT_VIEW_DISTANCE;
+}
+
+/// Rest energy gain for creatures saved before resting existed
+fn default_rest_energy_gain() -> usize {
+    return DEFAULT_REST_ENERGY_GAIN;
+}
+
+/// Resting kill cost for creatures saved before resting existed
+fn default_kill_resting_energy_cost() -> usize {
+    return DEFAULT_KILL_RESTING_ENERGY_COST;
+}
+
+/// Territory marking cost for creatures saved before territory existed
+fn default_mark_territory_energy_cost() -> usize {
+    return DEFAULT_MARK_TERRITORY_ENERGY_COST;
+}
+
+/// Action counts saved before an action was added have fewer entries. Missing entries start at zero
+fn deserialize_action_counts<'de, D : serde::Deserializer<'de>>(deserializer : D) -> Result<[usize; NUM_CREATURE_ACTIONS], D::Error> {
+    let saved_counts = Vec::<usize>::deserialize(deserializer)?;
+    let mut action_counts = [0; NUM_CREATURE_ACTIONS];
+    for (count, saved_count) in action_counts.iter_mut().zip(saved_counts) {
+        *count = saved_count;
+    }
+    return Ok(action_counts);
+}
+
+/// Mutation scale for brains saved before the gene existed
+fn default_mutation_scale() -> f32 {
+    return 1.0;
 }
 
 // Define the number and size of internal layers in the brain neural net
@@ -519,6 +1114,14 @@ const PLACEHOLDER_NUM_NODES : usize = 0;
 const BRAIN_V2_LAYER_SIZES : [usize; 4] = [PLACEHOLDER_NUM_NODES, 6, 6, PLACEHOLDER_NUM_NODES]; // 2 internal layers with 6 neurons each. First/Last layer sizes will be specified by constructor
 const BRAIN_V2_MIN_INIT_NODE_VAL : f32 = -25.0;  // Min initial value that a node will take
 const BRAIN_V2_MAX_INIT_NODE_VAL : f32 = 25.0;   // Max initial value that a node will take 
+const MUTATION_SCALE_LEARNING_RATE : f32 = 0.2;  // How quickly the self-adaptive mutation scale gene drifts (std dev of its log-normal update)
+const MIN_MUTATION_SCALE : f32 = 0.01;           // Smallest value the mutation scale gene can take
+const MAX_MUTATION_SCALE : f32 = 4.0;            // Largest value the mutation scale gene can take
+const BRAIN_DESCRIBE_NUM_PATHWAYS : usize = 5;   // Number of strongest input->output pathways listed by `Brain::describe`
+const SALIENCY_REL_STEP : f32 = 0.1;             // Saliency nudges each input up/down by this fraction of its value...
+const SALIENCY_MIN_STEP : f32 = 1.0;             // ...but by at least this much, so inputs that are 0 still get nudged
+pub const BRAIN_DOT_DEFAULT_MIN_WEIGHT : f32 = 5.0; // Connections weaker than this are left out of `Brain::to_dot` graphs by default
+const BRAIN_DOT_MAX_PEN_WIDTH : f32 = 5.0;       // Line thickness of the strongest connection in `Brain::to_dot` graphs
 
 impl Brain {
 
@@ -537,24 +1140,309 @@ impl Brain {
             net : NeuralNet::new(&layer_sizes, BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL),
             input_node_types : input_node_types.clone(), 
             output_node_types : output_node_types.clone(),
+            mutation_scale : default_mutation_scale(),
         };
     }
 
     /// Create a new brain copy, but randomly mutate some of the weights/biases
-    /// with chance of mutation for each of mutation_prob 
-    pub fn new_copy(other_brain : &Brain, mutation_prob : f32) -> Brain {
+    /// with chance of mutation for each of `mutation.prob`. The type of each mutation is picked from `mutation.mix`.
+    /// The strength of the mutations depends on `mutation.schedule`:
+    /// * Constant - always full strength
+    /// * AnnealByGeneration - strength is 1 / (1 + anneal_rate * generation), where `generation` is that of the new brain
+    /// * SelfAdaptive - the parent's mutation scale gene is mutated first, then used as the strength
+    pub fn new_copy(other_brain : &Brain, mutation : &MutationConfig, generation : usize) -> Brain {
+        let mut rng = sim_rng();
+
+        let mut mutation_scale = other_brain.mutation_scale;
+        let magnitude = match mutation.schedule {
+            MutationSchedule::Constant => 1.0,
+            MutationSchedule::AnnealByGeneration => 1.0 / (1.0 + mutation.anneal_rate.max(0.0) * generation as f32),
+            MutationSchedule::SelfAdaptive => {
+                let log_step = MUTATION_SCALE_LEARNING_RATE * standard_normal_sample(&mut rng);
+                mutation_scale = (mutation_scale * log_step.exp()).clamp(MIN_MUTATION_SCALE, MAX_MUTATION_SCALE);
+                mutation_scale
+            },
+        };
 
         // Copy the neural net and apply random mutations to it
         let mut nn = other_brain.net.clone();
-        nn.apply_rand_mutations(mutation_prob, BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
+        nn.apply_mutations(mutation.prob, &mutation.mix, magnitude, BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
 
         return Brain {
             net : nn,
             input_node_types : other_brain.input_node_types.clone(), 
             output_node_types : other_brain.output_node_types.clone(),
+            mutation_scale : mutation_scale,
         };
     }
 
+    /// Returns true if the network is well formed and its input/output layers have one neuron per input/output type
+    pub fn is_valid(&self) -> bool {
+        let layer_sizes = self.net.get_layer_sizes();
+        return self.net.is_well_formed()
+            && layer_sizes.first() == Some(&self.input_node_types.len())
+            && layer_sizes.last() == Some(&self.output_node_types.len());
+    }
+
+    /// Rewire the input and output layers for a different set of input/output types. Connections for types that are in
+    /// both the old and new sets are kept. New inputs start out ignored (zero weights) and new outputs start out random
+    pub fn adapt_io(&mut self, input_node_types : &[CreatureInputs], output_node_types : &[CreatureActions]) {
+        let first_weights = &self.net.weights[0];
+        let mut new_first_weights : Matrix<f32> = Matrix::new(first_weights.get_nrows(), input_node_types.len());
+        for (new_col, input_type) in input_node_types.iter().enumerate() {
+            if let Some(old_col) = self.input_node_types.iter().position(|t| t == input_type) {
+                for row in 0..first_weights.get_nrows() {
+                    new_first_weights.set(row, new_col, first_weights.get(row, old_col));
+                }
+            }
+        }
+        self.net.weights[0] = new_first_weights;
+
+        let last_idx = self.net.weights.len() - 1;
+        let (last_weights, last_biases) = (&self.net.weights[last_idx], &self.net.biases[last_idx]);
+        let num_hidden = last_weights.get_ncols();
+        let mut new_last_weights = Matrix::random(output_node_types.len(), num_hidden, BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
+        let mut new_last_biases = Matrix::random(output_node_types.len(), 1, BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
+        for (new_row, output_type) in output_node_types.iter().enumerate() {
+            if let Some(old_row) = self.output_node_types.iter().position(|t| t == output_type) {
+                for col in 0..num_hidden {
+                    new_last_weights.set(new_row, col, last_weights.get(old_row, col));
+                }
+                new_last_biases.set(new_row, 0, last_biases.get(old_row, 0));
+            }
+        }
+        self.net.weights[last_idx] = new_last_weights;
+        self.net.biases[last_idx] = new_last_biases;
+
+        self.input_node_types = input_node_types.to_vec();
+        self.output_node_types = output_node_types.to_vec();
+        self.net.reset_activations();
+    }
+
+    /// Genome distance between two brains (RMS difference of their weights/biases).
+    /// Brains with different network shapes are infinitely far apart
+    pub fn genome_distance(&self, other : &Brain) -> f32 {
+        return self.net.distance(&other.net).unwrap_or(f32::INFINITY);
+    }
+
+    /// Make sure the network has somewhere to put its activations. Saves made without activations
+    /// leave them empty, so this has to be called after loading
+    pub fn restore_activations(&mut self) {
+        if self.net.activations.len() != self.net.num_layers {
+            self.net.reset_activations();
+        }
+    }
+
+    /// Drop the activations of the network (they're rebuilt by `restore_activations`)
+    pub fn strip_activations(&mut self) {
+        self.net.activations.clear();
+    }
+
+    /// Get the total number of neurons in the network (inputs, hidden and outputs)
+    pub fn get_num_neurons(&self) -> usize {
+        return self.net.get_layer_sizes().iter().sum();
+    }
+
+    /// Round every weight and bias to `decimals` decimal places
+    pub fn round_genome(&mut self, decimals : i32) {
+        let scale = 10f32.powi(decimals);
+        for mat in self.net.weights.iter_mut().chain(self.net.biases.iter_mut()) {
+            for row in 0..mat.get_nrows() {
+                for col in 0..mat.get_ncols() {
+                    mat.set(row, col, (mat.get(row, col) * scale).round() / scale);
+                }
+            }
+        }
+    }
+
+    /// Replace every weight and bias that isn't a number (NaN or infinite) with 0. Returns how many were replaced
+    pub fn sanitize_genome(&mut self) -> usize {
+        let mut num_replaced : usize = 0;
+        for mat in self.net.weights.iter_mut().chain(self.net.biases.iter_mut()) {
+            for row in 0..mat.get_nrows() {
+                for col in 0..mat.get_ncols() {
+                    if !mat.get(row, col).is_finite() {
+                        mat.set(row, col, 0.0);
+                        num_replaced += 1;
+                    }
+                }
+            }
+        }
+        return num_replaced;
+    }
+
+    /// Get every weight and bias of the network as one flat list (weights of all layers first, then biases)
+    #[allow(dead_code)]
+    pub fn get_genome(&self) -> Vec<f32> {
+        let mut genome : Vec<f32> = Vec::new();
+        for mat in self.net.weights.iter().chain(self.net.biases.iter()) {
+            for row in 0..mat.get_nrows() {
+                for col in 0..mat.get_ncols() {
+                    genome.push(mat.get(row, col));
+                }
+            }
+        }
+        return genome;
+    }
+
+    /// Get a short human-readable summary of the brain: its layer sizes, the weight norm of each layer and
+    /// the strongest input->output pathways (see `NeuralNet::get_pathway_strengths`)
+    pub fn describe(&self) -> String {
+        let layer_sizes : Vec<String> = self.net.get_layer_sizes().iter().map(|s| s.to_string()).collect();
+        let weight_norms : Vec<String> = self.net.get_weight_norms().iter().map(|n| format!("{:.1}", n)).collect();
+        let mut text = format!("Layers: {}\nWeight norms: {}\nStrongest pathways:", layer_sizes.join("-"), weight_norms.join(", "));
+
+        if let Some(pathways) = self.net.get_pathway_strengths() {
+            let mut strengths : Vec<(usize, usize, f32)> = Vec::new();
+            for output_idx in 0..pathways.get_nrows().min(self.output_node_types.len()) {
+                for input_idx in 0..pathways.get_ncols().min(self.input_node_types.len()) {
+                    strengths.push((input_idx, output_idx, pathways.get(output_idx, input_idx)));
+                }
+            }
+            strengths.sort_by(|a, b| b.2.abs().total_cmp(&a.2.abs()));
+            for (input_idx, output_idx, strength) in strengths.iter().take(BRAIN_DESCRIBE_NUM_PATHWAYS) {
+                text.push_str(&format!("\n  {:?} -> {:?} ({:+.0})", self.input_node_types[*input_idx], self.output_node_types[*output_idx], strength));
+            }
+        }
+        return text;
+    }
+
+    /// Describe the brain as a GraphViz (DOT) graph, e.g. for figures: `dot -Tpng brain.dot -o brain.png`. Layers go left
+    /// to right with input/output neurons labeled by their input/action type. Edges are blue for positive weights and red
+    /// for negative ones, thicker the stronger they are. Connections with a weight magnitude below `min_weight` are left out
+    ///
+    /// ```
+    /// use evolution_sim::creature::*;
+    ///
+    /// let brain = Brain::new(&vec![CreatureInputs::Energy], &vec![CreatureActions::Stay]);
+    /// let dot = brain.to_dot(0.0);
+    /// assert!(dot.starts_with("digraph brain {"));
+    /// assert!(dot.contains("label=\"Energy\"") && dot.contains("label=\"Stay\""));
+    /// assert!(dot.contains("n0_0 -> n1_0"));
+    /// assert!(!brain.to_dot(f32::MAX).contains("->"));
+    /// ```
+    pub fn to_dot(&self, min_weight : f32) -> String {
+        let layer_sizes = self.net.get_layer_sizes();
+        let max_weight = self.net.weights.iter()
+            .flat_map(|w| (0..w.get_nrows()).flat_map(move |to| (0..w.get_ncols()).map(move |from| w.get(to, from).abs())))
+            .fold(0.0, f32::max);
+
+        let mut dot = String::from("digraph brain {\n    rankdir=LR;\n    node [shape=circle, label=\"\"];\n");
+        for (layer_idx, layer_size) in layer_sizes.iter().enumerate() {
+            // Keep each layer in its own column
+            dot.push_str("    { rank=same;");
+            for node_idx in 0..*layer_size {
+                let label = if layer_idx == 0 {
+                    self.input_node_types.get(node_idx).map(|input| format!("{:?}", input))
+                } else if layer_idx == layer_sizes.len() - 1 {
+                    self.output_node_types.get(node_idx).map(|action| format!("{:?}", action))
+                } else {
+                    None
+                };
+                match label {
+                    Some(label) => dot.push_str(&format!(" n{}_{} [shape=box, label=\"{}\"];", layer_idx, node_idx, label)),
+                    None => dot.push_str(&format!(" n{}_{};", layer_idx, node_idx)),
+                }
+            }
+            dot.push_str(" }\n");
+        }
+
+        for (layer_idx, layer_weights) in self.net.weights.iter().enumerate() {
+            for to in 0..layer_weights.get_nrows() {
+                for from in 0..layer_weights.get_ncols() {
+                    let weight = layer_weights.get(to, from);
+                    if weight.abs() < min_weight || max_weight <= 0.0 {
+                        continue;
+                    }
+                    let color = if weight >= 0.0 { "blue" } else { "red" };
+                    let pen_width = BRAIN_DOT_MAX_PEN_WIDTH * weight.abs() / max_weight;
+                    dot.push_str(&format!("    n{}_{} -> n{}_{} [color={}, penwidth={:.2}, tooltip=\"{:.2}\"];\n",
+                        layer_idx, from, layer_idx + 1, to, color, pen_width, weight));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        return dot;
+    }
+
+    /// Perturbation-based saliency: how much each input neuron drives the action the brain picks for its current inputs.
+    /// Each input is nudged up and down (by SALIENCY_REL_STEP of its value, at least SALIENCY_MIN_STEP) and the change in
+    /// the chosen action's output neuron is measured (central difference). Inputs are the ones last set, so this should be
+    /// called after the creature has sensed its surroundings. Returns the chosen action and a saliency per input neuron
+    #[allow(dead_code)]
+    pub fn get_input_saliency(&self) -> (CreatureActions, Vec<(CreatureInputs, f32)>) {
+        let mut net = self.net.clone();
+        if net.activations.len() != net.num_layers {
+            net.reset_activations();
+        }
+        let chosen_idx = net.evaluate_network().unwrap_or(0);
+
+        let mut saliency : Vec<(CreatureInputs, f32)> = Vec::with_capacity(self.input_node_types.len());
+        for (input_idx, input_type) in self.input_node_types.iter().enumerate() {
+            let orig_val = net.get_input_node(input_idx);
+            let step = (orig_val.abs() * SALIENCY_REL_STEP).max(SALIENCY_MIN_STEP);
+
+            let mut output_at = |val : f32| {
+                net.set_input_node(input_idx, val);
+                let _ = net.evaluate_network();
+                net.get_output_activations()[chosen_idx]
+            };
+            let diff = output_at(orig_val + step) - output_at(orig_val - step);
+            net.set_input_node(input_idx, orig_val);
+            saliency.push((*input_type, diff.abs() / 2.0));
+        }
+
+        return (self.output_node_types[chosen_idx], saliency);
+    }
+
+    /// Run the brain on made-up input values (one per input neuron) without disturbing its real state.
+    /// Returns the activations of every layer, input layer first
+    pub fn get_activations_for_inputs(&self, inputs : &[f32]) -> Vec<Vec<f32>> {
+        return self.net.evaluate_layers(inputs);
+    }
+
+    /// Get the weights between each pair of neighboring layers (entry (i, j) of matrix n connects
+    /// neuron j of layer n to neuron i of layer n+1)
+    pub fn get_weights(&self) -> &[Matrix<f32>] {
+        return &self.net.weights;
+    }
+
+    /// Get the lowest and highest values a weight or bias can take
+    pub fn get_param_range() -> (f32, f32) {
+        return (BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
+    }
+
+    /// Get the current value of a weight or bias. Returns None if the brain doesn't have it
+    pub fn get_param(&self, param : BrainParam) -> Option<f32> {
+        let (layer, row, col) = match param {
+            BrainParam::Weight {layer, to, from} => (self.net.weights.get(layer)?, to, from),
+            BrainParam::Bias {layer, node} => (self.net.biases.get(layer)?, node, 0),
+        };
+        if row >= layer.get_nrows() || col >= layer.get_ncols() {
+            return None;
+        }
+        return Some(layer.get(row, col));
+    }
+
+    /// Set a weight or bias, clamped to the range in `get_param_range`. Returns the value that was actually set,
+    /// or None (and changes nothing) if the brain doesn't have that weight/bias
+    pub fn set_param(&mut self, param : BrainParam, value : f32) -> Option<f32> {
+        let entry = match param {
+            BrainParam::Weight {layer, to, from} => self.net.get_weight_mut(layer, to, from)?,
+            BrainParam::Bias {layer, node} => self.net.get_bias_mut(layer, node)?,
+        };
+        if value.is_nan() {
+            return None;
+        }
+        *entry = value.clamp(BRAIN_V2_MIN_INIT_NODE_VAL, BRAIN_V2_MAX_INIT_NODE_VAL);
+        return Some(*entry);
+    }
+
+    /// Number of connections that have to be evaluated for the brain to make one decision
+    pub fn get_compute_cost(&self) -> usize {
+        return self.net.get_num_connections();
+    }
+
     /// Set the value of the input neuron at specified index
     pub fn set_input(&mut self, neuron_idx : usize, value : f32) {
         self.net.set_input_node(neuron_idx, value);
@@ -570,3 +1458,181 @@ impl Brain {
 }
 
 
+#[cfg(test)]
+mod creature_test {
+    use super::*;
+    use crate::sim_rng::run_seeded;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const GAUSSIAN_ONLY : MutationMix = MutationMix {gaussian : 1.0, sign_flip : 0.0, reset : 0.0, swap : 0.0, layer_scale : 0.0};
+
+    /// Get how far a mutated copy of `parent`'s brain ends up from it
+    fn get_mutation_distance(parent : &CreatureV1, schedule : MutationSchedule, seed : u64) -> f32 {
+        let mutation = MutationConfig {prob : 1.0, mix : GAUSSIAN_ONLY, schedule : schedule, anneal_rate : 1.0};
+        let child = run_seeded(&mut Some(StdRng::seed_from_u64(seed)), || CreatureV1::new_offspring(1, parent, &mutation, 10));
+        return parent.brain.net.distance(&child.brain.net).unwrap();
+    }
+
+    /// Get a creature whose brain weights/biases are all 0, so mutations never get clamped at the bounds
+    fn get_blank_creature() -> CreatureV1 {
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        for mat in creature.brain.net.weights.iter_mut().chain(creature.brain.net.biases.iter_mut()) {
+            *mat = Matrix::new(mat.get_nrows(), mat.get_ncols());
+        }
+        return creature;
+    }
+
+    #[test]
+    fn test_genome_distance() {
+        let params = CreatureParams::new();
+        let (creature_a, creature_b) = (CreatureV1::new(0, &params), CreatureV1::new(1, &params));
+        assert_eq!(creature_a.brain.genome_distance(&creature_a.brain), 0.0);
+        assert_eq!(creature_a.brain.genome_distance(&creature_b.brain), creature_b.brain.genome_distance(&creature_a.brain));
+        assert!(creature_a.brain.genome_distance(&creature_b.brain) > 0.0);
+
+        // Brains with a different number of inputs are infinitely far apart
+        let other_shape = CreatureV1::new_with_inputs(2, &params, &vec![CreatureInputs::Energy]);
+        assert_eq!(creature_a.brain.genome_distance(&other_shape.brain), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_rest_energy_gain() {
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        let energy = creature.energy;
+        assert_eq!(creature.perform_next_action(Rest, false), Rest);
+        assert_eq!(creature.energy, energy + DEFAULT_REST_ENERGY_GAIN);
+
+        // Resting doesn't go over the max energy
+        creature.energy = creature.get_max_energy();
+        creature.perform_next_action(Rest, false);
+        assert_eq!(creature.energy, creature.get_max_energy());
+    }
+
+    #[test]
+    fn test_apply_action_result() {
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        let results = [ActionResult::Moved, ActionResult::Ate, ActionResult::Blocked, ActionResult::ReachedBeacon,
+                       ActionResult::Killed, ActionResult::Reproduced(2), ActionResult::Ate, ActionResult::NoEffect];
+        for result in results {
+            creature.apply_action_result(result);
+            assert_eq!(creature.last_action_result, result);
+        }
+
+        // Moving, eating and reaching the beacon all cover a space. Only eating counts as food
+        assert_eq!(creature.distance_traveled, 4);
+        assert_eq!(creature.food_eaten, 2);
+        assert_eq!(creature.num_kills, 1);
+    }
+
+    #[test]
+    fn test_energy_history() {
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        assert!(creature.energy_history.is_empty());
+
+        // Each step records the energy the creature started it with, keeping only the most recent steps (oldest first)
+        let num_steps = ENERGY_HISTORY_LEN + 10;
+        for step in 0..num_steps {
+            creature.energy = 100 + step;
+            creature.perform_next_action(Stay, false);
+            assert_eq!(creature.energy_history.len(), (step + 1).min(ENERGY_HISTORY_LEN));
+        }
+        let expected : Vec<usize> = (num_steps - ENERGY_HISTORY_LEN..num_steps).map(|step| 100 + step).collect();
+        assert_eq!(creature.energy_history.iter().copied().collect::<Vec<usize>>(), expected);
+
+        // Dead creatures stop recording
+        creature.energy = 0;
+        creature.perform_next_action(Stay, false);
+        assert_eq!(creature.energy_history.iter().copied().collect::<Vec<usize>>(), expected);
+    }
+
+    #[test]
+    fn test_input_saliency() {
+        // Input 1 drives output 2 through one path (doubled on the way) and input 3 drives output 0 through another
+        let mut creature = get_blank_creature();
+        let net = &mut creature.brain.net;
+        for (input, hidden, output, weight) in [(1, 0, 2, 2.0), (3, 1, 0, 1.0)] {
+            *net.get_weight_mut(0, hidden, input).unwrap() = weight;
+            *net.get_weight_mut(1, hidden, hidden).unwrap() = 1.0;
+            *net.get_weight_mut(2, output, hidden).unwrap() = 1.0;
+        }
+        net.set_input_node(1, 5.0);
+        net.set_input_node(3, 1.0);
+
+        // Output 2 wins, and only input 1 affects it. Nudging it by 1 either way moves the output by 2
+        let (action, saliency) = creature.brain.get_input_saliency();
+        assert_eq!(action, creature.output_neuron_types[2]);
+        assert_eq!(saliency.len(), creature.input_neuron_types.len());
+        for (input_idx, (input_type, value)) in saliency.iter().enumerate() {
+            assert_eq!(*input_type, creature.input_neuron_types[input_idx]);
+            assert_eq!(*value, if input_idx == 1 {2.0} else {0.0});
+        }
+
+        // Probing leaves the inputs alone
+        assert_eq!(creature.brain.net.get_input_node(1), 5.0);
+        assert_eq!(creature.brain.net.get_input_node(3), 1.0);
+    }
+
+    #[test]
+    fn test_brain_to_dot() {
+        // One input and one output, with only three connections left
+        let mut brain = Brain::new(&vec![CreatureInputs::Energy], &vec![CreatureActions::Stay]);
+        for mat in brain.net.weights.iter_mut() {
+            *mat = Matrix::new(mat.get_nrows(), mat.get_ncols());
+        }
+        *brain.net.get_weight_mut(0, 0, 0).unwrap() = 10.0;
+        *brain.net.get_weight_mut(1, 0, 0).unwrap() = -5.0;
+        *brain.net.get_weight_mut(2, 0, 0).unwrap() = 2.0;
+
+        let dot = brain.to_dot(3.0);
+        let lines : Vec<&str> = dot.lines().collect();
+        assert_eq!(lines[..3], ["digraph brain {", "    rankdir=LR;", "    node [shape=circle, label=\"\"];"]);
+
+        // A column of nodes per layer, labelled at the ends
+        assert_eq!(lines[3], "    { rank=same; n0_0 [shape=box, label=\"Energy\"]; }");
+        assert_eq!(lines[4], "    { rank=same; n1_0; n1_1; n1_2; n1_3; n1_4; n1_5; }");
+        assert_eq!(lines[5], "    { rank=same; n2_0; n2_1; n2_2; n2_3; n2_4; n2_5; }");
+        assert_eq!(lines[6], "    { rank=same; n3_0 [shape=box, label=\"Stay\"]; }");
+
+        // Connections over the min weight, sized relative to the strongest one and colored by sign
+        assert_eq!(lines[7], "    n0_0 -> n1_0 [color=blue, penwidth=5.00, tooltip=\"10.00\"];");
+        assert_eq!(lines[8], "    n1_0 -> n2_0 [color=red, penwidth=2.50, tooltip=\"-5.00\"];");
+        assert_eq!(lines[9..], ["}"]);
+    }
+
+    #[test]
+    fn test_anneal_by_generation() {
+        let mut parent = get_blank_creature();
+
+        // A first generation child is mutated at half strength, a 100th generation one at 1/100th
+        let constant = get_mutation_distance(&parent, MutationSchedule::Constant, 1);
+        assert!((get_mutation_distance(&parent, MutationSchedule::AnnealByGeneration, 1) - constant / 2.0).abs() < constant * 0.01);
+        parent.generation = 99;
+        assert!(get_mutation_distance(&parent, MutationSchedule::AnnealByGeneration, 1) < constant * 0.02);
+    }
+
+    #[test]
+    fn test_self_adaptive() {
+        let mutation = MutationConfig {prob : 1.0, mix : GAUSSIAN_ONLY, schedule : MutationSchedule::SelfAdaptive, anneal_rate : 0.0};
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        assert_eq!(creature.brain.mutation_scale, 1.0);
+
+        // The scale gene changes every generation but stays in bounds
+        let mut scales : Vec<f32> = Vec::new();
+        run_seeded(&mut Some(StdRng::seed_from_u64(3)), || {
+            for id in 1..200 {
+                creature = CreatureV1::new_offspring(id, &creature, &mutation, 10);
+                scales.push(creature.brain.mutation_scale);
+            }
+        });
+        assert!(scales.iter().all(|s| (MIN_MUTATION_SCALE..=MAX_MUTATION_SCALE).contains(s)));
+        assert!(scales.windows(2).all(|w| w[0] != w[1]));
+
+        // A brain with a small scale gene gets small mutations, and other schedules leave the gene alone
+        let mut parent = get_blank_creature();
+        parent.brain.mutation_scale = 0.05;
+        let constant = get_mutation_distance(&parent, MutationSchedule::Constant, 1);
+        assert!(get_mutation_distance(&parent, MutationSchedule::SelfAdaptive, 1) < constant * 0.2);
+        let child = CreatureV1::new_offspring(1, &parent, &MutationConfig {schedule : MutationSchedule::Constant, ..mutation}, 10);
+        assert_eq!(child.brain.mutation_scale, 0.05);
+    }
+}
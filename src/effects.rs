@@ -0,0 +1,185 @@
+/** ===============================================================================
+ * File: effects.rs
+ * Author: Scott Stack
+ * Description: Short visual effects drawn over the board in the GUI (a ring where a creature
+ * was killed, a sparkle where one was born and a fade where one died). They're started from
+ * environment events and only drawn on top of the board, so they never change what's on it
+ * ===============================================================================*/
+use evolution_sim::events::*;
+use evolution_sim::creature::CreaturePosition;
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const MAX_ACTIVE_EFFECTS : usize = 150;     // Max number of effects showing at once. The oldest are dropped after this
+pub const MAX_EFFECTS_PER_BATCH : usize = 20;   // Max number of each kind of effect started from one batch of events (e.g. a mass die-off)
+const KILL_RING_DURATION_S : f64 = 0.5;
+const BIRTH_SPARKLE_DURATION_S : f64 = 0.4;
+const DEATH_FADE_DURATION_S : f64 = 0.8;
+const KILL_RING_MAX_RADIUS : f32 = 2.0;         // Radius the ring grows to, in grid spaces
+const BIRTH_SPARKLE_LENGTH : f32 = 0.8;         // How far the sparkle's rays reach from the center of the space, in grid spaces
+const KILL_RING_COLOR : Color = Color::new(1.0, 0.2, 0.1, 1.0);
+const BIRTH_SPARKLE_COLOR : Color = Color::new(1.0, 0.95, 0.6, 1.0);
+const DEATH_FADE_COLOR : Color = Color::new(0.5, 0.5, 0.5, 0.8);
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Kinds of visual effects
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EffectKind {
+    KillRing,       // Expanding ring where a creature was killed
+    BirthSparkle,   // Sparkle where a creature was born
+    DeathFade,      // Grey square fading out where a creature died of old age/starvation
+}
+
+/// One effect that's showing on the board
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VisualEffect {
+    pub kind : EffectKind,
+    pub position : CreaturePosition,    // Space the effect is centered on
+    pub start_time : f64,               // Time (from `get_time`) the effect started
+}
+
+/// Every effect that's still showing (oldest first)
+#[derive(Debug, Clone, Default)]
+pub struct EffectsLayer {
+    pub effects : VecDeque<VisualEffect>,
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl EffectKind {
+
+    /// Get how long the effect lasts in seconds
+    pub fn get_duration(&self) -> f64 {
+        return match self {
+            EffectKind::KillRing => KILL_RING_DURATION_S,
+            EffectKind::BirthSparkle => BIRTH_SPARKLE_DURATION_S,
+            EffectKind::DeathFade => DEATH_FADE_DURATION_S,
+        };
+    }
+}
+
+impl EffectsLayer {
+
+    /// Create a new layer with no effects showing
+    pub fn new() -> EffectsLayer {
+        return EffectsLayer {
+            effects : VecDeque::new(),
+        };
+    }
+
+    /// Start the effects for a batch of events. Creatures that were killed get a kill ring rather than a death fade.
+    /// Only the first `MAX_EFFECTS_PER_BATCH` of each kind are started so a busy step doesn't cover the board
+    pub fn add_for_events(&mut self, events : &[EnvEvent], now : f64) {
+        let mut num_started = [0; 3];
+        for event in events.iter() {
+            let (kind, position) = match event {
+                EnvEvent::Kill {position, ..} => (EffectKind::KillRing, *position),
+                EnvEvent::Birth {position, ..} => (EffectKind::BirthSparkle, *position),
+                EnvEvent::Death {killed : false, position, ..} => (EffectKind::DeathFade, *position),
+                _ => continue,
+            };
+            if num_started[kind as usize] >= MAX_EFFECTS_PER_BATCH {
+                continue;
+            }
+            num_started[kind as usize] += 1;
+
+            if self.effects.len() >= MAX_ACTIVE_EFFECTS {
+                self.effects.pop_front();
+            }
+            self.effects.push_back(VisualEffect {kind : kind, position : position, start_time : now});
+        }
+    }
+
+    /// Drop the effects that have finished
+    pub fn update(&mut self, now : f64) {
+        self.effects.retain(|e| now - e.start_time < e.kind.get_duration());
+    }
+
+    /// Draw every effect that's showing on top of a board drawn with the given grid sizes
+    pub fn draw(&self, now : f64, grid_x_size : f32, grid_y_size : f32, x_offset : f32) {
+        for effect in self.effects.iter() {
+            // How far along the effect is (0 = just started, 1 = done)
+            let progress = ((now - effect.start_time) / effect.kind.get_duration()).clamp(0.0, 1.0) as f32;
+            let x = x_offset + effect.position.x as f32 * grid_x_size;
+            let y = effect.position.y as f32 * grid_y_size;
+            let center = vec2(x + grid_x_size / 2.0, y + grid_y_size / 2.0);
+            let grid_size = grid_x_size.min(grid_y_size);
+
+            match effect.kind {
+                EffectKind::KillRing => {
+                    let radius = grid_size * (0.5 + (KILL_RING_MAX_RADIUS - 0.5) * progress);
+                    draw_circle_lines(center.x, center.y, radius, 2.0, with_alpha(KILL_RING_COLOR, 1.0 - progress));
+                },
+                EffectKind::BirthSparkle => {
+                    // Four diagonal rays that grow outwards while fading
+                    let length = grid_size * BIRTH_SPARKLE_LENGTH * (0.3 + 0.7 * progress);
+                    let color = with_alpha(BIRTH_SPARKLE_COLOR, 1.0 - progress);
+                    for (dx, dy) in [(1.0, 1.0), (1.0, -1.0), (-1.0, 1.0), (-1.0, -1.0)] {
+                        draw_line(center.x, center.y, center.x + dx * length, center.y + dy * length, 1.5, color);
+                    }
+                },
+                EffectKind::DeathFade => {
+                    let color = DEATH_FADE_COLOR;
+                    draw_rectangle(x, y, grid_x_size, grid_y_size, with_alpha(color, color.a * (1.0 - progress)));
+                },
+            }
+        }
+    }
+
+    /// Remove every effect (e.g. when the board is replaced)
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+}
+
+/// Get a copy of a color with a different alpha
+fn with_alpha(color : Color, alpha : f32) -> Color {
+    return Color::new(color.r, color.g, color.b, alpha);
+}
+
+
+#[cfg(test)]
+mod effects_test {
+    use super::*;
+
+    #[test]
+    fn test_add_for_events() {
+        let position = CreaturePosition {x : 2, y : 3};
+        let mut events = vec![
+            EnvEvent::Kill {killer_id : 0, victim_id : 1, position : position},
+            EnvEvent::Death {id : 1, killed : true, position : position},
+            EnvEvent::Death {id : 2, killed : false, position : position},
+            EnvEvent::StepCompleted {time_step : 1, num_creatures : 1, num_food : 0, num_species : 1},
+        ];
+        let mut layer = EffectsLayer::new();
+        layer.add_for_events(&events, 10.0);
+        assert_eq!(layer.effects.iter().map(|e| e.kind).collect::<Vec<EffectKind>>(), vec![EffectKind::KillRing, EffectKind::DeathFade]);
+
+        // Effects end after their own durations
+        layer.update(10.0 + KILL_RING_DURATION_S);
+        assert_eq!(layer.effects.iter().map(|e| e.kind).collect::<Vec<EffectKind>>(), vec![EffectKind::DeathFade]);
+        layer.update(10.0 + DEATH_FADE_DURATION_S);
+        assert!(layer.effects.is_empty());
+
+        // A big batch only starts a limited number of each kind, and the total showing is capped
+        events = (0..100).map(|id| EnvEvent::Birth {id : id, parent_id : None, position : position}).collect();
+        layer.add_for_events(&events, 20.0);
+        assert_eq!(layer.effects.len(), MAX_EFFECTS_PER_BATCH);
+        for batch in 0..MAX_ACTIVE_EFFECTS {
+            layer.add_for_events(&events[..1], 21.0 + batch as f64);
+        }
+        assert_eq!(layer.effects.len(), MAX_ACTIVE_EFFECTS);
+        assert_eq!(layer.effects.front().unwrap().start_time, 21.0);
+    }
+}
@@ -5,19 +5,85 @@
  */
 use std::thread;
 use std::time;
-use crate::environment::*;
-use crate::creature::*;
+use evolution_sim::environment::*;
+use evolution_sim::creature::*;
+use evolution_sim::experiments::*;
+use evolution_sim::snapshot::*;
+use evolution_sim::catastrophes::*;
+use evolution_sim::analysis::{NoveltyDescriptor, get_species_color};
+use evolution_sim::commands::*;
+use evolution_sim::render::{Renderer, SpeedGovernor};
+use evolution_sim::replay::Replay;
+use evolution_sim::events::EventStreamWriter;
+use evolution_sim::params_watch::ParamsWatcher;
 use std::io;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
 
 // Constant escape sequences for visualization
 const PREVIOUS_LINE_ESAPE_SEQ : &str = "\x1B[F";
 const CLEAR_SCREEN_ESCAPE_SEQ : &str = "\x1B[1J";
+const RESET_COLOR_ESCAPE_SEQ : &str = "\x1B[0m";
 
 // characters to be printed for each space type
 const CREATURE_PRINT_CHAR : &str = "⚇";
 const WALL_PRINT_CHAR : &str = "▮";
 const FOOD_PRINT_CHAR : &str = "+";
 const FIGHT_PRINT_CHAR : &str = "☠";
+const BEACON_PRINT_CHAR : &str = "◎";
+
+// Scale (in pixels per space) of PNG snapshots written by the console demo
+const SNAPSHOT_PNG_SCALE : u32 = 8;
+
+// Number of steps between rewrites of the lifetime CSV file (it's also written when the sim ends)
+const LIFETIME_CSV_WRITE_INTERVAL : usize = 50;
+
+// Number of steps between rewrites of the brain DOT file (it's also written when the sim ends)
+const BRAIN_DOT_WRITE_INTERVAL : usize = 50;
+
+// File the summary report is written to when the demo ends (unless another one is given)
+pub const DEFAULT_REPORT_FILE : &str = "summary_report.md";
+
+// Default number of steps between time-lapse frames
+pub const DEFAULT_TIMELAPSE_INTERVAL : usize = 10;
+
+// How long a command socket client gets to send its next command before it's hung up on
+#[cfg(unix)]
+const COMMAND_READ_TIMEOUT : time::Duration = time::Duration::from_millis(200);
+
+// How often the command socket is checked while the demo is paused
+const PAUSED_POLL_INTERVAL : time::Duration = time::Duration::from_millis(100);
+
+/// Files the console demo writes while it runs
+pub struct DemoOutputs {
+    pub snapshot_file : Option<String>,     // PNG image of the board at the peak population
+    pub lifetime_csv_file : Option<String>, // Summary of every dead creature's life as CSV (rewritten periodically)
+    pub brain_dot_file : Option<String>,    // Brain of the oldest living creature as a GraphViz graph (rewritten periodically)
+    pub report_file : String,               // Summary report, written when the creatures die out for good
+    pub events_file : Option<String>,       // Every event (step summaries, births, deaths, kills, restarts, ...) as JSON lines, written as they happen
+}
+
+/// Unix socket that scripts can send commands to (see `SimCommand`) while the demo runs, e.g.
+/// `echo stats | nc -U <socket>`. Each line is one command and gets a one line reply
+#[cfg(unix)]
+pub struct CommandSocket {
+    listener : UnixListener,
+    path : String,
+}
+
+/// Prints the board to the terminal with a character per space
+pub struct ConsoleRenderer {
+    pub color_by_species : bool,    // Print creatures in their species' color (using 24-bit ANSI colors)
+}
+
+/// Where and how often time-lapse frames are exported
+pub struct TimelapseOptions {
+    pub dir : String,               // Directory the frames are written to (frame_<step>.png)
+    pub every_n_steps : usize,      // Number of steps between frames
+    pub stats_footer : bool,        // Add a footer with the step, population and kills to each frame
+}
 
 
 // Default parameters that the console simulation visualization will start with
@@ -31,57 +97,452 @@ const DEFAULT_CONSOLE_PARAMS : EnvironmentParams = EnvironmentParams {
     energy_per_kill : DEFAULT_ENERGY_PER_KILL,
     max_offspring_per_reproduce : DEFAULT_OFFSPRING_PER_REPRODUCE,
     mutation_prob : DEFAULT_MUTATION_PROB,
+    mutation_mix : DEFAULT_MUTATION_MIX,
+    mutation_schedule : MutationSchedule::Constant,
+    mutation_anneal_rate : DEFAULT_MUTATION_ANNEAL_RATE,
+    offspring_energy_fraction : DEFAULT_OFFSPRING_ENERGY_FRACTION,
+    gestation_steps : DEFAULT_GESTATION_STEPS,
+    gestation_upkeep_energy : DEFAULT_GESTATION_UPKEEP_ENERGY,
     avg_new_food_per_day : NEW_FOOD_PIECES_PER_STEP, 
     creature_repro_energy_cost : DEFAULT_REPRODUCE_ENERGY_COST,
     creature_starting_energy : DEFAULT_ENERGY_LEVEL,
+    enable_position_inputs : false,
+    enable_wall_distance_input : false,
+    enable_move_blocked_input : false,
+    one_hot_categorical_inputs : false,
+    enable_hearing_input : false,
+    enable_territory : false,
+    enable_rest : false,
+    enable_tribe_vision : false,
+    enable_decal_vision : false,
+    enable_beacon : false,
+    energy_per_beacon_reach : DEFAULT_ENERGY_PER_BEACON_REACH,
+    enable_novelty : false,
+    novelty_descriptor : NoveltyDescriptor::ActionDistribution,
+    novelty_weight : DEFAULT_NOVELTY_WEIGHT,
+    enable_speciation : false,
+    speciation_threshold : DEFAULT_SPECIATION_THRESHOLD,
+    speciation_interval : DEFAULT_SPECIATION_INTERVAL,
+    creature_order : CreatureOrder::Insertion,
+    view_distance : DEFAULT_VIEW_DISTANCE,
+    evolve_view_distance : false,
+    view_energy_cost : DEFAULT_VIEW_ENERGY_COST,
+    enable_metabolic_cost : false,
+    metabolic_energy_cost : DEFAULT_METABOLIC_ENERGY_COST,
+    hearing_radius : DEFAULT_HEARING_RADIUS,
+    brain_compute_budget : 0,
+    action_delay_steps : 0,
+    territory_decay : DEFAULT_TERRITORY_DECAY,
+    tribe_vision_radius : DEFAULT_TRIBE_VISION_RADIUS,
+    season_length : 0,
+    mating_season_start : DEFAULT_MATING_SEASON_START,
+    mating_season_length : DEFAULT_MATING_SEASON_LENGTH,
+    food_die_off : DEFAULT_FOOD_DIE_OFF,
+    plague : DEFAULT_PLAGUE,
+    wall_collapse : DEFAULT_WALL_COLLAPSE,
+    enable_disease : false,
+    initial_infection_fraction : DEFAULT_INITIAL_INFECTION_FRACTION,
+    infection_prob : DEFAULT_INFECTION_PROB,
+    infection_duration : DEFAULT_INFECTION_DURATION,
+    infection_energy_drain : DEFAULT_INFECTION_ENERGY_DRAIN,
+    enable_elevation : false,
+    elevation_feature_size : DEFAULT_ELEVATION_FEATURE_SIZE,
+    elevation_energy_cost : DEFAULT_ELEVATION_ENERGY_COST,
+    high_ground_view_bonus : 0,
+    enable_river : false,
+    river_flow_strength : DEFAULT_RIVER_FLOW_STRENGTH,
+    river_source_food : DEFAULT_RIVER_SOURCE_FOOD,
+    enable_swimming : false,
+    initial_swimmer_fraction : DEFAULT_INITIAL_SWIMMER_FRACTION,
+    enable_large_bodies : false,
+    initial_large_fraction : DEFAULT_INITIAL_LARGE_FRACTION,
+    swim_energy_cost : DEFAULT_SWIM_ENERGY_COST,
+    immigration_rate : 0.0,
+    immigrant_source : ImmigrantSource::Random,
 };
 
+/// Run two copies of the console environment seeded with `seed` in lockstep and make sure they stay identical
+/// for `num_steps` steps. Prints the outcome and returns true if the copies never diverged
+pub fn run_determinism_audit(num_steps : usize, seed : u64) -> bool {
+    let mut params = DEFAULT_CONSOLE_PARAMS;
+    params.enable_speciation = true; // Exercise as much of the step code as possible
+    println!("Running determinism audit for {} steps with seed {}...", num_steps, seed);
+
+    return match audit_determinism(&params, seed, num_steps) {
+        Ok(steps_checked) => {
+            println!("PASS: state hashes matched for all {} steps", steps_checked);
+            true
+        },
+        Err(time_step) => {
+            println!("FAIL: state hashes diverged at time step {}", time_step);
+            false
+        },
+    };
+}
+
+/// Record a replay of `num_steps` steps of the console environment seeded with `seed`. The environment is saved to
+/// `save_file` first, and the replay recorded from that save so it can be verified against it later. Returns true if
+/// both files were written
+pub fn record_replay(replay_file : &str, save_file : &str, num_steps : usize, seed : u64) -> bool {
+    let env = EnvironmentV1::new_seeded(&DEFAULT_CONSOLE_PARAMS, seed);
+    let json = env.to_json();
+    if let Err(e) = std::fs::write(save_file, &json) {
+        println!("Error: could not save the starting environment to {}. Error = {e}", save_file);
+        return false;
+    }
+
+    // Record from what's actually in the save so verifying against it re-simulates exactly the same run
+    let mut env = match EnvironmentV1::from_json(&json) {
+        Ok(env) => env,
+        Err(msg) => {
+            println!("Error: could not reload the starting environment. Error = {}", msg);
+            return false;
+        }
+    };
+    println!("Recording a replay of {} steps with seed {}...", num_steps, seed);
+    let replay = Replay::record(&mut env, seed, num_steps);
+    if let Err(e) = std::fs::write(replay_file, replay.to_bytes()) {
+        println!("Error: could not write the replay to {}. Error = {e}", replay_file);
+        return false;
+    }
+    println!("Recorded {} steps to {} (starting environment in {})", replay.deltas.len(), replay_file, save_file);
+    return true;
+}
+
+/// Re-simulate the replay in `replay_file` from the save it started from and make sure every step matches.
+/// Prints the outcome and returns true if the replay is still valid
+pub fn run_replay_verification(replay_file : &str, save_file : &str) -> bool {
+    let replay = match std::fs::read(replay_file).map_err(|e| e.to_string()).and_then(|bytes| Replay::from_bytes(&bytes)) {
+        Ok(replay) => replay,
+        Err(msg) => {
+            println!("Error: could not read replay {}. Error = {}", replay_file, msg);
+            return false;
+        }
+    };
+    let mut env = match EnvironmentV1::read_json_file(save_file) {
+        Ok(env) => env,
+        Err(_) => return false,
+    };
+
+    println!("Verifying {} steps of {} (recorded by version {} with seed {})...", replay.deltas.len(), replay_file, replay.crate_version, replay.seed);
+    return match replay.verify(&mut env) {
+        Ok(steps_checked) => {
+            println!("PASS: all {} steps matched the replay", steps_checked);
+            true
+        },
+        Err(msg) => {
+            println!("FAIL: {}", msg);
+            false
+        },
+    };
+}
+
 /// Run an environment simulation that infinitely runs a bunch of simulations for
-/// demonstration purposes
-pub fn run_console_demo_mode() {
-    let mut env = EnvironmentV1::new_rand(&DEFAULT_CONSOLE_PARAMS);
+/// demonstration purposes. The files in `outputs` are written as it goes (see DemoOutputs). `run_policy` decides
+/// whether/how the simulation is restarted (e.g. after all creatures die). If `color_by_species` is set, species
+/// tracking is turned on and creatures are printed in their species' color. If `command_socket` is specified,
+/// commands sent to that Unix socket are carried out between steps (see CommandSocket)
+pub fn run_console_demo_mode(outputs : DemoOutputs, timelapse : Option<TimelapseOptions>, command_socket : Option<String>,
+                             params_file : Option<String>, governor : Option<SpeedGovernor>, mut run_policy : RunPolicy, color_by_species : bool) {
+    let mut params = DEFAULT_CONSOLE_PARAMS;
+    params.enable_speciation = params.enable_speciation || color_by_species;
+    let mut env = EnvironmentV1::new_rand(&params);
+    let mut peak_creatures : usize = 0;
+    let mut command_state = CommandState::default();
+    let mut renderer = ConsoleRenderer {color_by_species : color_by_species};
+
+    #[cfg(unix)]
+    let command_socket = match command_socket.map(|path| CommandSocket::bind(&path)) {
+        Some(Ok(socket)) => Some(socket),
+        Some(Err(msg)) => {
+            println!("Error: {}", msg);
+            return;
+        },
+        None => None,
+    };
+    #[cfg(not(unix))]
+    if let Some(path) = command_socket {
+        println!("Warning: cannot listen for commands on {}, command sockets are only supported on Unix", path);
+    }
 
-    // Run one initial step
+    let mut event_stream = match outputs.events_file.as_ref().map(|filename| EventStreamWriter::create(filename)) {
+        Some(Ok(writer)) => Some(writer),
+        Some(Err(msg)) => {
+            println!("Error: {}", msg);
+            return;
+        },
+        None => None,
+    };
+    let mut params_watcher = params_file.map(|filename| ParamsWatcher::new(&filename));
+
+    // Run one initial step. The run's limits count from before it
+    let _ = run_policy.check_limits(&env);
     env.advance_step();
+    renderer.init(&env.get_snapshot());
+
+    let stop_reason = loop {
+        #[cfg(unix)]
+        if let Some(socket) = &command_socket {
+            socket.poll(&mut env, &mut command_state);
+        }
+        if command_state.quit {
+            break StopReason::Quit;
+        }
+        if let Some(watcher) = &mut params_watcher {
+            match watcher.poll(&mut env) {
+                Some(Ok(changed)) if changed.is_empty() => println!("Reloaded params from {} (no live params changed)", watcher.filename),
+                Some(Ok(changed)) => println!("Reloaded params from {}: changed {}", watcher.filename, changed.join(", ")),
+                Some(Err(msg)) => println!("Error: {}", msg),
+                None => (),
+            }
+        }
+        if command_state.paused {
+            thread::sleep(PAUSED_POLL_INTERVAL);
+            continue;
+        }
 
-    while env.num_creatures > 0 {
         env.advance_step();
-        show_env(&env);
 
-        // Wait a bit
-        thread::sleep(time::Duration::from_millis(500));
+        // Reseed the sim from the last run if the policy says it's time
+        if run_policy.maybe_restart(&mut env) {
+            println!("Restarted simulation with {:?} selection ({} restarts so far)", run_policy.selection, run_policy.num_restarts);
+        }
+        write_event_stream(&mut env, &mut event_stream);
+        if let Some(reason) = run_policy.check_limits(&env) {
+            break reason;
+        }
+
+        if let Some(filename) = &outputs.lifetime_csv_file {
+            if env.time_step % LIFETIME_CSV_WRITE_INTERVAL == 0 {
+                env.stats.save_lifetimes_csv(filename);
+            }
+        }
+
+        if let Some(filename) = &outputs.brain_dot_file {
+            if env.time_step % BRAIN_DOT_WRITE_INTERVAL == 0 {
+                save_oldest_brain_dot(&env, filename);
+            }
+        }
+
+        if let Some(timelapse) = &timelapse {
+            if env.time_step.is_multiple_of(timelapse.every_n_steps.max(1)) {
+                save_timelapse_frame(&env, timelapse);
+            }
+        }
+
+        renderer.render_snapshot(&env.get_snapshot());
+        show_run_stats(&env);
+
+        // Keep the snapshot up to date with the most populated board we've seen
+        if env.num_creatures > peak_creatures {
+            peak_creatures = env.num_creatures;
+            if let Some(filename) = &outputs.snapshot_file {
+                save_snapshot(&env, filename);
+            }
+        }
+
+        // Wait a bit (less when lots of creatures are alive if there's a speed governor)
+        thread::sleep(governor.map(|g| g.get_step_interval(env.num_creatures)).unwrap_or(time::Duration::from_millis(500)));
 
         // Go back number of lines
         // for _nline in 0..env.params.env_y_size {
         //     print!("{}", PREVIOUS_LINE_ESAPE_SEQ);
         // }
         print!("{}", CLEAR_SCREEN_ESCAPE_SEQ);
+    };
+
+    // Make sure the final records make it to the file
+    write_event_stream(&mut env, &mut event_stream);
+    if let Some(filename) = &outputs.lifetime_csv_file {
+        env.stats.save_lifetimes_csv(filename);
     }
+    if let Some(filename) = &outputs.brain_dot_file {
+        save_oldest_brain_dot(&env, filename);
+    }
+
+    renderer.shutdown();
+    env.save_summary_report(&outputs.report_file);
+    println!("Simulation ended in step {} ({:?}). Summary report written to {}", env.time_step, stop_reason, outputs.report_file);
 }
 
+#[cfg(unix)]
+impl CommandSocket {
+
+    /// Start listening for commands on a Unix socket at `path`. A socket left behind by an earlier run is replaced
+    pub fn bind(path : &str) -> Result<CommandSocket, String> {
+        use std::os::unix::fs::FileTypeExt;
+        if std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let listener = UnixListener::bind(path).map_err(|e| format!("could not listen for commands on {}. Error {e}", path))?;
+        listener.set_nonblocking(true).map_err(|e| format!("could not set up command socket {}. Error {e}", path))?;
+        return Ok(CommandSocket {listener : listener, path : path.to_string()});
+    }
 
-/// Print the current state of the environment board
-pub fn show_env(env : &EnvironmentV1) {
-    println!();
-    let num_dashes = env.params.env_x_size * 3 + 1;
-    println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
-    for y in 0..env.params.env_y_size {
-        print!("|");
-        for x in 0..env.params.env_x_size {
-            match env.positions[x][y] {
-                SpaceStates::BlankSpace => print!("   "),
-                SpaceStates::CreatureSpace(id) => print!(" {} ", CREATURE_PRINT_CHAR), // print!("{:3}", id % 1000), // just wrap around if the creature id goes beyond 3 digits 
-                SpaceStates::FoodSpace => print!(" {} ", FOOD_PRINT_CHAR),
-                SpaceStates::WallSpace => print!(" {} ", WALL_PRINT_CHAR),
-                SpaceStates::FightSpace(_ttl) => print!(" {} ", FIGHT_PRINT_CHAR),
+    /// Carry out the commands from every client that's connected since the last poll. Doesn't wait for new clients
+    pub fn poll(&self, env : &mut EnvironmentV1, state : &mut CommandState) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _addr)) => handle_command_client(stream, env, state),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    println!("Error: could not accept command connection. Error {e}");
+                    break;
+                },
             }
         }
-        print!("|");
+    }
+}
+
+#[cfg(unix)]
+impl Drop for CommandSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Reply to each command a client sends until it hangs up (or goes quiet for COMMAND_READ_TIMEOUT)
+#[cfg(unix)]
+fn handle_command_client(stream : UnixStream, env : &mut EnvironmentV1, state : &mut CommandState) {
+    if stream.set_nonblocking(false).and_then(|_| stream.set_read_timeout(Some(COMMAND_READ_TIMEOUT))).is_err() {
+        return;
+    }
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match SimCommand::parse(&line) {
+            Ok(command) => command.execute(env, state),
+            Err(msg) => format!("Error: {}", msg),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// Write the events that happened since the last call to the event stream (if there is one). If writing fails,
+/// the stream is closed so the error is only printed once
+fn write_event_stream(env : &mut EnvironmentV1, event_stream : &mut Option<EventStreamWriter>) {
+    let events = env.events.take_all();
+    if let Some(writer) = event_stream {
+        if let Err(msg) = writer.write_events(&events) {
+            println!("Error: {}. No more events will be written", msg);
+            *event_stream = None;
+        }
+    }
+}
+
+/// Write the brain of the oldest living creature as a GraphViz graph. Nothing is written if every creature is dead,
+/// so the file keeps the last brain that was written
+fn save_oldest_brain_dot(env : &EnvironmentV1, filename : &str) {
+    if let Some(creature) = env.creatures.iter().max_by_key(|c| c.age) {
+        if let Err(e) = std::fs::write(filename, creature.brain.to_dot(BRAIN_DOT_DEFAULT_MIN_WEIGHT)) {
+            println!("Error: could not write brain graph to {}. Error {e}", filename);
+        }
+    }
+}
+
+/// Write the current board as the next time-lapse frame (only available with the `image` feature)
+fn save_timelapse_frame(env : &EnvironmentV1, timelapse : &TimelapseOptions) {
+    let filename = format!("{}/frame_{:07}.png", timelapse.dir, env.time_step);
+
+    #[cfg(feature = "image")]
+    {
+        let img = if timelapse.stats_footer { env.render_image_with_stats(SNAPSHOT_PNG_SCALE) } else { env.render_image(SNAPSHOT_PNG_SCALE) };
+        if let Err(e) = img.save(&filename) {
+            println!("Error: could not save time-lapse frame {}. Error = {e}", filename);
+        }
+    }
+
+    #[cfg(not(feature = "image"))]
+    {
+        let _ = (env, SNAPSHOT_PNG_SCALE, timelapse.stats_footer);
+        println!("Warning: cannot write time-lapse frame {}, built without the `image` feature", filename);
+    }
+}
+
+/// Write a PNG snapshot of the environment (only available with the `image` feature)
+fn save_snapshot(env : &EnvironmentV1, filename : &str) {
+    #[cfg(feature = "image")]
+    env.save_snapshot_png(filename, SNAPSHOT_PNG_SCALE);
+
+    #[cfg(not(feature = "image"))]
+    {
+        let _ = (env, SNAPSHOT_PNG_SCALE);
+        println!("Warning: cannot write snapshot {}, built without the `image` feature", filename);
+    }
+}
+
+
+impl Renderer for ConsoleRenderer {
+
+    /// Start from a clear screen
+    fn init(&mut self, _snapshot : &EnvSnapshot) {
+        print!("{}", CLEAR_SCREEN_ESCAPE_SEQ);
+    }
+
+    /// Print the board and the key
+    fn render_snapshot(&mut self, snapshot : &EnvSnapshot) {
         println!();
+        let num_dashes = snapshot.x_size * 3 + 1;
+        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
+        for y in 0..snapshot.y_size {
+            print!("|");
+            for x in 0..snapshot.x_size {
+                if snapshot.is_beacon_at(x, y) {
+                    print!(" {} ", BEACON_PRINT_CHAR);
+                    continue;
+                }
+                match snapshot.get_cell(x, y) {
+                    SnapshotCell::Blank if snapshot.get_decal_at(x, y) == Some(DecalKind::Fight) => print!(" {} ", FIGHT_PRINT_CHAR),
+                    SnapshotCell::Blank => print!("   "),
+                    SnapshotCell::Creature(c_idx) if self.color_by_species => {
+                        let color = get_species_color(snapshot.creatures[c_idx].species_id);
+                        print!(" \x1B[38;2;{};{};{}m{}{} ", color.red, color.green, color.blue, CREATURE_PRINT_CHAR, RESET_COLOR_ESCAPE_SEQ);
+                    },
+                    SnapshotCell::Creature(_c_idx) => print!(" {} ", CREATURE_PRINT_CHAR),
+                    SnapshotCell::Food => print!(" {} ", FOOD_PRINT_CHAR),
+                    SnapshotCell::Wall => print!(" {} ", WALL_PRINT_CHAR),
+                }
+            }
+            print!("|");
+            println!();
+        }
+        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
+        println!("Key:");
+        println!("Creature = {}\nFood = {}\nWall = {}\nBeacon = {}", CREATURE_PRINT_CHAR, FOOD_PRINT_CHAR, WALL_PRINT_CHAR, BEACON_PRINT_CHAR);
+    }
+
+    /// Replies to commands just go under the board
+    fn show_reply(&mut self, reply : &str) {
+        println!("{}", reply);
+    }
+}
+
+/// Print the run stats that go under the board (mutation settings and how the dead creatures did)
+pub fn show_run_stats(env : &EnvironmentV1) {
+    println!("Mutation mix: {} ({:?})", env.params.mutation_mix, env.params.mutation_schedule);
+    if let Some(summary) = env.stats.summarize_lifetimes() {
+        println!("Avg of {} dead creatures: lifespan {:.1}, food {:.1}, kills {:.1}, offspring {:.1}, moved {:.1}, rotated {:.1}",
+            summary.num_records, summary.avg_lifespan, summary.avg_food_eaten, summary.avg_kills, summary.avg_offspring,
+            summary.avg_distance_traveled, summary.avg_rotations);
+    }
+    if !env.stats.immigrants.is_empty() {
+        let lineages = env.stats.get_immigrant_lineage_stats(&env.creatures);
+        let num_surviving = lineages.iter().filter(|l| l.num_alive > 0).count();
+        println!("Immigrants: {} ({} of their lineages still alive)", lineages.len(), num_surviving);
     }
-    println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
-    println!("Key:");
-    println!("Creature = {}\nFood = {}\nWall = {}", CREATURE_PRINT_CHAR, FOOD_PRINT_CHAR, WALL_PRINT_CHAR);
 }
 
 
@@ -97,6 +558,8 @@ q = quit
 d = display the current state of the environment
 p = print stats for all creatures that are alive
 n = next step. Run one simulation step
+b <id> = describe the brain of the creature with the given ID
+g <id> = print the brain of the creature with the given ID as a GraphViz (DOT) graph
 r = run until no creatures left
 ";
 
@@ -135,6 +598,18 @@ fn interactive_console_mode() {
             "d" => env.show(),
             "n" => env.advance_step(),
             "r" => run_full_sim(&mut env),
+            cmd if cmd.starts_with("b ") => {
+                match cmd[2..].trim().parse::<usize>().ok().and_then(|id| env.get_creature_idx_from_id(id).ok()) {
+                    Some(c_idx) => println!("{}", env.creatures[c_idx].brain.describe()),
+                    None => println!("No living creature with ID {}", cmd[2..].trim()),
+                }
+            },
+            cmd if cmd.starts_with("g ") => {
+                match cmd[2..].trim().parse::<usize>().ok().and_then(|id| env.get_creature(id)) {
+                    Some(creature) => println!("{}", creature.brain.to_dot(BRAIN_DOT_DEFAULT_MIN_WEIGHT)),
+                    None => println!("No living creature with ID {}", cmd[2..].trim()),
+                }
+            },
             "q" => break,
             _ => println!("Invalid input {}", choice_str),
         }
@@ -4,15 +4,28 @@
  * Created: 5/1/2022
  * Description: Implements graphical 2D environment using `macroquad` graphics lib
  * ===============================================================================*/
-use crate::creature::*;
-use crate::environment;
-use crate::environment::*;
+use evolution_sim::creature::*;
+use evolution_sim::environment;
+use evolution_sim::environment::*;
+use evolution_sim::experiments::*;
+use evolution_sim::snapshot::*;
+#[cfg(feature = "audio")]
+use crate::sounds::SoundBank;
+use crate::effects::EffectsLayer;
+use evolution_sim::analysis::{NoveltyDescriptor, SPECIES_HISTORY_LEN, get_species_color, GroupBy, get_group_stats, GenomeFingerprint, FINGERPRINT_SIZE, get_genome_fingerprint};
+use evolution_sim::wall_map::WALL_MAP_EXTENSION;
+use evolution_sim::events::EnvEvent;
+use evolution_sim::stats::summarize_group;
+use evolution_sim::commands::*;
+use evolution_sim::render::*;
+use evolution_sim::param_docs::*;
 use std::io::Write;
 use std::fs::File;
+use std::collections::{HashMap, VecDeque};
 
 use macroquad::prelude::*;
 use macroquad::ui::{
-    hash, root_ui,Skin,
+    hash, root_ui,Skin, Ui,
     widgets::{self},
 };
 
@@ -41,17 +54,143 @@ const STATS_PANEL_HEIGHT : f32 = WINDOW_HEIGHT_PX / 2.5;
 // Param panel params
 const PARAM_PANEL_WIDTH : f32 = 400.0;
 const PARAM_PANEL_HEIGHT : f32 = WINDOW_HEIGHT_PX / 2.5;
+const PARAM_TOOLTIP_WIDTH : f32 = 320.0;
+const PARAM_TOOLTIP_FONT_SIZE : f32 = 18.0;
+const PARAM_TOOLTIP_LINE_CHARS : usize = 38;    // Tooltip descriptions are wrapped to lines of at most this many characters
 
 // Control panel 1 (that sits on the right ide of the display)
 const CONTROL1_PANEL_WIDTH : f32 = 400.0;
 const CONTROL1_PANEL_HEIGHT : f32 = WINDOW_HEIGHT_PX / 5.0;
 
 // Control panel params (sits below the main board)
-const CONTROL2_PANEL_HEIGHT : f32 = 125.0 + PANEL_Y_PADDING;
+const CONTROL2_PANEL_HEIGHT : f32 = 175.0 + PANEL_Y_PADDING;
 const CONTROL2_PANEL_WIDTH : f32 = SCREEN_SIZE_X + PANEL_X_PADDING;
 
+// Mutation schedule choices shown in the param panel (labels line up with the options)
+const MUTATION_SCHEDULE_OPTIONS : [MutationSchedule; 3] = [MutationSchedule::Constant, MutationSchedule::AnnealByGeneration, MutationSchedule::SelfAdaptive];
+const MUTATION_SCHEDULE_LABELS : [&str; 3] = ["Constant", "Anneal by Generation", "Self-Adaptive"];
+
+// Novelty descriptor choices shown in the param panel
+const NOVELTY_DESCRIPTOR_OPTIONS : [NoveltyDescriptor; 2] = [NoveltyDescriptor::ActionDistribution, NoveltyDescriptor::Visitation];
+const NOVELTY_DESCRIPTOR_LABELS : [&str; 2] = ["Action Distribution", "Visitation"];
+
+// Immigrant source choices shown in the param panel
+const IMMIGRANT_SOURCE_OPTIONS : [ImmigrantSource; 2] = [ImmigrantSource::Random, ImmigrantSource::HallOfFame];
+const IMMIGRANT_SOURCE_LABELS : [&str; 2] = ["Random", "Hall of Fame"];
+
+// Creature turn order choices shown in the param panel
+const CREATURE_ORDER_OPTIONS : [CreatureOrder; 3] = [CreatureOrder::Insertion, CreatureOrder::Random, CreatureOrder::Morton];
+const CREATURE_ORDER_LABELS : [&str; 3] = ["Insertion", "Random", "Morton (Spatial)"];
+
+// Restart selection choices shown in the param panel
+const SELECTION_METHOD_LABELS : [&str; 3] = ["Random", "Top K", "Tournament"];
+const FITNESS_METRIC_OPTIONS : [FitnessMetric; 2] = [FitnessMetric::Lifespan, FitnessMetric::Offspring];
+const FITNESS_METRIC_LABELS : [&str; 2] = ["Lifespan", "Offspring"];
+
 // Creature display params
 const ORIENTATION_LINE_THICKNESS : f32 = 2.0;
+const CREATURE_SPRITE_LOD_PX : f32 = 4.0;           // Below this grid size (in pixels) creatures are just drawn as a colored pixel block
+const CREATURE_SPRITE_OUTLINE_MIN_PX : f32 = 12.0;  // At this grid size and above, arrow sprites get an outline so they stand out
+const CREATURE_SPRITE_FILL_FRACTION : f32 = 0.9;    // Fraction of a grid square an arrow sprite fills
+const CREATURE_SPRITE_FILE : &str = "data/creature_sprite.png"; // Optional sprite for the texture style. Should point up and be white so it can be tinted
+const CREATURE_STYLE_OPTIONS : [CreatureSpriteStyle; 3] = [CreatureSpriteStyle::Arrow, CreatureSpriteStyle::Square, CreatureSpriteStyle::Texture];
+const CREATURE_STYLE_LABELS : [&str; 3] = ["Arrow", "Square", "Texture"];
+
+// Resizing the board mid-run
+const RESIZE_ANCHOR_OPTIONS : [ResizeAnchor; 9] = [ResizeAnchor::TopLeft, ResizeAnchor::Top, ResizeAnchor::TopRight, ResizeAnchor::Left,
+    ResizeAnchor::Center, ResizeAnchor::Right, ResizeAnchor::BottomLeft, ResizeAnchor::Bottom, ResizeAnchor::BottomRight];
+const RESIZE_ANCHOR_LABELS : [&str; 9] = ["Top Left", "Top", "Top Right", "Left", "Center", "Right", "Bottom Left", "Bottom", "Bottom Right"];
+
+// Importing creatures
+const IMPORT_REGION_LABELS : [&str; 5] = ["Anywhere", "Left Half", "Right Half", "Top Half", "Bottom Half"];
+
+// Merging a saved environment into the current one
+const MERGE_DIRECTION_OPTIONS : [MergeDirection; 2] = [MergeDirection::Horizontal, MergeDirection::Vertical];
+const MERGE_DIRECTION_LABELS : [&str; 2] = ["To the Right", "Below"];
+const MERGE_SEAM_OPTIONS : [MergeSeam; 3] = [MergeSeam::Open, MergeSeam::Wall, MergeSeam::Corridor];
+const MERGE_SEAM_LABELS : [&str; 3] = ["Open", "Wall", "Corridor"];
+
+// Overlays panel
+const OVERLAY_PANEL_WIDTH : f32 = 320.0;
+const OVERLAY_PANEL_HEIGHT : f32 = 250.0;
+// Creature editor panel
+const GROUP_PANEL_WIDTH : f32 = 320.0;
+const GROUP_PANEL_HEIGHT : f32 = 300.0;
+const GROUP_PANEL_MAX_ROWS : usize = 10;        // Only the biggest groups are listed
+const GROUP_PANEL_MAX_ALERTS : usize = 5;       // Number of recent extinctions kept for the panel
+const GROUP_BY_OPTIONS : [GroupBy; 2] = [GroupBy::Tribe, GroupBy::Species];
+const GROUP_BY_LABELS : [&str; 2] = ["Tribe", "Species"];
+const LEADERBOARD_PANEL_WIDTH : f32 = 300.0;
+const LEADERBOARD_PANEL_HEIGHT : f32 = 330.0;
+const LEADERBOARD_SELECTION_COLOR : Color = YELLOW; // Outline around the creature picked from the leaderboard
+const CREATURE_EDIT_PANEL_WIDTH : f32 = 320.0;
+const CREATURE_EDIT_PANEL_HEIGHT : f32 = 260.0;
+const SELECTION_PANEL_WIDTH : f32 = 340.0;
+const SELECTION_PANEL_HEIGHT : f32 = 330.0;
+const SELECTION_PANEL_MAX_COHORTS : usize = 5;  // Only the most recently tagged cohorts are listed
+const SELECTION_COLOR : Color = SKYBLUE;        // Drag box and outline around selected creatures
+const ORIENTATION_OPTIONS : [CreatureOrientation; NUM_ORIENTATION_STATES] = [CreatureOrientation::Up, CreatureOrientation::Down, CreatureOrientation::Left, CreatureOrientation::Right];
+const ORIENTATION_LABELS : [&str; NUM_ORIENTATION_STATES] = ["Up", "Down", "Left", "Right"];
+const DEFAULT_OVERLAYS : [OverlaySettings; 4] = [    // Every overlay the board knows how to draw, in the order they're drawn
+    OverlaySettings {kind : OverlayKind::River, label : "River", enabled : true, opacity : 1.0},
+    OverlaySettings {kind : OverlayKind::Hillshade, label : "Hillshade", enabled : true, opacity : 1.0},
+    OverlaySettings {kind : OverlayKind::Territory, label : "Territory", enabled : true, opacity : 1.0},
+    OverlaySettings {kind : OverlayKind::Density, label : "Density", enabled : false, opacity : 1.0},
+];
+
+// Density overlay
+const DENSITY_OVERLAY_OPTIONS : [DensitySource; 3] = [DensitySource::Creatures, DensitySource::Food, DensitySource::CreaturesAndFood];
+const DENSITY_OVERLAY_LABELS : [&str; 3] = ["Creatures", "Food", "Creatures + Food"];
+const DENSITY_SMOOTHING_RADIUS : usize = 3;     // Half-width (in spaces) of the window the density is averaged over
+const DENSITY_CONTOUR_LEVELS : usize = 6;       // Number of shading bands the density is split into
+const DENSITY_OVERLAY_MAX_ALPHA : f32 = 0.6;    // Opacity of the densest band
+
+// Disease
+const INFECTED_TINT_COLOR : [u8; 3] = [170, 255, 0];   // Sick creatures are blended towards this color
+const INFECTED_TINT_AMOUNT : f32 = 0.6;         // How much of the tint color is blended in [0, 1]
+
+// Hillshade overlay
+const HILLSHADE_OVERLAY_MAX_ALPHA : f32 = 0.5;  // Opacity of the darkest shadows/brightest highlights
+
+// River
+const RIVER_COLOR : Color = Color {r : 0.2, g : 0.45, b : 0.9, a : 0.45};
+
+// Decals
+const FIGHT_DECAL_COLOR : Color = Color {r : 1.0, g : 0.0, b : 0.0, a : 0.25};
+const BIRTH_DECAL_COLOR : Color = Color {r : 0.4, g : 0.7, b : 1.0, a : 0.2};
+const DEATH_DECAL_COLOR : Color = Color {r : 0.6, g : 0.6, b : 0.6, a : 0.2};
+
+// Territory overlay
+const TERRITORY_OVERLAY_MAX_ALPHA : f32 = 0.4;  // Opacity of a freshly marked space
+
+// Inspector params
+const SPARKLINE_WIDTH : f32 = 200.0;
+const SPARKLINE_HEIGHT : f32 = 30.0;
+const SALIENCY_BAR_X : f32 = 160.0;             // X position in the stats panel the saliency bars start at
+const SALIENCY_BAR_WIDTH : f32 = 150.0;         // Width of a full saliency bar
+const SALIENCY_BAR_HEIGHT : f32 = 10.0;
+const FINGERPRINT_CELL_SIZE : f32 = 3.0;        // Size in pixels of one cell of a genome fingerprint glyph
+
+// Dream mode (a paused creature's brain responding to made-up inputs)
+const DREAM_INPUT_AMPLITUDE : f32 = 50.0;       // The made-up inputs sweep between 0 and this
+const DREAM_SWEEP_SPEED : f32 = 1.5;            // How fast (radians per second) each input's sweep advances
+const DREAM_PHASE_STEP : f32 = 0.9;             // Phase offset between neighboring inputs so they don't all move together
+const DREAM_PANEL_MARGIN : f32 = 40.0;          // Gap between the edge of the board and the dream panel
+const DREAM_LABEL_WIDTH : f32 = 150.0;          // Space left for the input/output neuron names on either side of the network
+const DREAM_NODE_RADIUS : f32 = 9.0;
+const DREAM_FONT_SIZE : f32 = 16.0;
+const BRAIN_TWEAK_CLICK_DIST : f32 = 5.0;       // How close (in pixels) a click has to be to a connection in the dream panel to pick it
+const BRAIN_TWEAK_PANEL_WIDTH : f32 = 360.0;
+const BRAIN_TWEAK_PANEL_HEIGHT : f32 = 90.0;
+
+// Split-screen comparison
+const COMPARE_BOARD_GAP : f32 = 10.0;           // Gap between the two boards in pixels
+const COMPARE_HISTORY_LEN : usize = 200;        // Number of past population counts kept for each board in comparison mode
+const OBJECTIVE_BAR_HEIGHT : f32 = 6.0;         // Height of each board's bar in the objective plot
+const OBJECTIVE_BAR_COLORS : [Color; 2] = [Color::new(0.0, 0.47, 1.0, 1.0), Color::new(1.0, 0.63, 0.0, 1.0)]; // Bar colors for boards A and B
+
+// Sound
+const DEFAULT_SOUND_VOLUME : f32 = 0.5;
 
 // Window Parameters
 const WINDOW_BAR_HEIGHT : f32 = 20.0;
@@ -61,6 +200,15 @@ const WINDOW_WIDTH_PX : f32 = SCREEN_SIZE_X + STATS_PANEL_WIDTH + PANEL_X_PADDIN
 // Sim defaults
 const MACROQUAD_FRAME_TIME_S : f64 = 0.1;    // Time between sim steps for macroquad in seconds
 
+// Kiosk mode
+const KIOSK_LONG_PRESS_S : f64 = 2.0;           // How long the kiosk title has to be held to show/hide the parameter panel
+const KIOSK_IDLE_TIMEOUT_S : f64 = 60.0;        // Time without any input before the attract loop starts
+const KIOSK_ATTRACT_NEW_WORLD_S : f64 = 90.0;   // While in the attract loop, a new world is generated this often
+const KIOSK_TITLE_HEIGHT : f32 = 80.0;          // Height of the title area that has to be long-pressed
+const KIOSK_BUTTON_HEIGHT : f32 = 120.0;        // Height of the big kiosk buttons
+const KIOSK_BUTTON_FONT_SIZE : u16 = 40;
+const KIOSK_LABEL_FONT_SIZE : u16 = 24;
+
 
 //===============================================================================
 // DATA
@@ -69,18 +217,145 @@ const MACROQUAD_FRAME_TIME_S : f64 = 0.1;    // Time between sim steps for macro
 /// Parameters for this simulation
 struct SimParameters {
 
-    // String versions of the parameters for storing the text box versions
-    pub env_x_size : String,                    // X size of the sim in "spaces"
-    pub env_y_size : String,                    // Y size of the sim in "spaces"
-    pub num_start_creatures : String,           // Number of creatures to start the sim with
-    pub num_start_food : String,                // Number of starting food spaces
-    pub num_start_walls : String,               // Number of starting wall spaces
-    pub energy_per_food_piece : String,         // Number of energy units that will be given per food consumed 
-    pub max_offspring_per_reproduce : String,   // Maximum number of offspring that will be produced by one reproduction event
-    pub mutation_prob : String,                 // Probability that a single value in the creatures DNA will randomly mutate upon reproduction
-    pub avg_new_food_per_day : String,          // Average number of new food pieces added to the environment per day
+    // Values of the parameters as shown in the param panel sliders (numeric params are all f32 for the sliders)
+    pub env_x_size : f32,                       // X size of the sim in "spaces"
+    pub env_y_size : f32,                       // Y size of the sim in "spaces"
+    pub num_start_creatures : f32,              // Number of creatures to start the sim with
+    pub num_start_food : f32,                   // Number of starting food spaces
+    pub num_start_walls : f32,                  // Number of starting wall spaces
+    pub energy_per_food_piece : f32,            // Number of energy units that will be given per food consumed 
+    pub max_offspring_per_reproduce : f32,      // Maximum number of offspring that will be produced by one reproduction event
+    pub mutation_prob : f32,                    // Probability that a single value in the creatures DNA will randomly mutate upon reproduction
+    pub mutation_weight_gaussian : f32,         // Relative weight of gaussian perturbation mutations
+    pub mutation_weight_sign_flip : f32,        // Relative weight of sign flip mutations
+    pub mutation_weight_reset : f32,            // Relative weight of full reset mutations
+    pub mutation_weight_swap : f32,             // Relative weight of weight swap mutations
+    pub mutation_weight_layer_scale : f32,      // Relative weight of layer-wise scaling mutations
+    pub mutation_schedule_idx : usize,          // Index into MUTATION_SCHEDULE_OPTIONS of the selected mutation schedule
+    pub mutation_anneal_rate : f32,             // Rate at which mutation strength decays per generation when annealing
+    pub offspring_energy_fraction : f32,        // Fraction of the parent's energy given to each offspring (0 = flat starting energy)
+    pub gestation_steps : f32,                  // Steps between reproducing and offspring being born (0 = immediately)
+    pub gestation_upkeep_energy : f32,          // Energy a pregnant creature pays per step
+    pub avg_new_food_per_day : f32,             // Average number of new food pieces added to the environment per day
+    pub enable_position_inputs : bool,          // Creatures sense their normalized position on the board
+    pub enable_wall_distance_input : bool,      // Creatures sense the distance to the nearest wall ahead
+    pub enable_move_blocked_input : bool,       // Creatures sense whether their last move was blocked
+    pub one_hot_categorical_inputs : bool,      // Sense last action/orientation as one-hot neurons
+    pub enable_hearing_input : bool,            // Creatures sense nearby creatures/food in any direction
+    pub enable_territory : bool,                // Creatures can mark territory and sense foreign territory
+    pub enable_rest : bool,                     // Creatures get a Rest action
+    pub enable_tribe_vision : bool,             // Creatures sense what nearby creatures of their lineage see
+    pub enable_decal_vision : bool,             // Creatures see decals and other lineages' territory
+    pub enable_beacon : bool,                   // Place a beacon that creatures can navigate to
+    pub energy_per_beacon_reach : f32,          // Energy given for reaching the beacon
+    pub enable_novelty : bool,                  // Boost reproduction of behaviorally novel creatures
+    pub novelty_descriptor_idx : usize,         // Index into NOVELTY_DESCRIPTOR_OPTIONS
+    pub novelty_weight : f32,                   // Max chance of an extra offspring for novel creatures
+    pub enable_speciation : bool,               // Cluster creatures into species by genome distance
+    pub speciation_threshold : f32,             // Max genome distance from a species representative
+    pub speciation_interval : f32,              // Steps between species re-clustering
+    pub creature_order_idx : usize,             // Index into CREATURE_ORDER_OPTIONS of the order creatures act in
+    pub view_distance : f32,                    // Max number of spaces creatures can see ahead
+    pub evolve_view_distance : bool,            // Let view distance evolve per creature
+    pub view_energy_cost : f32,                 // Energy per step per space of view distance
+    pub enable_metabolic_cost : bool,           // Creatures pay per step for the size of their brain
+    pub metabolic_energy_cost : f32,            // Energy per step per brain neuron
+    pub hearing_radius : f32,                   // Max distance creatures can hear
+    pub brain_compute_budget : f32,             // Brain connections evaluated per step (0 = unlimited)
+    pub action_delay_steps : f32,               // Steps between deciding on an action and carrying it out
+    pub territory_decay : f32,                  // Fraction of a territory mark that fades every step
+    pub tribe_vision_radius : f32,              // Max distance tribe-mates share vision over
+    pub season_length : f32,                    // Steps in a full seasonal cycle (0 = no seasons)
+    pub mating_season_start : f32,              // Phase of the seasonal cycle mating season starts at
+    pub mating_season_length : f32,             // Fraction of the seasonal cycle creatures can reproduce in
+    pub food_die_off_rate : f32,                // Average food die-offs per 1000 steps
+    pub food_die_off_magnitude : f32,           // Fraction of food lost in a die-off
+    pub plague_rate : f32,                      // Average plagues per 1000 steps
+    pub plague_magnitude : f32,                 // Fraction of creatures a plague kills
+    pub wall_collapse_rate : f32,               // Average wall collapses per 1000 steps
+    pub wall_collapse_magnitude : f32,          // Fraction of walls that collapse
+    pub enable_disease : bool,                  // Creatures can catch a contagious disease
+    pub initial_infection_fraction : f32,       // Fraction of starting creatures that are sick
+    pub infection_prob : f32,                   // Chance per step of catching it from a sick neighbor
+    pub infection_duration : f32,               // Steps an infection lasts
+    pub infection_energy_drain : f32,           // Energy a sick creature loses per step
+    pub enable_elevation : bool,                // Give the board hills that cost energy to climb
+    pub elevation_feature_size : f32,           // Rough width of the biggest hills
+    pub elevation_energy_cost : f32,            // Energy to climb from the lowest to the highest point
+    pub high_ground_view_bonus : f32,           // Extra view distance from the highest point
+    pub enable_river : bool,                    // Run a river down the board that carries food downstream
+    pub river_flow_strength : f32,              // Chance per step food drifts downstream
+    pub river_source_food : f32,                // Food washed in at the source per step
+    pub enable_swimming : bool,                 // Water blocks creatures that can't swim
+    pub initial_swimmer_fraction : f32,         // Fraction of starting creatures that can swim
+    pub enable_large_bodies : bool,             // Some creatures take up two spaces
+    pub initial_large_fraction : f32,           // Fraction of starting creatures with large bodies
+    pub swim_energy_cost : f32,                 // Extra energy to move into water
+    pub immigration_rate : f32,                 // Average immigrants entering from the board edges per step
+    pub immigrant_source_idx : usize,           // Index into IMMIGRANT_SOURCE_OPTIONS
+
+    // Restart policy
+    pub restart_on_extinction : bool,           // Automatically restart when all creatures die
+    pub restart_every_n_generations : f32,      // Automatically restart every N generations (0 = never)
+    pub max_steps_per_run : f32,                // Stop the sim after this many steps of a run (0 = no limit)
+    pub selection_method_idx : usize,           // Index into SELECTION_METHOD_LABELS
+    pub selection_size : f32,                   // K for top-K selection, tournament size for tournament selection
+    pub fitness_metric_idx : usize,             // Index into FITNESS_METRIC_OPTIONS
+
+    // Split-screen comparison
+    pub compare_shared_params : bool,           // Board B uses exactly the same parameters as board A
+    pub compare_mutation_prob : f32,            // Mutation probability for board B (if parameters aren't shared)
 
     pub save_load_filename : String,            // Name of file to save/load from
+
+    // Optional files to pull individual layers from when loading (blank = use `save_load_filename`)
+    pub params_filename : String,
+    pub creatures_filename : String,
+    pub walls_filename : String,
+    pub food_filename : String,
+}
+
+/// How creatures are drawn on the board
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CreatureSpriteStyle {
+    Arrow,      // Triangle pointing the way the creature is facing
+    Square,     // Colored square with a line showing the way the creature is facing
+    Texture,    // Tinted, rotated sprite loaded from CREATURE_SPRITE_FILE (falls back to Arrow if it couldn't be loaded)
+}
+
+/// Layers that can be drawn on top of the board
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OverlayKind {
+    River,      // Water the river runs through (drawn underneath everything else)
+    Hillshade,  // Light and shadow from the elevation
+    Territory,  // Spaces tinted by the lineage that marked them
+    Density,    // Contour bands of how crowded each area is
+}
+
+/// Whether an overlay is shown and how strongly
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct OverlaySettings {
+    pub kind : OverlayKind,
+    pub label : &'static str,   // Name shown in the overlays panel
+    pub enabled : bool,         // Draw this overlay
+    pub opacity : f32,          // Scales the overlay's usual opacity [0, 1]
+}
+
+/// Values in the creature editor. They're only applied to the creature when the user hits APPLY
+struct CreatureEditor {
+    creature_id : usize,        // Creature being edited
+    energy : f32,
+    age : String,
+    orientation_idx : usize,    // Index into ORIENTATION_OPTIONS
+    color : [f32; 3],           // Red, green, blue [0, 255]
+    lineage : String,           // Lineage (tribe) ID
+}
+
+/// Weight or bias picked in the dream panel to tweak by hand
+struct BrainTweak {
+    creature_id : usize,        // Creature whose brain is being tweaked
+    param : BrainParam,         // Weight/bias being tweaked
+    value : f32,                // Slider value. Sent to the creature's brain whenever it changes
 }
 
 /// Enum defining state of the simulation (stopped/running)
@@ -100,10 +375,19 @@ pub struct LoadOptions {
     load_food : bool,
 }
 
+/// Second board that steps in lockstep with the main one so two setups can be compared side-by-side
+struct CompareBoard {
+    env : EnvironmentV1,                            // Environment shown on the right ("B") board
+    run_policy : RunPolicy,                         // Board B's own copy of the run policy so restarts are tracked separately
+    population_history : [VecDeque<usize>; 2],      // Number of living creatures on boards A and B over the last COMPARE_HISTORY_LEN steps
+}
+
 /// Environment
 pub struct EnvMacroquad {
     params : SimParameters,     // Constant values that sim is initialized with
     pub env : EnvironmentV1,    // Contains the whole environment
+    pub run_policy : RunPolicy, // Decides when the environment gets restarted and how it's reseeded
+    compare_board : Option<CompareBoard>, // Board B in split-screen comparison mode (None = single board)
 
     // Sim state
     pub state : SimState,       // Current state of the sim (running/stopped)
@@ -113,9 +397,63 @@ pub struct EnvMacroquad {
 
     // Data used by UI
     load_opts : LoadOptions,    // Options used when loading environment from a file
+    save_opts : SaveOptions,    // Options used when saving environment to a file
+    save_walls_only : bool,     // Save just the walls as a compact wall map instead of the whole environment
+    resize_anchor_idx : usize,  // Index into RESIZE_ANCHOR_OPTIONS of which part of the board stays put when resizing
+    merge_direction_idx : usize,// Index into MERGE_DIRECTION_OPTIONS of where a merged environment goes
+    merge_seam_idx : usize,     // Index into MERGE_SEAM_OPTIONS of what separates a merged environment from the current one
+    import_adapt_brains : bool, // Rewire imported creatures whose brains don't match the current inputs/actions
+    import_region_idx : usize,  // Index into IMPORT_REGION_LABELS of where imported creatures are placed
 
     // Data used to draw new spaces
     current_draw_space_type : Option<SpaceStates>,  // Current type of space that should be drawn if the user clicks on a space square
+    edit_creatures : bool,                          // Clicking a creature opens it in the editor instead of drawing
+    creature_editor : Option<CreatureEditor>,       // Open creature editor (None = closed)
+    select_creatures : bool,                        // Dragging a box on the board selects the creatures in it instead of drawing
+    selection_start : Option<Vec2>,                 // Screen position the current selection drag started at
+    selected_ids : Vec<usize>,                      // IDs of the creatures in the last selection box
+    cohort_name : String,                           // Name the selected creatures are tagged with when saved as a cohort
+
+    // Display options
+    color_by_species : bool,        // Color creatures by their species ID instead of their inherited color
+    creature_style_idx : usize,     // Index into CREATURE_STYLE_OPTIONS of how creatures are drawn
+    smooth_movement : bool,         // Slide creatures from their previous space to their new one between steps
+    speed_governor : bool,          // Step slower when few creatures are alive and faster during booms (see SpeedGovernor)
+    governor_min_rate : f32,        // Steps per second the speed governor slows down to
+    governor_max_rate : f32,        // Steps per second the speed governor speeds up to
+    overlays : Vec<OverlaySettings>,// Settings for every overlay, in the order they're drawn (starts as DEFAULT_OVERLAYS)
+    show_overlay_panel : bool,      // Show the panel for turning overlays on/off
+    show_group_panel : bool,        // Show the panel with live stats of each tribe/species
+    group_by_idx : usize,           // Index into GROUP_BY_OPTIONS of what the group panel lists
+    pause_on_group_extinction : bool, // Stop the sim when a tribe/species dies out
+    group_alerts : VecDeque<String>,// Most recent tribe/species extinctions, newest first
+    show_leaderboard : bool,        // Show the panel with the oldest creatures and the ones with the most offspring
+    density_overlay_idx : usize,    // Index into DENSITY_OVERLAY_OPTIONS of what the density overlay shows
+    elevation_brush : f32,          // How much clicking a space raises (or lowers) it. 0 = terrain brush off
+    dream_when_paused : bool,       // While stopped, show a creature's brain responding to made-up inputs
+    show_effects : bool,            // Show a ring on kills, a sparkle on births and a fade on deaths
+    effects : EffectsLayer,         // Visual effects showing over the board
+    dream_creature_id : Option<usize>, // Creature that dreams (the last one shown in the inspector)
+    brain_tweak : Option<BrainTweak>,  // Weight/bias picked in the dream panel (None = nothing picked)
+    prev_creature_positions : HashMap<usize, CreaturePosition>, // Position of each creature (by ID) before the last step, for smooth movement
+    prev_positions_step : usize,    // Time step that `prev_creature_positions` leads up to (they're stale if the env is at any other step)
+    load_error : Option<String>,    // Why the last load failed. Shown to the user until they dismiss it
+    load_repairs : Option<String>,  // What had to be fixed in the last loaded environment. Shown to the user until they dismiss it
+    summary_report : Option<String>,// Report on the run shown when the creatures die out, until the user dismisses it
+
+    // Sound
+    sound_volume : f32,             // Volume of the sound effects (0-1)
+    sound_muted : bool,             // Mute all sound effects
+    #[cfg(feature = "audio")]
+    sounds : Option<SoundBank>,     // Loaded sound effects (None until `load_sounds` succeeds)
+
+    // Kiosk mode
+    kiosk_mode : bool,                  // Simplified touchscreen UI with big buttons and the params hidden behind a long-press
+    kiosk_show_params : bool,           // Whether the param panel has been unlocked by a long-press
+    kiosk_press_start : Option<f64>,    // Time the current press on the kiosk title started (None if not pressed)
+    kiosk_press_handled : bool,         // Whether the current press already toggled the param panel
+    last_input_time : f64,              // Time of the last mouse/touch/key input (for the idle attract loop)
+    last_attract_world_time : f64,      // Time the attract loop last generated a new world
 
     // Environment derived parameters
     grid_x_size : f32,              // X size of a single grid square in pixels
@@ -135,8 +473,7 @@ pub struct EnvMacroquad {
     default_skin : Skin,            // default sytle for the UI
 
     // Assets
-    background_texture : Texture2D, // Background image texture
-    background_options : DrawTextureParams,
+    creature_texture : Option<Texture2D>, // Sprite for the texture creature style (None if CREATURE_SPRITE_FILE couldn't be loaded)
 }
 
 
@@ -163,20 +500,100 @@ impl EnvMacroquad {
 
         let mut temp_env = EnvMacroquad {
             params : SimParameters {
-                env_x_size : String::new(),
-                env_y_size : String::new(),
-                num_start_creatures : String::new(),
-                num_start_food : String::new(),
-                num_start_walls : String::new(),
-                energy_per_food_piece : String::new(),
-                max_offspring_per_reproduce : String::new(),
-                mutation_prob : String::new(),
-                avg_new_food_per_day : String::new(),
+                env_x_size : 0.0,
+                env_y_size : 0.0,
+                num_start_creatures : 0.0,
+                num_start_food : 0.0,
+                num_start_walls : 0.0,
+                energy_per_food_piece : 0.0,
+                max_offspring_per_reproduce : 0.0,
+                mutation_prob : 0.0,
+                mutation_weight_gaussian : 0.0,
+                mutation_weight_sign_flip : 0.0,
+                mutation_weight_reset : 0.0,
+                mutation_weight_swap : 0.0,
+                mutation_weight_layer_scale : 0.0,
+                mutation_schedule_idx : 0,
+                mutation_anneal_rate : 0.0,
+                offspring_energy_fraction : 0.0,
+                gestation_steps : 0.0,
+                gestation_upkeep_energy : 0.0,
+                avg_new_food_per_day : 0.0,
+                enable_position_inputs : false,
+                enable_wall_distance_input : false,
+                enable_move_blocked_input : false,
+                one_hot_categorical_inputs : false,
+                enable_hearing_input : false,
+                enable_territory : false,
+                enable_rest : false,
+                enable_tribe_vision : false,
+                enable_decal_vision : false,
+                enable_beacon : false,
+                energy_per_beacon_reach : 0.0,
+                enable_novelty : false,
+                novelty_descriptor_idx : 0,
+                novelty_weight : 0.0,
+                enable_speciation : false,
+                speciation_threshold : 0.0,
+                speciation_interval : 0.0,
+                creature_order_idx : 0,
+                view_distance : 0.0,
+                evolve_view_distance : false,
+                view_energy_cost : 0.0,
+                enable_metabolic_cost : false,
+                metabolic_energy_cost : 0.0,
+                hearing_radius : 0.0,
+                brain_compute_budget : 0.0,
+                action_delay_steps : 0.0,
+                territory_decay : 0.0,
+                tribe_vision_radius : 0.0,
+                season_length : 0.0,
+                mating_season_start : 0.0,
+                mating_season_length : 0.0,
+                food_die_off_rate : 0.0,
+                food_die_off_magnitude : 0.0,
+                plague_rate : 0.0,
+                plague_magnitude : 0.0,
+                wall_collapse_rate : 0.0,
+                wall_collapse_magnitude : 0.0,
+                enable_disease : false,
+                initial_infection_fraction : 0.0,
+                infection_prob : 0.0,
+                infection_duration : 0.0,
+                infection_energy_drain : 0.0,
+                enable_elevation : false,
+                elevation_feature_size : 0.0,
+                elevation_energy_cost : 0.0,
+                high_ground_view_bonus : 0.0,
+                enable_river : false,
+                river_flow_strength : 0.0,
+                river_source_food : 0.0,
+                enable_swimming : false,
+                initial_swimmer_fraction : 0.0,
+                enable_large_bodies : false,
+                initial_large_fraction : 0.0,
+                swim_energy_cost : 0.0,
+                immigration_rate : 0.0,
+                immigrant_source_idx : 0,
+                restart_on_extinction : false,
+                restart_every_n_generations : 0.0,
+                max_steps_per_run : 0.0,
+                selection_method_idx : 0,
+                selection_size : 0.0,
+                fitness_metric_idx : 0,
+                compare_shared_params : false,
+                compare_mutation_prob : 0.0,
                 save_load_filename : String::new(),
+                params_filename : String::new(),
+                creatures_filename : String::new(),
+                walls_filename : String::new(),
+                food_filename : String::new(),
             },
 
             // Generate the environment given the parameters
             env : EnvironmentV1::new_rand(&temp_env_params),
+            run_policy : RunPolicy::new(),
+            compare_board : None,
 
             // State
             state : SimState::RUNNING,
@@ -192,8 +609,61 @@ impl EnvMacroquad {
                 load_food : false,
             },
 
+            save_opts : SaveOptions::default(),
+            save_walls_only : false,
+            resize_anchor_idx : 0,
+            merge_direction_idx : 0,
+            merge_seam_idx : 0,
+            import_adapt_brains : true,
+            import_region_idx : 0,
+
             // Space drawing data
             current_draw_space_type : None,
+            edit_creatures : false,
+            creature_editor : None,
+            select_creatures : false,
+            selection_start : None,
+            selected_ids : Vec::new(),
+            cohort_name : String::new(),
+
+            // Display options
+            color_by_species : false,
+            creature_style_idx : 0,
+            smooth_movement : true,
+            speed_governor : false,
+            governor_min_rate : DEFAULT_GOVERNOR_MIN_RATE,
+            governor_max_rate : DEFAULT_GOVERNOR_MAX_RATE,
+            overlays : DEFAULT_OVERLAYS.to_vec(),
+            show_overlay_panel : false,
+            show_group_panel : false,
+            group_by_idx : 0,
+            pause_on_group_extinction : false,
+            group_alerts : VecDeque::new(),
+            show_leaderboard : false,
+            density_overlay_idx : 0,
+            elevation_brush : 0.0,
+            dream_when_paused : false,
+            show_effects : true,
+            effects : EffectsLayer::new(),
+            dream_creature_id : None,
+            brain_tweak : None,
+            sound_volume : DEFAULT_SOUND_VOLUME,
+            sound_muted : false,
+            #[cfg(feature = "audio")]
+            sounds : None,
+            prev_creature_positions : HashMap::new(),
+            prev_positions_step : 0,
+            load_error : None,
+            load_repairs : None,
+            summary_report : None,
+
+            // Kiosk mode
+            kiosk_mode : false,
+            kiosk_show_params : false,
+            kiosk_press_start : None,
+            kiosk_press_handled : false,
+            last_input_time : get_time(),
+            last_attract_world_time : get_time(),
 
             // Environment display params
             grid_x_size : SCREEN_SIZE_X / (NUM_GRID_SQUARES_X as f32),
@@ -213,20 +683,15 @@ impl EnvMacroquad {
             // Set default skin to default from macroquad (will be overwritten later)
             default_skin : Skin {..root_ui().default_skin()},
 
-            // background_image
-            background_texture : Texture2D::from_file_with_format(include_bytes!("../data/grass_texture.png"), Some(ImageFormat::Png)),
-            background_options : DrawTextureParams {
-                dest_size: Some(vec2(SCREEN_SIZE_X + PANEL_X_PADDING, SCREEN_SIZE_Y + PANEL_Y_PADDING)),
-                source: None,
-                rotation: 0.0,
-                flip_x: false,
-                flip_y: false,
-                pivot: None,
-            }
+            creature_texture : match std::fs::read(CREATURE_SPRITE_FILE) {
+                Ok(bytes) => Some(Texture2D::from_file_with_format(&bytes, Some(ImageFormat::Png))),
+                Err(_) => None,
+            },
         };
 
-        // Populate initial param strings with values from sim
-        temp_env.repopulate_parameter_strings();
+        // Populate initial param panel values from the sim
+        temp_env.repopulate_parameter_panel();
+        temp_env.params.compare_mutation_prob = temp_env.env.params.mutation_prob;
 
 
         return temp_env;
@@ -235,21 +700,178 @@ impl EnvMacroquad {
     /// Generate a new environment given the parameter values in the text boxes
     pub fn generate_new_environment(&mut self) {
 
-        // Populate the parameters with values from text boxes
-        if self.update_params_from_text() {
+        // Populate the parameters with values from the param panel
+        if self.update_params_from_panel() {
 
             // Generate a new environment with new params
             self.env = environment::EnvironmentV1::new_rand(&self.env.params);
+            self.run_policy.reset_limits();
+            self.effects.clear();
+
+            // Keep comparing from the new starting point
+            if self.compare_board.is_some() {
+                self.start_comparison();
+            }
+        }
+    }
+
+    /// Start split-screen comparison mode. Board B starts as an exact copy of the current board so both
+    /// runs share the same starting world, then optionally gets its own mutation probability
+    pub fn start_comparison(&mut self) {
+        let mut compare_env = self.env.clone();
+        if !self.params.compare_shared_params {
+            compare_env.params.mutation_prob = self.params.compare_mutation_prob.clamp(0.0, 1.0);
         }
+
+        self.compare_board = Some(CompareBoard {
+            env : compare_env,
+            run_policy : self.run_policy.clone(),
+            population_history : [VecDeque::new(), VecDeque::new()],
+        });
+        self.update_grid_size();
+    }
+
+    /// Leave split-screen comparison mode and go back to the single board
+    pub fn stop_comparison(&mut self) {
+        self.compare_board = None;
+        self.update_grid_size();
+    }
+
+    /// Recompute the size of a grid square from the board size. Boards are half as wide when comparing
+    fn update_grid_size(&mut self) {
+        let board_width = match self.compare_board {
+            Some(_) => (SCREEN_SIZE_X - COMPARE_BOARD_GAP) / 2.0,
+            None => SCREEN_SIZE_X,
+        };
+        self.grid_x_size = board_width / (self.env.params.env_x_size as f32);
+        self.grid_y_size = SCREEN_SIZE_Y / (self.env.params.env_y_size as f32);
+    }
+
+    /// Record the current population of both boards for the comparison stats
+    fn record_comparison_history(&mut self) {
+        if let Some(compare) = &mut self.compare_board {
+            let populations = [self.env.num_creatures, compare.env.num_creatures];
+            for (history, population) in compare.population_history.iter_mut().zip(populations) {
+                if history.len() >= COMPARE_HISTORY_LEN {
+                    history.pop_front();
+                }
+                history.push_back(population);
+            }
+        }
+    }
+
+    /// Switch the GUI to kiosk mode, meant for unattended touchscreens. The sim always restarts on extinction
+    pub fn enable_kiosk_mode(&mut self) {
+        self.kiosk_mode = true;
+        self.kiosk_show_params = false;
+        self.run_policy.restart_on_extinction = true;
+        self.speed_governor = true;
+        self.state = SimState::RUNNING;
+        self.repopulate_parameter_panel();
+    }
+
+    /// Turn on the speed governor, e.g. for display only mode
+    pub fn enable_speed_governor(&mut self) {
+        self.speed_governor = true;
+    }
+
+    /// Get the time (in seconds) between sim steps. With the speed governor on, it depends on how many creatures are alive
+    fn get_step_interval(&self, num_creatures : usize) -> f64 {
+        if !self.speed_governor {
+            return MACROQUAD_FRAME_TIME_S;
+        }
+        let governor = SpeedGovernor::new(self.governor_min_rate, self.governor_max_rate.max(self.governor_min_rate));
+        return governor.get_step_interval(num_creatures).as_secs_f64();
     }
 
     /// Run and display the next step of the simulation
     pub fn run_next_step(&mut self) {
+        // Remember where everyone was so the move to the new positions can be animated
+        self.prev_creature_positions = self.env.creatures.iter().map(|c| (c.id, c.position)).collect();
+
         self.env.advance_step();
+        if self.run_policy.maybe_restart(&mut self.env) {
+            self.prev_creature_positions.clear();
+        }
+        self.prev_positions_step = self.env.time_step;
+        self.stop_at_run_limits();
+
+        // Board B steps in lockstep with the main board
+        if let Some(compare) = &mut self.compare_board {
+            compare.env.advance_step();
+            compare.run_policy.maybe_restart(&mut compare.env);
+        }
+        self.record_comparison_history();
+        self.handle_events(true);
     }
 
-    /// Save the full current environment to a file
+    /// Stop the sim if the run policy's limits were reached. Starting it again runs until the limits are reached again.
+    /// Extinction is left to the restart policy (and the summary report)
+    fn stop_at_run_limits(&mut self) {
+        match self.run_policy.check_limits(&self.env) {
+            None | Some(StopReason::Extinction) => (),
+            Some(_reason) => {
+                self.state = SimState::STOPPED;
+                self.run_policy.reset_limits();
+            },
+        }
+    }
+
+    /// Take the events the environment(s) queued up since the last call and react to them.
+    /// Sound and visual effects are only played if `play_sounds` is set (e.g. not while fast forwarding)
+    fn handle_events(&mut self, play_sounds : bool) {
+        let events = self.env.events.take_all();
+        if let Some(compare) = &mut self.compare_board {
+            compare.env.events.take_all();
+        }
+
+        self.effects.update(get_time());
+        if play_sounds && self.show_effects {
+            self.effects.add_for_events(&events, get_time());
+        }
+
+        // Sum up the run once everything's dead (and the run policy didn't restart it)
+        if self.env.creatures.is_empty() && events.iter().any(|e| matches!(e, EnvEvent::Extinction {..})) {
+            self.summary_report = Some(self.env.summary_report());
+        }
+
+        // Note tribes/species dying out, and stop to look at it if asked to
+        for event in events.iter() {
+            let alert = match event {
+                EnvEvent::LineageExtinct {time_step, lineage_id} => format!("Step {}: tribe {} died out", time_step, lineage_id),
+                EnvEvent::SpeciesExtinct {time_step, species_id} => format!("Step {}: species {} died out", time_step, species_id),
+                _ => continue,
+            };
+            self.group_alerts.push_front(alert);
+            self.group_alerts.truncate(GROUP_PANEL_MAX_ALERTS);
+            if self.pause_on_group_extinction && self.state == SimState::RUNNING {
+                self.state = SimState::STOPPED;
+            }
+        }
+
+        #[cfg(feature = "audio")]
+        if let Some(sounds) = &self.sounds {
+            if play_sounds && !self.sound_muted {
+                sounds.play_for_events(&events, self.sound_volume);
+            }
+        }
+        #[cfg(not(feature = "audio"))]
+        let _ = (events, play_sounds);
+    }
+
+    /// Synthesize and load the sound effects. Needs to be awaited once from the main loop before sounds will play
+    #[cfg(feature = "audio")]
+    pub async fn load_sounds(&mut self) {
+        self.sounds = SoundBank::load().await;
+    }
+
+    /// Save the full current environment to a file (or just the walls, if that option is checked)
     fn save_environment(&self, filename : String) {
+        if self.save_walls_only {
+            self.env.save_wall_map(&filename);
+            return;
+        }
+
         let json_file_res = File::create(&filename);
         let mut json_file : File;
         match json_file_res {
@@ -260,41 +882,388 @@ impl EnvMacroquad {
             Ok(f) => json_file = f,
         }
 
-        json_file.write(self.env.to_json().as_bytes()).expect("Error writing environment to file!");
+        json_file.write(self.env.to_json_with_options(&self.save_opts).as_bytes()).expect("Error writing environment to file!");
     }
 
-    /// Load the full environment and creatures from json file
+    /// Resize the current board to the size in the parameters panel, keeping what's on it
+    fn resize_environment(&mut self) {
+        let new_x_size = self.params.env_x_size.round() as usize;
+        let new_y_size = self.params.env_y_size.round() as usize;
+        self.env.resize(new_x_size, new_y_size, RESIZE_ANCHOR_OPTIONS[self.resize_anchor_idx]);
+
+        // Board B is restarted from the resized board so the two stay the same size
+        if self.compare_board.is_some() {
+            self.start_comparison();
+        }
+        self.update_grid_size();
+    }
+
+    /// Import the creatures from a saved environment (or a single saved creature) into the current board
+    fn import_creatures(&mut self, filename : &str) {
+        let (max_x, max_y) = (self.env.params.env_x_size - 1, self.env.params.env_y_size - 1);
+        let (mid_x, mid_y) = (max_x / 2, max_y / 2);
+        let corners = |x0, y0, x1, y1| Some((CreaturePosition {x : x0, y : y0}, CreaturePosition {x : x1, y : y1}));
+        let region = match self.import_region_idx {
+            1 => corners(0, 0, mid_x, max_y),
+            2 => corners(mid_x + 1, 0, max_x, max_y),
+            3 => corners(0, 0, max_x, mid_y),
+            4 => corners(0, mid_y + 1, max_x, max_y),
+            _ => None,
+        };
+        let options = CreatureImportOptions {adapt_brains : self.import_adapt_brains, region : region};
+
+        match self.env.import_creatures_from_file(filename, &options) {
+            Err(msg) => self.load_error = Some(msg),
+            Ok(report) => {
+                self.load_error = None;
+                println!("Imported {} creatures ({} adapted, {} rejected)", report.num_imported, report.num_adapted, report.num_rejected);
+            },
+        }
+    }
+
+    /// Merge a saved environment onto the side of the current one
+    fn merge_environment(&mut self, filename : &str) {
+        let other = match EnvironmentV1::read_json_file(filename) {
+            Err(msg) => {
+                self.load_error = Some(msg);
+                return;
+            },
+            Ok(env) => env,
+        };
+        self.load_error = None;
+        self.env = EnvironmentV1::new_merged(&self.env, &other, MERGE_DIRECTION_OPTIONS[self.merge_direction_idx], MERGE_SEAM_OPTIONS[self.merge_seam_idx]);
+
+        if self.compare_board.is_some() {
+            self.start_comparison();
+        }
+        self.update_grid_size();
+        self.repopulate_parameter_panel();
+    }
+
+    /// Load the full environment and creatures from json file. Any of the per-layer filename boxes
+    /// that are filled in override `filename` for that layer
     fn load_environmnt(&mut self, filename : &str) {
+        // Wall maps only replace the walls
+        if filename.ends_with(WALL_MAP_EXTENSION) {
+            self.load_error = self.env.load_wall_map(filename).err();
+            return;
+        }
+
         let load_opts : JsonEnvLoadParams = JsonEnvLoadParams {
             load_all : false,
             load_parameters : self.load_opts.load_params,
             load_creatures : self.load_opts.load_creatures,
             load_food : self.load_opts.load_food,
             load_walls : self.load_opts.load_walls,
+            parameters_file : Some(self.params.params_filename.clone()),
+            creatures_file : Some(self.params.creatures_filename.clone()),
+            walls_file : Some(self.params.walls_filename.clone()),
+            food_file : Some(self.params.food_filename.clone()),
         };
 
-        self.env.load_from_json(filename, &load_opts);
+        match self.env.load_from_json(filename, &load_opts) {
+            Err(msg) => {
+                self.load_error = Some(msg);
+                return;
+            },
+            Ok(report) => {
+                self.load_error = None;
+                self.run_policy.reset_limits();
+                self.load_repairs = if report.num_fixes() > 0 { Some(report.describe()) } else { None };
+                println!("Loaded {} ({})", filename, self.env.metadata.describe());
+            },
+        }
+
+        // Loading params may have changed the board size. The loaded board replaces both sides of a comparison
+        if self.compare_board.is_some() {
+            self.start_comparison();
+        }
+        self.update_grid_size();
+        self.repopulate_parameter_panel();
     }
 
     /// Update the simulation env board
     fn update_sim_display(&self) {
 
-        // Draw background
-        // draw_texture_ex(&self.background_texture, 0.0, 0.0, WHITE, self.background_options.clone());
+        // While running normally, slide creatures over the frame interval from where they were to where they are
+        let mut movement : Option<(&HashMap<usize, CreaturePosition>, f32)> = None;
+        if self.smooth_movement && self.state == SimState::RUNNING && self.prev_positions_step == self.env.time_step {
+            let progress = ((get_time() - self.last_sim_update) / self.get_step_interval(self.env.num_creatures)).clamp(0.0, 1.0) as f32;
+            movement = Some((&self.prev_creature_positions, progress));
+        }
+        self.draw_board(&self.env.get_snapshot(), 0.0, movement);
+        if self.show_effects {
+            self.effects.draw(get_time(), self.grid_x_size, self.grid_y_size, 0.0);
+        }
+
+        // In comparison mode, board B goes on the right half with a divider in between
+        if let Some(compare) = &self.compare_board {
+            let board_width = (SCREEN_SIZE_X - COMPARE_BOARD_GAP) / 2.0;
+            draw_rectangle(board_width, 0.0, COMPARE_BOARD_GAP, SCREEN_SIZE_Y, DARKGRAY);
+            self.draw_board(&compare.env.get_snapshot(), board_width + COMPARE_BOARD_GAP, None);
+            draw_text("A", 5.0, 25.0, 30.0, YELLOW);
+            draw_text("B", board_width + COMPARE_BOARD_GAP + 5.0, 25.0, 30.0, YELLOW);
+        }
+
+        // While stopped, the last inspected creature (or the oldest one if it's gone) dreams on top of the board
+        if let Some(creature) = self.get_dreamer() {
+            self.draw_dream(creature);
+        }
+    }
+
+    /// Get the creature shown in the dream panel, if it's showing. The creature whose brain is being tweaked keeps
+    /// dreaming, otherwise it's the last inspected creature (or the oldest one if it's gone)
+    fn get_dreamer(&self) -> Option<&CreatureV1> {
+        if !self.dream_when_paused || self.state != SimState::STOPPED {
+            return None;
+        }
+        if let Some(tweak) = &self.brain_tweak {
+            if let Some(creature) = self.env.get_creature(tweak.creature_id) {
+                return Some(creature);
+            }
+        }
+        return self.get_inspected_creature();
+    }
+
+    /// Get the creature last shown in the inspector, or the oldest one if it's gone
+    fn get_inspected_creature(&self) -> Option<&CreatureV1> {
+        return self.dream_creature_id.and_then(|id| self.env.get_creature(id)).or_else(|| self.env.creatures.iter().max_by_key(|c| c.age));
+    }
+
+    /// Write the brain of the last inspected creature (see `get_inspected_creature`) as a GraphViz graph
+    fn save_brain_dot(&self, filename : &str) {
+        let creature = match self.get_inspected_creature() {
+            Some(creature) => creature,
+            None => {
+                println!("Error: no living creature to export the brain of");
+                return;
+            }
+        };
+        if let Err(e) = std::fs::write(filename, creature.brain.to_dot(BRAIN_DOT_DEFAULT_MIN_WEIGHT)) {
+            println!("Error: could not write brain graph to {}. Error {e}", filename);
+        }
+    }
+
+    /// Draw a creature's brain responding to made-up inputs that slowly sweep up and down. Neurons glow with their activation
+    /// and connections are drawn by how much signal they carry (blue = excitatory, red = inhibitory)
+    fn draw_dream(&self, creature : &CreatureV1) {
+        let panel = get_dream_panel();
+        draw_rectangle(panel.x, panel.y, panel.w, panel.h, Color::from_rgba(0, 0, 0, 220));
+        draw_text(&format!("Creature {} is dreaming...", creature.id), panel.x + 10.0, panel.y + 25.0, 24.0, YELLOW);
+
+        // Every input sweeps between 0 and DREAM_INPUT_AMPLITUDE, each a little behind the one before it
+        let time = get_time() as f32;
+        let inputs : Vec<f32> = (0..creature.brain.input_node_types.len())
+            .map(|i| DREAM_INPUT_AMPLITUDE * 0.5 * (1.0 + (time * DREAM_SWEEP_SPEED + i as f32 * DREAM_PHASE_STEP).sin())).collect();
+        let layers = creature.brain.get_activations_for_inputs(&inputs);
+        let weights = creature.brain.get_weights();
+        let layer_sizes : Vec<usize> = layers.iter().map(|layer| layer.len()).collect();
+        let node_pos = |layer_idx : usize, node_idx : usize| get_dream_node_pos(&layer_sizes, layer_idx, node_idx);
+
+        for (layer_idx, layer_weights) in weights.iter().enumerate() {
+            let max_signal = (0..layer_weights.get_nrows())
+                .flat_map(|to| (0..layer_weights.get_ncols()).map(move |from| (to, from)))
+                .map(|(to, from)| (layer_weights.get(to, from) * layers[layer_idx][from]).abs())
+                .fold(0.0, f32::max);
+            if max_signal <= 0.0 {
+                continue;
+            }
+            for to in 0..layer_weights.get_nrows() {
+                for from in 0..layer_weights.get_ncols() {
+                    let signal = layer_weights.get(to, from) * layers[layer_idx][from];
+                    let alpha = 0.05 + 0.6 * signal.abs() / max_signal;
+                    let color = if signal >= 0.0 { Color::new(0.2, 0.5, 1.0, alpha) } else { Color::new(1.0, 0.2, 0.2, alpha) };
+                    let (start, end) = (node_pos(layer_idx, from), node_pos(layer_idx + 1, to));
+                    draw_line(start.x, start.y, end.x, end.y, 1.5, color);
+                }
+            }
+        }
+
+        // The output neuron that wins is the action the creature would take
+        let outputs = &layers[layers.len() - 1];
+        let chosen_idx = outputs.iter().enumerate().fold(0, |best, (i, val)| if *val >= outputs[best] { i } else { best });
+        for (layer_idx, layer) in layers.iter().enumerate() {
+            let max_act = layer.iter().cloned().fold(0.0, f32::max);
+            for (node_idx, act) in layer.iter().enumerate() {
+                let glow = if max_act > 0.0 { act / max_act } else { 0.0 };
+                let pos = node_pos(layer_idx, node_idx);
+                draw_circle(pos.x, pos.y, DREAM_NODE_RADIUS, Color::new(1.0, 0.85, 0.2, 0.1 + 0.9 * glow));
+                draw_circle_lines(pos.x, pos.y, DREAM_NODE_RADIUS, 1.0, GRAY);
+            }
+        }
+
+        // Highlight the weight/bias being tweaked
+        if let Some(tweak) = self.brain_tweak.as_ref().filter(|tweak| tweak.creature_id == creature.id) {
+            match tweak.param {
+                BrainParam::Weight {layer, to, from} => {
+                    let (start, end) = (node_pos(layer, from), node_pos(layer + 1, to));
+                    draw_line(start.x, start.y, end.x, end.y, 3.0, YELLOW);
+                },
+                BrainParam::Bias {layer, node} => {
+                    let pos = node_pos(layer + 1, node);
+                    draw_circle_lines(pos.x, pos.y, DREAM_NODE_RADIUS + 3.0, 3.0, YELLOW);
+                },
+            }
+        }
+
+        for (input_idx, input_type) in creature.brain.input_node_types.iter().enumerate() {
+            let pos = node_pos(0, input_idx);
+            draw_text(&format!("{:?}", input_type), panel.x + 10.0, pos.y + 5.0, DREAM_FONT_SIZE, LIGHTGRAY);
+        }
+        for (output_idx, action) in creature.brain.output_node_types.iter().enumerate() {
+            let pos = node_pos(layers.len() - 1, output_idx);
+            let color = if output_idx == chosen_idx { YELLOW } else { LIGHTGRAY };
+            draw_text(&format!("{:?}", action), pos.x + 2.0 * DREAM_NODE_RADIUS, pos.y + 5.0, DREAM_FONT_SIZE, color);
+        }
+    }
+
+    /// Draw every space of a board snapshot, starting `x_offset` pixels from the left of the window.
+    /// If `movement` is given (previous positions by creature ID, progress from 0 to 1), creatures that moved
+    /// one space are drawn part way between their previous and current space
+    fn draw_board(&self, snapshot : &EnvSnapshot, x_offset : f32, movement : Option<(&HashMap<usize, CreaturePosition>, f32)>) {
+
+        // River goes underneath everything so food floating on it is still visible
+        if let Some(opacity) = self.get_overlay_opacity(OverlayKind::River) {
+            self.draw_river(snapshot, x_offset, opacity);
+        }
 
         // For each simulation space on the board, update with proper piece
-        for x in 0..self.env.params.env_x_size {
-            for y in 0..self.env.params.env_y_size {
-                match self.env.positions[x][y] {
-                    SpaceStates::CreatureSpace(id) => {
-                        let c_id = self.env.get_creature_idx_from_id(id).unwrap(); 
-                        let creature : &CreatureV1 = &self.env.creatures[c_id];
-                        self.draw_creature_square(x, y, creature.orientation, creature.color);
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                match snapshot.get_cell(x, y) {
+                    SnapshotCell::Creature(c_idx) => {
+                        let creature = &snapshot.creatures[c_idx];
+                        let mut color = if self.color_by_species { get_species_color(creature.species_id) } else { creature.color };
+                        if creature.infected {
+                            color = get_infected_tint(color);
+                        }
+
+                        // The second space of a large creature is just its body color
+                        if creature.position != (CreaturePosition {x : x, y : y}) {
+                            self.draw_creature_tail(x, y, x_offset, color);
+                            continue;
+                        }
+                        let (mut draw_x, mut draw_y) = (x as f32, y as f32);
+                        if let Some((prev_positions, progress)) = movement {
+                            if let Some(prev) = prev_positions.get(&creature.id) {
+                                // Only slide single-space moves. Anything else (e.g. being pushed out of a wall) just jumps
+                                if prev.x.abs_diff(x) + prev.y.abs_diff(y) == 1 {
+                                    draw_x = prev.x as f32 + (draw_x - prev.x as f32) * progress;
+                                    draw_y = prev.y as f32 + (draw_y - prev.y as f32) * progress;
+                                }
+                            }
+                        }
+                        self.draw_creature_square(draw_x, draw_y, x_offset, creature.orientation, color);
                     }
-                    SpaceStates::FoodSpace => self.draw_food_space(x, y),
-                    SpaceStates::WallSpace => self.draw_wall_space(x, y),
-                    SpaceStates::FightSpace(_ttl) => self.draw_fight_space(x, y), 
-                    SpaceStates::BlankSpace => (),
+                    SnapshotCell::Food => self.draw_food_space(x, y, x_offset),
+                    SnapshotCell::Wall => self.draw_wall_space(x, y, x_offset),
+                    SnapshotCell::Blank => if let Some(decal) = snapshot.get_decal_at(x, y) {
+                        self.draw_decal(x, y, x_offset, decal);
+                    },
+                }
+            }
+        }
+
+        // Draw the beacon on top of whatever space it's in
+        if let Some(beacon) = snapshot.beacon {
+            self.draw_beacon(beacon.x, beacon.y, x_offset);
+        }
+
+        for overlay in self.overlays.iter().filter(|o| o.enabled) {
+            match overlay.kind {
+                OverlayKind::River => (), // Already drawn underneath
+                OverlayKind::Hillshade => self.draw_hillshade_overlay(snapshot, x_offset, overlay.opacity),
+                OverlayKind::Territory => self.draw_territory_overlay(snapshot, x_offset, overlay.opacity),
+                OverlayKind::Density => self.draw_density_overlay(snapshot, DENSITY_OVERLAY_OPTIONS[self.density_overlay_idx], x_offset, overlay.opacity),
+            }
+        }
+
+        // Outline the creature picked from the leaderboard
+        if self.show_leaderboard {
+            if let Some(creature) = snapshot.creatures.iter().find(|c| Some(c.id) == self.dream_creature_id) {
+                let (x, y) = (x_offset + creature.position.x as f32 * self.grid_x_size, creature.position.y as f32 * self.grid_y_size);
+                draw_rectangle_lines(x, y, self.grid_x_size, self.grid_y_size, 2.0, LEADERBOARD_SELECTION_COLOR);
+            }
+        }
+    }
+
+    /// Get the opacity to draw an overlay at, or None if it's turned off
+    fn get_overlay_opacity(&self, kind : OverlayKind) -> Option<f32> {
+        return self.overlays.iter().find(|o| o.kind == kind && o.enabled).map(|o| o.opacity);
+    }
+
+    /// Draw the river as a band of translucent blue spaces
+    fn draw_river(&self, snapshot : &EnvSnapshot, x_offset : f32, opacity : f32) {
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                if snapshot.is_river_at(x, y) {
+                    let pos_x = x_offset + (x as f32) * self.grid_x_size;
+                    let pos_y = (y as f32) * self.grid_y_size;
+                    draw_rectangle(pos_x, pos_y, self.grid_x_size, self.grid_y_size, Color {a : RIVER_COLOR.a * opacity, ..RIVER_COLOR});
+                }
+            }
+        }
+    }
+
+    /// Shade the board so hills stand out. Slopes facing the light are brightened and slopes facing away are darkened
+    fn draw_hillshade_overlay(&self, snapshot : &EnvSnapshot, x_offset : f32, opacity : f32) {
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                if let Some(shade) = snapshot.get_hillshade_at(x, y) {
+                    // Flat ground is lit at 1/sqrt(2) (the light comes in at 45 degrees), so shade relative to that
+                    let contrast = (shade - std::f32::consts::FRAC_1_SQRT_2) / std::f32::consts::FRAC_1_SQRT_2;
+                    let base = if contrast > 0.0 { WHITE } else { BLACK };
+                    let pos_x = x_offset + (x as f32) * self.grid_x_size;
+                    let pos_y = (y as f32) * self.grid_y_size;
+                    draw_rectangle(pos_x, pos_y, self.grid_x_size, self.grid_y_size, Color {a : contrast.abs().min(1.0) * HILLSHADE_OVERLAY_MAX_ALPHA * opacity, ..base});
+                }
+            }
+        }
+    }
+
+    /// Tint every marked space with a color picked from the owning lineage's ID. Fresh marks are more opaque than fading ones
+    fn draw_territory_overlay(&self, snapshot : &EnvSnapshot, x_offset : f32, opacity : f32) {
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                if let Some(mark) = snapshot.get_territory_at(x, y) {
+                    let color = get_species_color(mark.owner);
+                    let pos_x = x_offset + (x as f32) * self.grid_x_size;
+                    let pos_y = (y as f32) * self.grid_y_size;
+                    draw_rectangle(pos_x, pos_y, self.grid_x_size, self.grid_y_size, Color::from_rgba(color.red, color.green, color.blue, (mark.strength * TERRITORY_OVERLAY_MAX_ALPHA * opacity * 255.0) as u8));
+                }
+            }
+        }
+    }
+
+    /// Shade the board by how crowded each area is. The smoothed density is split into bands (relative to the densest
+    /// spot on the board) that go from transparent blue to red, with a line drawn wherever two bands meet
+    fn draw_density_overlay(&self, snapshot : &EnvSnapshot, source : DensitySource, x_offset : f32, opacity : f32) {
+        let density = snapshot.get_density_field(source, DENSITY_SMOOTHING_RADIUS);
+        let max_density = density.iter().cloned().fold(0.0, f32::max);
+        if max_density <= 0.0 {
+            return;
+        }
+
+        let num_levels = DENSITY_CONTOUR_LEVELS;
+        let levels : Vec<usize> = density.iter().map(|d| ((d / max_density) * num_levels as f32).min(num_levels as f32 - 1.0) as usize).collect();
+        let level_at = |x : usize, y : usize| levels[x * snapshot.y_size + y];
+
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                let level = level_at(x, y);
+                let pos_x = x_offset + (x as f32) * self.grid_x_size;
+                let pos_y = (y as f32) * self.grid_y_size;
+                if level > 0 {
+                    let frac = level as f32 / (num_levels - 1) as f32;
+                    draw_rectangle(pos_x, pos_y, self.grid_x_size, self.grid_y_size, Color {r: frac, g: 0.2, b: 1.0 - frac, a: frac * DENSITY_OVERLAY_MAX_ALPHA * opacity});
+                }
+
+                // Contour lines on the right/bottom edges where the band changes
+                if x + 1 < snapshot.x_size && level_at(x + 1, y) != level {
+                    draw_line(pos_x + self.grid_x_size, pos_y, pos_x + self.grid_x_size, pos_y + self.grid_y_size, 1.0, WHITE);
+                }
+                if y + 1 < snapshot.y_size && level_at(x, y + 1) != level {
+                    draw_line(pos_x, pos_y + self.grid_y_size, pos_x + self.grid_x_size, pos_y + self.grid_y_size, 1.0, WHITE);
                 }
             }
         }
@@ -347,6 +1316,86 @@ impl EnvMacroquad {
             ui.label(None, &stat_txt); 
             stat_txt = format!("{:22} {:<12}", "NUM NATURAL DEATHS:", self.env.num_natural_deaths);
             ui.label(None, &stat_txt); 
+            if self.env.beacon.is_some() {
+                stat_txt = format!("{:22} {:<12}", "NUM BEACON REACHES:", self.env.num_beacon_reaches);
+                ui.label(None, &stat_txt); 
+            }
+            if self.env.params.enable_disease {
+                let (num_sick, num_immune) = self.env.get_infection_counts();
+                stat_txt = format!("{:22} {} / {}", "SICK / IMMUNE:", num_sick, num_immune);
+                ui.label(None, &stat_txt); 
+                stat_txt = format!("{:22} {} / {}", "INFECTIONS / RECOVERED:", self.env.num_infections, self.env.num_recoveries);
+                ui.label(None, &stat_txt); 
+            }
+            if let Some(summary) = self.env.stats.summarize_lifetimes() {
+                stat_txt = format!("{:22} {:<12.1}", "AVG LIFESPAN (DEAD):", summary.avg_lifespan);
+                ui.label(None, &stat_txt); 
+                stat_txt = format!("{:22} {:.1} / {:.1} / {:.1}", "AVG FOOD/KILLS/KIDS:", summary.avg_food_eaten, summary.avg_kills, summary.avg_offspring);
+                ui.label(None, &stat_txt); 
+                stat_txt = format!("{:22} {:.1} / {:.1}", "AVG MOVED/ROTATED:", summary.avg_distance_traveled, summary.avg_rotations);
+                ui.label(None, &stat_txt); 
+            }
+            if self.env.params.enable_speciation {
+                stat_txt = format!("{:22} {:<12}", "NUM SPECIES:", self.env.num_species);
+                ui.label(None, &stat_txt); 
+                let species_history : Vec<usize> = self.env.species_count_history.iter().copied().collect();
+                draw_sparkline(ui, &species_history, species_history.iter().copied().max().unwrap_or(0), SPECIES_HISTORY_LEN);
+            }
+            if !self.env.stats.immigrants.is_empty() {
+                let lineages = self.env.stats.get_immigrant_lineage_stats(&self.env.creatures);
+                let num_surviving = lineages.iter().filter(|l| l.num_alive > 0).count();
+                stat_txt = format!("{:22} {} ({} lineages alive)", "IMMIGRANTS:", lineages.len(), num_surviving);
+                ui.label(None, &stat_txt); 
+                if let Some(best) = lineages.first().filter(|l| l.num_alive > 0) {
+                    stat_txt = format!("{:22} {} ({} alive, {} dead)", "TOP IMMIGRANT LINEAGE:", best.lineage_id, best.num_alive, best.num_dead);
+                    ui.label(None, &stat_txt); 
+                }
+            }
+            if self.env.params.enable_swimming {
+                stat_txt = format!("{:22} {:<12}", "SWIMMERS:", self.env.get_num_swimmers());
+                ui.label(None, &stat_txt); 
+                let swimmer_history : Vec<usize> = self.env.swimmer_count_history.iter().copied().collect();
+                draw_sparkline(ui, &swimmer_history, self.env.num_creatures.max(1), SWIMMER_HISTORY_LEN);
+            }
+
+            // Combined stats comparing the two boards
+            if let Some(compare) = &self.compare_board {
+                let (env_a, env_b) = (&self.env, &compare.env);
+                let max_gen = |env : &EnvironmentV1| env.creatures.iter().map(|c| c.generation).max().unwrap_or(0);
+                ui.label(None, "");
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "COMPARISON:", "A", "B"));
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "MUTATION PROB:", env_a.params.mutation_prob, env_b.params.mutation_prob));
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "CURRENT CREATURES:", env_a.num_creatures, env_b.num_creatures));
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "TOTAL CREATURES:", env_a.num_total_creatures, env_b.num_total_creatures));
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "MAX GENERATION:", max_gen(env_a), max_gen(env_b)));
+                ui.label(None, &format!("{:22} {:<12} {:<12}", "NUM KILLS:", env_a.num_kills, env_b.num_kills));
+                if env_a.params.enable_speciation || env_b.params.enable_speciation {
+                    ui.label(None, &format!("{:22} {:<12} {:<12}", "NUM SPECIES:", env_a.num_species, env_b.num_species));
+                }
+
+                // Population graphs share a scale so they can be compared directly
+                let max_population = compare.population_history.iter().flatten().copied().max().unwrap_or(0);
+                for (label, history) in ["A", "B"].iter().zip(compare.population_history.iter()) {
+                    ui.label(None, &format!("POPULATION {}:", label));
+                    let history : Vec<usize> = history.iter().copied().collect();
+                    draw_sparkline(ui, &history, max_population, COMPARE_HISTORY_LEN);
+                }
+
+                // Score both boards on every objective and show which are on the Pareto front
+                let report = ParetoReport::new(vec![
+                    (String::from("A"), ObjectiveScores::from_run(env_a)),
+                    (String::from("B"), ObjectiveScores::from_run(env_b)),
+                ]);
+                ui.label(None, "");
+                let (scores_a, scores_b) = (&report.entries[0].scores, &report.entries[1].scores);
+                for objective in ALL_OBJECTIVES {
+                    let label = format!("{}:", objective.get_name().to_uppercase());
+                    ui.label(None, &format!("{:22} {:<12.2} {:<12.2}", label, scores_a.get(objective), scores_b.get(objective)));
+                }
+                let front : Vec<&str> = report.get_front().iter().map(|entry| entry.label.as_str()).collect();
+                ui.label(None, &format!("{:22} {}", "PARETO FRONT:", front.join(", ")));
+                draw_objective_bars(ui, &report);
+            }
 
             // Get info on the space the mouse is hovering over
             ui.label(None, "");
@@ -361,20 +1410,67 @@ impl EnvMacroquad {
                 ui.label(None, format!(" Space X:{} Y:{}    {:?}", env_x, env_y, space_type).as_str());
                 match space_type {
                     SpaceStates::CreatureSpace(c_id) => {
+                        self.dream_creature_id = Some(c_id);
                         let c_idx = self.env.get_creature_idx_from_id(c_id).unwrap();
                         let creature = &self.env.creatures[c_idx];
                         ui.label(None, format!("  Creature ID:      {}", creature.id).as_str());
+                        ui.label(None, "  Fingerprint:");
+                        ui.same_line(SALIENCY_BAR_X);
+                        draw_fingerprint(ui, &get_genome_fingerprint(&creature.brain));
                         ui.label(None, format!("  Age:              {}", creature.age).as_str());
+                        ui.label(None, format!("  Generation:       {}", creature.generation).as_str());
+                        ui.label(None, format!("  Moved/Rotated:    {} / {}", creature.distance_traveled, creature.num_rotations).as_str());
+                        ui.label(None, format!("  Food/Kills/Kids:  {} / {} / {}", creature.food_eaten, creature.num_kills, creature.num_offspring).as_str());
+                        if self.env.params.enable_speciation {
+                            ui.label(None, format!("  Species:          {}", creature.species_id).as_str());
+                        }
+                        if self.env.params.enable_novelty {
+                            ui.label(None, format!("  Novelty:          {:.3} (rank {:.2})", creature.novelty, creature.novelty_rank).as_str());
+                        }
                         ui.label(None, format!("  Energy:           {}", creature.energy).as_str());
+                        let energy_history : Vec<usize> = creature.energy_history.iter().copied().collect();
+                        draw_sparkline(ui, &energy_history, energy_history.iter().copied().max().unwrap_or(0), ENERGY_HISTORY_LEN);
                         ui.label(None, format!("  Color (r, g, b):  {}, {}, {}", creature.color.red, creature.color.green, creature.color.blue).as_str());
                         ui.label(None, format!("  Last Action:      {:?}", creature.last_action).as_str());
+                        ui.label(None, format!("  Last Result:      {:?}", creature.last_action_result).as_str());
+                        if let Some(next_action) = self.env.get_intended_action(creature.id) {
+                            ui.label(None, format!("  Next Action:      {:?} ({:?})", next_action, self.env.preview_action(creature.id, next_action)).as_str());
+                        }
+                        if creature.is_pregnant() {
+                            ui.label(None, format!("  Pregnant:         {} offspring in {} steps", creature.pending_offspring, creature.gestation_steps_remaining).as_str());
+                        }
                         ui.label(None, format!("  Orientation:      {:?}", creature.orientation).as_str());
+                        if self.env.params.evolve_view_distance {
+                            ui.label(None, format!("  View Distance:    {}", creature.view_distance).as_str());
+                        }
+                        if self.env.params.enable_disease {
+                            ui.label(None, format!("  Infection:        {:?}", creature.infection).as_str());
+                        }
+                        if self.env.params.enable_swimming {
+                            ui.label(None, format!("  Can Swim:         {}", creature.can_swim).as_str());
+                        }
+                        if let Some(pending_action) = creature.pending_action {
+                            ui.label(None, format!("  Thinking:         {:?} in {} steps", pending_action, creature.thinking_steps_left).as_str());
+                        }
                         ui.label(None, format!("  Vision (r,g,b, dist): {}, {}, {}, {}", 
                             creature.vision_state.color.red,
                             creature.vision_state.color.green,
                             creature.vision_state.color.blue,
                             creature.vision_state.dist,
                             ).as_str());
+                        for line in creature.brain.describe().lines() {
+                            ui.label(None, &format!("  {}", line));
+                        }
+
+                        // Which inputs the chosen action is most sensitive to
+                        let (chosen_action, saliency) = creature.brain.get_input_saliency();
+                        ui.label(None, &format!("  Input saliency for {:?}:", chosen_action));
+                        let max_saliency = saliency.iter().map(|(_, s)| *s).fold(0.0, f32::max);
+                        for (input_type, value) in saliency.iter() {
+                            ui.label(None, &format!("    {:?}", input_type));
+                            ui.same_line(SALIENCY_BAR_X);
+                            draw_bar(ui, if max_saliency > 0.0 { value / max_saliency } else { 0.0 });
+                        }
                     },
                     _ => {},
                 }
@@ -385,7 +1481,10 @@ impl EnvMacroquad {
                 ui.label(None, "BLACK       => Wall space");
                 ui.label(None, "BLUE        => Passive Creature (has not killed)");
                 ui.label(None, "RED         => Violent Creature (turns more red with each kill)");
-                ui.label(None, "LIGHT RED   => Fight space (creature was killed here)")
+                ui.label(None, "LIGHT RED   => Fight decal (creature was killed here)");
+                ui.label(None, "LIGHT BLUE  => Birth decal (creature was born here)");
+                ui.label(None, "GREY        => Death decal (creature died of old age/starvation here)");
+                ui.label(None, "YELLOW RING => Beacon (if enabled)")
 
             }
 
@@ -394,6 +1493,15 @@ impl EnvMacroquad {
             if is_mouse_button_down(MouseButton::Left) {
                 if env_x < self.env.params.env_x_size && env_y < self.env.params.env_y_size {
                     let pos = CreaturePosition {x : env_x, y : env_y};
+                    if self.select_creatures {
+                        return;
+                    }
+                    if self.edit_creatures {
+                        if let SpaceStates::CreatureSpace(c_id) = self.env.positions[env_x][env_y] {
+                            self.open_creature_editor(c_id);
+                        }
+                        return;
+                    }
                     match self.current_draw_space_type {
                         None => (),
                         Some(SpaceStates::FoodSpace) => self.env.add_food_space(pos),
@@ -401,6 +1509,9 @@ impl EnvMacroquad {
                         Some(SpaceStates::BlankSpace) => self.env.add_blank_space(pos),
                         _ => (),
                     }
+                    if self.elevation_brush != 0.0 {
+                        self.env.adjust_elevation(pos, self.elevation_brush);
+                    }
 
                 }
             }
@@ -439,6 +1550,52 @@ impl EnvMacroquad {
                 }
             }
 
+            ui.checkbox(hash!(), "Color by Species", &mut self.color_by_species);
+            if self.color_by_species && !self.env.params.enable_speciation {
+                // Species colors need species tracking, so turn it on and sort the creatures into species right away
+                self.env.params.enable_speciation = true;
+                self.params.enable_speciation = true;
+                self.env.update_species();
+            }
+            ui.combo_box(hash!(), "Creature Style", &CREATURE_STYLE_LABELS, &mut self.creature_style_idx);
+            ui.checkbox(hash!(), "Smooth Movement", &mut self.smooth_movement);
+            ui.checkbox(hash!(), "Speed Governor", &mut self.speed_governor);
+            if self.speed_governor {
+                ui.slider(hash!(), "Min Steps/Sec", 0.5..10.0, &mut self.governor_min_rate);
+                ui.slider(hash!(), "Max Steps/Sec", 1.0..60.0, &mut self.governor_max_rate);
+            }
+            if ui.button(None, "OVERLAYS") {
+                self.show_overlay_panel = !self.show_overlay_panel;
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "GROUPS") {
+                self.show_group_panel = !self.show_group_panel;
+            }
+            ui.same_line(0.0);
+            if ui.button(None, "LEADERS") {
+                self.show_leaderboard = !self.show_leaderboard;
+            }
+            ui.checkbox(hash!(), "Dream When Paused", &mut self.dream_when_paused);
+            ui.checkbox(hash!(), "Visual Effects", &mut self.show_effects);
+            ui.slider(hash!(), "Sound Volume", 0.0..1.0, &mut self.sound_volume);
+            ui.checkbox(hash!(), "Mute Sound", &mut self.sound_muted);
+
+            // Notes are kept in the run's metadata and written into every save
+            ui.input_text(hash!(), "Run Notes", &mut self.env.metadata.notes);
+            if let Some(save_id) = &self.env.metadata.save_id {
+                ui.label(None, &format!("Loaded From Save {}", save_id));
+            }
+
+            // Split-screen comparison against a copy of the current board
+            ui.checkbox(hash!(), "Compare: Shared Params", &mut self.params.compare_shared_params);
+            ui.slider(hash!(), "Compare: B Mutation Prob", get_slider_range("mutation_prob"), &mut self.params.compare_mutation_prob);
+            if ui.button(None, "START/STOP COMPARISON") {
+                match self.compare_board {
+                    Some(_) => self.stop_comparison(),
+                    None => self.start_comparison(),
+                }
+            }
+
             let chosen_option = ui.combo_box(hash!(), "Space to Draw", &["None", "Food", "Wall", "Blank"], None);
             match chosen_option {
                 0 => self.current_draw_space_type = None,
@@ -447,6 +1604,38 @@ impl EnvMacroquad {
                 3 => self.current_draw_space_type = Some(SpaceStates::BlankSpace),
                 _ => self.current_draw_space_type = None, 
             }
+
+            ui.checkbox(hash!(), "Edit Creatures (click one)", &mut self.edit_creatures);
+            ui.checkbox(hash!(), "Select Creatures (drag a box)", &mut self.select_creatures);
+
+            let chosen_brush = ui.combo_box(hash!(), "Terrain Brush", &["None", "Raise", "Lower"], None);
+            self.elevation_brush = match chosen_brush {
+                1 => ELEVATION_BRUSH_STEP,
+                2 => -ELEVATION_BRUSH_STEP,
+                _ => 0.0,
+            };
+
+            // Grow/crop the running board to the Env X/Y Size parameters without restarting
+            ui.combo_box(hash!(), "Resize Anchor", &RESIZE_ANCHOR_LABELS, &mut self.resize_anchor_idx);
+            if ui.button(None, "RESIZE BOARD") {
+                self.resize_environment();
+            }
+
+            // Bring in creatures from the file in the filename box
+            ui.checkbox(hash!(), "Import: Adapt Brains", &mut self.import_adapt_brains);
+            ui.combo_box(hash!(), "Import Region", &IMPORT_REGION_LABELS, &mut self.import_region_idx);
+            if ui.button(None, "IMPORT CREATURES") {
+                let temp_filename = self.params.save_load_filename.clone();
+                self.import_creatures(temp_filename.as_str());
+            }
+
+            // Stitch the saved environment in the filename box onto the current one
+            ui.combo_box(hash!(), "Merge Side", &MERGE_DIRECTION_LABELS, &mut self.merge_direction_idx);
+            ui.combo_box(hash!(), "Merge Seam", &MERGE_SEAM_LABELS, &mut self.merge_seam_idx);
+            if ui.button(None, "MERGE WITH SAVED FILE") {
+                let temp_filename = self.params.save_load_filename.clone();
+                self.merge_environment(temp_filename.as_str());
+            }
         });
 
 
@@ -480,6 +1669,51 @@ impl EnvMacroquad {
                 let temp_filename = self.params.save_load_filename.clone();
                 self.load_environmnt(temp_filename.as_str());
             }
+            // Button to replace the walls with a layout drawn in an image file
+            #[cfg(feature = "image")]
+            if ui.button(Vec2{x : 400.0, y: text_height_px * 3.0}, "IMPORT WALLS FROM IMAGE") {
+                let temp_filename = self.params.save_load_filename.clone();
+                self.env.load_walls_from_image(temp_filename.as_str());
+            }
+            // Button to export a summary of every dead creature's life as CSV
+            if ui.button(Vec2{x : 650.0, y: text_height_px * 3.0}, "EXPORT LIFETIMES CSV") {
+                self.env.stats.save_lifetimes_csv(self.params.save_load_filename.as_str());
+            }
+            // Button to export the last inspected creature's brain as a GraphViz graph
+            if ui.button(Vec2{x : 870.0, y: text_height_px * 3.0}, "EXPORT BRAIN DOT") {
+                self.save_brain_dot(self.params.save_load_filename.as_str());
+            }
+            // Button to export the last inspected creature as a PNG "card" (color, fingerprint, stats, genome hash and brain)
+            #[cfg(feature = "image")]
+            if ui.button(Vec2{x : 760.0, y: text_height_px * 1.4}, "EXPORT CREATURE CARD") {
+                match self.get_inspected_creature() {
+                    Some(creature) => self.env.save_creature_card_png(creature.id, self.params.save_load_filename.as_str()),
+                    None => println!("Error: no living creature to export a card of"),
+                }
+            }
+
+            // Check boxes for making saves smaller
+            let save_box_size = Vec2 { x: 250.0, y: text_height_px };
+            widgets::Checkbox::new(hash!())
+                .label("Save: Strip Activations")
+                .pos(Vec2{x : 0.0, y: text_height_px * 7.0})
+                .size(save_box_size)
+                .ui(ui, &mut self.save_opts.exclude_activations);
+            widgets::Checkbox::new(hash!())
+                .label("Save: Map Only")
+                .pos(Vec2{x : 250.0, y: text_height_px * 7.0})
+                .size(save_box_size)
+                .ui(ui, &mut self.save_opts.exclude_creatures);
+            widgets::Checkbox::new(hash!())
+                .label("Save: Round Weights")
+                .pos(Vec2{x : 500.0, y: text_height_px * 7.0})
+                .size(save_box_size)
+                .ui(ui, &mut self.save_opts.round_weights);
+            widgets::Checkbox::new(hash!())
+                .label("Save: Walls Only")
+                .pos(Vec2{x : 750.0, y: text_height_px * 7.0})
+                .size(Vec2 { x: 200.0, y: text_height_px })
+                .ui(ui, &mut self.save_walls_only);
 
             // Text box that gets file name to load/save
             widgets::InputText::new(hash!())
@@ -488,33 +1722,314 @@ impl EnvMacroquad {
                 .size(Vec2 { x: CONTROL2_PANEL_WIDTH / 1.5, y: text_height_px })
                 .ui(ui, &mut self.params.save_load_filename);
 
+            // Optional per-layer files, so e.g. the map can come from one save and the creatures from another
+            let layer_box_size = Vec2 { x: CONTROL2_PANEL_WIDTH / 2.5, y: text_height_px };
+            let layer_col2_x = self.control_panel_x_pos + CONTROL2_PANEL_WIDTH / 2.0;
+            widgets::InputText::new(hash!())
+                .position(Vec2{x: self.control_panel_x_pos + 5., y: self.control_panel_y_pos + text_height_px * 4.6})
+                .label("Params From")
+                .size(layer_box_size)
+                .ui(ui, &mut self.params.params_filename);
+            widgets::InputText::new(hash!())
+                .position(Vec2{x: layer_col2_x, y: self.control_panel_y_pos + text_height_px * 4.6})
+                .label("Creatures From")
+                .size(layer_box_size)
+                .ui(ui, &mut self.params.creatures_filename);
+            widgets::InputText::new(hash!())
+                .position(Vec2{x: self.control_panel_x_pos + 5., y: self.control_panel_y_pos + text_height_px * 5.8})
+                .label("Walls From")
+                .size(layer_box_size)
+                .ui(ui, &mut self.params.walls_filename);
+            widgets::InputText::new(hash!())
+                .position(Vec2{x: layer_col2_x, y: self.control_panel_y_pos + text_height_px * 5.8})
+                .label("Food From")
+                .size(layer_box_size)
+                .ui(ui, &mut self.params.food_filename);
+
 
             // ui.pop_skin();
         });
     }
 
-    /// Update the simulation parameters panel
+    /// Update the simulation parameters panel. Hovering over a param shows what it does in a tooltip next to the panel
     fn update_sim_param_panel(&mut self) {
+        let mut param_rows : Vec<(f32, &'static str)> = Vec::new();
 
         root_ui().window(hash!(), vec2(self.param_panel_x_pos, self.param_panel_y_pos), vec2(PARAM_PANEL_WIDTH, PARAM_PANEL_HEIGHT), |ui| {
                 ui.label(None, "SIMULATION PARAMETERS");
-                ui.input_text(hash!(), "Env X Size", &mut self.params.env_x_size);
-                ui.input_text(hash!(), "Env Y Size", &mut self.params.env_y_size);
-                ui.input_text(hash!(), "Num Start Creatures", &mut self.params.num_start_creatures);
-                ui.input_text(hash!(), "Num Start Food", &mut self.params.num_start_food);
-                ui.input_text(hash!(), "Num Start Walls", &mut self.params.num_start_walls);
-                ui.input_text(hash!(), "Energy per Food", &mut self.params.energy_per_food_piece);
-                ui.input_text(hash!(), "Max offspring per Reproduce", &mut self.params.max_offspring_per_reproduce);
-                ui.input_text(hash!(), "Mutation Probability", &mut self.params.mutation_prob);
-                ui.input_text(hash!(), "Avg New Food per Step", &mut self.params.avg_new_food_per_day);
+                mark_param_row(ui, &mut param_rows, "env_x_size");
+                ui.slider(hash!(), "Env X Size", get_slider_range("env_x_size"), &mut self.params.env_x_size);
+                mark_param_row(ui, &mut param_rows, "env_y_size");
+                ui.slider(hash!(), "Env Y Size", get_slider_range("env_y_size"), &mut self.params.env_y_size);
+                mark_param_row(ui, &mut param_rows, "num_start_creatures");
+                ui.slider(hash!(), "Num Start Creatures", get_slider_range("num_start_creatures"), &mut self.params.num_start_creatures);
+                mark_param_row(ui, &mut param_rows, "num_start_food");
+                ui.slider(hash!(), "Num Start Food", get_slider_range("num_start_food"), &mut self.params.num_start_food);
+                mark_param_row(ui, &mut param_rows, "num_start_walls");
+                ui.slider(hash!(), "Num Start Walls", get_slider_range("num_start_walls"), &mut self.params.num_start_walls);
+                mark_param_row(ui, &mut param_rows, "energy_per_food_piece");
+                ui.slider(hash!(), "Energy per Food", get_slider_range("energy_per_food_piece"), &mut self.params.energy_per_food_piece);
+                mark_param_row(ui, &mut param_rows, "max_offspring_per_reproduce");
+                ui.slider(hash!(), "Max offspring per Reproduce", get_slider_range("max_offspring_per_reproduce"), &mut self.params.max_offspring_per_reproduce);
+                mark_param_row(ui, &mut param_rows, "mutation_prob");
+                ui.slider(hash!(), "Mutation Probability", get_slider_range("mutation_prob"), &mut self.params.mutation_prob);
+                mark_param_row(ui, &mut param_rows, "mutation_mix");
+                ui.slider(hash!(), "Mutation Weight: Gaussian", get_slider_range("mutation_mix"), &mut self.params.mutation_weight_gaussian);
+                mark_param_row(ui, &mut param_rows, "mutation_mix");
+                ui.slider(hash!(), "Mutation Weight: Sign Flip", get_slider_range("mutation_mix"), &mut self.params.mutation_weight_sign_flip);
+                mark_param_row(ui, &mut param_rows, "mutation_mix");
+                ui.slider(hash!(), "Mutation Weight: Reset", get_slider_range("mutation_mix"), &mut self.params.mutation_weight_reset);
+                mark_param_row(ui, &mut param_rows, "mutation_mix");
+                ui.slider(hash!(), "Mutation Weight: Swap", get_slider_range("mutation_mix"), &mut self.params.mutation_weight_swap);
+                mark_param_row(ui, &mut param_rows, "mutation_mix");
+                ui.slider(hash!(), "Mutation Weight: Layer Scale", get_slider_range("mutation_mix"), &mut self.params.mutation_weight_layer_scale);
+                mark_param_row(ui, &mut param_rows, "mutation_schedule");
+                ui.combo_box(hash!(), "Mutation Schedule", &MUTATION_SCHEDULE_LABELS, &mut self.params.mutation_schedule_idx);
+                mark_param_row(ui, &mut param_rows, "mutation_anneal_rate");
+                ui.slider(hash!(), "Mutation Anneal Rate", get_slider_range("mutation_anneal_rate"), &mut self.params.mutation_anneal_rate);
+                mark_param_row(ui, &mut param_rows, "offspring_energy_fraction");
+                ui.slider(hash!(), "Offspring Energy Fraction", get_slider_range("offspring_energy_fraction"), &mut self.params.offspring_energy_fraction);
+                mark_param_row(ui, &mut param_rows, "gestation_steps");
+                ui.slider(hash!(), "Gestation Steps", get_slider_range("gestation_steps"), &mut self.params.gestation_steps);
+                mark_param_row(ui, &mut param_rows, "gestation_upkeep_energy");
+                ui.slider(hash!(), "Gestation Upkeep Energy", get_slider_range("gestation_upkeep_energy"), &mut self.params.gestation_upkeep_energy);
+                mark_param_row(ui, &mut param_rows, "avg_new_food_per_day");
+                ui.slider(hash!(), "Avg New Food per Step", get_slider_range("avg_new_food_per_day"), &mut self.params.avg_new_food_per_day);
+                mark_param_row(ui, &mut param_rows, "enable_position_inputs");
+                ui.checkbox(hash!(), "Position Inputs", &mut self.params.enable_position_inputs);
+                mark_param_row(ui, &mut param_rows, "enable_wall_distance_input");
+                ui.checkbox(hash!(), "Wall Distance Input", &mut self.params.enable_wall_distance_input);
+                mark_param_row(ui, &mut param_rows, "enable_move_blocked_input");
+                ui.checkbox(hash!(), "Move Blocked Input", &mut self.params.enable_move_blocked_input);
+                mark_param_row(ui, &mut param_rows, "one_hot_categorical_inputs");
+                ui.checkbox(hash!(), "One-Hot Categorical Inputs", &mut self.params.one_hot_categorical_inputs);
+                mark_param_row(ui, &mut param_rows, "enable_hearing_input");
+                ui.checkbox(hash!(), "Hearing Input", &mut self.params.enable_hearing_input);
+                mark_param_row(ui, &mut param_rows, "enable_territory");
+                ui.checkbox(hash!(), "Territory", &mut self.params.enable_territory);
+                mark_param_row(ui, &mut param_rows, "enable_rest");
+                ui.checkbox(hash!(), "Rest Action", &mut self.params.enable_rest);
+                mark_param_row(ui, &mut param_rows, "enable_tribe_vision");
+                ui.checkbox(hash!(), "Tribe Vision", &mut self.params.enable_tribe_vision);
+                mark_param_row(ui, &mut param_rows, "enable_decal_vision");
+                ui.checkbox(hash!(), "Decal Vision", &mut self.params.enable_decal_vision);
+                mark_param_row(ui, &mut param_rows, "enable_beacon");
+                ui.checkbox(hash!(), "Beacon", &mut self.params.enable_beacon);
+                mark_param_row(ui, &mut param_rows, "energy_per_beacon_reach");
+                ui.slider(hash!(), "Energy per Beacon Reach", get_slider_range("energy_per_beacon_reach"), &mut self.params.energy_per_beacon_reach);
+                mark_param_row(ui, &mut param_rows, "enable_novelty");
+                ui.checkbox(hash!(), "Novelty Search", &mut self.params.enable_novelty);
+                mark_param_row(ui, &mut param_rows, "novelty_descriptor");
+                ui.combo_box(hash!(), "Novelty Behavior", &NOVELTY_DESCRIPTOR_LABELS, &mut self.params.novelty_descriptor_idx);
+                mark_param_row(ui, &mut param_rows, "novelty_weight");
+                ui.slider(hash!(), "Novelty Weight", get_slider_range("novelty_weight"), &mut self.params.novelty_weight);
+                mark_param_row(ui, &mut param_rows, "enable_speciation");
+                ui.checkbox(hash!(), "Speciation", &mut self.params.enable_speciation);
+                mark_param_row(ui, &mut param_rows, "speciation_threshold");
+                ui.slider(hash!(), "Speciation Threshold", get_slider_range("speciation_threshold"), &mut self.params.speciation_threshold);
+                mark_param_row(ui, &mut param_rows, "speciation_interval");
+                ui.slider(hash!(), "Speciation Interval", get_slider_range("speciation_interval"), &mut self.params.speciation_interval);
+                mark_param_row(ui, &mut param_rows, "creature_order");
+                ui.combo_box(hash!(), "Creature Order", &CREATURE_ORDER_LABELS, &mut self.params.creature_order_idx);
+                mark_param_row(ui, &mut param_rows, "view_distance");
+                ui.slider(hash!(), "View Distance", get_slider_range("view_distance"), &mut self.params.view_distance);
+                mark_param_row(ui, &mut param_rows, "evolve_view_distance");
+                ui.checkbox(hash!(), "Evolve View Distance", &mut self.params.evolve_view_distance);
+                mark_param_row(ui, &mut param_rows, "view_energy_cost");
+                ui.slider(hash!(), "View Energy Cost", get_slider_range("view_energy_cost"), &mut self.params.view_energy_cost);
+                mark_param_row(ui, &mut param_rows, "enable_metabolic_cost");
+                ui.checkbox(hash!(), "Metabolic Cost", &mut self.params.enable_metabolic_cost);
+                mark_param_row(ui, &mut param_rows, "metabolic_energy_cost");
+                ui.slider(hash!(), "Metabolic Cost Per Neuron", get_slider_range("metabolic_energy_cost"), &mut self.params.metabolic_energy_cost);
+                mark_param_row(ui, &mut param_rows, "hearing_radius");
+                ui.slider(hash!(), "Hearing Radius", get_slider_range("hearing_radius"), &mut self.params.hearing_radius);
+                mark_param_row(ui, &mut param_rows, "brain_compute_budget");
+                ui.slider(hash!(), "Brain Compute Budget", get_slider_range("brain_compute_budget"), &mut self.params.brain_compute_budget);
+                mark_param_row(ui, &mut param_rows, "action_delay_steps");
+                ui.slider(hash!(), "Action Delay Steps", get_slider_range("action_delay_steps"), &mut self.params.action_delay_steps);
+                mark_param_row(ui, &mut param_rows, "territory_decay");
+                ui.slider(hash!(), "Territory Decay", get_slider_range("territory_decay"), &mut self.params.territory_decay);
+                mark_param_row(ui, &mut param_rows, "tribe_vision_radius");
+                ui.slider(hash!(), "Tribe Vision Radius", get_slider_range("tribe_vision_radius"), &mut self.params.tribe_vision_radius);
+                mark_param_row(ui, &mut param_rows, "season_length");
+                ui.slider(hash!(), "Season Length", get_slider_range("season_length"), &mut self.params.season_length);
+                mark_param_row(ui, &mut param_rows, "mating_season_start");
+                ui.slider(hash!(), "Mating Season Start", get_slider_range("mating_season_start"), &mut self.params.mating_season_start);
+                mark_param_row(ui, &mut param_rows, "mating_season_length");
+                ui.slider(hash!(), "Mating Season Length", get_slider_range("mating_season_length"), &mut self.params.mating_season_length);
+                mark_param_row(ui, &mut param_rows, "food_die_off");
+                ui.slider(hash!(), "Food Die-Off Rate", CATASTROPHE_RATE_RANGE.0..CATASTROPHE_RATE_RANGE.1, &mut self.params.food_die_off_rate);
+                mark_param_row(ui, &mut param_rows, "food_die_off");
+                ui.slider(hash!(), "Food Die-Off Magnitude", CATASTROPHE_MAGNITUDE_RANGE.0..CATASTROPHE_MAGNITUDE_RANGE.1, &mut self.params.food_die_off_magnitude);
+                mark_param_row(ui, &mut param_rows, "plague");
+                ui.slider(hash!(), "Plague Rate", CATASTROPHE_RATE_RANGE.0..CATASTROPHE_RATE_RANGE.1, &mut self.params.plague_rate);
+                mark_param_row(ui, &mut param_rows, "plague");
+                ui.slider(hash!(), "Plague Magnitude", CATASTROPHE_MAGNITUDE_RANGE.0..CATASTROPHE_MAGNITUDE_RANGE.1, &mut self.params.plague_magnitude);
+                mark_param_row(ui, &mut param_rows, "wall_collapse");
+                ui.slider(hash!(), "Wall Collapse Rate", CATASTROPHE_RATE_RANGE.0..CATASTROPHE_RATE_RANGE.1, &mut self.params.wall_collapse_rate);
+                mark_param_row(ui, &mut param_rows, "wall_collapse");
+                ui.slider(hash!(), "Wall Collapse Magnitude", CATASTROPHE_MAGNITUDE_RANGE.0..CATASTROPHE_MAGNITUDE_RANGE.1, &mut self.params.wall_collapse_magnitude);
+                mark_param_row(ui, &mut param_rows, "enable_disease");
+                ui.checkbox(hash!(), "Disease", &mut self.params.enable_disease);
+                mark_param_row(ui, &mut param_rows, "initial_infection_fraction");
+                ui.slider(hash!(), "Initial Infection Fraction", get_slider_range("initial_infection_fraction"), &mut self.params.initial_infection_fraction);
+                mark_param_row(ui, &mut param_rows, "infection_prob");
+                ui.slider(hash!(), "Infection Probability", get_slider_range("infection_prob"), &mut self.params.infection_prob);
+                mark_param_row(ui, &mut param_rows, "infection_duration");
+                ui.slider(hash!(), "Infection Duration", get_slider_range("infection_duration"), &mut self.params.infection_duration);
+                mark_param_row(ui, &mut param_rows, "infection_energy_drain");
+                ui.slider(hash!(), "Infection Energy Drain", get_slider_range("infection_energy_drain"), &mut self.params.infection_energy_drain);
+                mark_param_row(ui, &mut param_rows, "enable_elevation");
+                ui.checkbox(hash!(), "Elevation", &mut self.params.enable_elevation);
+                mark_param_row(ui, &mut param_rows, "elevation_feature_size");
+                ui.slider(hash!(), "Hill Size", get_slider_range("elevation_feature_size"), &mut self.params.elevation_feature_size);
+                mark_param_row(ui, &mut param_rows, "elevation_energy_cost");
+                ui.slider(hash!(), "Climb Energy Cost", get_slider_range("elevation_energy_cost"), &mut self.params.elevation_energy_cost);
+                mark_param_row(ui, &mut param_rows, "high_ground_view_bonus");
+                ui.slider(hash!(), "High Ground View Bonus", get_slider_range("high_ground_view_bonus"), &mut self.params.high_ground_view_bonus);
+                mark_param_row(ui, &mut param_rows, "enable_river");
+                ui.checkbox(hash!(), "River", &mut self.params.enable_river);
+                mark_param_row(ui, &mut param_rows, "river_flow_strength");
+                ui.slider(hash!(), "River Flow Strength", get_slider_range("river_flow_strength"), &mut self.params.river_flow_strength);
+                mark_param_row(ui, &mut param_rows, "river_source_food");
+                ui.slider(hash!(), "River Source Food", get_slider_range("river_source_food"), &mut self.params.river_source_food);
+                mark_param_row(ui, &mut param_rows, "enable_swimming");
+                ui.checkbox(hash!(), "Swimming", &mut self.params.enable_swimming);
+                mark_param_row(ui, &mut param_rows, "initial_swimmer_fraction");
+                ui.slider(hash!(), "Initial Swimmer Fraction", get_slider_range("initial_swimmer_fraction"), &mut self.params.initial_swimmer_fraction);
+                mark_param_row(ui, &mut param_rows, "enable_large_bodies");
+                ui.checkbox(hash!(), "Large Bodies", &mut self.params.enable_large_bodies);
+                mark_param_row(ui, &mut param_rows, "initial_large_fraction");
+                ui.slider(hash!(), "Initial Large Fraction", get_slider_range("initial_large_fraction"), &mut self.params.initial_large_fraction);
+                mark_param_row(ui, &mut param_rows, "swim_energy_cost");
+                ui.slider(hash!(), "Swim Energy Cost", get_slider_range("swim_energy_cost"), &mut self.params.swim_energy_cost);
+                mark_param_row(ui, &mut param_rows, "immigration_rate");
+                ui.slider(hash!(), "Immigrants per Step", get_slider_range("immigration_rate"), &mut self.params.immigration_rate);
+                mark_param_row(ui, &mut param_rows, "immigrant_source");
+                ui.combo_box(hash!(), "Immigrant Source", &IMMIGRANT_SOURCE_LABELS, &mut self.params.immigrant_source_idx);
+                mark_param_row(ui, &mut param_rows, "restart_on_extinction");
+                ui.checkbox(hash!(), "Restart on Extinction", &mut self.params.restart_on_extinction);
+                mark_param_row(ui, &mut param_rows, "restart_every_n_generations");
+                ui.slider(hash!(), "Restart Every N Generations", get_slider_range("restart_every_n_generations"), &mut self.params.restart_every_n_generations);
+                mark_param_row(ui, &mut param_rows, "max_steps_per_run");
+                ui.slider(hash!(), "Max Steps Per Run (0 = none)", get_slider_range("max_steps_per_run"), &mut self.params.max_steps_per_run);
+                mark_param_row(ui, &mut param_rows, "selection_method");
+                ui.combo_box(hash!(), "Restart Selection", &SELECTION_METHOD_LABELS, &mut self.params.selection_method_idx);
+                mark_param_row(ui, &mut param_rows, "selection_size");
+                ui.slider(hash!(), "Selection K / Tournament Size", get_slider_range("selection_size"), &mut self.params.selection_size);
+                mark_param_row(ui, &mut param_rows, "fitness_metric");
+                ui.combo_box(hash!(), "Selection Fitness", &FITNESS_METRIC_LABELS, &mut self.params.fitness_metric_idx);
+
+                mark_param_row(ui, &mut param_rows, "");
 
                 // Add button to regenerate new environment
                 if ui.button(None, "Generate New Random Environment") {
                     self.generate_new_environment();
                 }
             });
+
+        // Rows run from where they were marked to where the next one was, and are only hovered within the panel
+        let (mouse_x, mouse_y) = mouse_position();
+        let panel_rect = Rect::new(self.param_panel_x_pos, self.param_panel_y_pos, PARAM_PANEL_WIDTH, PARAM_PANEL_HEIGHT);
+        if !panel_rect.contains(vec2(mouse_x, mouse_y)) {
+            return;
+        }
+        let hovered = param_rows.windows(2).find(|rows| rows[0].0 <= mouse_y && mouse_y < rows[1].0);
+        if let Some(doc) = hovered.and_then(|rows| get_param_doc(rows[0].1)) {
+            draw_param_tooltip(doc, self.param_panel_x_pos - PANEL_X_PADDING, mouse_y);
+        }
+    }
+
+
+    /// Update the kiosk panel (replaces the stats and control panels in kiosk mode)
+    fn update_kiosk_panel(&mut self) {
+        // Leave room for the param panel if it's been unlocked
+        let panel_height = if self.kiosk_show_params {self.param_panel_y_pos} else {WINDOW_HEIGHT_PX};
+        let button_size = vec2(STATS_PANEL_WIDTH - 4.0 * PANEL_X_PADDING, KIOSK_BUTTON_HEIGHT);
+
+        root_ui().window(hash!(), vec2(self.stats_panel_x_pos, self.stats_panel_y_pos), vec2(STATS_PANEL_WIDTH, panel_height), |ui| {
+            let kiosk_skin = Skin {
+                label_style : ui.style_builder()
+                    .text_color(Color::from_rgba(0, 0, 0, 255))
+                    .font_size(KIOSK_LABEL_FONT_SIZE)
+                    .build(),
+                button_style : ui.style_builder()
+                    .color(Color {r: 0.5, g: 0.5, b: 0.5, a: 1.0})
+                    .color_hovered(Color {r: 0.7, g: 0.7, b: 0.7, a: 1.0})
+                    .font_size(KIOSK_BUTTON_FONT_SIZE)
+                    .build(),
+                ..ui.default_skin()
+            };
+            ui.push_skin(&kiosk_skin);
+
+            ui.label(None, "EVOLUTION SIM");
+            ui.label(None, &format!("Creatures alive: {}", self.env.num_creatures));
+            ui.label(None, &format!("Step: {}", self.env.time_step));
+            ui.label(None, &format!("New worlds: {}", self.run_policy.num_restarts));
+
+            let start_stop_label = if self.state == SimState::RUNNING {"STOP"} else {"START"};
+            if widgets::Button::new(start_stop_label).size(button_size).ui(ui) {
+                self.state = match self.state {
+                    SimState::RUNNING => SimState::STOPPED,
+                    SimState::STOPPED => SimState::RUNNING,
+                    SimState::FASTFORWARD => SimState::STOPPED,
+                }
+            }
+            if widgets::Button::new("NEW WORLD").size(button_size).ui(ui) {
+                self.env = environment::EnvironmentV1::new_rand(&self.env.params);
+                self.state = SimState::RUNNING;
+            }
+
+            ui.pop_skin();
+        });
+    }
+
+    /// Show/hide the param panel when the kiosk title is held down for KIOSK_LONG_PRESS_S.
+    /// Touches are reported as mouse presses by macroquad, so this works for both
+    fn update_kiosk_long_press(&mut self) {
+        let (mouse_x, mouse_y) = mouse_position();
+        let title_rect = Rect::new(self.stats_panel_x_pos, self.stats_panel_y_pos, STATS_PANEL_WIDTH, KIOSK_TITLE_HEIGHT);
+        if !is_mouse_button_down(MouseButton::Left) || !title_rect.contains(vec2(mouse_x, mouse_y)) {
+            self.kiosk_press_start = None;
+            self.kiosk_press_handled = false;
+            return;
+        }
+
+        let cur_time = get_time();
+        let press_start = *self.kiosk_press_start.get_or_insert(cur_time);
+        if !self.kiosk_press_handled && cur_time - press_start >= KIOSK_LONG_PRESS_S {
+            self.kiosk_show_params = !self.kiosk_show_params;
+            self.kiosk_press_handled = true;
+        }
     }
 
+    /// Run the attract loop if nobody has touched the screen for a while: keep the sim running,
+    /// periodically generate a new world and invite visitors to touch the screen
+    fn update_kiosk_idle(&mut self) {
+        let cur_time = get_time();
+        if is_mouse_button_down(MouseButton::Left) || !touches().is_empty() || get_last_key_pressed().is_some() {
+            self.last_input_time = cur_time;
+        }
+        if cur_time - self.last_input_time < KIOSK_IDLE_TIMEOUT_S {
+            return;
+        }
+
+        // Nobody is around, so hide the params again and keep things moving
+        self.kiosk_show_params = false;
+        self.state = SimState::RUNNING;
+        if cur_time - self.last_attract_world_time > KIOSK_ATTRACT_NEW_WORLD_S {
+            self.env = environment::EnvironmentV1::new_rand(&self.env.params);
+            self.last_attract_world_time = cur_time;
+        }
+
+        // Pulse the invitation text
+        let alpha = 0.6 + 0.4 * (cur_time * 3.0).sin() as f32;
+        let text = "TOUCH TO PLAY";
+        let font_size = 80.0;
+        let text_size = measure_text(text, None, font_size as u16, 1.0);
+        draw_rectangle(0.0, SCREEN_SIZE_Y / 2.0 - font_size, SCREEN_SIZE_X, font_size * 1.5, Color::new(0.0, 0.0, 0.0, 0.4 * alpha));
+        draw_text(text, (SCREEN_SIZE_X - text_size.width) / 2.0, SCREEN_SIZE_Y / 2.0, font_size, Color::new(1.0, 1.0, 1.0, alpha));
+    }
 
     /// Update the display
     pub fn update_display(&mut self) {
@@ -526,6 +2041,17 @@ impl EnvMacroquad {
         // Update the main board
         self.update_sim_display();
 
+        // Kiosk mode only shows the board, a few big buttons and (if unlocked) the params
+        if self.kiosk_mode {
+            self.update_kiosk_idle();
+            self.update_kiosk_long_press();
+            self.update_kiosk_panel();
+            if self.kiosk_show_params {
+                self.update_sim_param_panel();
+            }
+            return;
+        }
+
         // Update statistics on the side
         self.update_stats_panel(); 
 
@@ -537,22 +2063,442 @@ impl EnvMacroquad {
 
         // Update the control panel below the environment display
         self.update_bottom_control_panel();
+
+        // Overlay switches float over the top-left corner of the board
+        if self.show_overlay_panel {
+            self.update_overlay_panel();
+        }
+
+        if self.show_group_panel {
+            self.update_group_panel();
+        }
+
+        if self.show_leaderboard {
+            self.update_leaderboard();
+        }
+
+        if self.creature_editor.is_some() {
+            self.update_creature_editor();
+        }
+
+        if self.select_creatures {
+            self.update_selection();
+            self.update_selection_panel();
+        }
+
+        // Clicking a connection/neuron in the dream panel picks it for tweaking
+        self.update_brain_tweak();
+
+        // Report on the run when the creatures die out
+        self.update_summary_report_window();
+
+        // Warn about failed (or repaired) loads on top of everything else
+        self.update_load_error_window();
+    }
+
+    /// Show the summary report of the run until the user dismisses it. It can also be saved to the file in the filename box
+    fn update_summary_report_window(&mut self) {
+        let mut dismissed = false;
+        if let Some(report) = &self.summary_report {
+            let window_size = vec2(SCREEN_SIZE_X / 1.5, SCREEN_SIZE_Y / 1.2);
+            let window_pos = vec2((SCREEN_SIZE_X - window_size.x) / 2.0, (SCREEN_SIZE_Y - window_size.y) / 2.0);
+            root_ui().window(hash!(), window_pos, window_size, |ui| {
+                if ui.button(None, "SAVE REPORT") {
+                    if let Err(e) = std::fs::write(&self.params.save_load_filename, report) {
+                        println!("Error: could not save summary report to {}. Error {e}", self.params.save_load_filename);
+                    }
+                }
+                ui.same_line(0.0);
+                dismissed = ui.button(None, "OK");
+                for line in report.lines() {
+                    ui.label(None, line);
+                }
+            });
+        }
+        if dismissed {
+            self.summary_report = None;
+        }
     }
 
-    /// Draw a single creature square to the specified location on the screen
-    fn draw_creature_square(&self, x_pos : usize, y_pos : usize, orientation : CreatureOrientation, color : CreatureColor) {
+    /// Panel with an on/off switch and opacity slider for each overlay
+    fn update_overlay_panel(&mut self) {
+        let mut close = false;
+        root_ui().window(hash!(), vec2(PANEL_X_PADDING, PANEL_Y_PADDING), vec2(OVERLAY_PANEL_WIDTH, OVERLAY_PANEL_HEIGHT), |ui| {
+            ui.label(None, "OVERLAYS");
+            for (idx, overlay) in self.overlays.iter_mut().enumerate() {
+                // Each overlay's widgets need their own IDs since they're made in a loop
+                ui.checkbox(hash!("overlay_enabled", idx), overlay.label, &mut overlay.enabled);
+                ui.slider(hash!("overlay_opacity", idx), "Opacity", 0.0..1.0, &mut overlay.opacity);
+                if overlay.kind == OverlayKind::Density {
+                    ui.combo_box(hash!(), "Density Of", &DENSITY_OVERLAY_LABELS, &mut self.density_overlay_idx);
+                }
+            }
+            close = ui.button(None, "CLOSE");
+        });
+        if close {
+            self.show_overlay_panel = false;
+        }
+    }
 
-        let xpos_pix = (x_pos as f32) * self.grid_x_size;
-        let ypos_pix = (y_pos as f32) * self.grid_y_size;
+    /// Panel listing the living tribes/species with their stats, plus the most recent extinctions
+    fn update_group_panel(&mut self) {
+        let mut close = false;
+        let window_pos = vec2(PANEL_X_PADDING, SCREEN_SIZE_Y - GROUP_PANEL_HEIGHT - PANEL_Y_PADDING);
+        root_ui().window(hash!(), window_pos, vec2(GROUP_PANEL_WIDTH, GROUP_PANEL_HEIGHT), |ui| {
+            ui.label(None, "GROUPS");
+            ui.combo_box(hash!(), "Group By", &GROUP_BY_LABELS, &mut self.group_by_idx);
+            ui.checkbox(hash!(), "Pause on Extinction", &mut self.pause_on_group_extinction);
+
+            let group_by = GROUP_BY_OPTIONS[self.group_by_idx];
+            let groups = get_group_stats(&self.env.creatures, group_by);
+            ui.label(None, &format!("{} living", groups.len()));
+            for group in groups.iter().take(GROUP_PANEL_MAX_ROWS) {
+                ui.label(None, &format!("{} {}: {} alive, energy {:.0}, kills {}",
+                    GROUP_BY_LABELS[self.group_by_idx], group.id, group.num_creatures, group.mean_energy, group.num_kills));
+            }
 
-        // Draw the rectangle "body" of the creature
+            ui.label(None, "Extinctions:");
+            for alert in self.group_alerts.iter() {
+                ui.label(None, alert);
+            }
+            close = ui.button(None, "CLOSE");
+        });
+        if close {
+            self.show_group_panel = false;
+        }
+    }
+
+    /// Panel with the top living creatures by age and by number of offspring (from the stats recorder, which
+    /// updates them once a step). Clicking one makes it the inspected creature and outlines it on the board
+    fn update_leaderboard(&mut self) {
+        let mut close = false;
+        let mut selected : Option<usize> = None;
+        let window_pos = vec2(SCREEN_SIZE_X - LEADERBOARD_PANEL_WIDTH - PANEL_X_PADDING, SCREEN_SIZE_Y - LEADERBOARD_PANEL_HEIGHT - PANEL_Y_PADDING);
+        let stats = &self.env.stats;
+        root_ui().window(hash!(), window_pos, vec2(LEADERBOARD_PANEL_WIDTH, LEADERBOARD_PANEL_HEIGHT), |ui| {
+            for (title, value_label, entries) in [("OLDEST", "age", &stats.oldest_creatures), ("MOST OFFSPRING", "kids", &stats.most_offspring)] {
+                ui.label(None, title);
+                for entry in entries.iter() {
+                    if let Some(creature) = self.env.get_creature(entry.id) {
+                        draw_fingerprint(ui, &get_genome_fingerprint(&creature.brain));
+                        ui.same_line(0.0);
+                    }
+                    let marker = if self.dream_creature_id == Some(entry.id) { ">" } else { " " };
+                    if ui.button(None, format!("{} Creature {} ({} {})", marker, entry.id, value_label, entry.value).as_str()) {
+                        selected = Some(entry.id);
+                    }
+                }
+            }
+            close = ui.button(None, "CLOSE");
+        });
+        if selected.is_some() {
+            self.dream_creature_id = selected;
+        }
+        if close {
+            self.show_leaderboard = false;
+        }
+    }
+
+    /// Open the editor with the current values of a creature
+    fn open_creature_editor(&mut self, creature_id : usize) {
+        if let Some(creature) = self.env.get_creature(creature_id) {
+            self.creature_editor = Some(CreatureEditor {
+                creature_id : creature_id,
+                energy : creature.energy as f32,
+                age : creature.age.to_string(),
+                orientation_idx : ORIENTATION_OPTIONS.iter().position(|o| *o == creature.orientation).unwrap_or(0),
+                color : creature.color.get_as_vec().map(|c| c as f32),
+                lineage : creature.lineage_id.to_string(),
+            });
+        }
+    }
+
+    /// Panel for changing a living creature's attributes by hand
+    fn update_creature_editor(&mut self) {
+        let mut apply = false;
+        let mut close = false;
+        let editor = self.creature_editor.as_mut().unwrap();
+        let window_pos = vec2(SCREEN_SIZE_X - CREATURE_EDIT_PANEL_WIDTH - PANEL_X_PADDING, PANEL_Y_PADDING);
+        root_ui().window(hash!(), window_pos, vec2(CREATURE_EDIT_PANEL_WIDTH, CREATURE_EDIT_PANEL_HEIGHT), |ui| {
+            ui.label(None, &format!("EDIT CREATURE {}", editor.creature_id));
+            ui.slider(hash!(), "Energy", 0.0..MAX_POSSIBLE_ENERGY as f32, &mut editor.energy);
+            ui.input_text(hash!(), "Age", &mut editor.age);
+            ui.combo_box(hash!(), "Orientation", &ORIENTATION_LABELS, &mut editor.orientation_idx);
+            ui.slider(hash!(), "Red", 0.0..255.0, &mut editor.color[0]);
+            ui.slider(hash!(), "Green", 0.0..255.0, &mut editor.color[1]);
+            ui.slider(hash!(), "Blue", 0.0..255.0, &mut editor.color[2]);
+            ui.input_text(hash!(), "Tribe (Lineage)", &mut editor.lineage);
+            apply = ui.button(None, "APPLY");
+            ui.same_line(0.0);
+            close = ui.button(None, "CLOSE");
+        });
+
+        if apply {
+            // Fields that don't parse are left unchanged
+            let edit = CreatureEdit {
+                energy : Some(editor.energy as usize),
+                age : editor.age.trim().parse::<usize>().ok(),
+                orientation : Some(ORIENTATION_OPTIONS[editor.orientation_idx]),
+                color : Some(editor.color.map(|c| c as u8)),
+                lineage_id : editor.lineage.trim().parse::<usize>().ok(),
+            };
+            if let Err(msg) = self.env.edit_creature(editor.creature_id, &edit) {
+                println!("Error: {}", msg);
+                close = true;
+            }
+        }
+        if close {
+            self.creature_editor = None;
+        }
+    }
+
+    /// Drag a box on the board to select every creature in it, and outline the selected ones that are still alive
+    fn update_selection(&mut self) {
+        let mouse = Vec2::from(mouse_position());
+        let board = Rect::new(0.0, 0.0, self.grid_x_size * self.env.params.env_x_size as f32, self.grid_y_size * self.env.params.env_y_size as f32);
+        if is_mouse_button_pressed(MouseButton::Left) && board.contains(mouse) && !get_selection_panel().contains(mouse) {
+            self.selection_start = Some(mouse);
+        }
+
+        if let Some(start) = self.selection_start {
+            let end = mouse.clamp(board.point(), board.point() + board.size());
+            if is_mouse_button_down(MouseButton::Left) {
+                draw_rectangle_lines(start.x.min(end.x), start.y.min(end.y), (end.x - start.x).abs(), (end.y - start.y).abs(), 2.0, SELECTION_COLOR);
+            }
+            else {
+                // Every creature whose head space is inside the box (a plain click picks just the space under it)
+                let (min_x, max_x) = ((start.x.min(end.x) / self.grid_x_size) as usize, (start.x.max(end.x) / self.grid_x_size) as usize);
+                let (min_y, max_y) = ((start.y.min(end.y) / self.grid_y_size) as usize, (start.y.max(end.y) / self.grid_y_size) as usize);
+                self.selected_ids = self.env.creatures.iter()
+                    .filter(|c| (min_x..=max_x).contains(&c.position.x) && (min_y..=max_y).contains(&c.position.y))
+                    .map(|c| c.id)
+                    .collect();
+                self.selection_start = None;
+            }
+        }
+
+        for creature in self.selected_ids.iter().filter_map(|id| self.env.get_creature(*id)) {
+            let (x, y) = (creature.position.x as f32 * self.grid_x_size, creature.position.y as f32 * self.grid_y_size);
+            draw_rectangle_lines(x, y, self.grid_x_size, self.grid_y_size, 2.0, SELECTION_COLOR);
+        }
+    }
+
+    /// Panel with the combined stats of the selected creatures (the ones still alive), buttons for tagging them as a
+    /// cohort or saving all of their genomes to the file in the filename box, and how the tagged cohorts are doing
+    fn update_selection_panel(&mut self) {
+        let mut tag = false;
+        let mut export = false;
+        let mut clear = false;
+        let mut close = false;
+        let selected : Vec<&CreatureV1> = self.selected_ids.iter().filter_map(|id| self.env.get_creature(*id)).collect();
+        let summary = summarize_group(&selected);
+        let cohorts = &self.env.stats.cohorts;
+        let cohort_name = &mut self.cohort_name;
+        root_ui().window(hash!(), get_selection_panel().point(), get_selection_panel().size(), |ui| {
+            ui.label(None, &format!("SELECTION ({} of {} alive)", summary.num_creatures, self.selected_ids.len()));
+            ui.label(None, &format!("Tribes: {}", summary.num_lineages));
+            ui.label(None, &format!("Avg Age: {:.1}", summary.avg_age));
+            ui.label(None, &format!("Avg Energy: {:.1}", summary.avg_energy));
+            ui.label(None, &format!("Avg Generation: {:.1}", summary.avg_generation));
+            ui.label(None, &format!("Total Kills: {}", summary.total_kills));
+            ui.label(None, &format!("Total Offspring: {}", summary.total_offspring));
+
+            widgets::InputText::new(hash!()).label("Cohort Name").size(vec2(180.0, 20.0)).ui(ui, cohort_name);
+            tag = ui.button(None, "TAG AS COHORT");
+            ui.same_line(0.0);
+            export = ui.button(None, "EXPORT GENOMES");
+
+            ui.label(None, "Cohorts (members / descendants alive):");
+            for cohort in cohorts.iter().rev().take(SELECTION_PANEL_MAX_COHORTS) {
+                if let Some(sample) = cohort.history.back() {
+                    ui.label(None, &format!("{} (step {}): {} / {}", cohort.name, cohort.tagged_step, sample.num_members_alive, sample.num_descendants_alive));
+                }
+                else {
+                    ui.label(None, &format!("{} (step {}): {} members", cohort.name, cohort.tagged_step, cohort.member_ids.len()));
+                }
+            }
+
+            clear = ui.button(None, "CLEAR");
+            ui.same_line(0.0);
+            close = ui.button(None, "CLOSE");
+        });
+
+        let alive_ids : Vec<usize> = selected.iter().map(|c| c.id).collect();
+        if tag {
+            if alive_ids.is_empty() {
+                println!("Error: no living creatures selected to tag as a cohort");
+            }
+            else {
+                let name = if self.cohort_name.trim().is_empty() { format!("Cohort {}", self.env.stats.cohorts.len() + 1) } else { self.cohort_name.trim().to_string() };
+                let members : Vec<&CreatureV1> = self.env.creatures.iter().filter(|c| alive_ids.contains(&c.id)).collect();
+                self.env.stats.add_cohort(&name, &members, self.env.time_step);
+            }
+        }
+        if export {
+            if alive_ids.is_empty() {
+                println!("Error: no living creatures selected to export");
+            }
+            else {
+                self.env.save_creatures(&alive_ids, self.params.save_load_filename.as_str());
+            }
+        }
+        if clear {
+            self.selected_ids.clear();
+        }
+        if close {
+            self.select_creatures = false;
+            self.selection_start = None;
+        }
+    }
+
+    /// Pick a weight/bias by clicking on it in the dream panel, and show a slider for changing it.
+    /// Changes go straight into the creature's brain, so the dream shows the new behavior right away
+    fn update_brain_tweak(&mut self) {
+        let dreamer = match self.get_dreamer() {
+            Some(creature) => creature,
+            None => return,
+        };
+        let tweak_window = Rect::new(DREAM_PANEL_MARGIN, SCREEN_SIZE_Y - DREAM_PANEL_MARGIN - BRAIN_TWEAK_PANEL_HEIGHT, BRAIN_TWEAK_PANEL_WIDTH, BRAIN_TWEAK_PANEL_HEIGHT);
+
+        // Neurons are picked before connections since connections all end at a neuron. Input neurons have no bias
+        let (mouse_x, mouse_y) = mouse_position();
+        let mouse = vec2(mouse_x, mouse_y);
+        let picking = self.brain_tweak.is_none() || !tweak_window.contains(mouse);
+        if is_mouse_button_pressed(MouseButton::Left) && picking && get_dream_panel().contains(mouse) {
+            let weights = dreamer.brain.get_weights();
+            let mut layer_sizes : Vec<usize> = weights.iter().map(|w| w.get_ncols()).take(1).collect();
+            layer_sizes.extend(weights.iter().map(|w| w.get_nrows()));
+            let mut picked : Option<BrainParam> = None;
+            for (layer, layer_weights) in weights.iter().enumerate() {
+                for to in 0..layer_weights.get_nrows() {
+                    let end = get_dream_node_pos(&layer_sizes, layer + 1, to);
+                    if end.distance(mouse) <= DREAM_NODE_RADIUS {
+                        picked = Some(BrainParam::Bias {layer : layer, node : to});
+                    }
+                    for from in 0..layer_weights.get_ncols() {
+                        let start = get_dream_node_pos(&layer_sizes, layer, from);
+                        if picked.is_none() && get_dist_to_segment(mouse, start, end) <= BRAIN_TWEAK_CLICK_DIST {
+                            picked = Some(BrainParam::Weight {layer : layer, to : to, from : from});
+                        }
+                    }
+                }
+            }
+            if let Some(param) = picked {
+                self.brain_tweak = Some(BrainTweak {creature_id : dreamer.id, param : param, value : dreamer.brain.get_param(param).unwrap_or(0.0)});
+            }
+        }
+
+        let tweak = match self.brain_tweak.as_mut() {
+            Some(tweak) => tweak,
+            None => return,
+        };
+        let prev_value = tweak.value;
+        let mut close = false;
+        let (min_val, max_val) = Brain::get_param_range();
+        root_ui().window(hash!(), tweak_window.point(), tweak_window.size(), |ui| {
+            ui.label(None, &format!("TWEAK CREATURE {}: {:?}", tweak.creature_id, tweak.param));
+            ui.slider(hash!(), "Value", min_val..max_val, &mut tweak.value);
+            close = ui.button(None, "CLOSE");
+        });
+
+        if tweak.value != prev_value {
+            match self.env.set_creature_brain_param(tweak.creature_id, tweak.param, tweak.value) {
+                Ok(set_value) => tweak.value = set_value,
+                Err(msg) => {
+                    println!("Error: {}", msg);
+                    close = true;
+                }
+            }
+        }
+        if close {
+            self.brain_tweak = None;
+        }
+    }
+
+    /// Show why the last load failed (e.g. the file is truncated or corrupted), or what had to be fixed in what was
+    /// loaded, until the user dismisses it
+    fn update_load_error_window(&mut self) {
+        if let Some(msg) = &self.load_error {
+            if draw_message_window("LOAD FAILED - NOTHING WAS CHANGED", msg) {
+                self.load_error = None;
+            }
+        } else if let Some(msg) = &self.load_repairs {
+            if draw_message_window("LOADED - SOME PROBLEMS IN THE SAVE WERE FIXED", msg) {
+                self.load_repairs = None;
+            }
+        }
+    }
+
+    /// Draw the second space of a large creature
+    fn draw_creature_tail(&self, x_pos : usize, y_pos : usize, x_offset : f32, color : CreatureColor) {
+        let xpos_pix = x_offset + x_pos as f32 * self.grid_x_size;
+        let ypos_pix = y_pos as f32 * self.grid_y_size;
         draw_rectangle(xpos_pix, ypos_pix, self.grid_x_size, self.grid_y_size, Color::from_rgba(color.red, color.green, color.blue, 255));
+    }
+
+    /// Draw a single creature to the specified location on the screen.
+    /// The sprite style depends on `creature_style_idx`. When the board is zoomed far out, creatures are just colored blocks
+    /// Positions are in spaces, but can be fractional (e.g. while a creature is moving)
+    fn draw_creature_square(&self, x_pos : f32, y_pos : f32, x_offset : f32, orientation : CreatureOrientation, color : CreatureColor) {
+
+        let xpos_pix = x_offset + x_pos * self.grid_x_size;
+        let ypos_pix = y_pos * self.grid_y_size;
+        let body_color = Color::from_rgba(color.red, color.green, color.blue, 255);
+
+        // Too small to see any detail, so don't bother with the sprite
+        if self.grid_x_size.min(self.grid_y_size) < CREATURE_SPRITE_LOD_PX {
+            draw_rectangle(xpos_pix, ypos_pix, self.grid_x_size, self.grid_y_size, body_color);
+            return;
+        }
 
-        // Draw a short line to indicate which direction the creature is facing
         let x_gridsize_div_2 = self.grid_x_size / 2.0;
         let y_gridsize_div_2 = self.grid_y_size / 2.0;
         let center_x = xpos_pix + x_gridsize_div_2;
         let center_y = ypos_pix + y_gridsize_div_2; 
+
+        // Unit vector pointing the way the creature is facing (screen y points down)
+        let (dir_x, dir_y) = match orientation {
+            CreatureOrientation::Up => (0.0, -1.0),
+            CreatureOrientation::Down => (0.0, 1.0),
+            CreatureOrientation::Left => (-1.0, 0.0),
+            CreatureOrientation::Right => (1.0, 0.0),
+        };
+
+        let mut style = CREATURE_STYLE_OPTIONS[self.creature_style_idx];
+        if style == CreatureSpriteStyle::Texture && self.creature_texture.is_none() {
+            style = CreatureSpriteStyle::Arrow;
+        }
+        match style {
+            CreatureSpriteStyle::Arrow => {
+                let half_x = x_gridsize_div_2 * CREATURE_SPRITE_FILL_FRACTION;
+                let half_y = y_gridsize_div_2 * CREATURE_SPRITE_FILL_FRACTION;
+                let tip = vec2(center_x + dir_x * half_x, center_y + dir_y * half_y);
+                let base_center = vec2(center_x - dir_x * half_x, center_y - dir_y * half_y);
+                let base_offset = vec2(-dir_y * half_x, dir_x * half_y);
+                draw_triangle(tip, base_center + base_offset, base_center - base_offset, body_color);
+                if self.grid_x_size.min(self.grid_y_size) >= CREATURE_SPRITE_OUTLINE_MIN_PX {
+                    draw_triangle_lines(tip, base_center + base_offset, base_center - base_offset, 1.0, Color {r:0.1, g:0.1, b:0.1, a:1.0});
+                }
+                return;
+            },
+            CreatureSpriteStyle::Texture => {
+                if let Some(texture) = &self.creature_texture {
+                    let params = DrawTextureParams {
+                        dest_size : Some(vec2(self.grid_x_size, self.grid_y_size)),
+                        rotation : f32::atan2(dir_x, -dir_y),
+                        ..Default::default()
+                    };
+                    draw_texture_ex(texture, xpos_pix, ypos_pix, body_color, params);
+                }
+                return;
+            },
+            CreatureSpriteStyle::Square => (),
+        }
+
+        // Draw the rectangle "body" of the creature
+        draw_rectangle(xpos_pix, ypos_pix, self.grid_x_size, self.grid_y_size, body_color);
+
+        // Draw a short line to indicate which direction the creature is facing
         let orientation_line_color = Color {r:0.8, g:0.8, b:0.8, a:1.0};
 
         match orientation {
@@ -564,53 +2510,215 @@ impl EnvMacroquad {
     }
 
     /// Draw a single food space on the screen
-    fn draw_food_space(&self, x_pos : usize, y_pos : usize) {
+    fn draw_food_space(&self, x_pos : usize, y_pos : usize, x_offset : f32) {
         let food_color = Color {r: (FOOD_SPACE_COLOR[0] as f32) / 255.0, g: (FOOD_SPACE_COLOR[1] as f32) / 255.0, b : (FOOD_SPACE_COLOR[2] as f32) / 255.0 , a: 1.0};
-        draw_rectangle((x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, food_color);
+        draw_rectangle(x_offset + (x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, food_color);
     }
 
     /// Draw a wall space on the screen
-    fn draw_wall_space(&self, x_pos : usize, y_pos : usize) {
-        draw_rectangle((x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, WHITE);
+    fn draw_wall_space(&self, x_pos : usize, y_pos : usize, x_offset : f32) {
+        draw_rectangle(x_offset + (x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, WHITE);
     }
 
-    /// Draw a single food space on the screen
-    fn draw_fight_space(&self, x_pos : usize, y_pos : usize) {
-        draw_rectangle((x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, Color {r: 1.0, g: 0.0, b: 0.0, a: 0.25});
+    /// Draw the beacon as a ring centered in the specified space
+    fn draw_beacon(&self, x_pos : usize, y_pos : usize, x_offset : f32) {
+        let beacon_color = Color::from_rgba(BEACON_COLOR[0], BEACON_COLOR[1], BEACON_COLOR[2], 255);
+        let center_x = x_offset + (x_pos as f32 + 0.5) * self.grid_x_size;
+        let center_y = (y_pos as f32 + 0.5) * self.grid_y_size;
+        draw_circle_lines(center_x, center_y, self.grid_x_size.min(self.grid_y_size) * 0.6, 2.0, beacon_color);
     }
 
-    /// Update the temporary parameter strings that param panel is populated from with the
+    /// Draw the decal left on a single (blank) space
+    fn draw_decal(&self, x_pos : usize, y_pos : usize, x_offset : f32, kind : DecalKind) {
+        let color = match kind {
+            DecalKind::Fight => FIGHT_DECAL_COLOR,
+            DecalKind::Birth => BIRTH_DECAL_COLOR,
+            DecalKind::Death => DEATH_DECAL_COLOR,
+        };
+        draw_rectangle(x_offset + (x_pos as f32) * self.grid_x_size, (y_pos as f32) * self.grid_y_size, self.grid_x_size, self.grid_y_size, color);
+    }
+
+    /// Update the temporary parameter values that param panel is populated from with the
     /// actual values from the environment
-    fn repopulate_parameter_strings(&mut self) {
-        self.params.env_x_size = format!("{}", self.env.params.env_x_size); 
-        self.params.env_y_size = format!("{}", self.env.params.env_y_size); 
-        self.params.num_start_creatures = format!("{}", self.env.params.num_start_creatures); 
-        self.params.num_start_food = format!("{}", self.env.params.num_start_food); 
-        self.params.num_start_walls = format!("{}", self.env.params.num_start_walls); 
-        self.params.energy_per_food_piece = format!("{}", self.env.params.energy_per_food_piece); 
-        self.params.max_offspring_per_reproduce = format!("{}", self.env.params.max_offspring_per_reproduce); 
-        self.params.mutation_prob = format!("{}", self.env.params.mutation_prob); 
-        self.params.avg_new_food_per_day = format!("{}", self.env.params.avg_new_food_per_day); 
-    }
-
-    /// Update the environment parameters from the values that are in the text boxes
+    fn repopulate_parameter_panel(&mut self) {
+        self.params.env_x_size = self.env.params.env_x_size as f32;
+        self.params.env_y_size = self.env.params.env_y_size as f32;
+        self.params.num_start_creatures = self.env.params.num_start_creatures as f32;
+        self.params.num_start_food = self.env.params.num_start_food as f32;
+        self.params.num_start_walls = self.env.params.num_start_walls as f32;
+        self.params.energy_per_food_piece = self.env.params.energy_per_food_piece as f32;
+        self.params.max_offspring_per_reproduce = self.env.params.max_offspring_per_reproduce as f32;
+        self.params.mutation_prob = self.env.params.mutation_prob;
+        self.params.mutation_weight_gaussian = self.env.params.mutation_mix.gaussian;
+        self.params.mutation_weight_sign_flip = self.env.params.mutation_mix.sign_flip;
+        self.params.mutation_weight_reset = self.env.params.mutation_mix.reset;
+        self.params.mutation_weight_swap = self.env.params.mutation_mix.swap;
+        self.params.mutation_weight_layer_scale = self.env.params.mutation_mix.layer_scale;
+        self.params.mutation_schedule_idx = MUTATION_SCHEDULE_OPTIONS.iter().position(|s| *s == self.env.params.mutation_schedule).unwrap_or(0);
+        self.params.mutation_anneal_rate = self.env.params.mutation_anneal_rate;
+        self.params.offspring_energy_fraction = self.env.params.offspring_energy_fraction;
+        self.params.gestation_steps = self.env.params.gestation_steps as f32;
+        self.params.gestation_upkeep_energy = self.env.params.gestation_upkeep_energy as f32;
+        self.params.avg_new_food_per_day = self.env.params.avg_new_food_per_day;
+        self.params.enable_position_inputs = self.env.params.enable_position_inputs;
+        self.params.enable_wall_distance_input = self.env.params.enable_wall_distance_input;
+        self.params.enable_move_blocked_input = self.env.params.enable_move_blocked_input;
+        self.params.one_hot_categorical_inputs = self.env.params.one_hot_categorical_inputs;
+        self.params.enable_hearing_input = self.env.params.enable_hearing_input;
+        self.params.enable_territory = self.env.params.enable_territory;
+        self.params.enable_rest = self.env.params.enable_rest;
+        self.params.enable_tribe_vision = self.env.params.enable_tribe_vision;
+        self.params.enable_decal_vision = self.env.params.enable_decal_vision;
+        self.params.enable_beacon = self.env.params.enable_beacon;
+        self.params.energy_per_beacon_reach = self.env.params.energy_per_beacon_reach as f32;
+        self.params.enable_novelty = self.env.params.enable_novelty;
+        self.params.novelty_descriptor_idx = NOVELTY_DESCRIPTOR_OPTIONS.iter().position(|d| *d == self.env.params.novelty_descriptor).unwrap_or(0);
+        self.params.novelty_weight = self.env.params.novelty_weight;
+        self.params.enable_speciation = self.env.params.enable_speciation;
+        self.params.speciation_threshold = self.env.params.speciation_threshold;
+        self.params.speciation_interval = self.env.params.speciation_interval as f32;
+        self.params.creature_order_idx = CREATURE_ORDER_OPTIONS.iter().position(|o| *o == self.env.params.creature_order).unwrap_or(0);
+        self.params.view_distance = self.env.params.view_distance as f32;
+        self.params.evolve_view_distance = self.env.params.evolve_view_distance;
+        self.params.view_energy_cost = self.env.params.view_energy_cost;
+        self.params.enable_metabolic_cost = self.env.params.enable_metabolic_cost;
+        self.params.metabolic_energy_cost = self.env.params.metabolic_energy_cost;
+        self.params.hearing_radius = self.env.params.hearing_radius as f32;
+        self.params.brain_compute_budget = self.env.params.brain_compute_budget as f32;
+        self.params.action_delay_steps = self.env.params.action_delay_steps as f32;
+        self.params.territory_decay = self.env.params.territory_decay;
+        self.params.tribe_vision_radius = self.env.params.tribe_vision_radius as f32;
+        self.params.season_length = self.env.params.season_length as f32;
+        self.params.mating_season_start = self.env.params.mating_season_start;
+        self.params.mating_season_length = self.env.params.mating_season_length;
+        self.params.food_die_off_rate = self.env.params.food_die_off.rate;
+        self.params.food_die_off_magnitude = self.env.params.food_die_off.magnitude;
+        self.params.plague_rate = self.env.params.plague.rate;
+        self.params.plague_magnitude = self.env.params.plague.magnitude;
+        self.params.wall_collapse_rate = self.env.params.wall_collapse.rate;
+        self.params.wall_collapse_magnitude = self.env.params.wall_collapse.magnitude;
+        self.params.enable_disease = self.env.params.enable_disease;
+        self.params.initial_infection_fraction = self.env.params.initial_infection_fraction;
+        self.params.infection_prob = self.env.params.infection_prob;
+        self.params.infection_duration = self.env.params.infection_duration as f32;
+        self.params.infection_energy_drain = self.env.params.infection_energy_drain as f32;
+        self.params.enable_elevation = self.env.params.enable_elevation;
+        self.params.elevation_feature_size = self.env.params.elevation_feature_size as f32;
+        self.params.elevation_energy_cost = self.env.params.elevation_energy_cost;
+        self.params.high_ground_view_bonus = self.env.params.high_ground_view_bonus as f32;
+        self.params.enable_river = self.env.params.enable_river;
+        self.params.river_flow_strength = self.env.params.river_flow_strength;
+        self.params.river_source_food = self.env.params.river_source_food;
+        self.params.enable_swimming = self.env.params.enable_swimming;
+        self.params.initial_swimmer_fraction = self.env.params.initial_swimmer_fraction;
+        self.params.enable_large_bodies = self.env.params.enable_large_bodies;
+        self.params.initial_large_fraction = self.env.params.initial_large_fraction;
+        self.params.swim_energy_cost = self.env.params.swim_energy_cost as f32;
+        self.params.immigration_rate = self.env.params.immigration_rate;
+        self.params.immigrant_source_idx = IMMIGRANT_SOURCE_OPTIONS.iter().position(|s| *s == self.env.params.immigrant_source).unwrap_or(0);
+        self.params.restart_on_extinction = self.run_policy.restart_on_extinction;
+        self.params.restart_every_n_generations = self.run_policy.restart_every_n_generations as f32;
+        self.params.max_steps_per_run = self.run_policy.limits.max_steps.unwrap_or(0) as f32;
+        (self.params.selection_method_idx, self.params.selection_size) = match self.run_policy.selection {
+            SelectionMethod::Random => (0, DEFAULT_TOP_K as f32),
+            SelectionMethod::TopK(k) => (1, k as f32),
+            SelectionMethod::Tournament(size) => (2, size as f32),
+        };
+        self.params.fitness_metric_idx = FITNESS_METRIC_OPTIONS.iter().position(|m| *m == self.run_policy.fitness).unwrap_or(0);
+    }
+
+    /// Update the environment parameters from the values that are in the param panel sliders
     /// This should be called right before a new simulation is set up/generated
     /// Function returns True if all parameters are updated and false if any parameter is invalid
-    /// or cannot be parsed
-    fn update_params_from_text(&mut self) -> bool {
+    fn update_params_from_panel(&mut self) -> bool {
         // Create temporary params struct to validate everything before we apply it
         let mut temp_params = EnvironmentParams::new();
 
-        // Parse the text to make sure that at least works
-        temp_params.env_x_size = self.params.env_x_size.parse::<usize>().expect("Error parsing env_x_size");
-        temp_params.env_y_size = self.params.env_y_size.parse::<usize>().expect("Error parsing env_y_size");
-        temp_params.num_start_creatures = self.params.num_start_creatures.parse::<usize>().expect("Error parsing num_start_creatures");
-        temp_params.num_start_food = self.params.num_start_food.parse::<usize>().expect("Error parsing num_start_food");
-        temp_params.num_start_walls = self.params.num_start_walls.parse::<usize>().expect("Error parsing num_start_walls");
-        temp_params.energy_per_food_piece = self.params.energy_per_food_piece.parse::<usize>().expect("Error parsing energy_per_food_piece");
-        temp_params.max_offspring_per_reproduce = self.params.max_offspring_per_reproduce.parse::<usize>().expect("Error parsing max_offspring_per_reproduce");
-        temp_params.mutation_prob = self.params.mutation_prob.parse::<f32>().expect("Error parsing mutation_prob");
-        temp_params.avg_new_food_per_day = self.params.avg_new_food_per_day.parse::<f32>().expect("Error parsing avg_new_food_per_day");
+        // Copy over the slider values (counts are rounded to whole numbers)
+        temp_params.env_x_size = self.params.env_x_size.round() as usize;
+        temp_params.env_y_size = self.params.env_y_size.round() as usize;
+        temp_params.num_start_creatures = self.params.num_start_creatures.round() as usize;
+        temp_params.num_start_food = self.params.num_start_food.round() as usize;
+        temp_params.num_start_walls = self.params.num_start_walls.round() as usize;
+        temp_params.energy_per_food_piece = self.params.energy_per_food_piece.round() as usize;
+        temp_params.max_offspring_per_reproduce = self.params.max_offspring_per_reproduce.round() as usize;
+        temp_params.mutation_prob = self.params.mutation_prob;
+        temp_params.mutation_mix.gaussian = self.params.mutation_weight_gaussian;
+        temp_params.mutation_mix.sign_flip = self.params.mutation_weight_sign_flip;
+        temp_params.mutation_mix.reset = self.params.mutation_weight_reset;
+        temp_params.mutation_mix.swap = self.params.mutation_weight_swap;
+        temp_params.mutation_mix.layer_scale = self.params.mutation_weight_layer_scale;
+        temp_params.mutation_schedule = MUTATION_SCHEDULE_OPTIONS[self.params.mutation_schedule_idx];
+        temp_params.mutation_anneal_rate = self.params.mutation_anneal_rate;
+        temp_params.offspring_energy_fraction = self.params.offspring_energy_fraction;
+        temp_params.gestation_steps = self.params.gestation_steps.round() as usize;
+        temp_params.gestation_upkeep_energy = self.params.gestation_upkeep_energy.round() as usize;
+        temp_params.avg_new_food_per_day = self.params.avg_new_food_per_day;
+        temp_params.enable_position_inputs = self.params.enable_position_inputs;
+        temp_params.enable_wall_distance_input = self.params.enable_wall_distance_input;
+        temp_params.enable_move_blocked_input = self.params.enable_move_blocked_input;
+        temp_params.one_hot_categorical_inputs = self.params.one_hot_categorical_inputs;
+        temp_params.enable_hearing_input = self.params.enable_hearing_input;
+        temp_params.enable_territory = self.params.enable_territory;
+        temp_params.enable_rest = self.params.enable_rest;
+        temp_params.enable_tribe_vision = self.params.enable_tribe_vision;
+        temp_params.enable_decal_vision = self.params.enable_decal_vision;
+        temp_params.enable_beacon = self.params.enable_beacon;
+        temp_params.energy_per_beacon_reach = self.params.energy_per_beacon_reach.round() as usize;
+        temp_params.enable_novelty = self.params.enable_novelty;
+        temp_params.novelty_descriptor = NOVELTY_DESCRIPTOR_OPTIONS[self.params.novelty_descriptor_idx];
+        temp_params.novelty_weight = self.params.novelty_weight;
+        temp_params.enable_speciation = self.params.enable_speciation;
+        temp_params.speciation_threshold = self.params.speciation_threshold;
+        temp_params.speciation_interval = self.params.speciation_interval.round() as usize;
+        temp_params.creature_order = CREATURE_ORDER_OPTIONS[self.params.creature_order_idx];
+        temp_params.view_distance = self.params.view_distance.round() as usize;
+        temp_params.evolve_view_distance = self.params.evolve_view_distance;
+        temp_params.view_energy_cost = self.params.view_energy_cost;
+        temp_params.enable_metabolic_cost = self.params.enable_metabolic_cost;
+        temp_params.metabolic_energy_cost = self.params.metabolic_energy_cost;
+        temp_params.hearing_radius = self.params.hearing_radius.round() as usize;
+        temp_params.brain_compute_budget = self.params.brain_compute_budget.round() as usize;
+        temp_params.action_delay_steps = self.params.action_delay_steps.round() as usize;
+        temp_params.territory_decay = self.params.territory_decay;
+        temp_params.tribe_vision_radius = self.params.tribe_vision_radius.round() as usize;
+        temp_params.season_length = self.params.season_length.round() as usize;
+        temp_params.mating_season_start = self.params.mating_season_start;
+        temp_params.mating_season_length = self.params.mating_season_length;
+        temp_params.food_die_off.rate = self.params.food_die_off_rate;
+        temp_params.food_die_off.magnitude = self.params.food_die_off_magnitude;
+        temp_params.plague.rate = self.params.plague_rate;
+        temp_params.plague.magnitude = self.params.plague_magnitude;
+        temp_params.wall_collapse.rate = self.params.wall_collapse_rate;
+        temp_params.wall_collapse.magnitude = self.params.wall_collapse_magnitude;
+        temp_params.enable_disease = self.params.enable_disease;
+        temp_params.initial_infection_fraction = self.params.initial_infection_fraction;
+        temp_params.infection_prob = self.params.infection_prob;
+        temp_params.infection_duration = self.params.infection_duration.round() as usize;
+        temp_params.infection_energy_drain = self.params.infection_energy_drain.round() as usize;
+        temp_params.enable_elevation = self.params.enable_elevation;
+        temp_params.elevation_feature_size = self.params.elevation_feature_size.round() as usize;
+        temp_params.elevation_energy_cost = self.params.elevation_energy_cost;
+        temp_params.high_ground_view_bonus = self.params.high_ground_view_bonus.round() as usize;
+        temp_params.enable_river = self.params.enable_river;
+        temp_params.river_flow_strength = self.params.river_flow_strength;
+        temp_params.river_source_food = self.params.river_source_food;
+        temp_params.enable_swimming = self.params.enable_swimming;
+        temp_params.initial_swimmer_fraction = self.params.initial_swimmer_fraction;
+        temp_params.enable_large_bodies = self.params.enable_large_bodies;
+        temp_params.initial_large_fraction = self.params.initial_large_fraction;
+        temp_params.swim_energy_cost = self.params.swim_energy_cost.round() as usize;
+        temp_params.immigration_rate = self.params.immigration_rate;
+        temp_params.immigrant_source = IMMIGRANT_SOURCE_OPTIONS[self.params.immigrant_source_idx];
+
+        // Restart policy
+        let restart_every_n_generations = self.params.restart_every_n_generations.round() as usize;
+        let selection_size = self.params.selection_size.round() as usize;
+        let selection = match self.params.selection_method_idx {
+            1 => SelectionMethod::TopK(selection_size),
+            2 => SelectionMethod::Tournament(selection_size),
+            _ => SelectionMethod::Random,
+        };
 
         let num_spaces = temp_params.env_x_size * temp_params.env_y_size;
 
@@ -623,18 +2731,31 @@ impl EnvMacroquad {
             println!("Error: mutation_prob is invalid. Must be between 0 and 1");
             return false;
         }
+        let mix = temp_params.mutation_mix;
+        if mix.gaussian < 0.0 || mix.sign_flip < 0.0 || mix.reset < 0.0 || mix.swap < 0.0 || mix.layer_scale < 0.0 {
+            println!("Error: mutation weights must not be negative");
+            return false;
+        }
+        if temp_params.offspring_energy_fraction > 1.0 || temp_params.offspring_energy_fraction < 0.0 {
+            println!("Error: offspring_energy_fraction is invalid. Must be between 0 and 1");
+            return false;
+        }
         if temp_params.num_start_food > num_spaces || temp_params.num_start_creatures > num_spaces || temp_params.num_start_walls > num_spaces {
             println!("Error: number of start food/creatures/walls is too large for a {} x {} grid", temp_params.env_x_size, temp_params.env_y_size);
             return false;
         }
 
-        // Update some internal macroquad variables whos values are derived from env variables
-        self.grid_x_size = SCREEN_SIZE_X / (temp_params.env_x_size as f32);
-        self.grid_y_size = SCREEN_SIZE_Y / (temp_params.env_y_size as f32);
-
         // All good, copy the temp params into the real one
         self.env.params = temp_params;
 
+        // Update some internal macroquad variables whos values are derived from env variables
+        self.update_grid_size();
+        self.run_policy.restart_on_extinction = self.params.restart_on_extinction;
+        self.run_policy.restart_every_n_generations = restart_every_n_generations;
+        self.run_policy.limits.max_steps = Some(self.params.max_steps_per_run.round() as usize).filter(|max_steps| *max_steps > 0);
+        self.run_policy.selection = selection;
+        self.run_policy.fitness = FITNESS_METRIC_OPTIONS[self.params.fitness_metric_idx];
+
         return true;
     }
 
@@ -645,18 +2766,23 @@ impl EnvMacroquad {
 
         // Run several steps
         let steps_to_go = self.step_to_jump_to - self.env.time_step;
-        let res : Result<(), EnvErrors>;
-        if steps_to_go >= NUM_STEPS_PER_CALL {
-            res = self.env.run_n_steps(NUM_STEPS_PER_CALL);
-        } else {
-            res = self.env.run_n_steps(steps_to_go);
+        let limits = RunLimits::with_max_steps(steps_to_go.min(NUM_STEPS_PER_CALL));
+        let stop_reason = self.env.run_with_limits(&limits);
+
+        // Keep board B in lockstep. It's allowed to go extinct without stopping the fast forward
+        if let Some(compare) = &mut self.compare_board {
+            let _ = compare.env.run_with_limits(&limits);
+            compare.run_policy.maybe_restart(&mut compare.env);
         }
+        self.record_comparison_history();
+        self.handle_events(false);
 
-        // If we couldn't run the sim, just stop
-        match res {
-            Err(_e) => self.state = SimState::STOPPED,
-            Ok(_) => (),
+        // Give the run policy a chance to restart the sim. If we couldn't run the sim and it wasn't restarted, just stop
+        let restarted = self.run_policy.maybe_restart(&mut self.env);
+        if stop_reason == StopReason::Extinction && !restarted {
+            self.state = SimState::STOPPED;
         }
+        self.stop_at_run_limits();
 
         // Check for done condition
         if self.env.time_step >= self.step_to_jump_to {
@@ -697,13 +2823,247 @@ impl EnvMacroquad {
         let cur_time = get_time();
 
         // Decide whether we should run the next sim step
-        if (self.state == SimState::RUNNING) && (cur_time - self.last_sim_update > MACROQUAD_FRAME_TIME_S) {
+        if (self.state == SimState::RUNNING) && (cur_time - self.last_sim_update > self.get_step_interval(self.env.num_creatures)) {
             self.run_next_step();
             self.last_sim_update = get_time();
         }
 
     }
 
+    /// Display only mode: just the board of a new environment (no panels), stepped through the same `Renderer`
+    /// loop as the other frontends until the creatures die out or the user quits (see `handle_input` for the keys)
+    pub async fn run_display_only_mode(&mut self) {
+        let mut env = EnvironmentV1::new_rand(&self.env.params);
+        let mut state = CommandState::default();
+        let mut last_step_time = get_time();
+        self.init(&env.get_snapshot());
+
+        loop {
+            if get_time() - last_step_time > self.get_step_interval(env.num_creatures) {
+                if !run_frame(&mut env, self, &mut state) {
+                    break;
+                }
+                last_step_time = get_time();
+            } else {
+                // Keys pressed between steps still count
+                for command in self.handle_input() {
+                    command.execute(&mut env, &mut state);
+                }
+                if state.quit {
+                    break;
+                }
+                self.render_snapshot(&env.get_snapshot());
+            }
+            next_frame().await
+        }
+        self.shutdown();
+    }
+
+}
+
+impl Renderer for EnvMacroquad {
+
+    /// Size the grid squares so the board fills the window
+    fn init(&mut self, snapshot : &EnvSnapshot) {
+        self.grid_x_size = SCREEN_SIZE_X / (snapshot.x_size as f32);
+        self.grid_y_size = SCREEN_SIZE_Y / (snapshot.y_size as f32);
+    }
+
+    /// Draw the board on its own
+    fn render_snapshot(&mut self, snapshot : &EnvSnapshot) {
+        clear_background(BLACK);
+        self.draw_board(snapshot, 0.0, None);
+    }
+
+    /// P pauses, R resumes and Q or Escape quits
+    fn handle_input(&mut self) -> Vec<SimCommand> {
+        let mut commands : Vec<SimCommand> = Vec::new();
+        if is_key_pressed(KeyCode::P) {
+            commands.push(SimCommand::Pause);
+        }
+        if is_key_pressed(KeyCode::R) {
+            commands.push(SimCommand::Resume);
+        }
+        if is_key_pressed(KeyCode::Q) || is_key_pressed(KeyCode::Escape) {
+            commands.push(SimCommand::Quit);
+        }
+        return commands;
+    }
+}
+
+/// Draw a message window in the middle of the screen with an OK button. Returns true when OK is clicked
+fn draw_message_window(title : &str, msg : &str) -> bool {
+    let mut dismissed = false;
+    let window_size = vec2(SCREEN_SIZE_X / 1.5, 150.0);
+    let window_pos = vec2((SCREEN_SIZE_X - window_size.x) / 2.0, (SCREEN_SIZE_Y - window_size.y) / 2.0);
+    root_ui().window(hash!(), window_pos, window_size, |ui| {
+        ui.label(None, title);
+        ui.label(None, "");
+
+        // Labels don't wrap, so split long messages up
+        let chars : Vec<char> = msg.chars().collect();
+        for line in chars.chunks(80) {
+            ui.label(None, &line.iter().collect::<String>());
+        }
+        if ui.button(None, "OK") {
+            dismissed = true;
+        }
+    });
+    return dismissed;
+}
+
+/// Remember where the param panel row for the param called `name` starts (see `update_sim_param_panel`)
+fn mark_param_row(ui : &mut Ui, rows : &mut Vec<(f32, &'static str)>, name : &'static str) {
+    rows.push((ui.canvas().cursor().y, name));
+}
+
+/// Get the range of a param's slider from its doc (0 to 1 if it doesn't have a documented range)
+fn get_slider_range(name : &str) -> std::ops::Range<f32> {
+    let (min, max) = get_param_doc(name).and_then(|doc| doc.range).unwrap_or((0.0, 1.0));
+    return min..max;
 }
 
+/// Draw a tooltip describing a param, with its right edge at `right_x` and its top at `y`. It's drawn over the board
+/// (before the UI windows), so it's moved up if it would run off the bottom of the board
+fn draw_param_tooltip(doc : &ParamDoc, right_x : f32, y : f32) {
+    // Wrap the description on word boundaries
+    let mut lines : Vec<String> = vec![String::new()];
+    for word in doc.description.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.len() + word.len() + 1 > PARAM_TOOLTIP_LINE_CHARS {
+            lines.push(word.to_string());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    let units_and_range = doc.get_units_and_range();
+    if !units_and_range.is_empty() {
+        lines.push(format!("[{}]", units_and_range));
+    }
+
+    let line_height = PARAM_TOOLTIP_FONT_SIZE * 1.1;
+    let x = right_x - PARAM_TOOLTIP_WIDTH;
+    let height = (lines.len() + 1) as f32 * line_height;
+    let y = y.min(SCREEN_SIZE_Y - height);
+    draw_rectangle(x, y, PARAM_TOOLTIP_WIDTH, height, Color::from_rgba(30, 30, 30, 230));
+    draw_rectangle_lines(x, y, PARAM_TOOLTIP_WIDTH, height, 1.0, LIGHTGRAY);
+    draw_text(doc.name, x + 5.0, y + line_height, PARAM_TOOLTIP_FONT_SIZE, YELLOW);
+    for (line_idx, line) in lines.iter().enumerate() {
+        draw_text(line, x + 5.0, y + (line_idx + 2) as f32 * line_height, PARAM_TOOLTIP_FONT_SIZE, WHITE);
+    }
+}
 
+/// Draw a horizontal bar filled to `fraction` (0-1) inside the current UI window
+fn draw_bar(ui : &mut Ui, fraction : f32) {
+    let mut canvas = ui.canvas();
+    let origin = canvas.request_space(vec2(SALIENCY_BAR_WIDTH, SALIENCY_BAR_HEIGHT));
+    canvas.rect(Rect::new(origin.x, origin.y, SALIENCY_BAR_WIDTH, SALIENCY_BAR_HEIGHT), Color::from_rgba(0, 0, 0, 80), None);
+    let fill_width = SALIENCY_BAR_WIDTH * fraction.clamp(0.0, 1.0);
+    if fill_width > 0.0 {
+        canvas.rect(Rect::new(origin.x, origin.y, fill_width, SALIENCY_BAR_HEIGHT), None, Color::from_rgba(0, 120, 255, 255));
+    }
+}
+
+/// Draw a genome fingerprint glyph inside the current UI window
+fn draw_fingerprint(ui : &mut Ui, fingerprint : &GenomeFingerprint) {
+    let mut canvas = ui.canvas();
+    let glyph_size = FINGERPRINT_CELL_SIZE * FINGERPRINT_SIZE as f32;
+    let origin = canvas.request_space(vec2(glyph_size, glyph_size));
+    canvas.rect(Rect::new(origin.x, origin.y, glyph_size, glyph_size), None, Color::from_rgba(255, 255, 255, 255));
+    let color = Color::from_rgba(fingerprint.color.red, fingerprint.color.green, fingerprint.color.blue, 255);
+    for (row, row_cells) in fingerprint.cells.iter().enumerate() {
+        for (col, filled) in row_cells.iter().enumerate() {
+            if *filled {
+                let cell = Rect::new(origin.x + col as f32 * FINGERPRINT_CELL_SIZE, origin.y + row as f32 * FINGERPRINT_CELL_SIZE, FINGERPRINT_CELL_SIZE, FINGERPRINT_CELL_SIZE);
+                canvas.rect(cell, None, color);
+            }
+        }
+    }
+}
+
+/// Draw a small line graph of `values` (scaled from 0 to `max_val`) inside the current UI window.
+/// `capacity` is the number of values that fill the full width of the graph
+fn draw_sparkline(ui : &mut Ui, values : &[usize], max_val : usize, capacity : usize) {
+    let mut canvas = ui.canvas();
+    let origin = canvas.request_space(vec2(SPARKLINE_WIDTH, SPARKLINE_HEIGHT));
+    canvas.rect(Rect::new(origin.x, origin.y, SPARKLINE_WIDTH, SPARKLINE_HEIGHT), Color::from_rgba(0, 0, 0, 80), None);
+
+    if values.len() < 2 || max_val == 0 {
+        return;
+    }
+
+    // Space the points out evenly over the full width, with the newest value on the right
+    let x_step = SPARKLINE_WIDTH / ((capacity.max(2) - 1) as f32);
+    let x_start = origin.x + SPARKLINE_WIDTH - x_step * ((values.len() - 1) as f32);
+    let to_y = |val : usize| origin.y + SPARKLINE_HEIGHT * (1.0 - (val.min(max_val) as f32) / (max_val as f32));
+    for i in 1..values.len() {
+        let start = vec2(x_start + x_step * ((i - 1) as f32), to_y(values[i - 1]));
+        let end = vec2(x_start + x_step * (i as f32), to_y(values[i]));
+        canvas.line(start, end, Color::from_rgba(0, 120, 255, 255));
+    }
+}
+
+/// Draw a bar for each entry of a Pareto report on every objective, scaled so the best entry on each objective fills the width
+fn draw_objective_bars(ui : &mut Ui, report : &ParetoReport) {
+    let group_height = OBJECTIVE_BAR_HEIGHT * report.entries.len() as f32 + 4.0;
+    let mut canvas = ui.canvas();
+    let origin = canvas.request_space(vec2(SPARKLINE_WIDTH, group_height * ALL_OBJECTIVES.len() as f32));
+
+    for (objective_num, objective) in ALL_OBJECTIVES.iter().enumerate() {
+        let max_score = report.entries.iter().map(|entry| entry.scores.get(*objective)).fold(0.0, f32::max);
+        let group_y = origin.y + group_height * objective_num as f32;
+        canvas.rect(Rect::new(origin.x, group_y, SPARKLINE_WIDTH, group_height - 2.0), Color::from_rgba(0, 0, 0, 80), None);
+        if max_score <= 0.0 {
+            continue;
+        }
+        for (entry_num, entry) in report.entries.iter().enumerate() {
+            let width = SPARKLINE_WIDTH * entry.scores.get(*objective) / max_score;
+            let color = OBJECTIVE_BAR_COLORS[entry_num % OBJECTIVE_BAR_COLORS.len()];
+            canvas.rect(Rect::new(origin.x, group_y + 1.0 + OBJECTIVE_BAR_HEIGHT * entry_num as f32, width, OBJECTIVE_BAR_HEIGHT - 1.0), color, color);
+        }
+    }
+}
+
+/// Blend a creature's color towards INFECTED_TINT_COLOR to show that it's sick
+fn get_infected_tint(color : CreatureColor) -> CreatureColor {
+    let blend = |base : u8, tint : u8| (base as f32 + (tint as f32 - base as f32) * INFECTED_TINT_AMOUNT) as u8;
+    return CreatureColor {
+        red : blend(color.red, INFECTED_TINT_COLOR[0]),
+        green : blend(color.green, INFECTED_TINT_COLOR[1]),
+        blue : blend(color.blue, INFECTED_TINT_COLOR[2]),
+    };
+}
+
+/// Get the area of the screen the dream panel covers
+/// Get where the selection panel goes (top middle of the board)
+fn get_selection_panel() -> Rect {
+    return Rect::new((SCREEN_SIZE_X - SELECTION_PANEL_WIDTH) / 2.0, PANEL_Y_PADDING, SELECTION_PANEL_WIDTH, SELECTION_PANEL_HEIGHT);
+}
+
+fn get_dream_panel() -> Rect {
+    return Rect::new(DREAM_PANEL_MARGIN, DREAM_PANEL_MARGIN, SCREEN_SIZE_X - 2.0 * DREAM_PANEL_MARGIN, SCREEN_SIZE_Y - 2.0 * DREAM_PANEL_MARGIN);
+}
+
+/// Get where a neuron is drawn in the dream panel. The layers are spread out left to right and each layer's neurons top to bottom
+fn get_dream_node_pos(layer_sizes : &[usize], layer_idx : usize, node_idx : usize) -> Vec2 {
+    let panel = get_dream_panel();
+    let net_x = panel.x + DREAM_LABEL_WIDTH;
+    let net_width = panel.w - 2.0 * DREAM_LABEL_WIDTH;
+    let x = net_x + net_width * (layer_idx as f32 / (layer_sizes.len().max(2) - 1) as f32);
+    let y = panel.y + 40.0 + (panel.h - 50.0) * ((node_idx as f32 + 0.5) / layer_sizes[layer_idx] as f32);
+    return vec2(x, y);
+}
+
+/// Get the distance from `point` to the closest spot on the line segment from `start` to `end`
+fn get_dist_to_segment(point : Vec2, start : Vec2, end : Vec2) -> f32 {
+    let segment = end - start;
+    let len_sq = segment.length_squared();
+    if len_sq == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(segment) / len_sq).clamp(0.0, 1.0);
+    return point.distance(start + segment * t);
+}
@@ -5,9 +5,25 @@
  * Description: Implements environment features that the creature inhabits
  * ===============================================================================*/
 use crate::creature::*;
+use crate::neural_net::MutationMix;
+use crate::analysis::*;
+use crate::stats::{StatsRecorder, LifetimeRecord};
+use crate::events::*;
+use crate::snapshot::*;
+use crate::catastrophes::*;
+use crate::terrain::{generate_elevation, generate_river};
+use crate::wall_map::WallMap;
+use crate::experiments::{RunLimits, StopReason};
+use crate::sim_rng::{sim_rng, run_seeded};
 use serde::{Deserialize, Serialize};
 use rand::Rng;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "file_io")]
 use std::io::Read;
+#[cfg(feature = "file_io")]
 use std::fs::File;
 
 //===============================================================================
@@ -19,19 +35,104 @@ pub const DEBUG_LEVEL : usize = 0;
 pub const DEFAULT_ENERGY_PER_FOOD_PIECE : usize = 40;   // How much energy each piece of food will give a creature
 pub const DEFAULT_ENERGY_PER_KILL : usize = 20;         // How much energy each kill will provide another creature. This is less than the normal food pieces to encourage scavenging as well.
 pub const DEFAULT_MUTATION_PROB : f32 = 0.02;           // Default probability that each weight/bias in a creature's DNA will mutate upon reproduction
+pub const DEFAULT_MUTATION_MIX : MutationMix = MutationMix::RESET_ONLY; // By default mutations just re-randomize the value
+pub const DEFAULT_MUTATION_ANNEAL_RATE : f32 = 0.02;    // With generation annealing, mutation strength halves after 1/rate generations
 pub const NEW_FOOD_PIECES_PER_STEP : f32 = 3.0;         // Average number of new food pieces that should appear in the environment per step (can be less than 1)
 
 // Reproduction params
 pub const DEFAULT_OFFSPRING_PER_REPRODUCE : usize = 3;  // Number of offspring that each creature will have upon each reproduction event
 pub const MAX_OFFSPRING_SPAWN_DIST : isize = 3;         // Max distance (in spaces) that a creatures offspring will spawn from the parent
+pub const DEFAULT_OFFSPRING_ENERGY_FRACTION : f32 = 0.0;// By default offspring get a flat starting energy rather than a share of the parent's
+pub const DEFAULT_GESTATION_STEPS : usize = 0;          // By default offspring are born immediately when a creature reproduces
+pub const DEFAULT_GESTATION_UPKEEP_ENERGY : usize = 1;  // Extra energy a pregnant creature spends each step
+pub const DEFAULT_NOVELTY_WEIGHT : f32 = 0.5;           // With novelty search, the most novel creature gets an extra offspring with this probability
+pub const DEFAULT_SPECIATION_THRESHOLD : f32 = 5.0;     // Max genome distance between a creature and its species representative
+pub const DEFAULT_SPECIATION_INTERVAL : usize = 20;     // Species are re-clustered every this many steps
+pub const DEAD_CREATURE_HISTORY_LEN : usize = 500;      // Number of most recently dead creatures kept around (e.g. for selection when restarting)
+pub const IMMIGRATION_SPAWN_ATTEMPTS : usize = 20;      // Number of random edge spaces tried for each immigrant before giving up on it
 
 // Vision params
-pub const MAX_CREATURE_VIEW_DISTANCE : isize = 5;       // Defines max number of spaces a creature can "see"
+pub const DEFAULT_VIEW_ENERGY_COST : f32 = 0.0;         // By default seeing further is free
+pub const DEFAULT_METABOLIC_ENERGY_COST : f32 = 0.02;   // Default energy per step per brain neuron when the metabolic cost is on (a 20 neuron brain pays one energy every few steps)
+pub const DEFAULT_HEARING_RADIUS : usize = 3;           // Default max distance creatures can hear (if the hearing input is enabled)
+pub const DEFAULT_TRIBE_VISION_RADIUS : usize = 5;      // Default max distance tribe-mates share what they see over (if tribe vision is enabled)
 pub const FOOD_SPACE_COLOR : [u8; 3] = [40, 255, 40];   // color of food space (green)
 pub const WALL_SPACE_COLOR : [u8; 3] = [200, 200, 200]; // color of wall space (white)
+pub const FIGHT_DECAL_VISION_COLOR : [u8; 3] = [255, 0, 0];     // color creatures see fight decals as with decal vision (red)
+pub const BIRTH_DECAL_VISION_COLOR : [u8; 3] = [255, 150, 200]; // color creatures see birth decals as with decal vision (pink)
+pub const DEATH_DECAL_VISION_COLOR : [u8; 3] = [110, 60, 0];    // color creatures see death decals (corpses) as with decal vision (brown)
+pub const TERRITORY_VISION_COLOR : [u8; 3] = [160, 0, 160];     // color creatures see other lineages' territory as with decal vision (purple)
+pub const BEACON_COLOR : [u8; 3] = [255, 220, 0];       // color the beacon is drawn with (yellow)
+
+// Beacon params
+pub const DEFAULT_ENERGY_PER_BEACON_REACH : usize = 40; // How much energy a creature gets for reaching the beacon
+
+// Season params
+pub const DEFAULT_MATING_SEASON_START : f32 = 0.0;      // Phase [0, 1) of the seasonal cycle that mating season starts at
+pub const DEFAULT_MATING_SEASON_LENGTH : f32 = 1.0;     // Fraction of the seasonal cycle creatures can reproduce in (1 = all year)
+
+// Disease params
+pub const DEFAULT_INITIAL_INFECTION_FRACTION : f32 = 0.05; // Fraction of the starting creatures that start out sick
+pub const DEFAULT_INFECTION_PROB : f32 = 0.2;           // Chance each step of catching the disease from each sick neighbor
+pub const DEFAULT_INFECTION_DURATION : usize = 20;      // Number of steps an infection lasts before the creature recovers
+pub const DEFAULT_INFECTION_ENERGY_DRAIN : usize = 1;   // Energy a sick creature loses every step
+
+// Elevation params
+pub const DEFAULT_ELEVATION_FEATURE_SIZE : usize = 12;  // Rough width (in spaces) of the biggest hills
+pub const DEFAULT_ELEVATION_ENERGY_COST : f32 = 5.0;    // Extra energy it takes to climb from the lowest to the highest point (going down refunds up to the move cost)
+pub const ELEVATION_BRUSH_STEP : f32 = 0.02;            // How much the GUI terrain brush raises/lowers a space per frame
+
+// River params
+pub const DEFAULT_RIVER_FLOW_STRENGTH : f32 = 0.5;      // Chance each step that a piece of food on the river drifts one space downstream
+pub const DEFAULT_RIVER_SOURCE_FOOD : f32 = 0.2;        // Average number of food pieces that wash in at the river's source per step
+
+// Swimming params
+pub const DEFAULT_INITIAL_SWIMMER_FRACTION : f32 = 0.1; // Fraction of the starting creatures that can swim
+pub const DEFAULT_SWIM_ENERGY_COST : usize = 2;         // Extra energy it takes to move into a water space
+pub const SWIMMER_HISTORY_LEN : usize = 200;            // Number of past swimmer counts kept for the stats graph
+
+// Large body params
+pub const DEFAULT_INITIAL_LARGE_FRACTION : f32 = 0.25;  // Fraction of the starting creatures that have large bodies
+
+// Merge params
+pub const MERGE_CORRIDOR_WIDTH : usize = 4;             // Width of the gap left in the middle of the wall between two merged environments
+
+// Territory params
+pub const DEFAULT_TERRITORY_DECAY : f32 = 0.01;         // Fraction of a territory mark's strength that fades every step
+pub const TERRITORY_MIN_STRENGTH : f32 = 0.05;          // Marks that fade below this strength are removed
+
+// Save params
+pub const SAVE_WEIGHT_DECIMALS : i32 = 3;               // Number of decimal places weights/biases are rounded to when saving with `round_weights`
+
+// State hash params (64-bit FNV-1a)
+#[allow(dead_code)]
+const FNV_OFFSET_BASIS : u64 = 0xcbf29ce484222325;
+#[allow(dead_code)]
+const FNV_PRIME : u64 = 0x100000001b3;
+
+// Summary report params
+pub const REPORT_NUM_TOP_LINEAGES : usize = 5;          // Number of lineages listed in the summary report
+pub const EXTINCTION_CAUSE_WINDOW : usize = 50;         // Deaths in this many steps before extinction decide what the report blames it on
 
 // Display params
-pub const FIGHT_SPACE_PERSISTENCE_STEPS : usize = 20;   // Number of time steps a fight space should persist for before it disappears
+pub const FIGHT_DECAL_PERSISTENCE_STEPS : usize = 20;   // Number of time steps a fight decal should persist for before it disappears
+pub const BIRTH_DECAL_PERSISTENCE_STEPS : usize = 5;    // Number of time steps a birth decal should persist for before it disappears
+pub const DEATH_DECAL_PERSISTENCE_STEPS : usize = 10;   // Number of time steps a (natural) death decal should persist for before it disappears
+
+// Image import/export params
+pub const IMAGE_WALL_LUMA_THRESHOLD : u8 = 128;         // Pixels darker than this (and mostly opaque) are turned into walls when importing an image
+pub const IMAGE_BLANK_SPACE_COLOR : [u8; 3] = [0, 0, 0];     // color of blank spaces in exported images (black, like the GUI)
+pub const IMAGE_FIGHT_SPACE_COLOR : [u8; 3] = [64, 0, 0];    // color of spaces with a fight decal in exported images (dark red, like the GUI)
+pub const IMAGE_FOOTER_TEXT_COLOR : [u8; 3] = [220, 220, 220]; // color of the stats footer text in exported images
+pub const FOOTER_GLYPH_WIDTH : u32 = 3;                     // Width (in font pixels) of a character in the stats footer font
+pub const FOOTER_GLYPH_HEIGHT : u32 = 5;                    // Height (in font pixels) of a character in the stats footer font
+pub const FOOTER_PIXEL_SIZE : u32 = 2;                      // Each font pixel is drawn as a square this many image pixels wide
+pub const FOOTER_PADDING : u32 = 4;                         // Gap (in image pixels) around the footer text
+pub const CARD_WIDTH : u32 = 184;                           // Width (in image pixels) of a creature card. Fits a 21 character line of footer text
+pub const CARD_SWATCH_SIZE : u32 = 25;                      // Size of the color swatch and genome fingerprint at the top of a creature card
+pub const CARD_BRAIN_HEIGHT : u32 = 80;                     // Height of the brain diagram at the bottom of a creature card
+pub const CARD_POSITIVE_WEIGHT_COLOR : [u8; 3] = [0, 120, 255];  // color of the strongest positive brain connections on a creature card
+pub const CARD_NEGATIVE_WEIGHT_COLOR : [u8; 3] = [255, 60, 60];  // color of the strongest negative brain connections on a creature card
 
 
 //===============================================================================
@@ -51,13 +152,160 @@ pub enum SpaceStates {
     CreatureSpace(usize),       // Space has a creature in it. The single argument represents the ID of the creature
     FoodSpace,                  // Space has a food in it
     WallSpace,                  // Space that contains a wall
-    FightSpace(usize),          // Indicator that a creature was killed in this space. Argument is the number of remaining time steps that this space has remaining before it should disappear
+}
+
+/// Space states as they can appear in saves. Saves from older versions can have fight spaces on the board,
+/// which are decals now (see `Decal`), so they're read as blank spaces
+#[derive(Deserialize)]
+#[allow(clippy::enum_variant_names)] // Names have to match the ones in the saves
+enum SavedSpaceState {
+    BlankSpace,
+    CreatureSpace(usize),
+    FoodSpace,
+    WallSpace,
+    FightSpace(serde::de::IgnoredAny), // Time left on the fight space, which is dropped
+}
+
+
+/// Order creatures take their turns in within a step. Creatures that go first get first pick of contested
+/// food and spaces, so a fixed order is biased towards whoever is first in it
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CreatureOrder {
+    Insertion,  // Order the creatures were added to the board in (oldest first). Deterministic
+    Random,     // Shuffled every step to remove the ordering bias. Shuffles come from the sim RNG, so seeded runs are still reproducible
+    Morton,     // Sorted along a Z-order (Morton) curve of board position, so creatures near each other act one after another
+                // and board accesses stay local in memory. Deterministic, since only one creature can be in each space
+}
+
+
+/// Where the genomes of creatures immigrating onto the board come from
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum ImmigrantSource {
+    #[default]
+    Random,     // Brand new random brains
+    HallOfFame, // Mutated copies of the longest-lived of the recently dead creatures (see `dead_creatures`). Random if none have died yet
+}
+
+/// What would happen if a creature took an action right now (see `EnvironmentV1::preview_action`)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PreviewResult {
+    NoCreature,                     // No living creature has that ID
+    NoEffect,                       // Nothing on the board would change (stay, rest, rotate, kill with nothing in reach...)
+    Move(CreaturePosition),         // Would move to the given blank space
+    Blocked,                        // Would try to move but a wall, creature or water is in the way
+    Eat(CreaturePosition),          // Would move onto the food at the given space and eat it
+    ReachBeacon(CreaturePosition),  // Would move onto the beacon at the given space
+    Kill(usize),                    // Would kill the creature with the given ID
+    Reproduce,                      // Would reproduce (or start a pregnancy if gestation is enabled)
+    MarkTerritory,                  // Would claim the space it's standing on for its lineage
+}
+
+/// Which part of the board stays where it is when the board is resized
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResizeAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// How creatures brought in from another environment are fitted into this one
+#[derive(Copy, Clone, PartialEq)]
+pub struct CreatureImportOptions {
+    pub adapt_brains : bool,    // Rewire brains built for different inputs/actions to match this environment (otherwise they're rejected)
+    pub region : Option<(CreaturePosition, CreaturePosition)>, // Top left and bottom right corners (inclusive) of the area to place them in. None = anywhere
+}
+
+/// What happened to each creature in an import
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct CreatureImportReport {
+    pub num_imported : usize,   // Creatures added to the board (including adapted ones)
+    pub num_adapted : usize,    // Creatures whose brains had to be rewired for this environment's inputs/actions
+    pub num_rejected : usize,   // Creatures left out (dead, broken brain, wrong inputs/actions, or no room)
+}
+
+/// Information about a run that's kept in its saves, so a directory of saves can still be made sense of later.
+/// The version, step count, save time and IDs are filled in whenever the environment is saved
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RunMetadata {
+    pub seed : Option<u64>,                 // Seed the run was created with (None = unseeded)
+    pub created_at : u64,                   // When the run was created (seconds since the Unix epoch, 0 = unknown)
+    pub crate_version : String,             // Version of evolution_sim that wrote the save
+    pub total_steps : usize,                // Number of steps simulated when the save was written
+    pub saved_at : u64,                     // When the save was written (seconds since the Unix epoch, 0 = never saved)
+    pub save_id : Option<String>,           // ID of the save (None = never saved)
+    pub parent_save_id : Option<String>,    // ID of the save this run was loaded from when it was saved (None = a fresh run)
+    pub notes : String,                     // Anything the user wants to remember about the run
+}
+
+/// Problems found (and fixed) in an environment after it was loaded. See `EnvironmentV1::repair_after_load`
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct LoadRepairReport {
+    pub num_relocated : usize,          // Creatures moved because they were off the board, in a wall or on top of another creature
+    pub num_dropped : usize,            // Creatures left out because there was no blank space to move them to
+    pub num_ids_reissued : usize,       // Creatures given a new ID because another creature already had theirs
+    pub num_board_fixes : usize,        // Board spaces/layers that didn't match the creature list or the board size
+    pub num_brains_sanitized : usize,   // Creatures whose brains had weights/biases that weren't numbers (set to 0)
+}
+
+/// Changes to make to a living creature by hand. Anything left as None is unchanged
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct CreatureEdit {
+    pub energy : Option<usize>,                         // New energy level (capped at MAX_POSSIBLE_ENERGY). 0 means it starves next step
+    pub age : Option<usize>,                            // New age in time steps
+    pub orientation : Option<CreatureOrientation>,      // New direction to face
+    pub color : Option<[u8; 3]>,                        // New color (r, g, b)
+    pub lineage_id : Option<usize>,                     // New lineage (tribe) to belong to
+}
+
+/// How two environments are laid out when they're merged
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MergeDirection {
+    Horizontal,     // The second environment goes to the right of the first
+    Vertical,       // The second environment goes below the first
+}
+
+/// What separates two merged environments
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MergeSeam {
+    Open,           // Nothing, the boards are joined edge to edge
+    Wall,           // A solid wall, so the populations can't meet
+    Corridor,       // A wall with a MERGE_CORRIDOR_WIDTH gap in the middle for the populations to meet through
+}
+
+/// A lineage's claim on a single space. Creatures mark territory at full strength and it slowly fades
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TerritoryMark {
+    pub owner : usize,          // Lineage ID of the creature that marked the space
+    pub strength : f32,         // How fresh the mark is [0, 1]. 0 = unmarked
+}
+
+/// Kinds of decals that can be left on a space
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DecalKind {
+    Fight,      // A creature was killed here
+    Birth,      // A creature was born here
+    Death,      // A creature died of natural causes here
+}
+
+/// A short-lived marker on a space. Decals are kept in their own layer and are only for display, so they never
+/// block movement or spawning. A space holds one decal at a time (a new one replaces the old one)
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Decal {
+    pub kind : DecalKind,       // What happened on the space
+    pub ttl : usize,            // Number of steps left before the decal disappears
 }
 
 
 /// Struct that's used to specify what parts of the environment should be loaded
 /// from a JSON file
-#[derive(Serialize, Deserialize, Clone, Copy)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct JsonEnvLoadParams {
     pub load_all : bool,        // load everything from JSON file (ignores all other options)
 
@@ -65,10 +313,49 @@ pub struct JsonEnvLoadParams {
     pub load_creatures : bool,  // load creatures everything from JSON file
     pub load_walls : bool,      // load wall spaces from file
     pub load_food : bool,       // load food spaces from file
+
+    // Optional per-layer source files. If one of these is set, that layer is pulled from the given file
+    // instead of the main file passed to `load_from_json` (e.g. walls from one save and creatures from another)
+    pub parameters_file : Option<String>,
+    pub creatures_file : Option<String>,
+    pub walls_file : Option<String>,
+    pub food_file : Option<String>,
+}
+
+/// Options for shrinking save files
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SaveOptions {
+    pub exclude_activations : bool, // Leave out brain activations (only weights/biases are needed to restore a brain)
+    pub exclude_creatures : bool,   // Leave out creatures entirely (map-only save)
+    pub round_weights : bool,       // Round weights/biases to SAVE_WEIGHT_DECIMALS decimal places
 }
 
-/// Structure that defines all input parameters to a new environment
+/// What actually gets written to a save file: the environment plus a checksum of it and the version of
+/// the program that saved it, so corrupted or truncated files can be detected when loading
+#[derive(Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct EnvSaveFile {
+    pub crate_version : String,         // Version of evolution_sim that wrote the file
+    pub checksum : String,              // FNV-1a hash (hex) of the compact JSON of `environment`
+    pub environment : EnvironmentV1,    // The saved environment
+}
+
+/// A save file as it's read back in. The environment is kept as the raw JSON from the file so the checksum is checked
+/// against exactly what was written. Re-serializing it would fill in fields added since (with their defaults) and
+/// change the hash of saves made by older versions
+#[cfg(feature = "json")]
+#[derive(Deserialize)]
+struct RawEnvSaveFile<'a> {
+    crate_version : String,
+    checksum : String,
+    #[serde(borrow)]
+    environment : &'a serde_json::value::RawValue,
+}
+
+/// Structure that defines all input parameters to a new environment. Any parameters missing from
+/// a saved file (e.g. saved by an older version) take their default values
 #[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
 pub struct EnvironmentParams {
     pub env_x_size : usize,                 // X size of the sim in "spaces"
     pub env_y_size : usize,                 // Y size of the sim in "spaces"
@@ -79,10 +366,73 @@ pub struct EnvironmentParams {
     pub energy_per_kill : usize,            // Number of energy units that will be given if a creature hunts another
     pub max_offspring_per_reproduce : usize,// Maximum number of offspring that will be produced by one reproduction event
     pub mutation_prob : f32,                // Probability that a single value in the creatures DNA will randomly mutate upon reproduction
+    pub mutation_mix : MutationMix,         // Relative weights of each mutation operator used when a value does mutate
+    pub mutation_schedule : MutationSchedule, // How mutation strength changes over a lineage (constant, annealed by generation, self-adaptive)
+    pub mutation_anneal_rate : f32,         // Rate used by MutationSchedule::AnnealByGeneration
+    pub offspring_energy_fraction : f32,    // Fraction [0, 1] of the parent's current energy handed to each offspring. 0 = offspring get the flat starting energy instead
+    pub gestation_steps : usize,            // Number of steps between reproducing and the offspring being born. 0 = born immediately
+    pub gestation_upkeep_energy : usize,    // Extra energy a pregnant creature pays every step of gestation
     pub avg_new_food_per_day : f32,         // Average number of new food pieces added to the environment per day
 
     pub creature_repro_energy_cost : usize, // Energy cost for creature to reproduce
     pub creature_starting_energy : usize,   // Starting energy for each new creature
+
+    // Optional creature input neurons (new creatures get a brain sized to fit whatever is enabled)
+    pub enable_position_inputs : bool,      // Creatures sense their normalized x/y position on the board
+    pub enable_wall_distance_input : bool,  // Creatures sense the distance to the nearest wall in front of them
+    pub enable_move_blocked_input : bool,   // Creatures sense whether their last move was blocked by a wall/creature
+    pub one_hot_categorical_inputs : bool,  // Last action and orientation are sensed as one neuron per possible value (1 for the current one, 0 for the rest) instead of a single arbitrary number
+    pub enable_hearing_input : bool,        // Creatures sense how many creatures/food are within `hearing_radius` in any direction
+    pub enable_territory : bool,            // Creatures can mark territory for their lineage and sense when they're on another lineage's territory
+    pub enable_rest : bool,                 // Creatures get a Rest action that recovers a little energy but makes them cheaper to kill
+    pub enable_tribe_vision : bool,         // Creatures sense whether a creature of the same lineage within `tribe_vision_radius` sees food/a threat
+    pub enable_decal_vision : bool,         // Creatures see decals (fights, births, deaths) and other lineages' territory marks as colored objects, so they can learn to avoid danger
+
+    // Beacon (goal) params
+    pub enable_beacon : bool,               // Place a beacon on the board that creatures can sense the bearing/distance to
+    pub energy_per_beacon_reach : usize,    // Energy given to a creature that reaches the beacon (the beacon then moves somewhere else)
+    pub enable_novelty : bool,              // Boost the reproduction of creatures whose behavior is novel compared to the rest
+    pub novelty_descriptor : NoveltyDescriptor, // What behavior is compared for novelty search
+    pub novelty_weight : f32,               // Chance [0, 1] of an extra offspring for the most novel creature (scaled down by novelty rank for the rest)
+    pub enable_speciation : bool,           // Periodically cluster creatures into species by genome distance
+    pub speciation_threshold : f32,         // Max genome distance from a species representative for a creature to belong to that species
+    pub speciation_interval : usize,        // Number of steps between re-clustering species
+    pub creature_order : CreatureOrder,     // Order creatures take their turns in each step (see CreatureOrder)
+    pub view_distance : usize,              // Max number of spaces creatures can see ahead (starting value if it evolves)
+    pub evolve_view_distance : bool,        // Each creature carries its own view distance gene that mutates on reproduction
+    pub view_energy_cost : f32,             // Energy each creature pays per step for every space of view distance
+    pub enable_metabolic_cost : bool,       // Creatures pay a small energy cost every step for sensing and thinking, even if they just stay put
+    pub metabolic_energy_cost : f32,        // Energy each creature pays per step for every neuron in its brain (if enable_metabolic_cost is set)
+    pub hearing_radius : usize,             // Max distance (in spaces along either axis) creatures can hear
+    pub brain_compute_budget : usize,       // Brain connections a creature can evaluate per step (bigger brains take several steps to decide). 0 = unlimited
+    pub action_delay_steps : usize,         // Steps between a creature deciding on an action and carrying it out, so brains have to anticipate. 0 = right away
+    pub territory_decay : f32,              // Fraction of a territory mark's strength that fades every step
+    pub tribe_vision_radius : usize,        // Max distance (in spaces along either axis) tribe-mates share vision over
+    pub season_length : usize,              // Number of steps in a full seasonal cycle. 0 = no seasons
+    pub mating_season_start : f32,          // Phase [0, 1) of the seasonal cycle that mating season starts at
+    pub mating_season_length : f32,         // Fraction [0, 1] of the seasonal cycle creatures can reproduce in. 1 = all year
+    pub food_die_off : CatastropheParams,   // Rate/size of random food die-offs (see CatastropheParams)
+    pub plague : CatastropheParams,         // Rate/size of random plagues that kill creatures
+    pub wall_collapse : CatastropheParams,  // Rate/size of random wall collapses
+    pub enable_disease : bool,              // Creatures can catch a contagious disease from their neighbors (see the infection params)
+    pub initial_infection_fraction : f32,   // Fraction [0, 1] of the starting creatures that start out sick
+    pub infection_prob : f32,               // Chance [0, 1] each step of catching the disease from each sick neighbor
+    pub infection_duration : usize,         // Number of steps an infection lasts. Creatures that survive it become immune
+    pub infection_energy_drain : usize,     // Energy a sick creature loses every step
+    pub enable_elevation : bool,            // Give the board hills. Climbing costs extra energy and going downhill costs less
+    pub elevation_feature_size : usize,     // Rough width (in spaces) of the biggest hills when generating elevation
+    pub elevation_energy_cost : f32,        // Extra energy it takes to climb from the lowest to the highest point
+    pub high_ground_view_bonus : usize,     // Extra spaces creatures can see from the highest point (scaled down with height). 0 = off
+    pub enable_river : bool,                // Run a river down the board that carries food downstream
+    pub river_flow_strength : f32,          // Chance [0, 1] each step that food on the river drifts one space downstream
+    pub river_source_food : f32,            // Average number of food pieces that wash in at the river's source per step
+    pub enable_swimming : bool,             // Water (the river) blocks creatures that can't swim. Swimming is an inherited trait that can mutate
+    pub initial_swimmer_fraction : f32,     // Fraction [0, 1] of the starting creatures that can swim
+    pub enable_large_bodies : bool,         // Experimental: some creatures have large bodies that take up two spaces (more energy storage and kill energy, higher move cost). Inherited trait that can mutate
+    pub initial_large_fraction : f32,       // Fraction [0, 1] of the starting creatures that have large bodies
+    pub swim_energy_cost : usize,           // Extra energy it takes to move into a water space
+    pub immigration_rate : f32,             // Average number of immigrants entering from the edges of the board per step. 0 = no immigration
+    pub immigrant_source : ImmigrantSource, // Where immigrants' genomes come from
 }
 impl EnvironmentParams {
     /// Return a default version of the parameters
@@ -97,10 +447,200 @@ impl EnvironmentParams {
             energy_per_kill : DEFAULT_ENERGY_PER_KILL,
             max_offspring_per_reproduce : DEFAULT_OFFSPRING_PER_REPRODUCE,
             mutation_prob : DEFAULT_MUTATION_PROB,
+            mutation_mix : DEFAULT_MUTATION_MIX,
+            mutation_schedule : MutationSchedule::Constant,
+            mutation_anneal_rate : DEFAULT_MUTATION_ANNEAL_RATE,
+            offspring_energy_fraction : DEFAULT_OFFSPRING_ENERGY_FRACTION,
+            gestation_steps : DEFAULT_GESTATION_STEPS,
+            gestation_upkeep_energy : DEFAULT_GESTATION_UPKEEP_ENERGY,
             avg_new_food_per_day : NEW_FOOD_PIECES_PER_STEP, 
             creature_repro_energy_cost : DEFAULT_REPRODUCE_ENERGY_COST,
             creature_starting_energy : DEFAULT_ENERGY_LEVEL,
+            enable_position_inputs : false,
+            enable_wall_distance_input : false,
+            enable_move_blocked_input : false,
+            one_hot_categorical_inputs : false,
+            enable_hearing_input : false,
+            enable_territory : false,
+            enable_rest : false,
+            enable_tribe_vision : false,
+            enable_decal_vision : false,
+            enable_beacon : false,
+            energy_per_beacon_reach : DEFAULT_ENERGY_PER_BEACON_REACH,
+            enable_novelty : false,
+            novelty_descriptor : NoveltyDescriptor::ActionDistribution,
+            novelty_weight : DEFAULT_NOVELTY_WEIGHT,
+            enable_speciation : false,
+            speciation_threshold : DEFAULT_SPECIATION_THRESHOLD,
+            speciation_interval : DEFAULT_SPECIATION_INTERVAL,
+            creature_order : CreatureOrder::Insertion,
+            view_distance : DEFAULT_VIEW_DISTANCE,
+            evolve_view_distance : false,
+            view_energy_cost : DEFAULT_VIEW_ENERGY_COST,
+            enable_metabolic_cost : false,
+            metabolic_energy_cost : DEFAULT_METABOLIC_ENERGY_COST,
+            hearing_radius : DEFAULT_HEARING_RADIUS,
+            brain_compute_budget : 0,
+            action_delay_steps : 0,
+            territory_decay : DEFAULT_TERRITORY_DECAY,
+            tribe_vision_radius : DEFAULT_TRIBE_VISION_RADIUS,
+            season_length : 0,
+            mating_season_start : DEFAULT_MATING_SEASON_START,
+            mating_season_length : DEFAULT_MATING_SEASON_LENGTH,
+            food_die_off : DEFAULT_FOOD_DIE_OFF,
+            plague : DEFAULT_PLAGUE,
+            wall_collapse : DEFAULT_WALL_COLLAPSE,
+            enable_disease : false,
+            initial_infection_fraction : DEFAULT_INITIAL_INFECTION_FRACTION,
+            infection_prob : DEFAULT_INFECTION_PROB,
+            infection_duration : DEFAULT_INFECTION_DURATION,
+            infection_energy_drain : DEFAULT_INFECTION_ENERGY_DRAIN,
+            enable_elevation : false,
+            elevation_feature_size : DEFAULT_ELEVATION_FEATURE_SIZE,
+            elevation_energy_cost : DEFAULT_ELEVATION_ENERGY_COST,
+            high_ground_view_bonus : 0,
+            enable_river : false,
+            river_flow_strength : DEFAULT_RIVER_FLOW_STRENGTH,
+            river_source_food : DEFAULT_RIVER_SOURCE_FOOD,
+            enable_swimming : false,
+            initial_swimmer_fraction : DEFAULT_INITIAL_SWIMMER_FRACTION,
+            enable_large_bodies : false,
+            initial_large_fraction : DEFAULT_INITIAL_LARGE_FRACTION,
+            swim_energy_cost : DEFAULT_SWIM_ENERGY_COST,
+            immigration_rate : 0.0,
+            immigrant_source : ImmigrantSource::Random,
+        }
+    }
+
+    /// Get the list of input neuron types that new creatures should be created with given the
+    /// currently enabled optional inputs
+    pub fn get_enabled_creature_inputs(&self) -> Vec<CreatureInputs> {
+        let mut inputs = ENABLED_CREATURE_INPUTS.to_vec();
+        if self.one_hot_categorical_inputs {
+            inputs.retain(|input| *input != CreatureInputs::LastAction && *input != CreatureInputs::Orientation);
+            inputs.extend(ALL_ORIENTATIONS.iter().map(|orientation| CreatureInputs::OrientationIs(*orientation)));
+            inputs.extend(self.get_enabled_creature_actions().into_iter().map(CreatureInputs::LastActionIs));
         }
+        if self.enable_position_inputs {
+            inputs.push(CreatureInputs::PositionX);
+            inputs.push(CreatureInputs::PositionY);
+        }
+        if self.enable_wall_distance_input {
+            inputs.push(CreatureInputs::DistanceToWallAhead);
+        }
+        if self.enable_move_blocked_input {
+            inputs.push(CreatureInputs::LastMoveBlocked);
+        }
+        if self.enable_hearing_input {
+            inputs.push(CreatureInputs::HeardCreatures);
+            inputs.push(CreatureInputs::HeardFood);
+        }
+        if self.enable_beacon {
+            inputs.push(CreatureInputs::BeaconBearing);
+            inputs.push(CreatureInputs::BeaconDistance);
+        }
+        if self.enable_territory {
+            inputs.push(CreatureInputs::OnForeignTerritory);
+        }
+        if self.enable_tribe_vision {
+            inputs.push(CreatureInputs::TribeSeesFood);
+            inputs.push(CreatureInputs::TribeSeesThreat);
+        }
+        if self.season_length > 0 {
+            inputs.push(CreatureInputs::SeasonPhase);
+        }
+        return inputs;
+    }
+
+    /// Get the list of output neuron types (actions) that new creatures should be created with given the
+    /// currently enabled optional actions
+    pub fn get_enabled_creature_actions(&self) -> Vec<CreatureActions> {
+        let mut actions = ENABLED_CREATURE_ACTIONS.to_vec();
+        if self.enable_territory {
+            actions.push(CreatureActions::MarkTerritory);
+        }
+        if self.enable_rest {
+            actions.push(CreatureActions::Rest);
+        }
+        return actions;
+    }
+
+    /// Get the settings used to mutate the brains of offspring
+    pub fn get_mutation_config(&self) -> MutationConfig {
+        return MutationConfig {
+            prob : self.mutation_prob,
+            mix : self.mutation_mix,
+            schedule : self.mutation_schedule,
+            anneal_rate : self.mutation_anneal_rate,
+        };
+    }
+
+    /// Default parameters for an `x_size` by `y_size` board. The starting number of creatures, food and walls
+    /// (and the rate new food appears) are scaled from the defaults so the board is just as crowded
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    ///
+    /// let params = EnvironmentParams::with_size(20, 10);
+    /// assert_eq!((params.env_x_size, params.env_y_size), (20, 10));
+    /// assert!(params.num_start_creatures < EnvironmentParams::new().num_start_creatures);
+    /// ```
+    pub fn with_size(x_size : usize, y_size : usize) -> EnvironmentParams {
+        let mut params = EnvironmentParams::new();
+        let scale = |count : usize| count * x_size * y_size / (params.env_x_size * params.env_y_size);
+        params.num_start_creatures = scale(params.num_start_creatures);
+        params.num_start_food = scale(params.num_start_food);
+        params.num_start_walls = scale(params.num_start_walls);
+        params.avg_new_food_per_day *= (x_size * y_size) as f32 / (params.env_x_size * params.env_y_size) as f32;
+        params.env_x_size = x_size;
+        params.env_y_size = y_size;
+        return params;
+    }
+
+    /// Copy the params that are safe to change while a sim is running from `new_params`. These are the ones the sim
+    /// reads fresh every step (energies, rates, probabilities). Anything that changes the board, the creatures' brains or
+    /// a creature's own copy of the params is left alone. Returns the names of the params that changed
+    pub fn copy_live_params(&mut self, new_params : &EnvironmentParams) -> Vec<&'static str> {
+        let mut changed : Vec<&'static str> = Vec::new();
+        copy_if_changed(&mut self.energy_per_food_piece, new_params.energy_per_food_piece, "energy_per_food_piece", &mut changed);
+        copy_if_changed(&mut self.energy_per_kill, new_params.energy_per_kill, "energy_per_kill", &mut changed);
+        copy_if_changed(&mut self.max_offspring_per_reproduce, new_params.max_offspring_per_reproduce, "max_offspring_per_reproduce", &mut changed);
+        copy_if_changed(&mut self.mutation_prob, new_params.mutation_prob, "mutation_prob", &mut changed);
+        copy_if_changed(&mut self.mutation_anneal_rate, new_params.mutation_anneal_rate, "mutation_anneal_rate", &mut changed);
+        copy_if_changed(&mut self.offspring_energy_fraction, new_params.offspring_energy_fraction, "offspring_energy_fraction", &mut changed);
+        copy_if_changed(&mut self.avg_new_food_per_day, new_params.avg_new_food_per_day, "avg_new_food_per_day", &mut changed);
+        copy_if_changed(&mut self.energy_per_beacon_reach, new_params.energy_per_beacon_reach, "energy_per_beacon_reach", &mut changed);
+        copy_if_changed(&mut self.novelty_weight, new_params.novelty_weight, "novelty_weight", &mut changed);
+        copy_if_changed(&mut self.view_energy_cost, new_params.view_energy_cost, "view_energy_cost", &mut changed);
+        copy_if_changed(&mut self.metabolic_energy_cost, new_params.metabolic_energy_cost, "metabolic_energy_cost", &mut changed);
+        copy_if_changed(&mut self.territory_decay, new_params.territory_decay, "territory_decay", &mut changed);
+        copy_if_changed(&mut self.food_die_off, new_params.food_die_off, "food_die_off", &mut changed);
+        copy_if_changed(&mut self.plague, new_params.plague, "plague", &mut changed);
+        copy_if_changed(&mut self.wall_collapse, new_params.wall_collapse, "wall_collapse", &mut changed);
+        copy_if_changed(&mut self.infection_prob, new_params.infection_prob, "infection_prob", &mut changed);
+        copy_if_changed(&mut self.infection_energy_drain, new_params.infection_energy_drain, "infection_energy_drain", &mut changed);
+        copy_if_changed(&mut self.elevation_energy_cost, new_params.elevation_energy_cost, "elevation_energy_cost", &mut changed);
+        copy_if_changed(&mut self.river_flow_strength, new_params.river_flow_strength, "river_flow_strength", &mut changed);
+        copy_if_changed(&mut self.river_source_food, new_params.river_source_food, "river_source_food", &mut changed);
+        copy_if_changed(&mut self.swim_energy_cost, new_params.swim_energy_cost, "swim_energy_cost", &mut changed);
+        copy_if_changed(&mut self.enable_decal_vision, new_params.enable_decal_vision, "enable_decal_vision", &mut changed);
+        copy_if_changed(&mut self.immigration_rate, new_params.immigration_rate, "immigration_rate", &mut changed);
+        copy_if_changed(&mut self.immigrant_source, new_params.immigrant_source, "immigrant_source", &mut changed);
+        return changed;
+    }
+}
+
+/// Set `value` to `new_value`, adding `name` to `changed` if that changed it
+fn copy_if_changed<T : PartialEq + Copy>(value : &mut T, new_value : T, name : &'static str, changed : &mut Vec<&'static str>) {
+    if *value != new_value {
+        *value = new_value;
+        changed.push(name);
+    }
+}
+
+impl Default for EnvironmentParams {
+    fn default() -> EnvironmentParams {
+        return EnvironmentParams::new();
     }
 }
 
@@ -112,6 +652,7 @@ pub struct EnvironmentV1 {
 
     // Current state
     pub creatures : Vec<CreatureV1>,    // Vector containing all creature instances
+    #[serde(deserialize_with = "deserialize_positions")]
     pub positions : Vec<Vec<SpaceStates>>, // Contains the states of each space.
     pub time_step : usize,              // Represents the current time step in the sim
     pub num_food : usize,               // Number of current food pieces on the board
@@ -122,15 +663,186 @@ pub struct EnvironmentV1 {
 
     pub num_kills : usize,              // Number of creatures killed
     pub num_natural_deaths: usize,      // Number of creatures that've died of "old age"
+
+    #[serde(default)]
+    pub beacon : Option<CreaturePosition>, // Position of the beacon (if enabled). Creatures can walk over it, it doesn't block anything
+    #[serde(default)]
+    pub num_beacon_reaches : usize,     // Number of times any creature has reached the beacon
+    #[serde(default)]
+    pub num_infections : usize,         // Number of times any creature has caught the disease
+    #[serde(default)]
+    pub num_recoveries : usize,         // Number of infections that were survived
+    #[serde(default)]
+    pub river : Vec<CreaturePosition>,  // Spaces the river runs through, from the source to the mouth (empty unless the river is enabled)
+    #[serde(default)]
+    pub elevation : Vec<Vec<f32>>,      // Height of each space [0, 1], indexed like `positions` (empty unless elevation is enabled)
+    #[serde(default)]
+    pub territory : Vec<Vec<TerritoryMark>>, // Territory layer, indexed like `positions` (empty unless territory is enabled)
+    #[serde(default)]
+    pub metadata : RunMetadata,         // Seed, notes, save IDs, ... of the run
+    #[serde(default)]
+    pub decals : Vec<Vec<Option<Decal>>>, // Decal layer, indexed like `positions` (empty until the first decal is left)
+
+    #[serde(skip)]
+    pub dead_creatures : VecDeque<CreatureV1>, // The last `DEAD_CREATURE_HISTORY_LEN` creatures removed from the board (oldest first)
+    #[serde(skip)]
+    pub novelty_archive : VecDeque<Vec<f32>>, // Behaviors of past novel creatures that novelty is also measured against
+
+    #[serde(default)]
+    pub num_species : usize,            // Number of species at the last clustering (if speciation is enabled)
+    #[serde(default)]
+    pub next_species_id : usize,        // ID that will be given to the next new species
+    #[serde(default)]
+    pub species_count_history : VecDeque<usize>, // Number of species at each of the last `SPECIES_HISTORY_LEN` clusterings (oldest first)
+    #[serde(default)]
+    pub swimmer_count_history : VecDeque<usize>, // Number of living swimmers at each of the last `SWIMMER_HISTORY_LEN` steps (oldest first)
+    #[serde(skip)]
+    pub species_representatives : Vec<SpeciesRepresentative>, // Genome each species is compared against
+
+    #[serde(skip)]
+    pub stats : StatsRecorder,          // Statistics recorded while the sim runs (e.g. lifetime summaries of dead creatures)
+    #[serde(skip)]
+    pub events : EventQueue,            // Births, deaths, etc... that happened since a frontend last took them
+    #[serde(skip)]
+    pub rng : Option<StdRng>,           // Generator for seeded runs (None = unseeded, uses the thread RNG)
+}
+
+/// 64-bit FNV-1a hasher. Used instead of the std `DefaultHasher` because its output is guaranteed
+/// to be the same between runs, platforms and Rust versions
+#[allow(dead_code)]
+pub struct Fnv1aHasher {
+    state : u64,
+}
+
+#[allow(dead_code)]
+impl Fnv1aHasher {
+    /// Create a new hasher with nothing hashed yet
+    pub fn new() -> Fnv1aHasher {
+        return Fnv1aHasher {state : FNV_OFFSET_BASIS};
+    }
+
+    /// Add raw bytes to the hash
+    pub fn write_bytes(&mut self, bytes : &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    /// Values are always hashed as 64-bit little endian so the hash doesn't depend on the platform
+    pub fn write_usize(&mut self, val : usize) {
+        self.write_bytes(&(val as u64).to_le_bytes());
+    }
+
+    /// Add a float to the hash (by its exact bit pattern)
+    pub fn write_f32(&mut self, val : f32) {
+        self.write_bytes(&val.to_bits().to_le_bytes());
+    }
+
+    /// Get the hash of everything written so far
+    pub fn finish(&self) -> u64 {
+        return self.state;
+    }
+}
+impl Default for Fnv1aHasher {
+    fn default() -> Fnv1aHasher {
+        return Fnv1aHasher::new();
+    }
+}
+
+impl RunMetadata {
+
+    /// Describe the run in a line (times are in seconds since the Unix epoch)
+    pub fn describe(&self) -> String {
+        let mut parts : Vec<String> = vec![match self.seed {
+            Some(seed) => format!("seed {}", seed),
+            None => String::from("unseeded"),
+        }];
+        if self.created_at > 0 {
+            parts.push(format!("created {}", self.created_at));
+        }
+        if let Some(save_id) = &self.save_id {
+            parts.push(format!("save {} written {} by version {} after {} steps", save_id, self.saved_at, self.crate_version, self.total_steps));
+        }
+        if let Some(parent_id) = &self.parent_save_id {
+            parts.push(format!("continued from save {}", parent_id));
+        }
+        if !self.notes.is_empty() {
+            parts.push(format!("notes: {}", self.notes));
+        }
+        return parts.join(", ");
+    }
+}
+
+impl LoadRepairReport {
+    /// Get the total number of fixes
+    pub fn num_fixes(&self) -> usize {
+        return self.num_relocated + self.num_dropped + self.num_ids_reissued + self.num_board_fixes + self.num_brains_sanitized;
+    }
+
+    /// Describe the fixes in a sentence (e.g. for showing the user after a load)
+    pub fn describe(&self) -> String {
+        let fixes = [
+            (self.num_relocated, "creatures moved to a blank space"),
+            (self.num_dropped, "creatures dropped for lack of space"),
+            (self.num_ids_reissued, "duplicate creature IDs re-issued"),
+            (self.num_board_fixes, "board spaces fixed to match the creatures/size"),
+            (self.num_brains_sanitized, "brains with invalid weights zeroed"),
+        ];
+        let parts : Vec<String> = fixes.iter().filter(|(count, _)| *count > 0).map(|(count, what)| format!("{} {}", count, what)).collect();
+        if parts.is_empty() {
+            return String::from("Nothing needed fixing");
+        }
+        return parts.join(", ");
+    }
+}
+
+impl ResizeAnchor {
+    /// Where the anchor is across and down the board, in halves (0 = left/top, 1 = middle, 2 = right/bottom)
+    pub fn get_halves(&self) -> (isize, isize) {
+        return match self {
+            ResizeAnchor::TopLeft => (0, 0),
+            ResizeAnchor::Top => (1, 0),
+            ResizeAnchor::TopRight => (2, 0),
+            ResizeAnchor::Left => (0, 1),
+            ResizeAnchor::Center => (1, 1),
+            ResizeAnchor::Right => (2, 1),
+            ResizeAnchor::BottomLeft => (0, 2),
+            ResizeAnchor::Bottom => (1, 2),
+            ResizeAnchor::BottomRight => (2, 2),
+        };
+    }
 }
 
 
 /// Implementation of EnvironmentV1
 impl EnvironmentV1 {
 
+    /// Constructor for a randomly populated environment whose whole run is reproducible from `seed`.
+    /// Two environments made with the same params and seed stay identical step for step
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    ///
+    /// let params = EnvironmentParams::with_size(16, 16);
+    /// let mut env_a = EnvironmentV1::new_seeded(&params, 7);
+    /// let mut env_b = EnvironmentV1::new_seeded(&params, 7);
+    /// let _ = env_a.run_n_steps(20);
+    /// let _ = env_b.run_n_steps(20);
+    /// assert_eq!(env_a.state_hash(), env_b.state_hash());
+    /// ```
+    #[allow(dead_code)]
+    pub fn new_seeded(in_params : &EnvironmentParams, seed : u64) -> EnvironmentV1 {
+        let mut rng = Some(StdRng::seed_from_u64(seed));
+        let mut env = run_seeded(&mut rng, || EnvironmentV1::new_rand(in_params));
+        env.rng = rng;
+        env.metadata.seed = Some(seed);
+        return env;
+    }
+
     /// Constructor for new environment instance that's randomly populated
     pub fn new_rand(in_params : &EnvironmentParams) -> EnvironmentV1 {
-        let mut rng = rand::thread_rng();
+        let mut rng = sim_rng();
 
         // Initialize all positions to be blank at first
         let temp_positions = vec![vec![SpaceStates::BlankSpace; in_params.env_y_size]; in_params.env_x_size];
@@ -152,21 +864,48 @@ impl EnvironmentV1 {
             num_total_creatures : in_params.num_start_creatures,
             num_kills : 0,
             num_natural_deaths : 0,
+            beacon : None,
+            num_beacon_reaches : 0,
+            num_infections : 0,
+            num_recoveries : 0,
+            river : Vec::new(),
+            elevation : Vec::new(),
+            territory : Vec::new(),
+            metadata : RunMetadata {created_at : get_unix_time(), ..Default::default()},
+            decals : Vec::new(),
+            dead_creatures : VecDeque::new(),
+            novelty_archive : VecDeque::new(),
+            num_species : 0,
+            next_species_id : 0,
+            species_count_history : VecDeque::new(),
+            swimmer_count_history : VecDeque::new(),
+            species_representatives : Vec::new(),
+            stats : StatsRecorder::new(),
+            events : EventQueue::new(),
+            rng : None,
         };
 
         // Fill in random spaces with food
         for _food_num in 0..in_params.num_start_food {
-            let pos = temp_env.get_rand_blank_space();
-            temp_env.add_food_space(pos);
+            match temp_env.get_rand_blank_space() {
+                Some(pos) => temp_env.add_food_space(pos),
+                None => break,
+            }
         }
 
         // Fill in random spaces with creatures
+        let creature_inputs = in_params.get_enabled_creature_inputs();
+        let creature_actions = in_params.get_enabled_creature_actions();
         for creature_num in 0..in_params.num_start_creatures {
             // Create creature
-            let mut creature = CreatureV1::new(creature_num, &CreatureParams::new());
+            let mut creature = CreatureV1::new_with_io(creature_num, &CreatureParams::new(), &creature_inputs, &creature_actions);
+            creature.view_distance = in_params.view_distance.clamp(1, MAX_VIEW_DISTANCE);
 
-            // Set few parameters of the new creature
-            let pos = temp_env.get_rand_blank_space();
+            // Set few parameters of the new creature (stop adding creatures once the board is full)
+            let pos = match temp_env.get_rand_blank_space() {
+                Some(pos) => pos,
+                None => break,
+            };
             creature.set_position(pos.x, pos.y);
 
             // Set random initial orientation
@@ -184,52 +923,152 @@ impl EnvironmentV1 {
             temp_env.add_creature(creature);
         }
 
+        // Raise the hills before anything is placed so the generated terrain doesn't depend on the rest of the board
+        if in_params.enable_elevation {
+            temp_env.elevation = generate_elevation(in_params.env_x_size, in_params.env_y_size, in_params.elevation_feature_size);
+        }
+
+        // Some of the starting creatures bring the disease with them
+        if in_params.enable_disease {
+            for creature in temp_env.creatures.iter_mut() {
+                if rng.gen::<f32>() < in_params.initial_infection_fraction && creature.infect(in_params.infection_duration) {
+                    temp_env.num_infections += 1;
+                }
+            }
+        }
+
+        // Some of the starting creatures can swim
+        if in_params.enable_swimming {
+            for creature in temp_env.creatures.iter_mut() {
+                creature.can_swim = rng.gen::<f32>() < in_params.initial_swimmer_fraction;
+            }
+        }
+
+        // Some of the starting creatures have large bodies, with their tails right behind them where there's room
+        if in_params.enable_large_bodies {
+            for creature_idx in 0..temp_env.creatures.len() {
+                temp_env.creatures[creature_idx].is_large = rng.gen::<f32>() < in_params.initial_large_fraction;
+                temp_env.update_tail(creature_idx);
+            }
+        }
+
         // Fill random wall spaces
         for _wall_num in 0..in_params.num_start_walls {
-            let pos = temp_env.get_rand_blank_space();
-            temp_env.add_wall_space(pos);
+            match temp_env.get_rand_blank_space() {
+                Some(pos) => temp_env.add_wall_space(pos),
+                None => break,
+            }
+        }
+
+        // Carve the river through whatever walls landed on it
+        if in_params.enable_river {
+            temp_env.river = generate_river(in_params.env_x_size, in_params.env_y_size);
+            temp_env.clear_river_walls();
+        }
+
+        // Place the beacon last so it's guaranteed to be on a reachable (blank) space
+        if in_params.enable_beacon {
+            temp_env.beacon = temp_env.get_rand_blank_space();
         }
 
         return temp_env;
 
     }
 
-    /// Convert this environment to JSON representation for saving/loading
+    #[cfg(feature = "json")]
+    /// Convert this environment to JSON representation for saving/loading. The environment is wrapped
+    /// in an `EnvSaveFile` so it can be verified when it's loaded again
+    #[allow(dead_code)]
     pub fn to_json(&self) -> String {
-        let json_string = serde_json::to_string_pretty(&self).unwrap();
-        return json_string;
+        return self.to_json_with_options(&SaveOptions::default());
     }
 
-    /// Load environment parameters and spaces from json file
-    pub fn load_from_json(&mut self, json_file : &str, load_ops : &JsonEnvLoadParams) {
-        let res = File::open(&json_file);
-        let mut file : File;
-        match res {
-            Err(e) => {
-                println!("Error could not open file {}. Error = {e}", &json_file);
-                return;
-            },
-            Ok(f) => file = f,
+    #[cfg(feature = "json")]
+    /// Same as `to_json`, but `options` can strip data out of the save to make it smaller
+    pub fn to_json_with_options(&self, options : &SaveOptions) -> String {
+        let mut environment = self.clone();
+        if options.exclude_creatures {
+            environment.remove_all_creatures();
+            environment.creatures.clear();
+        }
+        for creature in environment.creatures.iter_mut() {
+            if options.exclude_activations {
+                creature.brain.strip_activations();
+            }
+            if options.round_weights {
+                creature.brain.round_genome(SAVE_WEIGHT_DECIMALS);
+            }
         }
 
-        // Read all contents into temporary string
-        let mut json_contents : String = String::new();
-        let _ = file.read_to_string(&mut json_contents);
+        // The save gets a new ID, and whichever save this run was loaded from (if any) becomes its parent
+        environment.metadata.crate_version = env!("CARGO_PKG_VERSION").to_string();
+        environment.metadata.total_steps = self.time_step;
+        environment.metadata.saved_at = get_unix_time();
+        environment.metadata.parent_save_id = self.metadata.save_id.clone();
+        let mut hasher = Fnv1aHasher::new();
+        hasher.write_bytes(environment.get_checksum().as_bytes());
+        hasher.write_usize(environment.metadata.saved_at as usize);
+        environment.metadata.save_id = Some(format!("{:016x}", hasher.finish()));
+
+        let save_file = EnvSaveFile {
+            crate_version : env!("CARGO_PKG_VERSION").to_string(),
+            checksum : environment.get_checksum(),
+            environment : environment,
+        };
+        let json_string = serde_json::to_string_pretty(&save_file).unwrap();
+        return json_string;
+    }
 
-        // Create a temporary instantiation of the environment, so we can pull various things from it
-        let temp_env_res : Result<EnvironmentV1, serde_json::Error>  = serde_json::from_str(&json_contents); 
-        let temp_env : EnvironmentV1;
-        match temp_env_res {
-            Err(e) => {
-                println!("Error: Could not create `Environment` from JSON. Might mean JSON is incompatible with current version, or is corrupted");
-                println!("Full Error Msg: {e}");
-                return;
+    #[cfg(feature = "json")]
+    /// Same as `to_json`, but only the creatures with the given IDs are kept on the board (e.g. a group selected in the GUI).
+    /// Importing the save (see `import_creatures_from_file`) brings back all of their genomes at once
+    pub fn creatures_to_json(&self, creature_ids : &[usize]) -> String {
+        let mut environment = self.clone();
+        environment.remove_all_creatures();
+        environment.creatures.retain(|c| creature_ids.contains(&c.id));
+        for creature in environment.creatures.iter() {
+            for pos in std::iter::once(creature.position).chain(creature.tail) {
+                environment.positions[pos.x][pos.y] = SpaceStates::CreatureSpace(creature.id);
             }
-            Ok(val) => temp_env = val,
         }
+        environment.recount_spaces();
+        return environment.to_json_with_options(&SaveOptions {exclude_activations : true, ..SaveOptions::default()});
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Save the creatures with the given IDs to a file (see `creatures_to_json`)
+    pub fn save_creatures(&self, creature_ids : &[usize], filename : &str) {
+        if let Err(e) = std::fs::write(filename, self.creatures_to_json(creature_ids)) {
+            println!("Error: could not save creatures to {}. Error = {e}", filename);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    /// Checksum of everything in the environment that gets saved (hex string of the FNV-1a hash of its compact JSON)
+    pub fn get_checksum(&self) -> String {
+        return get_json_checksum(&serde_json::to_string(&self).unwrap());
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Load environment parameters and spaces from json file. Each layer (params, creatures, walls, food)
+    /// is taken from `json_file` unless `load_ops` specifies a different file for that layer. Creatures
+    /// that end up on a wall or another creature after the load are moved to the nearest blank space, along with the
+    /// rest of the fixes in `repair_after_load`, and what was fixed is returned.
+    /// If any of the files can't be read or fail verification, nothing is changed and the reason is returned
+    pub fn load_from_json(&mut self, json_file : &str, load_ops : &JsonEnvLoadParams) -> Result<LoadRepairReport, String> {
+        let load_parameters = load_ops.load_all || load_ops.load_parameters;
+        let load_creatures = load_ops.load_all || load_ops.load_creatures;
+        let load_walls = load_ops.load_all || load_ops.load_walls;
+        let load_food = load_ops.load_all || load_ops.load_food;
+
+        // Read every file we need up front so a bad file doesn't leave us with a half-loaded environment
+        let params_env = if load_parameters {Some(Self::read_layer_source(json_file, &load_ops.parameters_file)?)} else {None};
+        let creatures_env = if load_creatures {Some(Self::read_layer_source(json_file, &load_ops.creatures_file)?)} else {None};
+        let walls_env = if load_walls {Some(Self::read_layer_source(json_file, &load_ops.walls_file)?)} else {None};
+        let food_env = if load_food {Some(Self::read_layer_source(json_file, &load_ops.food_file)?)} else {None};
 
         // load different components of the environment based on what options are specified
-        if load_ops.load_parameters {
+        if let Some(temp_env) = params_env {
 
             // If env size parameters changed, we have to re-size the board. Create a new blank board
             if temp_env.params.env_x_size != self.params.env_x_size || temp_env.params.env_y_size != self.params.env_y_size {
@@ -239,112 +1078,853 @@ impl EnvironmentV1 {
                 self.positions = vec![vec![SpaceStates::BlankSpace; temp_env.params.env_y_size]; temp_env.params.env_x_size];
             }
 
-            // Re-write the parameters
+            // Re-write the parameters. The run's metadata comes along with them
             self.params = temp_env.params.clone();
+            self.metadata = temp_env.metadata.clone();
 
         }
-        if load_ops.load_creatures {
-            self.remove_all_creatures();
-            self.creatures = temp_env.creatures.clone();
-            self.update_creature_positions();
+
+        // Take all creatures off the board while the map layers are swapped out. They're placed back
+        // at the end so that any conflicts with the new layers can be resolved
+        self.remove_all_creatures();
+        if let Some(temp_env) = creatures_env {
+            self.creatures = temp_env.creatures;
         }
-        if load_ops.load_walls {
+        if let Some(temp_env) = walls_env {
             self.remove_all_walls();
             self.add_walls_from_positions(&temp_env.positions);
+
+            // Elevation is part of the map, so it comes along with the walls
+            self.elevation = temp_env.elevation;
+            if !self.has_elevation() {
+                self.elevation = Vec::new();
+            }
         }
-        if load_ops.load_food {
+        if let Some(temp_env) = food_env {
             self.remove_all_food();
             self.add_food_from_positions(&temp_env.positions);
         }
+        return Ok(self.repair_after_load());
+    }
+
+    /// Make a freshly loaded environment consistent, since saves can be hand-edited or come from older versions.
+    /// Duplicate creature IDs are re-issued, brain weights that aren't numbers are zeroed, the board is resized to
+    /// match the parameters, creature spaces that don't match the creature list are cleared and creatures that are off
+    /// the board, in a wall or on another creature are moved to the nearest blank space. Returns what was fixed
+    pub fn repair_after_load(&mut self) -> LoadRepairReport {
+        let mut report = LoadRepairReport::default();
+        let (x_size, y_size) = (self.params.env_x_size, self.params.env_y_size);
+
+        // IDs have to be unique before anything else, since the board refers to creatures by ID
+        let max_id = self.creatures.iter().map(|c| c.id + 1).max().unwrap_or(0);
+        self.num_total_creatures = self.num_total_creatures.max(max_id);
+        let mut seen_ids : HashSet<usize> = HashSet::new();
+        for creature in self.creatures.iter_mut() {
+            if !seen_ids.insert(creature.id) {
+                creature.id = self.num_total_creatures;
+                self.num_total_creatures += 1;
+                report.num_ids_reissued += 1;
+            }
+            if creature.brain.sanitize_genome() > 0 {
+                report.num_brains_sanitized += 1;
+            }
+        }
 
+        // Board has to match the size in the parameters
+        if self.positions.len() != x_size || self.positions.iter().any(|column| column.len() != y_size) {
+            self.positions.resize(x_size, vec![SpaceStates::BlankSpace; y_size]);
+            for column in self.positions.iter_mut() {
+                column.resize(y_size, SpaceStates::BlankSpace);
+            }
+            report.num_board_fixes += 1;
+        }
+        if !self.decals.is_empty() && (self.decals.len() != x_size || self.decals.iter().any(|column| column.len() != y_size)) {
+            self.decals = Vec::new();
+            report.num_board_fixes += 1;
+        }
+        let num_river_spaces = self.river.len();
+        self.river.retain(|pos| pos.x < x_size && pos.y < y_size);
+        report.num_board_fixes += num_river_spaces - self.river.len();
+
+        // Creature spaces on the board that don't match a creature (or a large creature's tail) standing there
+        let creature_spaces : HashMap<usize, CreaturePosition> = self.creatures.iter().map(|c| (c.id, c.position)).collect();
+        let tail_spaces : HashMap<usize, CreaturePosition> = self.creatures.iter().filter_map(|c| c.tail.map(|tail| (c.id, tail))).collect();
+        for (x, column) in self.positions.iter().enumerate() {
+            for (y, space) in column.iter().enumerate() {
+                if let SpaceStates::CreatureSpace(id) = space {
+                    let pos = CreaturePosition {x : x, y : y};
+                    if creature_spaces.get(id) != Some(&pos) && tail_spaces.get(id) != Some(&pos) {
+                        report.num_board_fixes += 1;
+                    }
+                }
+            }
+        }
 
+        // Put every creature back on the board, moving the ones that can't stay where they are
+        let prev_positions = creature_spaces;
+        self.remove_all_creatures();
+        self.update_creature_positions();
+        report.num_dropped = prev_positions.len().saturating_sub(self.creatures.len());
+        report.num_relocated = self.creatures.iter().filter(|c| prev_positions.get(&c.id) != Some(&c.position)).count();
+        self.recount_spaces();
+        return report;
     }
 
-    /// Main interface to run a certain number of simulation steps
-    pub fn run_n_steps(&mut self, num_steps : usize) -> Result<(), EnvErrors> {
-        for n in 0..num_steps {
+    #[cfg(feature = "file_io")]
+    /// Read the environment that a single layer should be loaded from. `layer_file` overrides the main
+    /// `json_file` if it's specified and not empty
+    fn read_layer_source(json_file : &str, layer_file : &Option<String>) -> Result<EnvironmentV1, String> {
+        match layer_file {
+            Some(f) if !f.trim().is_empty() => Self::read_json_file_unrepaired(f.trim()),
+            _ => Self::read_json_file_unrepaired(json_file),
+        }
+    }
 
-            // Run the step
-            self.advance_step();
+    #[cfg(feature = "json")]
+    /// Create an environment from JSON made by `to_json`. The checksum is verified, and older saves without one are
+    /// still loaded. Returns the reason (also printed) if the JSON is corrupted
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    ///
+    /// let env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 3);
+    /// let loaded = EnvironmentV1::from_json(&env.to_json()).unwrap();
+    /// assert_eq!(loaded.state_hash(), env.state_hash());
+    ///
+    /// // Corrupted saves are rejected
+    /// assert!(EnvironmentV1::from_json("{\"checksum\": \"0\"}").is_err());
+    /// ```
+    pub fn from_json(json_contents : &str) -> Result<EnvironmentV1, String> {
+        let mut env = Self::parse_json(json_contents, "JSON")?;
+        env.repair_with_warning("JSON");
+        return Ok(env);
+    }
 
-            // Check whether there's any creatures left
-            if self.num_creatures == 0 {
-                if DEBUG_LEVEL > 0 {
-                    println!("Stopping simulation after {} steps because there are no creatures left", n);
-                }
-                return Err(EnvErrors::EarlyExitErr);
-            }
+    #[cfg(feature = "file_io")]
+    /// Read a full environment from a JSON file. If the file has a checksum, it's verified. Older saves without
+    /// one are still loaded. Anything inconsistent is fixed (see `repair_after_load`, the fixes are printed).
+    /// Returns the reason (also printed) if the file can't be read or is corrupted
+    pub fn read_json_file(json_file : &str) -> Result<EnvironmentV1, String> {
+        let mut env = Self::read_json_file_unrepaired(json_file)?;
+        env.repair_with_warning(&format!("File {}", json_file));
+        return Ok(env);
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Read a full environment from a JSON file as it was saved (see `read_json_file`)
+    fn read_json_file_unrepaired(json_file : &str) -> Result<EnvironmentV1, String> {
+        let res = File::open(&json_file);
+        let mut file : File;
+        match res {
+            Err(e) => {
+                let msg = format!("Could not open file {}. Error = {e}", &json_file);
+                println!("Error: {}", msg);
+                return Err(msg);
+            },
+            Ok(f) => file = f,
         }
-        return Ok(());
+
+        // Read all contents into temporary string
+        let mut json_contents : String = String::new();
+        if let Err(e) = file.read_to_string(&mut json_contents) {
+            let msg = format!("Could not read file {}. Error = {e}", &json_file);
+            println!("Error: {}", msg);
+            return Err(msg);
+        }
+        return Self::parse_json(&json_contents, &format!("File {}", json_file));
     }
 
+    #[cfg(feature = "json")]
+    /// Run `repair_after_load` and print a warning about anything that was fixed. `source` is where the env came from
+    fn repair_with_warning(&mut self, source : &str) {
+        let report = self.repair_after_load();
+        if report.num_fixes() > 0 {
+            println!("Warning: {} had problems that were fixed: {}", source, report.describe());
+        }
+    }
 
-    /// Print the current state of the environment board
-    pub fn show(&self) {
-        println!();
-        let num_dashes = self.params.env_x_size * 3 + 1;
-        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
-        for y in 0..self.params.env_y_size {
-            print!("|");
-            for x in 0..self.params.env_x_size {
-                match self.positions[x][y] {
-                    SpaceStates::BlankSpace => print!("   "),
-                    SpaceStates::CreatureSpace(id) => print!("{:3}", id % 1000), // just wrap around if the creature id goes beyond 3 digits 
-                    SpaceStates::FoodSpace => print!(" # "),
-                    SpaceStates::WallSpace => print!("|-|"),
-                    SpaceStates::FightSpace(_ttl) => print!(" x "),
+    #[cfg(feature = "json")]
+    /// Parse a full environment out of saved JSON. `source` describes where the JSON came from for error messages
+    fn parse_json(json_contents : &str, source : &str) -> Result<EnvironmentV1, String> {
+        // A file that isn't even valid JSON was most likely cut off while it was being written
+        let json_value : serde_json::Value = match serde_json::from_str(json_contents) {
+            Err(e) => {
+                let msg = format!("{} is truncated or corrupted ({e})", source);
+                println!("Error: {}", msg);
+                return Err(msg);
+            },
+            Ok(val) => val,
+        };
+
+        // Create a temporary instantiation of the environment, so we can pull various things from it
+        let is_save_file = json_value.get("checksum").is_some();
+        let temp_env_res : Result<EnvironmentV1, serde_json::Error> = if is_save_file {
+            serde_json::from_str::<RawEnvSaveFile>(json_contents).and_then(|save_file| {
+                if save_file.crate_version != env!("CARGO_PKG_VERSION") {
+                    println!("Warning: {} was saved by version {} (this is version {})", source, save_file.crate_version, env!("CARGO_PKG_VERSION"));
+                }
+                if get_json_checksum(save_file.environment.get()) != save_file.checksum {
+                    return Err(serde::de::Error::custom("checksum does not match the contents"));
                 }
+                return serde_json::from_str::<EnvironmentV1>(save_file.environment.get());
+            })
+        } else {
+            println!("Warning: {} has no checksum (saved by an older version), it can't be verified", source);
+            serde_json::from_value(json_value)
+        };
+        match temp_env_res {
+            Err(e) => {
+                let msg = format!("Could not create `Environment` from {}. It might be incompatible with this version, or corrupted ({e})", source);
+                println!("Error: {}", msg);
+                return Err(msg);
             }
-            print!("|");
-            println!();
+            Ok(mut val) => {
+                // Compact saves don't include brain activations
+                for creature in val.creatures.iter_mut() {
+                    creature.brain.restore_activations();
+                }
+                return Ok(val);
+            },
         }
-        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
-        println!("Key:");
-        println!("Creature = <id num>\nFood = #\nWall = |-|");
     }
 
-    /// Print all creature info in columns to stdout
+    /// Replace all walls in the environment with a wall layout drawn in an image file. The image is
+    /// scaled to the board size and thresholded, so that any dark, opaque pixel becomes a wall. This lets
+    /// maps be drawn in any paint program. Creatures that end up inside a wall are moved to the nearest blank space
+    #[cfg(feature = "image")]
     #[allow(dead_code)]
-    pub fn show_all_creature_info(&self) {
-        println!("{:12} {:12} {:12} {:15} ", "Creature Id", "Age", "Energy", "Last Action");
-        for creature_idx in 0..self.creatures.len() {
-            let creature = &self.creatures[creature_idx];
-            println!("{:<12} {:<12} {:<12} {:<15?} ", creature.id, creature.age, creature.energy, creature.last_action);
+    pub fn load_walls_from_image(&mut self, image_file : &str) {
+        let img = match image::open(image_file) {
+            Err(e) => {
+                println!("Error: could not load image {}. Error = {e}", image_file);
+                return;
+            },
+            Ok(img) => img,
+        };
+        if let Err(msg) = self.apply_wall_image(&img) {
+            println!("Error: {}", msg);
         }
     }
 
+    /// Replace all walls with the ones drawn in an image that's already been loaded (see `load_walls_from_image`)
+    #[cfg(feature = "image")]
+    fn apply_wall_image(&mut self, img : &image::DynamicImage) -> Result<(), String> {
+        // Scale the image to one pixel per space on the board
+        let img = img.to_luma_alpha8();
+        let scaled = image::imageops::resize(&img, self.params.env_x_size as u32, self.params.env_y_size as u32, image::imageops::FilterType::Nearest);
+
+        let wall_map = WallMap::new(self.params.env_x_size, self.params.env_y_size, |x, y| {
+            let pixel = scaled.get_pixel(x as u32, y as u32);
+            let (luma, alpha) = (pixel[0], pixel[1]);
+            return luma < IMAGE_WALL_LUMA_THRESHOLD && alpha >= 128;
+        });
+        return self.apply_wall_map(&wall_map);
+    }
 
-    /// Audit the counters of each space type vs. the position matrix to make sure everything is in sync
-    fn update_space_counters(&mut self) {
-        let mut temp_food : usize = 0; 
-        let mut temp_walls : usize = 0; 
-        let mut temp_creatures : usize = 0; 
-        let mut temp_blank : usize = 0; 
-        for x in 0..self.params.env_x_size {
-            for y in 0..self.params.env_y_size {
-                match self.positions[x][y] {
-                    SpaceStates::BlankSpace => temp_blank += 1,
-                    SpaceStates::FoodSpace => temp_food += 1,
-                    SpaceStates::CreatureSpace(_id) => temp_creatures += 1,
-                    SpaceStates::WallSpace => temp_walls += 1,
+    /// Create a new randomly populated environment whose walls come from `wall_map` instead of being random.
+    /// The board size is taken from the map
+    pub fn new_from_wall_map(in_params : &EnvironmentParams, wall_map : &WallMap) -> EnvironmentV1 {
+        let mut params = *in_params;
+        params.env_x_size = wall_map.x_size;
+        params.env_y_size = wall_map.y_size;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_rand(&params);
+        env.apply_wall_map(wall_map).unwrap();
+        return env;
+    }
 
-                    // Fight space counts as a blank space, but use this opportunity to
-                    // evaluate whether the time-to-live (ttl) of the fight space is up
-                    // and it should disappear (fight spaces should only be temporary)
-                    SpaceStates::FightSpace(ttl) => {
-                        temp_blank += 1;
-                        if ttl > 0 {
-                            self.positions[x][y] = SpaceStates::FightSpace(ttl - 1);
-                        } else {
-                            self.positions[x][y] = SpaceStates::BlankSpace;
-                        }
+    /// Grow or crop the board to `new_x_size` x `new_y_size`, keeping the side (or corner, or middle) of the board given
+    /// by `anchor` in place. Walls, food, terrain and territory keep their spot relative to the anchor and anything
+    /// that falls off the edge is dropped. Creatures that fall off the edge (or land on something) are moved to the
+    /// nearest blank space instead
+    pub fn resize(&mut self, new_x_size : usize, new_y_size : usize, anchor : ResizeAnchor) {
+        if new_x_size == 0 || new_y_size == 0 {
+            println!("Error: can't resize the environment to {}x{}", new_x_size, new_y_size);
+            return;
+        }
+
+        // How far everything moves. The anchored edge doesn't move, the opposite edge takes all of the change
+        let (old_x_size, old_y_size) = (self.params.env_x_size, self.params.env_y_size);
+        let (x_halves, y_halves) = anchor.get_halves();
+        let dx = (new_x_size as isize - old_x_size as isize) * x_halves / 2;
+        let dy = (new_y_size as isize - old_y_size as isize) * y_halves / 2;
+        let shift = |pos : CreaturePosition| -> Option<CreaturePosition> {
+            let (x, y) = (pos.x as isize + dx, pos.y as isize + dy);
+            if x < 0 || y < 0 || x >= new_x_size as isize || y >= new_y_size as isize {
+                return None;
+            }
+            return Some(CreaturePosition {x : x as usize, y : y as usize});
+        };
+
+        self.remove_all_creatures();
+        let mut positions = vec![vec![SpaceStates::BlankSpace; new_y_size]; new_x_size];
+        let mut territory : Vec<Vec<TerritoryMark>> = Vec::new();
+        if !self.territory.is_empty() {
+            territory = vec![vec![TerritoryMark::default(); new_y_size]; new_x_size];
+        }
+        for x in 0..old_x_size {
+            for y in 0..old_y_size {
+                if let Some(pos) = shift(CreaturePosition {x : x, y : y}) {
+                    positions[pos.x][pos.y] = self.positions[x][y];
+                    if let Some(mark) = self.territory.get(x).and_then(|column| column.get(y)) {
+                        territory[pos.x][pos.y] = *mark;
                     }
                 }
             }
         }
 
-        self.num_blank = temp_blank;
-        self.num_creatures = temp_creatures;
+        // New ground is as high as the nearest edge of the old board
+        if self.has_elevation() {
+            let old_elevation = std::mem::take(&mut self.elevation);
+            self.elevation = (0..new_x_size).map(|x| (0..new_y_size).map(|y| {
+                let old_x = (x as isize - dx).clamp(0, old_x_size as isize - 1) as usize;
+                let old_y = (y as isize - dy).clamp(0, old_y_size as isize - 1) as usize;
+                return old_elevation[old_x][old_y];
+            }).collect()).collect();
+        }
+
+        // Creatures that were cropped off are pulled back onto the nearest edge, then moved off anything they landed on
+        for creature in self.creatures.iter_mut() {
+            let x = (creature.position.x as isize + dx).clamp(0, new_x_size as isize - 1) as usize;
+            let y = (creature.position.y as isize + dy).clamp(0, new_y_size as isize - 1) as usize;
+            creature.set_position(x, y);
+        }
+
+        self.positions = positions;
+        self.territory = territory;
+        self.decals = Vec::new(); // Decals are only for display, so they're just dropped
+        self.river = self.river.iter().filter_map(|pos| shift(*pos)).collect();
+        self.beacon = self.beacon.and_then(shift);
+        self.params.env_x_size = new_x_size;
+        self.params.env_y_size = new_y_size;
+        self.update_creature_positions();
+        self.recount_spaces();
+
+        // Creatures moved (or were dropped), so what everyone saw is out of date
+        self.update_creature_vision();
+    }
+
+    /// Add creatures from somewhere else (e.g. another save) to the board. Each one gets a fresh ID and starts its own
+    /// lineage, so it can't collide with the creatures already here. Brains that don't match this environment's
+    /// enabled inputs/actions are rewired or rejected depending on `options`, and the creatures are placed on random
+    /// blank spaces inside `options.region`
+    pub fn import_creatures(&mut self, creatures : Vec<CreatureV1>, options : &CreatureImportOptions) -> CreatureImportReport {
+        let mut rng = sim_rng();
+        let mut report = CreatureImportReport::default();
+        let inputs = self.params.get_enabled_creature_inputs();
+        let actions = self.params.get_enabled_creature_actions();
+
+        // Blank spaces the imports can go on
+        let (top_left, bottom_right) = options.region.unwrap_or((CreaturePosition {x : 0, y : 0},
+            CreaturePosition {x : self.params.env_x_size - 1, y : self.params.env_y_size - 1}));
+        let mut open_spaces : Vec<CreaturePosition> = Vec::new();
+        for x in top_left.x..=bottom_right.x.min(self.params.env_x_size - 1) {
+            for y in top_left.y..=bottom_right.y.min(self.params.env_y_size - 1) {
+                if self.positions[x][y] == SpaceStates::BlankSpace {
+                    open_spaces.push(CreaturePosition {x : x, y : y});
+                }
+            }
+        }
+        open_spaces.shuffle(&mut rng);
+
+        for mut creature in creatures {
+            if creature.is_dead() || !creature.brain.is_valid() {
+                report.num_rejected += 1;
+                continue;
+            }
+            if !creature.has_io(&inputs, &actions) {
+                if !options.adapt_brains {
+                    report.num_rejected += 1;
+                    continue;
+                }
+                creature.adapt_io(&inputs, &actions);
+                report.num_adapted += 1;
+            }
+            let pos = match open_spaces.pop() {
+                Some(pos) => pos,
+                None => {
+                    report.num_rejected += 1;
+                    continue;
+                }
+            };
+
+            creature.id = self.num_total_creatures;
+            creature.parent_id = None;
+            creature.lineage_id = creature.id;
+            creature.species_id = 0;
+            creature.set_position(pos.x, pos.y);
+            self.add_creature(creature);
+            report.num_imported += 1;
+        }
+        self.recount_spaces();
+
+        // What the imports saw was in their old environment (with its IDs), so they look again
+        self.update_creature_vision();
+        return report;
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Import creatures from a file (see `import_creatures`). The file can be a whole saved environment or a
+    /// single creature saved with `CreatureV1::to_json`. Returns the reason (also printed) if it can't be read
+    pub fn import_creatures_from_file(&mut self, creature_file : &str, options : &CreatureImportOptions) -> Result<CreatureImportReport, String> {
+        let contents = match std::fs::read_to_string(creature_file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                let msg = format!("Could not read file {}. Error = {e}", creature_file);
+                println!("Error: {}", msg);
+                return Err(msg);
+            }
+        };
+        let creatures = match CreatureV1::new_from_json(0, &contents) {
+            Ok(creature) => vec![creature],
+            Err(_e) => Self::parse_json(&contents, &format!("File {}", creature_file))?.creatures,
+        };
+        return Ok(self.import_creatures(creatures, options));
+    }
+
+    /// Change attributes of a living creature by hand (god mode). Only the creature itself changes, the board and
+    /// counters are left alone, so a creature edited down to 0 energy dies of starvation like any other on the next step.
+    /// Returns the reason if there's no living creature with that ID
+    pub fn edit_creature(&mut self, creature_id : usize, edit : &CreatureEdit) -> Result<(), String> {
+        let c_idx = match self.get_creature_idx_from_id(creature_id) {
+            Ok(c_idx) => c_idx,
+            Err(_e) => return Err(format!("No living creature with ID {}", creature_id)),
+        };
+        let creature = &mut self.creatures[c_idx];
+
+        if let Some(energy) = edit.energy {
+            creature.energy = energy.min(creature.get_max_energy());
+        }
+        if let Some(age) = edit.age {
+            creature.age = age;
+        }
+        if let Some(orientation) = edit.orientation {
+            creature.orientation = orientation;
+        }
+        if let Some(color) = edit.color {
+            creature.color = CreatureColor::new_from_vec(color);
+        }
+        if let Some(lineage_id) = edit.lineage_id {
+            creature.lineage_id = lineage_id;
+        }
+        return Ok(());
+    }
+
+    /// Set one weight or bias in a living creature's brain by hand (clamped to `Brain::get_param_range`). Takes effect
+    /// the next time the creature thinks. Returns the value that was set, or the reason nothing changed
+    pub fn set_creature_brain_param(&mut self, creature_id : usize, param : BrainParam, value : f32) -> Result<f32, String> {
+        let c_idx = match self.get_creature_idx_from_id(creature_id) {
+            Ok(c_idx) => c_idx,
+            Err(_e) => return Err(format!("No living creature with ID {}", creature_id)),
+        };
+        return match self.creatures[c_idx].brain.set_param(param, value) {
+            Some(set_value) => Ok(set_value),
+            None => Err(format!("Creature {} has no brain parameter {:?} (or {} isn't a number)", creature_id, param, value)),
+        };
+    }
+
+    /// Stitch `second` onto the side of `first` (given by `direction`) to make one bigger environment, with `seam`
+    /// between them. Parameters, the river and the beacon come from `first`. Creatures from `second` are given new
+    /// IDs (and lineages/species) so they can't be mixed up with the ones from `first`
+    pub fn new_merged(first : &EnvironmentV1, second : &EnvironmentV1, direction : MergeDirection, seam : MergeSeam) -> EnvironmentV1 {
+        let seam_width = if seam == MergeSeam::Open { 0 } else { 1 };
+        let (first_x, first_y) = (first.params.env_x_size, first.params.env_y_size);
+        let (second_x, second_y) = (second.params.env_x_size, second.params.env_y_size);
+        let (dx, dy, new_x_size, new_y_size) = match direction {
+            MergeDirection::Horizontal => (first_x + seam_width, 0, first_x + seam_width + second_x, first_y.max(second_y)),
+            MergeDirection::Vertical => (0, first_y + seam_width, first_x.max(second_x), first_y + seam_width + second_y),
+        };
+
+        // Grow the first board (which stays in the top left) to fit everything
+        let mut merged = first.clone();
+        merged.resize(new_x_size, new_y_size, ResizeAnchor::TopLeft);
+        merged.remove_all_creatures();
+
+        // Build the seam along the far edge of the first board
+        if seam != MergeSeam::Open {
+            let seam_len = match direction { MergeDirection::Horizontal => new_y_size, MergeDirection::Vertical => new_x_size };
+            let corridor_start = seam_len.saturating_sub(MERGE_CORRIDOR_WIDTH) / 2;
+            for idx in 0..seam_len {
+                if seam == MergeSeam::Corridor && idx >= corridor_start && idx < corridor_start + MERGE_CORRIDOR_WIDTH {
+                    continue;
+                }
+                match direction {
+                    MergeDirection::Horizontal => merged.positions[first_x][idx] = SpaceStates::WallSpace,
+                    MergeDirection::Vertical => merged.positions[idx][first_y] = SpaceStates::WallSpace,
+                }
+            }
+        }
+
+        // Copy over the second board's spaces and layers
+        let merge_territory = !merged.territory.is_empty() && !second.territory.is_empty();
+        let merge_elevation = merged.has_elevation() && second.has_elevation();
+        let id_offset = first.num_total_creatures.max(first.creatures.iter().map(|c| c.id + 1).max().unwrap_or(0));
+        for x in 0..second_x {
+            for y in 0..second_y {
+                merged.positions[x + dx][y + dy] = match second.positions[x][y] {
+                    SpaceStates::CreatureSpace(_) => SpaceStates::BlankSpace,
+                    space => space,
+                };
+                if merge_territory {
+                    let mark = second.territory[x][y];
+                    merged.territory[x + dx][y + dy] = TerritoryMark {owner : mark.owner + id_offset, ..mark};
+                }
+                if merge_elevation {
+                    merged.elevation[x + dx][y + dy] = second.elevation[x][y];
+                }
+            }
+        }
+
+        // Bring the second board's creatures over with fresh IDs
+        for creature in second.creatures.iter() {
+            let mut creature = creature.clone();
+            creature.id += id_offset;
+            creature.parent_id = creature.parent_id.map(|parent_id| parent_id + id_offset);
+            creature.lineage_id += id_offset;
+            creature.species_id += first.next_species_id;
+            creature.set_position(creature.position.x + dx, creature.position.y + dy);
+            merged.creatures.push(creature);
+        }
+        let second_total = second.num_total_creatures.max(second.creatures.iter().map(|c| c.id + 1).max().unwrap_or(0));
+        merged.num_total_creatures = id_offset + second_total;
+        merged.next_species_id += second.next_species_id;
+        merged.num_kills += second.num_kills;
+        merged.num_natural_deaths += second.num_natural_deaths;
+
+        merged.update_creature_positions();
+        merged.recount_spaces();
+
+        // What the creatures saw was on their old boards (with their old IDs), so look again
+        merged.update_creature_vision();
+        return merged;
+    }
+
+    /// Recount the food, wall and blank spaces on the board
+    fn recount_spaces(&mut self) {
+        self.num_food = self.positions.iter().flatten().filter(|space| **space == SpaceStates::FoodSpace).count();
+        self.num_walls = self.positions.iter().flatten().filter(|space| **space == SpaceStates::WallSpace).count();
+        self.num_creatures = self.creatures.len();
+        self.num_blank = self.params.env_x_size * self.params.env_y_size - self.num_food - self.num_walls - self.num_creatures - self.get_num_tails();
+    }
+
+    /// Get just the walls of the board as a compact wall map
+    pub fn get_wall_map(&self) -> WallMap {
+        return WallMap::from_positions(&self.positions);
+    }
+
+    /// Replace all walls in the environment with the ones in `wall_map`. Creatures that end up inside a wall are
+    /// moved to the nearest blank space. Returns why (and changes nothing) if the map isn't the same size as the board
+    pub fn apply_wall_map(&mut self, wall_map : &WallMap) -> Result<(), String> {
+        if wall_map.x_size != self.params.env_x_size || wall_map.y_size != self.params.env_y_size {
+            return Err(format!("Wall map ({}, {}) is not the same size as the environment ({}, {})",
+                wall_map.x_size, wall_map.y_size, self.params.env_x_size, self.params.env_y_size));
+        }
+
+        self.remove_all_creatures();
+        self.remove_all_walls();
+        for (x, column) in wall_map.to_wall_grid().iter().enumerate() {
+            for (y, is_wall) in column.iter().enumerate() {
+                if *is_wall {
+                    self.positions[x][y] = SpaceStates::WallSpace;
+                }
+            }
+        }
+        self.num_walls = wall_map.num_walls();
+        self.clear_river_walls();
+        self.update_creature_positions();
+        return Ok(());
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Save just the walls of the board to a wall map file (see `WallMap::to_text`)
+    pub fn save_wall_map(&self, map_file : &str) {
+        if let Err(e) = std::fs::write(map_file, self.get_wall_map().to_text()) {
+            println!("Error: could not save wall map to {}. Error = {e}", map_file);
+        }
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Save the summary report (see `summary_report`) to a file
+    pub fn save_summary_report(&self, report_file : &str) {
+        if let Err(e) = std::fs::write(report_file, self.summary_report()) {
+            println!("Error: could not save summary report to {}. Error = {e}", report_file);
+        }
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Replace the walls with the ones in a wall map file. Returns the reason (also printed) if it can't be loaded
+    pub fn load_wall_map(&mut self, map_file : &str) -> Result<(), String> {
+        let res = std::fs::read_to_string(map_file).map_err(|e| format!("Could not read file {}. Error = {e}", map_file))
+            .and_then(|text| WallMap::from_text(&text))
+            .and_then(|wall_map| self.apply_wall_map(&wall_map));
+        if let Err(msg) = &res {
+            println!("Error: {}", msg);
+        }
+        return res;
+    }
+
+    /// Get a read-only snapshot of the board for renderers
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    /// use evolution_sim::snapshot::SnapshotCell;
+    ///
+    /// let env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 5);
+    /// let snapshot = env.get_snapshot();
+    /// let creature = &snapshot.creatures[0];
+    /// assert_eq!(snapshot.get_cell(creature.position.x, creature.position.y), SnapshotCell::Creature(0));
+    /// ```
+    pub fn get_snapshot(&self) -> EnvSnapshot {
+        return EnvSnapshot::new(self);
+    }
+
+    /// Render the current board into an RGB image where each space is drawn as a `scale` x `scale` pixel
+    /// square (walls, food, fight spaces and creatures in their own colors). Doesn't need the GUI
+    #[cfg(feature = "image")]
+    #[allow(dead_code)]
+    pub fn render_image(&self, scale : u32) -> image::RgbImage {
+        let scale = scale.max(1);
+        let snapshot = self.get_snapshot();
+        let mut img = image::RgbImage::new(snapshot.x_size as u32 * scale, snapshot.y_size as u32 * scale);
+        for x in 0..snapshot.x_size {
+            for y in 0..snapshot.y_size {
+                let color = match snapshot.get_cell(x, y) {
+                    SnapshotCell::Blank if snapshot.is_beacon_at(x, y) => BEACON_COLOR,
+                    SnapshotCell::Blank if snapshot.get_decal_at(x, y) == Some(DecalKind::Fight) => IMAGE_FIGHT_SPACE_COLOR,
+                    SnapshotCell::Blank => IMAGE_BLANK_SPACE_COLOR,
+                    SnapshotCell::Food => FOOD_SPACE_COLOR,
+                    SnapshotCell::Wall => WALL_SPACE_COLOR,
+                    SnapshotCell::Creature(c_idx) => snapshot.creatures[c_idx].color.get_as_vec(),
+                };
+
+                // Fill in the square of pixels for this space
+                for px in 0..scale {
+                    for py in 0..scale {
+                        img.put_pixel(x as u32 * scale + px, y as u32 * scale + py, image::Rgb(color));
+                    }
+                }
+            }
+        }
+        return img;
+    }
+
+    /// Same as `render_image`, but with a footer strip under the board showing the time step, population and kill count,
+    /// so exported frames (e.g. for time-lapse videos) describe themselves. Text that doesn't fit the width is cut off
+    #[cfg(feature = "image")]
+    #[allow(dead_code)]
+    pub fn render_image_with_stats(&self, scale : u32) -> image::RgbImage {
+        let board = self.render_image(scale);
+        let footer_height = FOOTER_GLYPH_HEIGHT * FOOTER_PIXEL_SIZE + 2 * FOOTER_PADDING;
+        let mut img = image::RgbImage::from_pixel(board.width(), board.height() + footer_height, image::Rgb(IMAGE_BLANK_SPACE_COLOR));
+        for (x, y, pixel) in board.enumerate_pixels() {
+            img.put_pixel(x, y, *pixel);
+        }
+
+        let text = format!("STEP {} POP {} KILLS {}", self.time_step, self.num_creatures, self.num_kills);
+        draw_footer_text(&mut img, &text, FOOTER_PADDING, board.height() + FOOTER_PADDING);
+        return img;
+    }
+
+    /// Render a shareable "card" for a creature: its color swatch next to its genome fingerprint, its key stats and
+    /// genome hash, and a small diagram of its brain (one column of neurons per layer, connections blue for positive
+    /// weights and red for negative ones, brighter the stronger they are). Returns an error if there's no such creature
+    #[cfg(feature = "image")]
+    #[allow(dead_code)]
+    pub fn render_creature_card(&self, creature_id : usize) -> Result<image::RgbImage, String> {
+        let creature = match self.get_creature(creature_id) {
+            Some(creature) => creature,
+            None => return Err(format!("no living creature with ID {}", creature_id)),
+        };
+        let line_height = (FOOTER_GLYPH_HEIGHT + 2) * FOOTER_PIXEL_SIZE;
+        let lines = [
+            format!("ID {}", creature.id),
+            format!("GEN {} AGE {}", creature.generation, creature.age),
+            format!("KIDS {} KILLS {}", creature.num_offspring, creature.num_kills),
+            format!("FOOD {} ENERGY {}", creature.food_eaten, creature.energy),
+            format!("HASH {:016X}", get_genome_hash(&creature.brain)),
+        ];
+        let text_y = FOOTER_PADDING * 2 + CARD_SWATCH_SIZE;
+        let brain_y = text_y + line_height * lines.len() as u32 + FOOTER_PADDING;
+        let mut img = image::RgbImage::from_pixel(CARD_WIDTH, brain_y + CARD_BRAIN_HEIGHT + FOOTER_PADDING, image::Rgb(IMAGE_BLANK_SPACE_COLOR));
+
+        // Color swatch with the genome fingerprint beside it
+        fill_image_rect(&mut img, FOOTER_PADDING, FOOTER_PADDING, CARD_SWATCH_SIZE, CARD_SWATCH_SIZE, creature.color.get_as_vec());
+        let fingerprint = get_genome_fingerprint(&creature.brain);
+        let cell_size = CARD_SWATCH_SIZE / FINGERPRINT_SIZE as u32;
+        let fingerprint_x = FOOTER_PADDING * 2 + CARD_SWATCH_SIZE;
+        for (row, row_cells) in fingerprint.cells.iter().enumerate() {
+            for (col, filled) in row_cells.iter().enumerate() {
+                if *filled {
+                    fill_image_rect(&mut img, fingerprint_x + col as u32 * cell_size, FOOTER_PADDING + row as u32 * cell_size, cell_size, cell_size, fingerprint.color.get_as_vec());
+                }
+            }
+        }
+
+        for (line_num, line) in lines.iter().enumerate() {
+            draw_footer_text(&mut img, line, FOOTER_PADDING, text_y + line_num as u32 * line_height);
+        }
+
+        // Mini brain diagram. Each layer is a column of evenly spaced neurons
+        let weights = creature.brain.get_weights();
+        let mut layer_sizes : Vec<usize> = weights.first().map(|w| vec![w.get_ncols()]).unwrap_or_default();
+        layer_sizes.extend(weights.iter().map(|w| w.get_nrows()));
+        let brain_width = CARD_WIDTH - 2 * FOOTER_PADDING;
+        let get_neuron_pos = |layer : usize, neuron : usize| -> (f32, f32) {
+            let x = FOOTER_PADDING as f32 + brain_width as f32 * (layer as f32 + 0.5) / layer_sizes.len() as f32;
+            let y = brain_y as f32 + CARD_BRAIN_HEIGHT as f32 * (neuron as f32 + 0.5) / layer_sizes[layer] as f32;
+            return (x, y);
+        };
+        let max_weight = weights.iter().flat_map(|w| (0..w.get_nrows()).flat_map(move |to| (0..w.get_ncols()).map(move |from| w.get(to, from).abs()))).fold(0.0, f32::max);
+        for (layer, layer_weights) in weights.iter().enumerate() {
+            for to in 0..layer_weights.get_nrows() {
+                for from in 0..layer_weights.get_ncols() {
+                    let weight = layer_weights.get(to, from);
+                    if max_weight <= 0.0 || weight == 0.0 {
+                        continue;
+                    }
+                    let full_color = if weight > 0.0 { CARD_POSITIVE_WEIGHT_COLOR } else { CARD_NEGATIVE_WEIGHT_COLOR };
+                    let strength = weight.abs() / max_weight;
+                    let color = full_color.map(|channel| (channel as f32 * strength) as u8);
+                    draw_image_line(&mut img, get_neuron_pos(layer, from), get_neuron_pos(layer + 1, to), color);
+                }
+            }
+        }
+        for (layer, layer_size) in layer_sizes.iter().enumerate() {
+            for neuron in 0..*layer_size {
+                let (x, y) = get_neuron_pos(layer, neuron);
+                fill_image_rect(&mut img, x as u32 - 1, y as u32 - 1, 3, 3, IMAGE_FOOTER_TEXT_COLOR);
+            }
+        }
+        return Ok(img);
+    }
+
+    /// Save a creature's card (see `render_creature_card`) as a PNG file
+    #[cfg(feature = "image")]
+    #[allow(dead_code)]
+    pub fn save_creature_card_png(&self, creature_id : usize, image_file : &str) {
+        let card = match self.render_creature_card(creature_id) {
+            Ok(card) => card,
+            Err(msg) => {
+                println!("Error: could not make a creature card. {}", msg);
+                return;
+            }
+        };
+        if let Err(e) = card.save(image_file) {
+            println!("Error: could not save creature card to {}. Error = {e}", image_file);
+        }
+    }
+
+    /// Save a snapshot of the current board as a PNG file. See `render_image` for details
+    #[cfg(feature = "image")]
+    #[allow(dead_code)]
+    pub fn save_snapshot_png(&self, image_file : &str, scale : u32) {
+        match self.render_image(scale).save(image_file) {
+            Err(e) => println!("Error: could not save snapshot to {}. Error = {e}", image_file),
+            Ok(_) => (),
+        }
+    }
+
+    /// Run the simulation until every creature dies or one of the `limits` is reached (with no limits it only stops
+    /// at extinction). Limits are counted from when this is called. Returns why it stopped
+    pub fn run_with_limits(&mut self, limits : &RunLimits) -> StopReason {
+        let start_step = self.time_step;
+        let start_time = std::time::Instant::now();
+        loop {
+            if let Some(reason) = limits.check(self, start_step, start_time) {
+                return reason;
+            }
+
+            self.advance_step();
+
+            // Check whether there's any creatures left
+            if self.num_creatures == 0 {
+                if DEBUG_LEVEL > 0 {
+                    println!("Stopping simulation after {} steps because there are no creatures left", self.time_step - start_step);
+                }
+                return StopReason::Extinction;
+            }
+        }
+    }
+
+    /// Main interface to run a certain number of simulation steps. Stops early with `EnvErrors::EarlyExitErr`
+    /// if every creature dies
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    ///
+    /// let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 2);
+    /// match env.run_n_steps(100) {
+    ///     Ok(()) => assert_eq!(env.time_step, 100),
+    ///     Err(_) => assert_eq!(env.num_creatures, 0),
+    /// }
+    /// ```
+    pub fn run_n_steps(&mut self, num_steps : usize) -> Result<(), EnvErrors> {
+        return match self.run_with_limits(&RunLimits::with_max_steps(num_steps)) {
+            StopReason::Extinction => Err(EnvErrors::EarlyExitErr),
+            _ => Ok(()),
+        };
+    }
+
+
+    /// Print the current state of the environment board
+    pub fn show(&self) {
+        let snapshot = self.get_snapshot();
+        println!();
+        let num_dashes = snapshot.x_size * 3 + 1;
+        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
+        for y in 0..snapshot.y_size {
+            print!("|");
+            for x in 0..snapshot.x_size {
+                if snapshot.is_beacon_at(x, y) {
+                    print!(" @ ");
+                    continue;
+                }
+                match snapshot.get_cell(x, y) {
+                    SnapshotCell::Blank if snapshot.get_decal_at(x, y) == Some(DecalKind::Fight) => print!(" x "),
+                    SnapshotCell::Blank => print!("   "),
+                    SnapshotCell::Creature(c_idx) => print!("{:3}", snapshot.creatures[c_idx].id % 1000), // just wrap around if the creature id goes beyond 3 digits 
+                    SnapshotCell::Food => print!(" # "),
+                    SnapshotCell::Wall => print!("|-|"),
+                }
+            }
+            print!("|");
+            println!();
+        }
+        println!("{:-<width$}", " ", width = num_dashes); // print horizontal dashes
+        println!("Key:");
+        println!("Creature = <id num>\nFood = #\nWall = |-|\nBeacon = @");
+    }
+
+    /// Print all creature info in columns to stdout
+    #[allow(dead_code)]
+    pub fn show_all_creature_info(&self) {
+        println!("{:12} {:12} {:12} {:15} ", "Creature Id", "Age", "Energy", "Last Action");
+        for creature_idx in 0..self.creatures.len() {
+            let creature = &self.creatures[creature_idx];
+            println!("{:<12} {:<12} {:<12} {:<15?} ", creature.id, creature.age, creature.energy, creature.last_action);
+        }
+    }
+
+
+    /// Audit the counters of each space type vs. the position matrix to make sure everything is in sync
+    fn update_space_counters(&mut self) {
+        let mut temp_food : usize = 0; 
+        let mut temp_walls : usize = 0; 
+        let mut temp_creatures : usize = 0; 
+        let mut temp_blank : usize = 0; 
+        for x in 0..self.params.env_x_size {
+            for y in 0..self.params.env_y_size {
+                match self.positions[x][y] {
+                    SpaceStates::BlankSpace => temp_blank += 1,
+                    SpaceStates::FoodSpace => temp_food += 1,
+                    SpaceStates::CreatureSpace(_id) => temp_creatures += 1,
+                    SpaceStates::WallSpace => temp_walls += 1,
+                }
+            }
+        }
+
+        self.num_blank = temp_blank;
+        self.num_creatures = temp_creatures - self.get_num_tails(); // Large creatures take up two spaces
         self.num_walls = temp_walls;
         self.num_food = temp_food;
     }
@@ -353,6 +1933,13 @@ impl EnvironmentV1 {
 
     /// Advance one "day"!
     pub fn advance_step(&mut self) {
+        let mut rng = self.rng.take();
+        run_seeded(&mut rng, || self.advance_step_inner());
+        self.rng = rng;
+    }
+
+    /// Does the work of `advance_step` using whichever RNG is currently installed
+    fn advance_step_inner(&mut self) {
 
         // Print some info about the env
         if DEBUG_LEVEL > 0 {
@@ -363,21 +1950,47 @@ impl EnvironmentV1 {
 
         // Audit the board on every step
         self.update_space_counters();
+        self.update_decals();
+        let num_creatures_at_start = self.creatures.len();
+        self.stats.record_population(num_creatures_at_start, self.time_step);
+        if self.params.enable_territory {
+            self.resize_territory();
+        }
+        if self.params.enable_elevation && !self.has_elevation() {
+            self.elevation = generate_elevation(self.params.env_x_size, self.params.env_y_size, self.params.elevation_feature_size);
+        }
+        if self.params.enable_river && self.river.is_empty() {
+            self.river = generate_river(self.params.env_x_size, self.params.env_y_size);
+            self.clear_river_walls();
+        }
+
+        // Put creatures in the order they'll act in this step
+        self.order_creatures();
 
         // Initialize the random number generator used in this function
-        let mut rng = rand::thread_rng();
+        let mut rng = sim_rng();
 
         // Create a temporary variable to hold new creatures that will spawn
         let mut temp_new_creatures : Vec<CreatureV1> = Vec::new();
 
+        // Creatures can only start a pregnancy during mating season
+        let can_reproduce = self.is_mating_season();
+        let budget = self.params.brain_compute_budget;
+        let delay_steps = self.params.action_delay_steps;
+
         // Evaluate the next action for each creature
         for creature_idx in 0..self.creatures.len() {
 
-            // First update the 'senses' of the creature
-            self.creatures[creature_idx].sense_surroundings();
+            // First update the 'senses' of the creature and evaluate its brain. Creatures whose brains cost more than the
+            // compute budget stay put for a few steps while they think. With an action delay, each decision is only carried
+            // out `action_delay_steps` steps later
+            let mut brain_action = self.creatures[creature_idx].decide_action_with_budget(budget);
+            if delay_steps > 0 {
+                brain_action = self.creatures[creature_idx].delay_action(brain_action, delay_steps);
+            }
 
-            // Then actually evaluate the brain net to get the next action it'll take
-            let action : CreatureActions = self.creatures[creature_idx].perform_next_action();
+            // Then actually carry it out. The creature may not do what its brain decided (e.g. if it's reproducing or dies)
+            let action : CreatureActions = self.creatures[creature_idx].perform_next_action(brain_action, can_reproduce);
 
             // Create a reference to the creature now that we've done the mutable work (perform next_action)
             let creature_copy = self.creatures[creature_idx].clone();
@@ -395,6 +2008,23 @@ impl EnvironmentV1 {
             }
 
             let mut next_position = creature_copy.position.clone();
+            let mut action_result = ActionResult::NoEffect;
+
+            // Seeing costs energy (if enabled)
+            if self.params.view_energy_cost > 0.0 {
+                self.creatures[creature_idx].pay_view_cost(self.params.view_energy_cost);
+            }
+
+            // So does sensing and thinking, whatever the action is (if enabled)
+            if self.params.enable_metabolic_cost {
+                self.creatures[creature_idx].pay_metabolic_cost(self.params.metabolic_energy_cost);
+            }
+
+            // If the creature is pregnant, pay the upkeep and give birth if the time is up
+            if let Some(num_offspring) = self.creatures[creature_idx].advance_gestation(self.params.gestation_upkeep_energy) {
+                temp_new_creatures.append(&mut self.create_offspring(creature_idx, num_offspring));
+                action_result = ActionResult::Reproduced(num_offspring);
+            }
 
             match action {
 
@@ -402,16 +2032,24 @@ impl EnvironmentV1 {
                 CreatureActions::Kill => {
                     if creature_copy.vision_state.obj_in_view && creature_copy.vision_state.dist == 1 {
                         match creature_copy.vision_state.space_type {
-                            SpaceStates::CreatureSpace(victim_cid) => {
-                                let victim_idx = self.get_creature_idx_from_id(victim_cid).unwrap();
-
-                                // Make sure victim is not already dead
-                                if !self.creatures[victim_idx].is_dead() {
+                            SpaceStates::CreatureSpace(victim_cid) if victim_cid != creature_copy.id => {
+                                // Make sure victim is still around and not already dead
+                                let victim_idx = self.get_creature_idx_from_id(victim_cid).ok();
+                                if let Some(victim_idx) = victim_idx.filter(|idx| !self.creatures[*idx].is_dead()) {
+
+                                    // Resting creatures can't fight back, so killing them is cheaper. Refund the difference
+                                    if self.creatures[victim_idx].last_action == CreatureActions::Rest {
+                                        let refund = creature_copy.params.kill_energy_cost.saturating_sub(creature_copy.params.kill_resting_energy_cost);
+                                        self.creatures[creature_idx].eat_food(refund);
+                                    }
                                     self.creatures[victim_idx].kill();
+                                    self.events.push(EnvEvent::Kill {killer_id : creature_copy.id, victim_id : victim_cid, position : self.creatures[victim_idx].position});
 
-                                    // Give creature the immediate energy
-                                    self.creatures[creature_idx].eat_food(self.params.energy_per_kill);
+                                    // Give creature the immediate energy (large creatures get more out of a kill)
+                                    let kill_energy = if creature_copy.is_large { self.params.energy_per_kill * LARGE_BODY_MULTIPLIER } else { self.params.energy_per_kill };
+                                    self.creatures[creature_idx].eat_food(kill_energy);
                                     self.creatures[creature_idx].set_killer();
+                                    action_result = ActionResult::Killed;
                                 }
                             },
                             _ => (),
@@ -427,22 +2065,41 @@ impl EnvironmentV1 {
                     next_position = self.get_next_position_for_creature(action, creature_copy.position, creature_copy.orientation);
                 }
 
-                // Handle reproduction
-                CreatureActions::Reproduce => {
+                // Handle reproduction (a creature that's already pregnant can't reproduce again)
+                CreatureActions::Reproduce if !self.creatures[creature_idx].is_pregnant() => {
                     // Randomly determine how many offspring this creature will have
-                    let num_offspring = rng.gen_range(1..=self.params.max_offspring_per_reproduce);
+                    let mut num_offspring = rng.gen_range(1..=self.params.max_offspring_per_reproduce);
+
+                    // Novel creatures get a chance at an extra offspring
+                    if self.params.enable_novelty && rng.gen::<f32>() < self.params.novelty_weight * self.creatures[creature_idx].novelty_rank {
+                        num_offspring += 1;
+                    }
                     if DEBUG_LEVEL > 1 {
                         println!("Creature {} is reproducing with {} offspring!", creature_copy.id, num_offspring);
                     }
-                    for _offspring_num in 0..num_offspring {
-                        let new_offspring = CreatureV1::new_offspring(self.num_total_creatures, &self.creatures[creature_idx], self.params.mutation_prob);
-                        self.num_total_creatures += 1;
-                        temp_new_creatures.push(new_offspring);
+
+                    // Either the offspring are born right away, or the creature has to carry them for a while
+                    if self.params.gestation_steps > 0 {
+                        self.creatures[creature_idx].start_gestation(num_offspring, self.params.gestation_steps);
+                        action_result = ActionResult::Conceived(num_offspring);
+                    } else {
+                        temp_new_creatures.append(&mut self.create_offspring(creature_idx, num_offspring));
+                        action_result = ActionResult::Reproduced(num_offspring);
                     }
                 },
+                CreatureActions::Reproduce => {},
 
                 // Actions that don't require any further processing
                 CreatureActions::Stay => {},
+                CreatureActions::Rest => {}, // energy gain handled inside creature code
+
+                // Handle territory marking
+                CreatureActions::MarkTerritory => {
+                    if self.params.enable_territory {
+                        let pos = creature_copy.position;
+                        self.territory[pos.x][pos.y] = TerritoryMark {owner : creature_copy.lineage_id, strength : 1.0};
+                    }
+                },
                 CreatureActions::RotateCCW => {}, // handled inside creature code
                 CreatureActions::RotateCW => {}, // handled inside creature code
             }
@@ -455,19 +2112,21 @@ impl EnvironmentV1 {
 
                 let pos = self.creatures[creature_idx].position.clone();
 
+                // Water is like a wall to creatures that can't swim
+                let into_water = self.params.enable_swimming && self.is_river(next_position);
+                let blocked_by_water = into_water && !self.creatures[creature_idx].can_swim;
+
                 // Detect collisions in next space
                 match self.positions[next_position.x][next_position.y] {
-                    // If next space is blank (or fight space), perform the move
+                    _ if blocked_by_water => action_result = ActionResult::Blocked,
+
+                    // If next space is blank, perform the move
                     SpaceStates::BlankSpace => {
                         self.positions[pos.x][pos.y] = SpaceStates::BlankSpace;
                         self.positions[next_position.x][next_position.y] = SpaceStates::CreatureSpace(self.creatures[creature_idx].id);
                         self.creatures[creature_idx].set_position(next_position.x, next_position.y);
+                        action_result = ActionResult::Moved;
                     },
-                    SpaceStates::FightSpace(_ttl) => {
-                        self.positions[pos.x][pos.y] = SpaceStates::BlankSpace;
-                        self.positions[next_position.x][next_position.y] = SpaceStates::CreatureSpace(self.creatures[creature_idx].id);
-                        self.creatures[creature_idx].set_position(next_position.x, next_position.y);
-                    }
 
                     // If next space is food, then eat it!
                     SpaceStates::FoodSpace => {
@@ -475,18 +2134,64 @@ impl EnvironmentV1 {
                         self.positions[next_position.x][next_position.y] = SpaceStates::CreatureSpace(self.creatures[creature_idx].id);
                         self.creatures[creature_idx].eat_food(self.params.energy_per_food_piece);
                         self.creatures[creature_idx].set_position(next_position.x, next_position.y);
+                        action_result = ActionResult::Ate;
                     }
 
                     // If space is wall, then move is invalid. Stay put
-                    SpaceStates::WallSpace => {}
+                    SpaceStates::WallSpace => action_result = ActionResult::Blocked,
+
+                    // Otherwise, another creature (or its own tail) is in the way. Stay put
+                    SpaceStates::CreatureSpace(_id) => action_result = ActionResult::Blocked,
+                }
+
+                // A large creature's tail follows it into the space it just left
+                if self.creatures[creature_idx].position != pos && self.creatures[creature_idx].tail.is_some() {
+                    self.clear_tail(creature_idx);
+                    self.positions[pos.x][pos.y] = SpaceStates::CreatureSpace(creature_copy.id);
+                    self.creatures[creature_idx].tail = Some(pos);
+                }
+
+                // Swimming is harder work than walking
+                if into_water && self.creatures[creature_idx].position != pos {
+                    let creature = &mut self.creatures[creature_idx];
+                    creature.energy = creature.energy.saturating_sub(self.params.swim_energy_cost);
+                }
+
+                // Climbing costs extra energy and going downhill gives some of the move cost back
+                if self.params.enable_elevation && self.creatures[creature_idx].position != pos && self.has_elevation() {
+                    let new_pos = self.creatures[creature_idx].position;
+                    let climb = (self.elevation[new_pos.x][new_pos.y] - self.elevation[pos.x][pos.y]) * self.params.elevation_energy_cost;
+                    if climb > 0.0 {
+                        let creature = &mut self.creatures[creature_idx];
+                        creature.energy = creature.energy.saturating_sub(climb.round() as usize);
+                    } else {
+                        let refund = (-climb).round() as usize;
+                        self.creatures[creature_idx].eat_food(refund.min(creature_copy.params.move_energy_cost));
+                    }
+                }
 
-                    // Otherwise, do nothing...
-                    _ => {}
+                // If the creature made it to the beacon, reward it and move the beacon somewhere else
+                if self.beacon == Some(self.creatures[creature_idx].position) {
+                    self.creatures[creature_idx].eat_food(self.params.energy_per_beacon_reach);
+                    self.num_beacon_reaches += 1;
+                    self.beacon = self.get_rand_blank_space();     // Taken off the board if there's nowhere left to put it
+                    action_result = ActionResult::ReachedBeacon;
                 }
             }
+
+            // Swing a large creature's tail back behind it if it turned or sidestepped (and there's room)
+            self.update_tail(creature_idx);
+
+            // Let the creature know how its action turned out so it can sense it next step
+            self.creatures[creature_idx].apply_action_result(action_result);
         } // end loop updating creatures
 
 
+        // Sick creatures get sicker (or better) and pass the disease on to their neighbors
+        if self.params.enable_disease {
+            self.update_disease();
+        }
+
         // Remove dead creatures from the environment
         self.remove_dead_creatures();
 
@@ -497,7 +2202,10 @@ impl EnvironmentV1 {
                 Some(new_pos) => {
                     new_creature.set_position(new_pos.x, new_pos.y);
                     self.positions[new_creature.position.x][new_creature.position.y] = SpaceStates::CreatureSpace(new_creature.id);
+                    self.events.push(EnvEvent::Birth {id : new_creature.id, parent_id : new_creature.parent_id, position : new_creature.position});
+                    self.add_decal(new_creature.position, DecalKind::Birth, BIRTH_DECAL_PERSISTENCE_STEPS);
                     self.creatures.push(new_creature);
+                    self.update_tail(self.creatures.len() - 1);
                 },
                 None => {
                     // Just don't spawn the creature cause there's no space
@@ -509,40 +2217,296 @@ impl EnvironmentV1 {
         // Add food pieces according to settings
         self.add_new_food_pieces();
 
-        // Evaluate the vision of each of the creatures now that everything is updated
+        // New creatures wander in from the edges of the board
+        if self.params.immigration_rate > 0.0 {
+            self.add_immigrants();
+        }
+
+        // Food on the river drifts downstream
+        if self.params.enable_river {
+            self.update_river();
+        }
+
+        // Old territory marks fade away
+        if self.params.enable_territory {
+            self.decay_territory();
+        }
+
+        // Every so often disaster strikes
+        self.apply_random_catastrophes();
+
+        // Evaluate the vision (and other senses) of each of the creatures now that everything is updated
         self.update_creature_vision();
+        self.update_creature_env_senses();
+
+        // Keep behavior novelty up to date
+        if self.params.enable_novelty {
+            self.update_novelty();
+        }
+
+        // Re-cluster species every so often
+        if self.params.enable_speciation && self.time_step.is_multiple_of(self.params.speciation_interval.max(1)) {
+            self.update_species();
+        }
+
+        // Track how the swimming trait spreads
+        if self.params.enable_swimming {
+            self.update_swimmer_history();
+        }
 
         // If proper debug level show the env after each step
         if DEBUG_LEVEL > 0 {
             self.show();
         }
 
+        // Keep the leaderboards up to date so frontends don't have to go through every creature themselves
+        self.stats.record_leaders(&self.creatures);
+        self.stats.record_cohorts(&self.creatures, self.time_step);
+
+        // Let frontends know the step is done (and whether it wiped everyone out)
+        self.events.push(EnvEvent::StepCompleted {time_step : self.time_step, num_creatures : self.creatures.len(), num_food : self.num_food, num_species : self.num_species});
+        if self.creatures.is_empty() && num_creatures_at_start > 0 {
+            self.events.push(EnvEvent::Extinction {time_step : self.time_step});
+        }
+
         // Increment the time step counter
         self.time_step += 1;
 
     }
 
-    /// Add random number of new food pieces to the board in random locations according to 
-    /// `avg_new_food_per_day` value.
-    fn add_new_food_pieces(&mut self) {
-        let mut rng = rand::thread_rng();
-
-        if self.params.avg_new_food_per_day < 1.0 {
-            // If the number of new food is less than 1, then decide whether to add
-            // a single food piece treating `avg_new_food_per_day` as a probability
-            if rng.gen::<f32>() < self.params.avg_new_food_per_day {
-                self.add_food_space(self.get_rand_blank_space());
+    /// Advance every infection by a step, then let each sick creature infect the healthy creatures in the four spaces
+    /// around it with probability `infection_prob`
+    fn update_disease(&mut self) {
+        for creature in self.creatures.iter_mut().filter(|c| !c.is_dead()) {
+            if creature.advance_infection(self.params.infection_energy_drain) {
+                self.num_recoveries += 1;
             }
-        } else {
-            // If avg the number of food pieces is greater than 1, then randomly sample from
-            // range where `avg_new_food_per_day` is the center of the distribution. Result
+        }
+
+        let mut rng = sim_rng();
+        let (x_size, y_size) = (self.params.env_x_size, self.params.env_y_size);
+        let creature_idxs : HashMap<usize, usize> = self.creatures.iter().enumerate().map(|(idx, c)| (c.id, idx)).collect();
+        let sick_positions : Vec<CreaturePosition> = self.creatures.iter().filter(|c| c.is_infected() && !c.is_dead()).map(|c| c.position).collect();
+        for pos in sick_positions {
+            let neighbors = [((pos.x + x_size - 1) % x_size, pos.y), ((pos.x + 1) % x_size, pos.y), (pos.x, (pos.y + y_size - 1) % y_size), (pos.x, (pos.y + 1) % y_size)];
+            for (x, y) in neighbors {
+                if let SpaceStates::CreatureSpace(id) = self.positions[x][y] {
+                    if let Some(idx) = creature_idxs.get(&id) {
+                        if !self.creatures[*idx].is_dead() && rng.gen::<f32>() < self.params.infection_prob && self.creatures[*idx].infect(self.params.infection_duration) {
+                            self.num_infections += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the number of creatures that are currently (sick, immune)
+    pub fn get_infection_counts(&self) -> (usize, usize) {
+        let num_sick = self.creatures.iter().filter(|c| c.is_infected()).count();
+        let num_immune = self.creatures.iter().filter(|c| c.infection == InfectionState::Immune).count();
+        return (num_sick, num_immune);
+    }
+
+    /// Roll for each kind of catastrophe and apply however many hit this step
+    fn apply_random_catastrophes(&mut self) {
+        for kind in ALL_CATASTROPHE_KINDS {
+            let catastrophe_params = self.get_catastrophe_params(kind);
+            for _ in 0..catastrophe_params.sample_num_per_step() {
+                self.apply_catastrophe(kind, catastrophe_params.magnitude);
+            }
+        }
+    }
+
+    /// Get the rate/magnitude parameters for a kind of catastrophe
+    fn get_catastrophe_params(&self, kind : CatastropheKind) -> CatastropheParams {
+        return match kind {
+            CatastropheKind::FoodDieOff => self.params.food_die_off,
+            CatastropheKind::Plague => self.params.plague,
+            CatastropheKind::WallCollapse => self.params.wall_collapse,
+        };
+    }
+
+    /// Hit the environment with a catastrophe. Each food piece/creature/wall (depending on `kind`) is hit with
+    /// probability `magnitude`. Returns the number of things hit, which is also reported as an event
+    pub fn apply_catastrophe(&mut self, kind : CatastropheKind, magnitude : f32) -> usize {
+        let mut rng = sim_rng();
+        let mut num_affected : usize = 0;
+        match kind {
+            CatastropheKind::FoodDieOff | CatastropheKind::WallCollapse => {
+                let target = if kind == CatastropheKind::FoodDieOff { SpaceStates::FoodSpace } else { SpaceStates::WallSpace };
+                for space in self.positions.iter_mut().flatten() {
+                    if *space == target && rng.gen::<f32>() < magnitude {
+                        *space = SpaceStates::BlankSpace;
+                        num_affected += 1;
+                    }
+                }
+                self.update_space_counters();
+            },
+            CatastropheKind::Plague => {
+                // Plague victims die of natural causes (they leave a death decal, not a fight one)
+                for creature in self.creatures.iter_mut() {
+                    if !creature.is_dead() && rng.gen::<f32>() < magnitude {
+                        creature.die();
+                        num_affected += 1;
+                    }
+                }
+                self.update_space_counters();
+                self.remove_dead_creatures();
+                self.stats.record_plague(num_affected, self.time_step);
+            },
+        }
+
+        if DEBUG_LEVEL > 0 {
+            println!("Catastrophe {:?} hit {} things in step {}", kind, num_affected, self.time_step);
+        }
+        self.events.push(EnvEvent::Catastrophe {time_step : self.time_step, kind : kind, num_affected : num_affected});
+        return num_affected;
+    }
+
+    /// Reorder the creature list according to `params.creature_order`
+    fn order_creatures(&mut self) {
+        match self.params.creature_order {
+            CreatureOrder::Insertion => self.creatures.sort_by_key(|c| c.id),
+            CreatureOrder::Random => self.creatures.shuffle(&mut sim_rng()),
+            CreatureOrder::Morton => self.creatures.sort_by_key(|c| get_morton_code(c.position.x, c.position.y)),
+        }
+    }
+
+    /// Create `num_offspring` new creatures from the creature at `creature_idx`. The offspring still have to
+    /// be placed on the board by the caller
+    fn create_offspring(&mut self, creature_idx : usize, num_offspring : usize) -> Vec<CreatureV1> {
+        let mut offspring : Vec<CreatureV1> = Vec::with_capacity(num_offspring);
+        for _offspring_num in 0..num_offspring {
+            // Offspring either get a cut of the parent's energy, or the flat starting energy
+            let offspring_energy = if self.params.offspring_energy_fraction > 0.0 {
+                let fraction = self.params.offspring_energy_fraction.min(1.0);
+                let endowment = (self.creatures[creature_idx].energy as f32 * fraction) as usize;
+                self.creatures[creature_idx].transfer_energy(endowment)
+            } else {
+                self.creatures[creature_idx].params.starting_energy
+            };
+
+            let mut new_offspring = CreatureV1::new_offspring(self.num_total_creatures, &self.creatures[creature_idx], &self.params.get_mutation_config(), offspring_energy);
+            if self.params.evolve_view_distance {
+                new_offspring.mutate_view_distance(self.params.mutation_prob);
+            } else {
+                new_offspring.view_distance = self.params.view_distance.clamp(1, MAX_VIEW_DISTANCE);
+            }
+            if self.params.enable_swimming {
+                new_offspring.mutate_swimming(self.params.mutation_prob);
+            }
+            if self.params.enable_large_bodies {
+                new_offspring.mutate_body_size(self.params.mutation_prob);
+            } else {
+                new_offspring.is_large = false;
+            }
+            self.num_total_creatures += 1;
+            self.creatures[creature_idx].num_offspring += 1;
+            offspring.push(new_offspring);
+        }
+        return offspring;
+    }
+
+    /// Add random number of new food pieces to the board in random locations according to 
+    /// `avg_new_food_per_day` value.
+    fn add_new_food_pieces(&mut self) {
+        let mut rng = sim_rng();
+
+        if self.params.avg_new_food_per_day < 1.0 {
+            // If the number of new food is less than 1, then decide whether to add
+            // a single food piece treating `avg_new_food_per_day` as a probability
+            if rng.gen::<f32>() < self.params.avg_new_food_per_day {
+                if let Some(pos) = self.get_rand_blank_space() {
+                    self.add_food_space(pos);
+                }
+            }
+        } else {
+            // If avg the number of food pieces is greater than 1, then randomly sample from
+            // range where `avg_new_food_per_day` is the center of the distribution. Result
             // will represent how many food pieces to add
             let max_food = self.params.avg_new_food_per_day * 2.0;
             let num_food = rng.gen_range(0.0..max_food).round() as usize;
             for _ in 0..num_food {
-                self.add_food_space(self.get_rand_blank_space());
+                // A crowded board can fill up completely, leaving nowhere for food
+                match self.get_rand_blank_space() {
+                    Some(pos) => self.add_food_space(pos),
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Let new creatures onto the board from its edges. The number each step is picked like the number of new food pieces
+    /// (see `add_new_food_pieces`) from `immigration_rate`. Each immigrant founds its own lineage, so how its descendants
+    /// do can be followed in the stats (see `StatsRecorder::get_immigrant_lineage_stats`)
+    fn add_immigrants(&mut self) {
+        let mut rng = sim_rng();
+        let num_immigrants = if self.params.immigration_rate < 1.0 {
+            if rng.gen::<f32>() < self.params.immigration_rate { 1 } else { 0 }
+        } else {
+            rng.gen_range(0.0..self.params.immigration_rate * 2.0).round() as usize
+        };
+
+        for _ in 0..num_immigrants {
+            let (position, orientation) = match self.get_rand_edge_space() {
+                Some(space) => space,
+                None => break,  // Every edge space tried was taken
+            };
+            let mut immigrant = self.new_immigrant(self.num_total_creatures);
+            immigrant.set_position(position.x, position.y);
+            immigrant.set_orientation(orientation);
+            self.stats.record_immigrant(immigrant.id, self.time_step);
+            self.events.push(EnvEvent::Immigration {time_step : self.time_step, id : immigrant.id, position : position});
+            self.add_creature(immigrant);
+        }
+    }
+
+    /// Create an immigrant with ID `id` (not placed on the board yet). Its genome comes from `immigrant_source`
+    fn new_immigrant(&self, id : usize) -> CreatureV1 {
+        let inputs = self.params.get_enabled_creature_inputs();
+        let actions = self.params.get_enabled_creature_actions();
+        let champion = match self.params.immigrant_source {
+            ImmigrantSource::Random => None,
+            ImmigrantSource::HallOfFame => self.dead_creatures.iter().max_by_key(|c| c.age),
+        };
+
+        let mut immigrant = match champion {
+            Some(champion) => {
+                let mut immigrant = CreatureV1::new_offspring(id, champion, &self.params.get_mutation_config(), champion.params.starting_energy);
+                immigrant.adapt_io(&inputs, &actions);
+                immigrant.generation = 0;
+                immigrant.parent_id = None;
+                immigrant
+            },
+            None => {
+                let mut immigrant = CreatureV1::new_with_io(id, &CreatureParams::new(), &inputs, &actions);
+                immigrant.view_distance = self.params.view_distance.clamp(1, MAX_VIEW_DISTANCE);
+                immigrant
+            },
+        };
+        immigrant.lineage_id = id;
+        return immigrant;
+    }
+
+    /// Get a random blank space on the edge of the board for an immigrant to enter at, along with the orientation
+    /// that faces into the board. Water is avoided when it blocks non-swimmers. Returns None if none of the spaces tried were free
+    fn get_rand_edge_space(&self) -> Option<(CreaturePosition, CreatureOrientation)> {
+        let mut rng = sim_rng();
+        let (x_size, y_size) = (self.params.env_x_size, self.params.env_y_size);
+        for _ in 0..IMMIGRATION_SPAWN_ATTEMPTS {
+            let (position, orientation) = match rng.gen_range(0..4) {
+                0 => (CreaturePosition {x : 0, y : rng.gen_range(0..y_size)}, CreatureOrientation::Right),
+                1 => (CreaturePosition {x : x_size - 1, y : rng.gen_range(0..y_size)}, CreatureOrientation::Left),
+                2 => (CreaturePosition {x : rng.gen_range(0..x_size), y : 0}, CreatureOrientation::Down),
+                _ => (CreaturePosition {x : rng.gen_range(0..x_size), y : y_size - 1}, CreatureOrientation::Up),
+            };
+            let is_water = self.params.enable_swimming && self.is_river(position);
+            if self.positions[position.x][position.y] == SpaceStates::BlankSpace && !is_water {
+                return Some((position, orientation));
             }
         }
+        return None;
     }
 
     /// Add a single food space to the specified location
@@ -558,12 +2522,14 @@ impl EnvironmentV1 {
     }
 
     /// Add single creature to the environment at position specified by creature itself
-    pub fn add_creature(&mut self, new_creature : CreatureV1) {
-        // Note: allow overwriting of other types of spaces for creatures
+    pub fn add_creature(&mut self, mut new_creature : CreatureV1) {
+        // Note: allow overwriting of other types of spaces for creatures. Large creatures get a new tail behind them
 
         self.positions[new_creature.position.x][new_creature.position.y] = SpaceStates::CreatureSpace(new_creature.id);
+        new_creature.tail = None;
         self.creatures.push(new_creature);
         self.num_total_creatures += 1;
+        self.update_tail(self.creatures.len() - 1);
     }
 
     /// Add a wall space to the specified location
@@ -592,6 +2558,7 @@ impl EnvironmentV1 {
     }
 
     /// Remove all wall spaces from position array
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn remove_all_walls(&mut self) {
         for x in 0..self.positions.len() {
             for y in 0..self.positions[0].len() {
@@ -604,6 +2571,7 @@ impl EnvironmentV1 {
     }
 
     /// Remove all creature spaces from position array
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn remove_all_creatures(&mut self) {
         for x in 0..self.positions.len() {
             for y in 0..self.positions[0].len() {
@@ -618,6 +2586,7 @@ impl EnvironmentV1 {
     }
 
     /// Remove all food spaces from position array
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn remove_all_food(&mut self) {
         for x in 0..self.positions.len() {
             for y in 0..self.positions[0].len() {
@@ -632,8 +2601,16 @@ impl EnvironmentV1 {
     }
 
     /// Update the position matrix with all food spaces from the provided "positions" matrix
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn add_food_from_positions(&mut self, new_positions : &Vec<Vec<SpaceStates>>) {
         let mut food_count : usize = 0;
+
+        // Ensure that new positions are same size
+        if new_positions.len() != self.params.env_x_size || new_positions[0].len() != self.params.env_y_size {
+            println!("Warning: New environment ({}, {}) is not the same size as existing ({}, {}) ! Nothing done...", new_positions.len(), new_positions[0].len(), self.params.env_x_size, self.params.env_y_size);
+            return;
+        }
+
         for x in 0..self.positions.len() {
             for y in 0..self.positions[0].len() {
                 match new_positions[x][y] {
@@ -650,6 +2627,7 @@ impl EnvironmentV1 {
     }
 
     /// Update the position matrix with all wall spaces from the provided "positions" matrix
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn add_walls_from_positions(&mut self, new_positions : &Vec<Vec<SpaceStates>>) {
         let mut wall_count : usize = 0;
 
@@ -675,15 +2653,81 @@ impl EnvironmentV1 {
     }
 
     /// Update the position matrix with create info in the creatures vector. This is only to be used
-    /// when loading all new creatures from a JSON file into the environment
+    /// when loading all new creatures from a JSON file into the environment. Creatures that land outside
+    /// the board, on a wall, or on top of another creature are moved to the nearest blank space. If
+    /// there's no room left at all, the creature is dropped
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
     fn update_creature_positions(&mut self) {
-        for c_idx in 0..self.creatures.len() {
-            let x = self.creatures[c_idx].position.x;
-            let y = self.creatures[c_idx].position.y;
-            self.positions[x][y] = SpaceStates::CreatureSpace(self.creatures[c_idx].id);
+        let mut placed_creatures : Vec<CreatureV1> = Vec::with_capacity(self.creatures.len());
+        for mut creature in std::mem::take(&mut self.creatures) {
+            let target = CreaturePosition {
+                x : creature.position.x.min(self.params.env_x_size - 1),
+                y : creature.position.y.min(self.params.env_y_size - 1),
+            };
+
+            let new_pos = match self.positions[target.x][target.y] {
+                SpaceStates::WallSpace | SpaceStates::CreatureSpace(_) => self.get_nearest_blank_space(target),
+                _ => Some(target),
+            };
+
+            match new_pos {
+                Some(pos) => {
+                    if DEBUG_LEVEL > 0 && pos != creature.position {
+                        println!("Creature {} relocated from {},{} to {},{}", creature.id, creature.position.x, creature.position.y, pos.x, pos.y);
+                    }
+                    creature.set_position(pos.x, pos.y);
+                    self.positions[pos.x][pos.y] = SpaceStates::CreatureSpace(creature.id);
+                    creature.tail = None;
+                    placed_creatures.push(creature);
+                },
+                None => println!("Warning: no blank space left for creature {}. It was not loaded", creature.id),
+            }
         }
 
+        self.creatures = placed_creatures;
         self.num_creatures = self.creatures.len();
+
+        // Large creatures get their tails put back right behind them (where there's room)
+        for creature_idx in 0..self.creatures.len() {
+            self.update_tail(creature_idx);
+        }
+    }
+
+    /// Move a large creature's tail to the space right behind it, if that space is free. If it isn't, the tail is pulled
+    /// in and the creature is squeezed into one space until there's room, so the tail never ends up beside or in front of
+    /// it after a turn. Normal creatures have no tail
+    fn update_tail(&mut self, creature_idx : usize) {
+        let creature = &self.creatures[creature_idx];
+        if !creature.is_large {
+            return;
+        }
+        let behind = self.get_next_position_for_creature(CreatureActions::MoveBackwards, creature.position, creature.orientation);
+        if creature.tail == Some(behind) {
+            return;
+        }
+        let blocked_by_water = self.params.enable_swimming && self.is_river(behind) && !creature.can_swim;
+        let id = creature.id;
+        self.clear_tail(creature_idx);
+        if blocked_by_water || self.positions[behind.x][behind.y] != SpaceStates::BlankSpace {
+            return;
+        }
+        self.positions[behind.x][behind.y] = SpaceStates::CreatureSpace(id);
+        self.creatures[creature_idx].tail = Some(behind);
+    }
+
+    /// Take a creature's tail (if it has one) off the board
+    fn clear_tail(&mut self, creature_idx : usize) {
+        let id = self.creatures[creature_idx].id;
+        if let Some(tail) = self.creatures[creature_idx].tail.take() {
+            if self.positions[tail.x][tail.y] == SpaceStates::CreatureSpace(id) {
+                self.positions[tail.x][tail.y] = SpaceStates::BlankSpace;
+            }
+        }
+    }
+
+    /// Get the number of spaces taken up by large creatures' tails
+    fn get_num_tails(&self) -> usize {
+        return self.creatures.iter().filter(|c| c.tail.is_some()).count();
     }
 
     /// Go through list of creatures and remove the ones that have died from the environment
@@ -695,32 +2739,62 @@ impl EnvironmentV1 {
             let creature = &self.creatures[creature_idx];
             if creature.is_dead() {
                 let pos = creature.position.clone();
+                let (id, was_killed) = (creature.id, creature.was_killed());
   
                 // Update the position map to remove this creature 
-                // if it was killed, leave behind a "fight" space just to indicate fight happened
-                if creature.was_killed() {
-                    self.positions[pos.x][pos.y] = SpaceStates::FightSpace(FIGHT_SPACE_PERSISTENCE_STEPS);
+                // and leave a decal behind to show whether it was killed or died naturally
+                self.positions[pos.x][pos.y] = SpaceStates::BlankSpace;
+                self.clear_tail(creature_idx);
+                if was_killed {
+                    self.add_decal(pos, DecalKind::Fight, FIGHT_DECAL_PERSISTENCE_STEPS);
                     self.num_kills += 1;
                 } else {
-                    self.positions[pos.x][pos.y] = SpaceStates::BlankSpace;
+                    self.add_decal(pos, DecalKind::Death, DEATH_DECAL_PERSISTENCE_STEPS);
                     self.num_natural_deaths += 1;
                 }
 
                 // Mark this dude for removal
-                to_remove.push(creature.id);
+                to_remove.push(id);
             }
         }
         
         // Remove the specified IDs from the list
+        let mut dead_lineages : Vec<usize> = Vec::new();
         for remove_id in to_remove {
             for x in 0..self.creatures.len() {
                 if self.creatures[x].id == remove_id {
-                    self.creatures.remove(x);
+                    let dead_creature = self.creatures.remove(x);
                     self.num_creatures -= 1;
+                    self.events.push(EnvEvent::Death {id : dead_creature.id, killed : dead_creature.was_killed(), position : dead_creature.position});
+                    dead_lineages.push(dead_creature.lineage_id);
+                    self.stats.record_death(&dead_creature, self.time_step);
+
+                    // Remember the behavior of novel creatures so future ones are pushed away from it
+                    if self.params.enable_novelty && dead_creature.novelty_rank >= NOVELTY_ARCHIVE_MIN_RANK {
+                        if self.novelty_archive.len() >= NOVELTY_ARCHIVE_LEN {
+                            self.novelty_archive.pop_front();
+                        }
+                        self.novelty_archive.push_back(get_behavior_descriptor(&dead_creature, self.params.novelty_descriptor));
+                    }
+
+                    // Remember it for a while
+                    if self.dead_creatures.len() >= DEAD_CREATURE_HISTORY_LEN {
+                        self.dead_creatures.pop_front();
+                    }
+                    self.dead_creatures.push_back(dead_creature);
                     break;
                 }
             }
         }
+
+        // Tribes whose last member just died
+        dead_lineages.sort_unstable();
+        dead_lineages.dedup();
+        for lineage_id in dead_lineages {
+            if !self.creatures.iter().any(|c| c.lineage_id == lineage_id) {
+                self.events.push(EnvEvent::LineageExtinct {time_step : self.time_step, lineage_id : lineage_id});
+            }
+        }
     }
 
     /// Update what each of the creatures is currently "seeing"
@@ -742,7 +2816,11 @@ impl EnvironmentV1 {
             let mut xpos = self.creatures[c_idx].position.x;
             let mut ypos = self.creatures[c_idx].position.y;
 
-            for _step in 0..MAX_CREATURE_VIEW_DISTANCE {
+            // With decal vision, the closest space marked as another lineage's territory is seen if nothing else is in view
+            let mut territory_dist : Option<usize> = None;
+
+            let view_distance = self.creatures[c_idx].view_distance + self.get_high_ground_view_bonus(self.creatures[c_idx].position);
+            for _step in 0..view_distance {
                 // Update the position we're currently looking in by checking the direction creature is facing
                 match self.creatures[c_idx].orientation {
                     CreatureOrientation::Up => {
@@ -773,8 +2851,26 @@ impl EnvironmentV1 {
 
                 // Check what type space is there
                 match self.positions[xpos][ypos] {
+
+                    // With decal vision, decals block the view like any other object
+                    SpaceStates::BlankSpace if self.params.enable_decal_vision => {
+                        if let Some(color) = self.get_decal_vision_color(xpos, ypos) {
+                            let vis : CreatureVisionState = CreatureVisionState {
+                                obj_in_view : true,
+                                dist : distance,
+                                color : CreatureColor::new_from_vec(color),
+                                space_type : SpaceStates::BlankSpace,
+                            };
+                            self.creatures[c_idx].set_vision(vis);
+                            break;
+                        }
+                        let lineage_id = self.creatures[c_idx].lineage_id;
+                        let foreign_territory = self.territory.get(xpos).and_then(|column| column.get(ypos)).is_some_and(|mark| mark.strength > 0.0 && mark.owner != lineage_id);
+                        if foreign_territory && territory_dist.is_none() {
+                            territory_dist = Some(distance);
+                        }
+                    },
                     SpaceStates::BlankSpace => {},
-                    SpaceStates::FightSpace(_ttl) => {},
 
                     // Food space is in view
                     SpaceStates::FoodSpace => {
@@ -800,6 +2896,9 @@ impl EnvironmentV1 {
                         break;
                     },
 
+                    // A large creature's own tail doesn't block its view
+                    SpaceStates::CreatureSpace(c_id) if c_id == self.creatures[c_idx].id => {},
+
                     // Another creature is in view, update the color with the creature's color
                     SpaceStates::CreatureSpace(c_id) => {
                         let target_cidx = self.get_creature_idx_from_id(c_id).unwrap();
@@ -815,7 +2914,423 @@ impl EnvironmentV1 {
                     }
                 }
             }
+
+            if let Some(dist) = territory_dist.filter(|_| !self.creatures[c_idx].vision_state.obj_in_view) {
+                let vis : CreatureVisionState = CreatureVisionState {
+                    obj_in_view : true,
+                    dist : dist,
+                    color : CreatureColor::new_from_vec(TERRITORY_VISION_COLOR),
+                    space_type : SpaceStates::BlankSpace,
+                };
+                self.creatures[c_idx].set_vision(vis);
+            }
+        }
+    }
+
+    /// Get the color a decal on the space at (x, y) is seen as with decal vision, if there's a decal there
+    fn get_decal_vision_color(&self, x : usize, y : usize) -> Option<[u8; 3]> {
+        let decal = self.decals.get(x).and_then(|column| column.get(y)).cloned().flatten()?;
+        return Some(match decal.kind {
+            DecalKind::Fight => FIGHT_DECAL_VISION_COLOR,
+            DecalKind::Birth => BIRTH_DECAL_VISION_COLOR,
+            DecalKind::Death => DEATH_DECAL_VISION_COLOR,
+        });
+    }
+
+    /// Assign every creature to a species and record the number of species. Called every `speciation_interval`
+    /// steps when speciation is on, but can be called any time (e.g. right after turning speciation on)
+    pub fn update_species(&mut self) {
+        let prev_species : Vec<usize> = self.species_representatives.iter().map(|rep| rep.species_id).collect();
+        self.num_species = assign_species(&mut self.creatures, &mut self.species_representatives, &mut self.next_species_id, self.params.speciation_threshold);
+        for species_id in prev_species {
+            if !self.species_representatives.iter().any(|rep| rep.species_id == species_id) {
+                self.events.push(EnvEvent::SpeciesExtinct {time_step : self.time_step, species_id : species_id});
+            }
+        }
+
+        if self.species_count_history.len() >= SPECIES_HISTORY_LEN {
+            self.species_count_history.pop_front();
+        }
+        self.species_count_history.push_back(self.num_species);
+    }
+
+    /// Record the number of living creatures that can swim
+    fn update_swimmer_history(&mut self) {
+        if self.swimmer_count_history.len() >= SWIMMER_HISTORY_LEN {
+            self.swimmer_count_history.pop_front();
+        }
+        self.swimmer_count_history.push_back(self.get_num_swimmers());
+    }
+
+    /// Get the number of living creatures that can swim
+    pub fn get_num_swimmers(&self) -> usize {
+        return self.creatures.iter().filter(|c| c.can_swim).count();
+    }
+
+    /// Write a Markdown report summarizing the run so far: the main parameters, how long it ran, the peak population,
+    /// what caused the extinction (if everything died), the biggest lineages and how often each action was taken
+    pub fn summary_report(&self) -> String {
+        let mut report = String::from("# Simulation Summary Report\n");
+
+        // Overview
+        let (peak_population, peak_step) = if self.creatures.len() > self.stats.peak_population {
+            (self.creatures.len(), self.time_step)
+        } else {
+            (self.stats.peak_population, self.stats.peak_population_step)
+        };
+        report.push_str("\n## Overview\n");
+        report.push_str(&format!("- Duration: {} steps\n", self.time_step));
+        report.push_str(&format!("- Peak population: {} (step {})\n", peak_population, peak_step));
+        report.push_str(&format!("- Creatures alive: {}\n", self.creatures.len()));
+        report.push_str(&format!("- Creatures ever created: {}\n", self.num_total_creatures));
+        report.push_str(&format!("- Deaths: {} killed, {} natural\n", self.num_kills, self.num_natural_deaths));
+        match self.get_extinction_cause() {
+            Some(cause) => report.push_str(&format!("- Outcome: extinct. {}\n", cause)),
+            None => report.push_str("- Outcome: still alive\n"),
+        }
+
+        // Parameters
+        let params = &self.params;
+        report.push_str("\n## Parameters\n\n| Parameter | Value |\n|---|---|\n");
+        let param_rows : [(&str, String); 9] = [
+            ("Board size", format!("{} x {}", params.env_x_size, params.env_y_size)),
+            ("Start creatures/food/walls", format!("{} / {} / {}", params.num_start_creatures, params.num_start_food, params.num_start_walls)),
+            ("New food per day", format!("{:.2}", params.avg_new_food_per_day)),
+            ("Energy per food/kill", format!("{} / {}", params.energy_per_food_piece, params.energy_per_kill)),
+            ("Starting energy", params.creature_starting_energy.to_string()),
+            ("Reproduction energy cost", params.creature_repro_energy_cost.to_string()),
+            ("Max offspring per reproduction", params.max_offspring_per_reproduce.to_string()),
+            ("Mutation probability", format!("{:.3}", params.mutation_prob)),
+            ("Mutation schedule", format!("{:?}", params.mutation_schedule)),
+        ];
+        for (name, value) in param_rows.iter() {
+            report.push_str(&format!("| {} | {} |\n", name, value));
+        }
+
+        // Biggest lineages by the number of creatures that have ever been in them
+        let mut lineages : HashMap<usize, (usize, usize)> = self.stats.lineage_deaths.iter().map(|(id, deaths)| (*id, (*deaths, 0))).collect();
+        for creature in self.creatures.iter() {
+            let (members, alive) = lineages.entry(creature.lineage_id).or_insert((0, 0));
+            *members += 1;
+            *alive += 1;
+        }
+        let mut lineages : Vec<(usize, (usize, usize))> = lineages.into_iter().collect();
+        lineages.sort_by(|a, b| b.1.0.cmp(&a.1.0).then(a.0.cmp(&b.0)));
+        report.push_str("\n## Top Lineages\n\n| Lineage | Members | Alive |\n|---|---|---|\n");
+        for (lineage_id, (members, alive)) in lineages.iter().take(REPORT_NUM_TOP_LINEAGES) {
+            report.push_str(&format!("| {} | {} | {} |\n", lineage_id, members, alive));
+        }
+
+        // Actions taken by every creature, living or dead
+        let mut action_counts = self.stats.dead_action_counts;
+        for creature in self.creatures.iter() {
+            for (total, count) in action_counts.iter_mut().zip(creature.action_counts.iter()) {
+                *total += count;
+            }
+        }
+        let total_actions = action_counts.iter().sum::<usize>().max(1);
+        report.push_str("\n## Action Distribution\n\n| Action | Count | Share |\n|---|---|---|\n");
+        for action in params.get_enabled_creature_actions() {
+            let count = action_counts[action as usize];
+            report.push_str(&format!("| {:?} | {} | {:.1}% |\n", action, count, 100.0 * count as f32 / total_actions as f32));
+        }
+        return report;
+    }
+
+    /// Describe what wiped out the creatures, based on how the last ones died. Returns None if there are creatures alive
+    /// (or there never were any)
+    fn get_extinction_cause(&self) -> Option<String> {
+        if !self.creatures.is_empty() {
+            return None;
+        }
+        let last_death_step = self.stats.lifetime_records.back()?.death_step;
+        if let Some((plague_step, num_victims)) = self.stats.last_plague {
+            if plague_step == last_death_step {
+                return Some(format!("A plague killed the last {} creatures", num_victims));
+            }
+        }
+
+        let recent_deaths : Vec<&LifetimeRecord> = self.stats.lifetime_records.iter().rev()
+            .take_while(|record| record.death_step + EXTINCTION_CAUSE_WINDOW > last_death_step).collect();
+        let num_killed = recent_deaths.iter().filter(|record| record.was_killed).count();
+        if num_killed * 2 > recent_deaths.len() {
+            return Some(format!("Killed off: {} of the last {} deaths were kills", num_killed, recent_deaths.len()));
+        }
+        return Some(format!("Starvation or old age: {} of the last {} deaths were natural", recent_deaths.len() - num_killed, recent_deaths.len()));
+    }
+
+    /// Record where each creature is (if needed) and periodically recompute novelty scores
+    fn update_novelty(&mut self) {
+        if self.params.novelty_descriptor == NoveltyDescriptor::Visitation {
+            for creature in self.creatures.iter_mut() {
+                let region = get_visit_region(creature.position.x, creature.position.y, self.params.env_x_size, self.params.env_y_size);
+                creature.record_visit(region, VISIT_GRID_SIZE * VISIT_GRID_SIZE);
+            }
+        }
+
+        if self.time_step.is_multiple_of(NOVELTY_UPDATE_INTERVAL) {
+            update_novelty_scores(&mut self.creatures, &self.novelty_archive, self.params.novelty_descriptor);
+        }
+    }
+
+    /// How far through the seasonal cycle the current time step is [0, 1). Always 0 if there are no seasons (`season_length` = 0)
+    pub fn get_season_phase(&self) -> f32 {
+        if self.params.season_length == 0 {
+            return 0.0;
+        }
+        return (self.time_step % self.params.season_length) as f32 / self.params.season_length as f32;
+    }
+
+    /// Returns true if creatures can reproduce in the current time step. Mating season starts at `mating_season_start`
+    /// and lasts for `mating_season_length` of the cycle (wrapping around the end of the cycle). Without seasons it's always mating season
+    pub fn is_mating_season(&self) -> bool {
+        if self.params.season_length == 0 || self.params.mating_season_length >= 1.0 {
+            return true;
+        }
+        return (self.get_season_phase() - self.params.mating_season_start).rem_euclid(1.0) < self.params.mating_season_length;
+    }
+
+    /// For each creature (by index), whether another creature of the same lineage within `tribe_vision_radius` can see
+    /// (food, a creature from another lineage). Each lineage gets a spatial index of its members that see something, so
+    /// the cost doesn't depend on the radius
+    fn get_tribe_sightings(&self) -> Vec<(bool, bool)> {
+        let lineages : HashMap<usize, usize> = self.creatures.iter().map(|c| (c.id, c.lineage_id)).collect();
+        let sees_food = |c : &CreatureV1| c.vision_state.obj_in_view && c.vision_state.space_type == SpaceStates::FoodSpace;
+        let sees_threat = |c : &CreatureV1| c.vision_state.obj_in_view && match c.vision_state.space_type {
+            SpaceStates::CreatureSpace(id) => lineages.get(&id).is_some_and(|lineage| *lineage != c.lineage_id),
+            _ => false,
+        };
+
+        // Board cells (indexed by `x * y_size + y`) holding creatures that see food/threats, one pair of grids per lineage
+        let (x_size, y_size) = (self.params.env_x_size, self.params.env_y_size);
+        let mut spotters : HashMap<usize, (Vec<bool>, Vec<bool>)> = HashMap::new();
+        for creature in self.creatures.iter().filter(|c| sees_food(c) || sees_threat(c)) {
+            let (food_spotters, threat_spotters) = spotters.entry(creature.lineage_id).or_insert_with(|| (vec![false; x_size * y_size], vec![false; x_size * y_size]));
+            let cell_idx = creature.position.x * y_size + creature.position.y;
+            food_spotters[cell_idx] = sees_food(creature);
+            threat_spotters[cell_idx] = sees_threat(creature);
+        }
+        let tribe_counts : HashMap<usize, (GridCounts, GridCounts)> = spotters.iter().map(|(lineage, (food_spotters, threat_spotters))| {
+            (*lineage, (
+                GridCounts::new(x_size, y_size, |x, y| food_spotters[x * y_size + y]),
+                GridCounts::new(x_size, y_size, |x, y| threat_spotters[x * y_size + y]),
+            ))
+        }).collect();
+
+        let radius = self.params.tribe_vision_radius;
+        return self.creatures.iter().map(|c| match tribe_counts.get(&c.lineage_id) {
+            Some((food_counts, threat_counts)) => {
+                // A creature doesn't count as its own tribe-mate
+                let (num_food_spotters, _) = food_counts.count_in_radius(c.position.x, c.position.y, radius);
+                let (num_threat_spotters, _) = threat_counts.count_in_radius(c.position.x, c.position.y, radius);
+                (num_food_spotters > sees_food(c) as usize, num_threat_spotters > sees_threat(c) as usize)
+            },
+            None => (false, false),
+        }).collect();
+    }
+
+    /// Returns true if the river runs through `pos`
+    pub fn is_river(&self, pos : CreaturePosition) -> bool {
+        return self.river.contains(&pos);
+    }
+
+    /// Turn any walls on the river into blank spaces so nothing dams it up
+    fn clear_river_walls(&mut self) {
+        for pos in self.river.clone() {
+            if pos.x < self.params.env_x_size && pos.y < self.params.env_y_size && self.positions[pos.x][pos.y] == SpaceStates::WallSpace {
+                self.add_blank_space(pos);
+            }
+        }
+    }
+
+    /// Wash new food in at the source, then drift each piece of food on the river one space downstream with probability
+    /// `river_flow_strength`. Food only moves into blank spaces, so it piles up behind anything in the way and at the mouth
+    fn update_river(&mut self) {
+        let mut rng = sim_rng();
+        if self.river.is_empty() {
+            return;
+        }
+
+        let source = self.river[0];
+        if self.positions[source.x][source.y] == SpaceStates::BlankSpace && rng.gen::<f32>() < self.params.river_source_food {
+            self.add_food_space(source);
+        }
+
+        // Go from the mouth up to the source so a run of food can all move in the same step
+        for idx in (0..self.river.len() - 1).rev() {
+            let (here, next) = (self.river[idx], self.river[idx + 1]);
+            if self.positions[here.x][here.y] == SpaceStates::FoodSpace && self.positions[next.x][next.y] == SpaceStates::BlankSpace
+                && rng.gen::<f32>() < self.params.river_flow_strength {
+                self.positions[here.x][here.y] = SpaceStates::BlankSpace;
+                self.positions[next.x][next.y] = SpaceStates::FoodSpace;
+            }
+        }
+    }
+
+    /// Returns true if there's an elevation layer that matches the board size
+    pub fn has_elevation(&self) -> bool {
+        return self.elevation.len() == self.params.env_x_size && self.elevation.iter().all(|column| column.len() == self.params.env_y_size);
+    }
+
+    /// Extra spaces a creature standing at `position` can see thanks to its height (0 unless elevation is on).
+    /// The highest point on the board gets the full `high_ground_view_bonus`
+    fn get_high_ground_view_bonus(&self, position : CreaturePosition) -> usize {
+        if !self.params.enable_elevation || self.params.high_ground_view_bonus == 0 || !self.has_elevation() {
+            return 0;
+        }
+        return (self.elevation[position.x][position.y] * self.params.high_ground_view_bonus as f32).round() as usize;
+    }
+
+    /// Raise (or lower, if `delta` is negative) the ground at `position`, keeping it within [0, 1]. Starts a flat
+    /// elevation layer if there isn't one yet
+    pub fn adjust_elevation(&mut self, position : CreaturePosition, delta : f32) {
+        if !self.has_elevation() {
+            self.elevation = vec![vec![0.0; self.params.env_y_size]; self.params.env_x_size];
+        }
+        let height = &mut self.elevation[position.x][position.y];
+        *height = (*height + delta).clamp(0.0, 1.0);
+    }
+
+    /// Leave a decal of the given kind on a space for `ttl` steps, replacing any decal already there
+    fn add_decal(&mut self, position : CreaturePosition, kind : DecalKind, ttl : usize) {
+        if self.decals.len() != self.params.env_x_size || self.decals.iter().any(|column| column.len() != self.params.env_y_size) {
+            self.decals = vec![vec![None; self.params.env_y_size]; self.params.env_x_size];
+        }
+        self.decals[position.x][position.y] = Some(Decal {kind : kind, ttl : ttl});
+    }
+
+    /// Count down the time left on every decal, clearing the ones that have run out
+    fn update_decals(&mut self) {
+        for space in self.decals.iter_mut().flatten() {
+            *space = match *space {
+                Some(decal) if decal.ttl > 0 => Some(Decal {ttl : decal.ttl - 1, ..decal}),
+                _ => None,
+            };
+        }
+    }
+
+    /// Make sure the territory layer matches the board size (it's empty until territory is first enabled)
+    fn resize_territory(&mut self) {
+        if self.territory.len() != self.params.env_x_size || self.territory.iter().any(|column| column.len() != self.params.env_y_size) {
+            self.territory = vec![vec![TerritoryMark::default(); self.params.env_y_size]; self.params.env_x_size];
+        }
+    }
+
+    /// Fade every territory mark by `territory_decay`, clearing the ones that have faded below TERRITORY_MIN_STRENGTH
+    fn decay_territory(&mut self) {
+        for mark in self.territory.iter_mut().flatten() {
+            mark.strength *= 1.0 - self.params.territory_decay.clamp(0.0, 1.0);
+            if mark.strength < TERRITORY_MIN_STRENGTH {
+                *mark = TerritoryMark::default();
+            }
+        }
+    }
+
+    /// Update the non-vision senses of each creature. These are only computed for creatures that
+    /// actually have the matching input neurons
+    fn update_creature_env_senses(&mut self) {
+        // Hearing counts come from spatial indexes of the board so they're cheap no matter how big the radius is
+        let mut hearing_counts : Option<(GridCounts, GridCounts)> = None;
+        if self.creatures.iter().any(|c| c.has_input(CreatureInputs::HeardCreatures) || c.has_input(CreatureInputs::HeardFood)) {
+            let positions = &self.positions;
+            hearing_counts = Some((
+                GridCounts::new(self.params.env_x_size, self.params.env_y_size, |x, y| matches!(positions[x][y], SpaceStates::CreatureSpace(_))),
+                GridCounts::new(self.params.env_x_size, self.params.env_y_size, |x, y| positions[x][y] == SpaceStates::FoodSpace),
+            ));
+        }
+
+        let tribe_sightings = if self.creatures.iter().any(|c| c.has_input(CreatureInputs::TribeSeesFood) || c.has_input(CreatureInputs::TribeSeesThreat)) {
+            self.get_tribe_sightings()
+        } else {
+            Vec::new()
+        };
+
+        for c_idx in 0..self.creatures.len() {
+            let mut senses = CreatureEnvSenses::default();
+            let pos = self.creatures[c_idx].position;
+
+            if self.creatures[c_idx].has_input(CreatureInputs::PositionX) || self.creatures[c_idx].has_input(CreatureInputs::PositionY) {
+                senses.position_x = pos.x as f32 / (self.params.env_x_size.max(2) - 1) as f32;
+                senses.position_y = pos.y as f32 / (self.params.env_y_size.max(2) - 1) as f32;
+            }
+
+            if self.creatures[c_idx].has_input(CreatureInputs::DistanceToWallAhead) {
+                senses.wall_dist_ahead = self.get_wall_dist_ahead(pos, self.creatures[c_idx].orientation);
+            }
+
+            if self.creatures[c_idx].has_input(CreatureInputs::BeaconBearing) || self.creatures[c_idx].has_input(CreatureInputs::BeaconDistance) {
+                (senses.beacon_bearing, senses.beacon_dist) = self.get_beacon_bearing_and_dist(pos, self.creatures[c_idx].orientation);
+            }
+
+            if let Some((creature_counts, food_counts)) = &hearing_counts {
+                let (num_creatures, _) = creature_counts.count_in_radius(pos.x, pos.y, self.params.hearing_radius);
+                let (num_food, _) = food_counts.count_in_radius(pos.x, pos.y, self.params.hearing_radius);
+                senses.heard_creatures = num_creatures.saturating_sub(1) as f32; // Don't count the creature itself
+                senses.heard_food = num_food as f32;
+            }
+
+            if self.creatures[c_idx].has_input(CreatureInputs::OnForeignTerritory) && !self.territory.is_empty() {
+                let mark = self.territory[pos.x][pos.y];
+                if mark.strength > 0.0 && mark.owner != self.creatures[c_idx].lineage_id {
+                    senses.foreign_territory = mark.strength;
+                }
+            }
+
+            if self.creatures[c_idx].has_input(CreatureInputs::SeasonPhase) {
+                senses.season_phase = self.get_season_phase();
+            }
+
+            if let Some((sees_food, sees_threat)) = tribe_sightings.get(c_idx) {
+                senses.tribe_sees_food = *sees_food as usize as f32;
+                senses.tribe_sees_threat = *sees_threat as usize as f32;
+            }
+
+            self.creatures[c_idx].set_env_senses(senses);
+        }
+    }
+
+    /// Get the bearing and distance from the specified position to the beacon. Bearing is the angle between
+    /// the direction the creature faces and the beacon normalized to [-1, 1] (negative = to the left, positive =
+    /// to the right, +/-1 = directly behind). Both are VISION_NEURON_INVALID_VAL if there is no beacon
+    fn get_beacon_bearing_and_dist(&self, position : CreaturePosition, orientation : CreatureOrientation) -> (f32, f32) {
+        let beacon = match self.beacon {
+            None => return (VISION_NEURON_INVALID_VAL, VISION_NEURON_INVALID_VAL),
+            Some(b) => b,
+        };
+
+        let dx = beacon.x as f32 - position.x as f32;
+        let dy = beacon.y as f32 - position.y as f32;
+        let (face_x, face_y) : (f32, f32) = match orientation {
+            CreatureOrientation::Up => (0.0, -1.0),
+            CreatureOrientation::Down => (0.0, 1.0),
+            CreatureOrientation::Left => (-1.0, 0.0),
+            CreatureOrientation::Right => (1.0, 0.0),
+        };
+
+        let dot = face_x * dx + face_y * dy;
+        let cross = face_x * dy - face_y * dx;
+        let bearing = cross.atan2(dot) / std::f32::consts::PI;
+        let dist = (dx * dx + dy * dy).sqrt();
+        return (bearing, dist);
+    }
+
+    /// Get the distance to the nearest wall in the direction of `orientation`. This follows the same
+    /// wrap-around at the board edges that movement does. Returns VISION_NEURON_INVALID_VAL if there's
+    /// no wall anywhere in that line
+    fn get_wall_dist_ahead(&self, position : CreaturePosition, orientation : CreatureOrientation) -> f32 {
+        let max_dist = match orientation {
+            CreatureOrientation::Up | CreatureOrientation::Down => self.params.env_y_size,
+            CreatureOrientation::Left | CreatureOrientation::Right => self.params.env_x_size,
+        };
+
+        let mut cur_pos = position;
+        for dist in 1..max_dist {
+            cur_pos = self.get_next_position_for_creature(CreatureActions::MoveForwards, cur_pos, orientation);
+            if self.positions[cur_pos.x][cur_pos.y] == SpaceStates::WallSpace {
+                return dist as f32;
+            }
         }
+        return VISION_NEURON_INVALID_VAL;
     }
 
     /// Given the current position and action
@@ -881,9 +3396,9 @@ impl EnvironmentV1 {
         return next_position;
     }
 
-    /// Get a random blank spot on the board
-    fn get_rand_blank_space(&self) -> CreaturePosition {
-        let mut rng = rand::thread_rng();
+    /// Get a random blank spot on the board. Returns None if the board is full
+    fn get_rand_blank_space(&self) -> Option<CreaturePosition> {
+        let mut rng = sim_rng();
         let mut done : bool = false;
         let mut found_x: usize = 0;
         let mut found_y: usize = 0;
@@ -906,22 +3421,30 @@ impl EnvironmentV1 {
                 },
             }
 
-            // Check loop watchdog
-            if attempts > 10_000 {
-                panic!("Error! No blank spaces left in the simulation!");
+            // If random guesses keep missing, the board is nearly (or completely) full, so pick from the
+            // blank spaces that are left instead
+            if attempts > self.params.env_x_size * self.params.env_y_size {
+                let blank_spaces : Vec<CreaturePosition> = (0..self.params.env_x_size)
+                    .flat_map(|x| (0..self.params.env_y_size).map(move |y| CreaturePosition {x : x, y : y}))
+                    .filter(|pos| self.positions[pos.x][pos.y] == SpaceStates::BlankSpace)
+                    .collect();
+                if blank_spaces.is_empty() {
+                    return None;
+                }
+                return Some(blank_spaces[rng.gen_range(0..blank_spaces.len())]);
             }
         }
 
-        return CreaturePosition {
+        return Some(CreaturePosition {
             x : found_x,
             y : found_y,
-        }
+        });
     }
 
     /// Get a random blank spot centered at the specified position. This is used during creature reproduction
     /// to determine where offspring should be placed
     fn get_blank_space_at_point(&self, target_pos : CreaturePosition) -> Option<CreaturePosition> {
-        let mut rng = rand::thread_rng();
+        let mut rng = sim_rng();
         let mut done : bool = false;
         let mut found_x: usize = 0;
         let mut found_y: usize = 0;
@@ -976,8 +3499,181 @@ impl EnvironmentV1 {
         });
     }
 
-    /// Get the index of the creature into the self.creatures array from creature ID
-    pub fn get_creature_idx_from_id(&self, creature_id : usize) -> Result<usize, &str> {
+    /// Get the blank space closest to the specified position, searching outwards in square "rings"
+    /// around it. Returns `None` if there are no blank spaces on the board at all
+    #[cfg_attr(not(feature = "file_io"), allow(dead_code))]
+    fn get_nearest_blank_space(&self, target_pos : CreaturePosition) -> Option<CreaturePosition> {
+        let max_radius = self.params.env_x_size.max(self.params.env_y_size) as isize;
+        for radius in 0..=max_radius {
+            for x_diff in -radius..=radius {
+                for y_diff in -radius..=radius {
+                    // Only check the outer edge of the ring, the inside was checked on previous passes
+                    if x_diff.abs() != radius && y_diff.abs() != radius {
+                        continue;
+                    }
+
+                    let x = target_pos.x as isize + x_diff;
+                    let y = target_pos.y as isize + y_diff;
+                    if x < 0 || y < 0 || x >= self.params.env_x_size as isize || y >= self.params.env_y_size as isize {
+                        continue;
+                    }
+
+                    if self.positions[x as usize][y as usize] == SpaceStates::BlankSpace {
+                        return Some(CreaturePosition {x : x as usize, y : y as usize});
+                    }
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Get a stable hash of the board and creature state. Two environments that hash the same are (almost certainly)
+    /// in the same state, so this can be used to cheaply check whether two runs have diverged. Only simulation
+    /// state is hashed: things like brain activations, decals, histories and the recorded stats are left out
+    #[allow(dead_code)]
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = Fnv1aHasher::new();
+        hasher.write_usize(self.time_step);
+        hasher.write_usize(self.params.env_x_size);
+        hasher.write_usize(self.params.env_y_size);
+
+        // Board. Each space is hashed as a type tag followed by its argument (if any)
+        for column in self.positions.iter() {
+            for space in column.iter() {
+                let (tag, arg) = match space {
+                    SpaceStates::BlankSpace => (0, 0),
+                    SpaceStates::CreatureSpace(id) => (1, *id),
+                    SpaceStates::FoodSpace => (2, 0),
+                    SpaceStates::WallSpace => (3, 0),
+                };
+                hasher.write_usize(tag);
+                hasher.write_usize(arg);
+            }
+        }
+        match self.beacon {
+            Some(beacon) => {
+                hasher.write_usize(1);
+                hasher.write_usize(beacon.x);
+                hasher.write_usize(beacon.y);
+            },
+            None => hasher.write_usize(0),
+        }
+
+        // River (empty unless it's enabled)
+        for pos in self.river.iter() {
+            hasher.write_usize(pos.x);
+            hasher.write_usize(pos.y);
+        }
+
+        // Elevation (empty unless it's enabled)
+        for height in self.elevation.iter().flatten() {
+            hasher.write_f32(*height);
+        }
+
+        // Territory (empty unless it's enabled)
+        for mark in self.territory.iter().flatten() {
+            hasher.write_usize(mark.owner);
+            hasher.write_f32(mark.strength);
+        }
+
+        // Creatures (in update order, since that affects how the sim plays out)
+        for creature in self.creatures.iter() {
+            hasher.write_usize(creature.id);
+            hasher.write_usize(creature.is_alive as usize);
+            hasher.write_usize(creature.position.x);
+            hasher.write_usize(creature.position.y);
+            hasher.write_usize(creature.orientation as usize);
+            hasher.write_usize(creature.energy);
+            hasher.write_usize(creature.age);
+            hasher.write_usize(creature.generation);
+            hasher.write_usize(creature.species_id);
+            hasher.write_usize(creature.gestation_steps_remaining);
+            hasher.write_usize(creature.view_distance);
+            hasher.write_usize(creature.pending_offspring);
+            if self.params.enable_swimming {
+                hasher.write_usize(creature.can_swim as usize);
+            }
+            if self.params.enable_large_bodies {
+                hasher.write_usize(creature.is_large as usize);
+            }
+            match creature.infection {
+                InfectionState::Healthy => hasher.write_usize(0),
+                InfectionState::Infected(steps_left) => hasher.write_usize(1 + steps_left),
+                InfectionState::Immune => hasher.write_usize(usize::MAX),
+            }
+            for val in creature.brain.get_genome() {
+                hasher.write_f32(val);
+            }
+        }
+
+        return hasher.finish();
+    }
+
+    /// Get the creature with the given ID, if it's still alive
+    ///
+    /// ```
+    /// use evolution_sim::environment::*;
+    ///
+    /// let env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 1);
+    /// let first_id = env.creatures[0].id;
+    /// assert_eq!(env.get_creature(first_id).unwrap().id, first_id);
+    /// assert!(env.get_creature(usize::MAX).is_none());
+    /// ```
+    pub fn get_creature(&self, creature_id : usize) -> Option<&CreatureV1> {
+        return self.creatures.iter().find(|c| c.id == creature_id);
+    }
+
+    /// Work out what would happen if creature `creature_id` took `action` right now, without changing anything. This
+    /// follows the same rules as `advance_step`, so kills depend on what the creature saw at the end of the last step.
+    /// Energy isn't taken into account, so a creature that can't afford the action would die instead
+    pub fn preview_action(&self, creature_id : usize, action : CreatureActions) -> PreviewResult {
+        let creature = match self.get_creature(creature_id) {
+            Some(creature) if !creature.is_dead() => creature,
+            _ => return PreviewResult::NoCreature,
+        };
+
+        return match action {
+            CreatureActions::Kill => {
+                let vision = &creature.vision_state;
+                match vision.space_type {
+                    SpaceStates::CreatureSpace(victim_id) if vision.obj_in_view && vision.dist == 1 && victim_id != creature_id => {
+                        match self.get_creature(victim_id) {
+                            Some(victim) if !victim.is_dead() => PreviewResult::Kill(victim_id),
+                            _ => PreviewResult::NoEffect,
+                        }
+                    },
+                    _ => PreviewResult::NoEffect,
+                }
+            },
+            CreatureActions::MoveBackwards |
+            CreatureActions::MoveForwards |
+            CreatureActions::MoveLeft |
+            CreatureActions::MoveRight => {
+                let next_position = self.get_next_position_for_creature(action, creature.position, creature.orientation);
+                let blocked_by_water = self.params.enable_swimming && self.is_river(next_position) && !creature.can_swim;
+                match self.positions[next_position.x][next_position.y] {
+                    _ if next_position == creature.position => PreviewResult::NoEffect,
+                    _ if blocked_by_water => PreviewResult::Blocked,
+                    SpaceStates::BlankSpace if self.beacon == Some(next_position) => PreviewResult::ReachBeacon(next_position),
+                    SpaceStates::BlankSpace => PreviewResult::Move(next_position),
+                    SpaceStates::FoodSpace => PreviewResult::Eat(next_position),
+                    SpaceStates::WallSpace | SpaceStates::CreatureSpace(_) => PreviewResult::Blocked,
+                }
+            },
+            CreatureActions::Reproduce if !creature.is_pregnant() && self.is_mating_season() => PreviewResult::Reproduce,
+            CreatureActions::MarkTerritory if self.params.enable_territory => PreviewResult::MarkTerritory,
+            _ => PreviewResult::NoEffect,
+        };
+    }
+
+    /// Get the action creature `creature_id`'s brain would decide on right now, without changing the creature
+    pub fn get_intended_action(&self, creature_id : usize) -> Option<CreatureActions> {
+        let mut creature = self.get_creature(creature_id)?.clone();
+        return Some(creature.decide_action());
+    }
+
+    /// Get the index of the creature into the self.creatures array from creature ID
+    pub fn get_creature_idx_from_id(&self, creature_id : usize) -> Result<usize, &str> {
         for creature_idx in 0..self.creatures.len() {
             if self.creatures[creature_idx].id == creature_id {
                 return Ok(creature_idx);
@@ -986,5 +3682,1620 @@ impl EnvironmentV1 {
         return Err("Invalid creature id");
     }
 
-} 
+}
+
+/// Draw text in the tiny stats footer font with its top left corner at (x, y). Text that doesn't fit the width is cut off
+#[cfg(feature = "image")]
+fn draw_footer_text(img : &mut image::RgbImage, text : &str, x : u32, y : u32) {
+    for (char_idx, c) in text.chars().enumerate() {
+        let char_x = x + char_idx as u32 * (FOOTER_GLYPH_WIDTH + 1) * FOOTER_PIXEL_SIZE;
+        for (row, bits) in get_footer_glyph(c).iter().enumerate() {
+            for col in 0..FOOTER_GLYPH_WIDTH {
+                if bits & (1 << (FOOTER_GLYPH_WIDTH - 1 - col)) != 0 {
+                    fill_image_rect(img, char_x + col * FOOTER_PIXEL_SIZE, y + row as u32 * FOOTER_PIXEL_SIZE, FOOTER_PIXEL_SIZE, FOOTER_PIXEL_SIZE, IMAGE_FOOTER_TEXT_COLOR);
+                }
+            }
+        }
+    }
+}
+
+/// Fill a rectangle of an image with a color. Any part outside the image is cut off
+#[cfg(feature = "image")]
+fn fill_image_rect(img : &mut image::RgbImage, x : u32, y : u32, width : u32, height : u32, color : [u8; 3]) {
+    for px in x..(x + width).min(img.width()) {
+        for py in y..(y + height).min(img.height()) {
+            img.put_pixel(px, py, image::Rgb(color));
+        }
+    }
+}
+
+/// Draw a one pixel wide line between two points of an image
+#[cfg(feature = "image")]
+fn draw_image_line(img : &mut image::RgbImage, start : (f32, f32), end : (f32, f32), color : [u8; 3]) {
+    let num_steps = (end.0 - start.0).abs().max((end.1 - start.1).abs()).ceil().max(1.0) as usize;
+    for step in 0..=num_steps {
+        let t = step as f32 / num_steps as f32;
+        let (x, y) = (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t);
+        if x >= 0.0 && y >= 0.0 && (x as u32) < img.width() && (y as u32) < img.height() {
+            img.put_pixel(x as u32, y as u32, image::Rgb(color));
+        }
+    }
+}
+
+/// Get the rows of a character in the tiny stats footer font (3 bits per row, most significant bit on the left).
+/// Only the characters the footer and creature cards use are drawn, anything else is blank
+#[cfg(feature = "image")]
+fn get_footer_glyph(c : char) -> [u8; FOOTER_GLYPH_HEIGHT as usize] {
+    return match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'N' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        _ => [0; FOOTER_GLYPH_HEIGHT as usize],
+    };
+}
+
+/// Get the position of (x, y) along a Z-order (Morton) curve, made by interleaving the bits of x and y.
+/// Points that are close on the board are usually close on the curve
+pub fn get_morton_code(x : usize, y : usize) -> u64 {
+    let mut code : u64 = 0;
+    for bit in 0..32 {
+        code |= (((x as u64) >> bit) & 1) << (2 * bit);
+        code |= (((y as u64) >> bit) & 1) << (2 * bit + 1);
+    }
+    return code;
+}
+
+/// Get the current time in seconds since the Unix epoch (0 if the clock is set before it)
+fn get_unix_time() -> u64 {
+    return std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_secs());
+}
+
+/// Checksum of a piece of JSON as it was written (hex string of the FNV-1a hash). Whitespace outside of strings is
+/// skipped, so pretty-printed JSON has the same checksum as the compact JSON it was made from
+#[cfg(feature = "json")]
+fn get_json_checksum(json : &str) -> String {
+    let mut hasher = Fnv1aHasher::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in json.bytes() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+        } else if byte == b'"' {
+            in_string = true;
+        } else if byte.is_ascii_whitespace() {
+            continue;
+        }
+        hasher.write_bytes(&[byte]);
+    }
+    return format!("{:016x}", hasher.finish());
+}
+
+/// Read the board out of a save. Fight spaces from older saves are turned into blank spaces
+fn deserialize_positions<'de, D : serde::Deserializer<'de>>(deserializer : D) -> Result<Vec<Vec<SpaceStates>>, D::Error> {
+    let saved : Vec<Vec<SavedSpaceState>> = Vec::deserialize(deserializer)?;
+    return Ok(saved.into_iter().map(|column| column.into_iter().map(|space| match space {
+        SavedSpaceState::BlankSpace | SavedSpaceState::FightSpace(_) => SpaceStates::BlankSpace,
+        SavedSpaceState::CreatureSpace(id) => SpaceStates::CreatureSpace(id),
+        SavedSpaceState::FoodSpace => SpaceStates::FoodSpace,
+        SavedSpaceState::WallSpace => SpaceStates::WallSpace,
+    }).collect()).collect());
+}
+
+
+#[cfg(test)]
+mod environment_test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_render_image_with_stats() {
+        let env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(40, 20), 1);
+        let board = env.render_image(2);
+        let img = env.render_image_with_stats(2);
+        assert_eq!(img.width(), board.width());
+        assert!(img.height() > board.height());
+
+        // The board is copied over untouched and the footer has some text in it
+        assert_eq!(img.get_pixel(5, 5), board.get_pixel(5, 5));
+        assert!(img.enumerate_pixels().any(|(_, y, p)| y >= board.height() && p.0 == IMAGE_FOOTER_TEXT_COLOR));
+    }
+
+    #[test]
+    fn test_immigration() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.num_start_creatures = 10;
+        params.immigration_rate = 2.0;
+        params.immigrant_source = ImmigrantSource::HallOfFame;
+        let mut env = EnvironmentV1::new_seeded(&params, 6);
+        let _ = env.run_n_steps(30);
+
+        // Immigrants arrive on the edges of the board, each founding its own lineage
+        let arrivals : Vec<(usize, CreaturePosition)> = env.events.take_all().iter().filter_map(|e| match e {
+            EnvEvent::Immigration {id, position, ..} => Some((*id, *position)),
+            _ => None,
+        }).collect();
+        assert!(!arrivals.is_empty());
+        assert_eq!(arrivals.len(), env.stats.immigrants.len());
+        for (id, position) in arrivals.iter() {
+            assert!(position.x == 0 || position.y == 0 || position.x == 15 || position.y == 15);
+            assert!(env.stats.immigrants.iter().any(|record| record.lineage_id == *id));
+        }
+
+        // Every member of an immigrant lineage (alive or dead) is counted
+        let lineages = env.stats.get_immigrant_lineage_stats(&env.creatures);
+        let num_alive : usize = lineages.iter().map(|l| l.num_alive).sum();
+        assert_eq!(num_alive, env.creatures.iter().filter(|c| lineages.iter().any(|l| l.lineage_id == c.lineage_id)).count());
+        assert!(lineages.windows(2).all(|pair| pair[0].num_alive >= pair[1].num_alive));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn test_render_creature_card() {
+        let env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(20, 20), 1);
+        let creature = &env.creatures[0];
+        let card = env.render_creature_card(creature.id).unwrap();
+        assert_eq!(card.width(), CARD_WIDTH);
+
+        // Swatch in the creature's color, the fingerprint beside it, text, and a brain with connections of both signs
+        assert_eq!(card.get_pixel(FOOTER_PADDING, FOOTER_PADDING).0, creature.color.get_as_vec());
+        let fingerprint = get_genome_fingerprint(&creature.brain);
+        assert!(card.pixels().any(|p| p.0 == fingerprint.color.get_as_vec()));
+        assert!(card.pixels().any(|p| p.0 == IMAGE_FOOTER_TEXT_COLOR));
+        let brain_pixels : Vec<[u8; 3]> = card.enumerate_pixels().filter(|(_, y, _)| *y >= card.height() - CARD_BRAIN_HEIGHT).map(|(_, _, p)| p.0).collect();
+        assert!(brain_pixels.iter().any(|p| p[2] > 0 && p[0] == 0));
+        assert!(brain_pixels.iter().any(|p| p[0] > 0 && p[1] < p[0]));
+
+        assert!(env.render_creature_card(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_territory() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.enable_territory = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        assert!(env.creatures[0].has_input(CreatureInputs::OnForeignTerritory));
+
+        // Another lineage marks the space under the first creature
+        env.resize_territory();
+        let pos = env.creatures[0].position;
+        let foreign_lineage = env.creatures[1].lineage_id;
+        env.territory[pos.x][pos.y] = TerritoryMark {owner : foreign_lineage, strength : 1.0};
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.foreign_territory, 1.0);
+
+        // Marks fade and eventually disappear
+        env.decay_territory();
+        assert!(env.territory[pos.x][pos.y].strength < 1.0);
+        env.params.territory_decay = 1.0;
+        env.decay_territory();
+        assert_eq!(env.territory[pos.x][pos.y], TerritoryMark::default());
+    }
+
+    #[test]
+    fn test_tribe_sightings() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.tribe_vision_radius = 16;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        for creature in env.creatures.iter_mut() {
+            creature.vision_state.obj_in_view = false;
+        }
+
+        // Creature 1 sees food and shares a lineage with creature 0, but not with creature 2
+        env.creatures[1].lineage_id = env.creatures[0].lineage_id;
+        env.creatures[1].vision_state.obj_in_view = true;
+        env.creatures[1].vision_state.space_type = SpaceStates::FoodSpace;
+        let sightings = env.get_tribe_sightings();
+        assert_eq!(sightings[0], (true, false));
+        assert_eq!(sightings[1], (false, false));
+        assert_eq!(sightings[2], (false, false));
+    }
+
+    #[test]
+    fn test_mating_season() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.season_length = 100;
+        params.mating_season_start = 0.9;
+        params.mating_season_length = 0.2;
+        let mut env = EnvironmentV1::new_seeded(&params, 1);
+
+        // Mating season wraps around the end of the cycle
+        let in_season : Vec<bool> = [0, 5, 15, 50, 89, 90, 95, 105].iter().map(|step| {
+            env.time_step = *step;
+            env.is_mating_season()
+        }).collect();
+        assert_eq!(in_season, vec![true, true, false, false, false, true, true, true]);
+
+        env.time_step = 125;
+        assert_eq!(env.get_season_phase(), 0.25);
+    }
+
+    #[test]
+    fn test_catastrophes() {
+        let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 2);
+        let num_creatures = env.creatures.len();
+        env.events.take_all();
+
+        assert_eq!(env.apply_catastrophe(CatastropheKind::Plague, 1.0), num_creatures);
+        assert!(env.creatures.is_empty());
+        assert_eq!(env.num_creatures, 0);
+
+        let num_walls = env.num_walls;
+        assert_eq!(env.apply_catastrophe(CatastropheKind::WallCollapse, 1.0), num_walls);
+        assert_eq!(env.num_walls, 0);
+        assert_eq!(env.apply_catastrophe(CatastropheKind::FoodDieOff, 0.0), 0);
+
+        let events = env.events.take_all();
+        assert_eq!(events.iter().filter(|e| matches!(e, EnvEvent::Death {..})).count(), num_creatures);
+        assert!(events.contains(&EnvEvent::Catastrophe {time_step : 0, kind : CatastropheKind::WallCollapse, num_affected : num_walls}));
+    }
+
+    #[test]
+    fn test_disease_spreads() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.enable_disease = true;
+        params.infection_prob = 1.0;
+        params.infection_duration = 2;
+        let mut env = EnvironmentV1::new_seeded(&params, 4);
+        for (id, x) in [(0, 2), (1, 3), (2, 6)] {
+            let mut creature = CreatureV1::new(id, &CreatureParams::new());
+            creature.set_position(x, 2);
+            env.add_blank_space(creature.position);
+            env.add_creature(creature);
+        }
+        env.creatures[0].infect(params.infection_duration);
+
+        // Only the neighbor catches it
+        env.update_disease();
+        assert_eq!(env.num_infections, 1);
+        assert!(env.creatures[1].is_infected());
+        assert_eq!(env.creatures[2].infection, InfectionState::Healthy);
+
+        // Both get better and can't catch it again
+        env.update_disease();
+        env.update_disease();
+        assert_eq!(env.num_recoveries, 2);
+        assert_eq!(env.get_infection_counts(), (0, 2));
+        assert!(env.creatures[0].energy < DEFAULT_ENERGY_LEVEL);
+    }
+
+    #[test]
+    fn test_metabolic_cost() {
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 8;
+        let mut free_env = EnvironmentV1::new_seeded(&params, 11);
+        params.enable_metabolic_cost = true;
+        params.metabolic_energy_cost = 1.0;
+        let mut paying_env = EnvironmentV1::new_seeded(&params, 11);
+
+        // Same creatures doing the same things, but thinking costs a whole energy per neuron
+        free_env.advance_step();
+        paying_env.advance_step();
+        for (free, paying) in free_env.creatures.iter().zip(paying_env.creatures.iter()) {
+            assert_eq!(free.id, paying.id);
+            assert_eq!(paying.energy, free.energy.saturating_sub(free.brain.get_num_neurons()));
+        }
+    }
+
+    #[test]
+    fn test_compute_budget() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.view_energy_cost = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        let mut creature = run_seeded(&mut Some(StdRng::seed_from_u64(5)), || CreatureV1::new(0, &CreatureParams::new()));
+        creature.set_position(3, 3);
+        creature.energy = DEFAULT_MIN_REPRODUCE_ENERGY;     // Not enough to reproduce instead of doing what it decided
+        env.add_creature(creature);
+
+        // The brain costs three steps' worth of the budget
+        let cost = env.creatures[0].brain.get_compute_cost();
+        env.params.brain_compute_budget = cost.div_ceil(3);
+        assert_eq!(cost.div_ceil(env.params.brain_compute_budget), 3);
+
+        // It decides on the first step, stays put while it thinks for two steps, then carries out the decision
+        env.advance_step();
+        let decided = env.creatures[0].pending_action.unwrap();
+        assert!(decided != CreatureActions::Stay);
+        for thinking_steps_left in [2, 1] {
+            assert_eq!(env.creatures[0].thinking_steps_left, thinking_steps_left);
+            assert_eq!(env.creatures[0].last_action, CreatureActions::Stay);
+            assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 3});
+            env.advance_step();
+        }
+        assert!(env.creatures[0].pending_action.is_none());
+        assert_eq!(env.creatures[0].last_action, decided);
+
+        // Without a budget it acts on the same step it decides
+        env.params.brain_compute_budget = 0;
+        env.advance_step();
+        assert!(env.creatures[0].pending_action.is_none());
+    }
+
+    #[test]
+    fn test_action_delay() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.action_delay_steps = 2;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(3, 3);
+        env.add_creature(creature);
+
+        // Force a move forwards, which only happens two steps after it was decided on
+        env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+        env.creatures[0].thinking_steps_left = 1;
+        env.advance_step();
+        env.advance_step();
+        assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 3});
+        assert_eq!(env.creatures[0].delayed_actions.front(), Some(&CreatureActions::MoveForwards));
+        env.advance_step();
+        assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 2});
+        assert_eq!(env.creatures[0].delayed_actions.len(), 2);
+    }
+
+    #[test]
+    fn test_full_board() {
+        // More walls than spaces fill the board without panicking
+        let mut params = EnvironmentParams::with_size(4, 4);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 20;
+        params.avg_new_food_per_day = 5.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        assert!(env.positions.iter().flatten().all(|space| *space == SpaceStates::WallSpace));
+        assert_eq!(env.get_rand_blank_space(), None);
+
+        // Food stops spawning once the board is full
+        env.advance_step();
+        assert_eq!(env.num_food, 0);
+
+        // The last blank space left is still found
+        let last_space = CreaturePosition {x : 1, y : 2};
+        env.positions[last_space.x][last_space.y] = SpaceStates::BlankSpace;
+        assert_eq!(env.get_rand_blank_space(), Some(last_space));
+    }
+
+    #[test]
+    fn test_rest() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.view_energy_cost = 0.0;
+        assert!(!params.get_enabled_creature_actions().contains(&CreatureActions::Rest));
+        params.enable_rest = true;
+        assert!(params.get_enabled_creature_actions().contains(&CreatureActions::Rest));
+
+        // Creature 0 faces up at creature 1, which rests while creature 0 waits and then kills it
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        for (id, y) in [(0, 3), (1, 2)] {
+            let mut creature = CreatureV1::new(id, &CreatureParams::new());
+            creature.set_position(3, y);
+            env.add_creature(creature);
+        }
+        env.update_creature_vision();
+        let force_actions = |env : &mut EnvironmentV1, killer_action : CreatureActions| {
+            for (idx, action) in [(0, killer_action), (1, CreatureActions::Rest)] {
+                env.creatures[idx].pending_action = Some(action);
+                env.creatures[idx].thinking_steps_left = 1;
+            }
+        };
+
+        // Resting recovers energy
+        let (killer_energy, victim_energy) = (env.creatures[0].energy, env.creatures[1].energy);
+        force_actions(&mut env, CreatureActions::Stay);
+        env.advance_step();
+        assert_eq!(env.creatures[0].energy, killer_energy);
+        assert_eq!(env.creatures[1].energy, victim_energy + DEFAULT_REST_ENERGY_GAIN);
+
+        // Killing a resting creature only costs the resting kill cost
+        let killer_energy = env.creatures[0].energy;
+        force_actions(&mut env, CreatureActions::Kill);
+        env.advance_step();
+        assert_eq!(env.creatures.len(), 1);
+        assert_eq!(env.creatures[0].energy, killer_energy - DEFAULT_KILL_RESTING_ENERGY_COST + params.energy_per_kill);
+    }
+
+    #[test]
+    fn test_preview_action() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        for (id, y) in [(0, 3), (1, 2)] {
+            let mut creature = CreatureV1::new(id, &CreatureParams::new());
+            creature.set_position(3, y);
+            env.add_creature(creature);
+        }
+        env.add_food_space(CreaturePosition {x : 2, y : 3});
+        env.add_wall_space(CreaturePosition {x : 4, y : 3});
+        env.update_creature_vision();
+
+        // Creature 0 faces up at creature 1, with food to its left and a wall to its right
+        let hash = env.state_hash();
+        assert_eq!(env.preview_action(0, CreatureActions::Kill), PreviewResult::Kill(1));
+        assert_eq!(env.preview_action(0, CreatureActions::MoveForwards), PreviewResult::Blocked);
+        assert_eq!(env.preview_action(0, CreatureActions::MoveLeft), PreviewResult::Eat(CreaturePosition {x : 2, y : 3}));
+        assert_eq!(env.preview_action(0, CreatureActions::MoveRight), PreviewResult::Blocked);
+        assert_eq!(env.preview_action(0, CreatureActions::MoveBackwards), PreviewResult::Move(CreaturePosition {x : 3, y : 4}));
+        assert_eq!(env.preview_action(0, CreatureActions::RotateCW), PreviewResult::NoEffect);
+        assert_eq!(env.preview_action(1, CreatureActions::Kill), PreviewResult::NoEffect);
+        assert_eq!(env.preview_action(7, CreatureActions::Stay), PreviewResult::NoCreature);
+        assert!(env.get_intended_action(0).is_some());
+        assert_eq!(env.state_hash(), hash);
+    }
+
+    #[test]
+    fn test_one_hot_inputs() {
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 5;
+        params.one_hot_categorical_inputs = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 2);
+
+        // The two categorical inputs are replaced by one neuron per orientation and per action
+        let inputs = params.get_enabled_creature_inputs();
+        let num_actions = params.get_enabled_creature_actions().len();
+        assert_eq!(inputs.len(), ENABLED_CREATURE_INPUTS.len() - 2 + NUM_ORIENTATION_STATES + num_actions);
+        assert!(!inputs.contains(&CreatureInputs::LastAction) && !inputs.contains(&CreatureInputs::Orientation));
+        assert!(env.creatures[0].has_input(CreatureInputs::OrientationIs(CreatureOrientation::Left)));
+        assert!(env.creatures[0].has_input(CreatureInputs::LastActionIs(CreatureActions::Kill)));
+
+        env.advance_step();
+        assert_eq!(env.creatures[0].brain.get_input_saliency().1.len(), inputs.len());
+    }
+
+    #[test]
+    fn test_position_and_wall_inputs() {
+        let mut params = EnvironmentParams::with_size(11, 11);
+        params.num_start_creatures = 1;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.enable_position_inputs = true;
+        params.enable_wall_distance_input = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 4);
+        assert!(env.creatures[0].has_input(CreatureInputs::PositionX) && env.creatures[0].has_input(CreatureInputs::DistanceToWallAhead));
+
+        // Creature at (3, 8) facing right, with a wall 4 spaces ahead of it
+        let old_pos = env.creatures[0].position;
+        env.positions[old_pos.x][old_pos.y] = SpaceStates::BlankSpace;
+        env.creatures[0].set_position(3, 8);
+        env.creatures[0].orientation = CreatureOrientation::Right;
+        env.positions[3][8] = SpaceStates::CreatureSpace(env.creatures[0].id);
+        env.add_wall_space(CreaturePosition {x : 7, y : 8});
+
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.position_x, 0.3);
+        assert_eq!(env.creatures[0].env_senses.position_y, 0.8);
+        assert_eq!(env.creatures[0].env_senses.wall_dist_ahead, 4.0);
+
+        // Looking the other way wraps around the edge of the board to the same wall
+        env.creatures[0].orientation = CreatureOrientation::Left;
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.wall_dist_ahead, 7.0);
+
+        // No wall anywhere in the column
+        env.creatures[0].orientation = CreatureOrientation::Up;
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.wall_dist_ahead, VISION_NEURON_INVALID_VAL);
+    }
+
+    #[test]
+    fn test_beacon() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.enable_beacon = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        let mut creature = CreatureV1::new_with_inputs(0, &CreatureParams::new(), &params.get_enabled_creature_inputs());
+        creature.set_position(3, 3);
+        creature.energy = DEFAULT_MIN_REPRODUCE_ENERGY;
+        env.add_creature(creature);
+
+        // Creature faces up. The beacon is 2 spaces to its right, then straight ahead of it
+        env.beacon = Some(CreaturePosition {x : 5, y : 3});
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.beacon_bearing, 0.5);
+        assert_eq!(env.creatures[0].env_senses.beacon_dist, 2.0);
+        env.beacon = Some(CreaturePosition {x : 3, y : 2});
+        env.update_creature_env_senses();
+        assert_eq!(env.creatures[0].env_senses.beacon_bearing, 0.0);
+        assert_eq!(env.creatures[0].env_senses.beacon_dist, 1.0);
+
+        // Walking onto it gives the reward (on top of whatever the same step costs without a beacon) and moves it
+        let mut no_beacon = env.clone();
+        no_beacon.beacon = None;
+        for env in [&mut env, &mut no_beacon] {
+            env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+            env.creatures[0].thinking_steps_left = 1;
+            env.advance_step();
+            assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 2});
+        }
+        assert_eq!(env.creatures[0].energy, no_beacon.creatures[0].energy + params.energy_per_beacon_reach);
+        assert_eq!(env.creatures[0].last_action_result, ActionResult::ReachedBeacon);
+        assert_eq!(env.num_beacon_reaches, 1);
+        let new_beacon = env.beacon.unwrap();
+        assert!(new_beacon != CreaturePosition {x : 3, y : 2});
+        assert_eq!(env.positions[new_beacon.x][new_beacon.y], SpaceStates::BlankSpace);
+    }
+
+    #[test]
+    fn test_move_blocked_input() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.enable_move_blocked_input = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        let mut creature = CreatureV1::new_with_inputs(0, &CreatureParams::new(), &params.get_enabled_creature_inputs());
+        creature.set_position(3, 3);
+        creature.energy = DEFAULT_MIN_REPRODUCE_ENERGY;
+        env.add_creature(creature);
+        env.add_wall_space(CreaturePosition {x : 3, y : 2});
+        assert!(env.creatures[0].has_input(CreatureInputs::LastMoveBlocked));
+
+        // Walking into the wall ahead is blocked, then backing away from it is not
+        let force_action = |env : &mut EnvironmentV1, action : CreatureActions| {
+            env.creatures[0].pending_action = Some(action);
+            env.creatures[0].thinking_steps_left = 1;
+            env.advance_step();
+            return env.creatures[0].last_action_result;
+        };
+        assert_eq!(force_action(&mut env, CreatureActions::MoveForwards), ActionResult::Blocked);
+        assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 3});
+        assert_eq!(force_action(&mut env, CreatureActions::MoveBackwards), ActionResult::Moved);
+        assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 4});
+
+        // Anything else that doesn't move the creature clears it too
+        assert_eq!(force_action(&mut env, CreatureActions::MoveForwards), ActionResult::Moved);
+        assert_eq!(force_action(&mut env, CreatureActions::MoveForwards), ActionResult::Blocked);
+        assert_eq!(force_action(&mut env, CreatureActions::RotateCW), ActionResult::NoEffect);
+    }
+
+    #[test]
+    fn test_offspring_endowment() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 1;
+        params.offspring_energy_fraction = 0.25;
+        let mut env = EnvironmentV1::new_seeded(&params, 6);
+        env.creatures[0].energy = 1000;
+
+        // Each offspring takes its cut of what the parent has left, and no energy is made or lost
+        let offspring = run_seeded(&mut Some(StdRng::seed_from_u64(6)), || env.create_offspring(0, 2));
+        assert_eq!(offspring.iter().map(|c| c.energy).collect::<Vec<usize>>(), vec![250, 187]);
+        assert_eq!(env.creatures[0].energy, 563);
+        assert_eq!(env.creatures[0].num_offspring, 2);
+
+        // Fractions over 1 hand over everything
+        env.params.offspring_energy_fraction = 1.5;
+        let offspring = run_seeded(&mut Some(StdRng::seed_from_u64(6)), || env.create_offspring(0, 1));
+        assert_eq!(offspring[0].energy, 563);
+        assert_eq!(env.creatures[0].energy, 0);
+
+        // Without a fraction, offspring get the flat starting energy for free
+        env.params.offspring_energy_fraction = 0.0;
+        env.creatures[0].energy = 1000;
+        let offspring = run_seeded(&mut Some(StdRng::seed_from_u64(6)), || env.create_offspring(0, 1));
+        assert_eq!(offspring[0].energy, env.creatures[0].params.starting_energy);
+        assert_eq!(env.creatures[0].energy, 1000);
+    }
+
+    #[test]
+    fn test_gestation() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.gestation_steps = 3;
+        params.gestation_upkeep_energy = 2;
+        let mut env = EnvironmentV1::new_seeded(&params, 7);
+        let mut creature = run_seeded(&mut Some(StdRng::seed_from_u64(7)), || CreatureV1::new(0, &CreatureParams::new()));
+        creature.set_position(3, 3);
+        creature.energy = 3 * DEFAULT_ENERGY_LEVEL;     // Enough to reproduce on the first step
+        env.add_creature(creature);
+        let stay = |env : &mut EnvironmentV1| {
+            env.creatures[0].pending_action = Some(CreatureActions::Stay);
+            env.creatures[0].thinking_steps_left = 1;
+            env.advance_step();
+        };
+
+        // Conceives on the first step and carries the offspring for 3 more steps
+        stay(&mut env);
+        assert!(matches!(env.creatures[0].last_action_result, ActionResult::Conceived(_)));
+        for steps_remaining in [3, 2, 1] {
+            assert_eq!(env.creatures.len(), 1);
+            assert_eq!(env.creatures[0].gestation_steps_remaining, steps_remaining);
+
+            // Each step of carrying costs the upkeep on top of the normal cost of staying put
+            let mut pregnant = env.clone();
+            let mut not_pregnant = env.clone();
+            not_pregnant.creatures[0].start_gestation(0, 0);
+            for env in [&mut pregnant, &mut not_pregnant] {
+                env.creatures[0].energy = DEFAULT_MIN_REPRODUCE_ENERGY;     // Not enough to reproduce again
+                stay(env);
+            }
+            assert_eq!(pregnant.creatures[0].energy + params.gestation_upkeep_energy, not_pregnant.creatures[0].energy);
+
+            stay(&mut env);
+        }
+
+        // Then gives birth
+        assert!(matches!(env.creatures[0].last_action_result, ActionResult::Reproduced(_)));
+        assert!(!env.creatures[0].is_pregnant());
+        assert!(env.creatures[0].num_offspring >= 1);
+        assert_eq!(env.creatures.len(), 1 + env.creatures[0].num_offspring);
+    }
+
+    #[test]
+    fn test_lifetime_counters() {
+        let mut params = EnvironmentParams::with_size(24, 24);
+        params.num_start_creatures = 40;
+        params.num_start_food = 80;
+        let mut env = EnvironmentV1::new_seeded(&params, 11);
+
+        // Recount everything the creatures did from the board and the events, one step at a time
+        #[derive(Default)]
+        struct Recount {distance : usize, food : usize, rotations : usize, kills : usize, offspring : usize, births : usize}
+        let mut recounts : HashMap<usize, Recount> = env.creatures.iter().map(|c| (c.id, Recount::default())).collect();
+        for _ in 0..40 {
+            let before : HashMap<usize, (CreaturePosition, CreatureOrientation)> = env.creatures.iter().map(|c| (c.id, (c.position, c.orientation))).collect();
+            let board_before = env.positions.clone();
+            env.events.take_all();
+            env.advance_step();
+
+            for creature in env.creatures.iter() {
+                let recount = recounts.entry(creature.id).or_default();
+                if let Some((position, orientation)) = before.get(&creature.id) {
+                    if creature.position != *position {
+                        recount.distance += 1;
+                        if board_before[creature.position.x][creature.position.y] == SpaceStates::FoodSpace {
+                            recount.food += 1;
+                        }
+                    }
+                    if creature.orientation != *orientation {
+                        recount.rotations += 1;
+                    }
+                }
+                if let ActionResult::Reproduced(num_offspring) = creature.last_action_result {
+                    recount.offspring += num_offspring;
+                }
+            }
+            for event in env.events.take_all() {
+                match event {
+                    EnvEvent::Kill {killer_id, ..} => recounts.entry(killer_id).or_default().kills += 1,
+                    EnvEvent::Birth {parent_id : Some(parent_id), ..} => recounts.entry(parent_id).or_default().births += 1,
+                    _ => (),
+                }
+            }
+        }
+
+        // Something of everything happened, and every survivor's counters match what was seen
+        let totals = |get_value : fn(&CreatureV1) -> usize| env.creatures.iter().map(get_value).sum::<usize>();
+        assert!(totals(|c| c.distance_traveled) > 0 && totals(|c| c.food_eaten) > 0 && totals(|c| c.num_rotations) > 0);
+        assert!(totals(|c| c.num_kills) > 0 && totals(|c| c.num_offspring) > 0);
+        for creature in env.creatures.iter() {
+            let recount = &recounts[&creature.id];
+            assert_eq!(creature.distance_traveled, recount.distance, "creature {} distance", creature.id);
+            assert_eq!(creature.food_eaten, recount.food, "creature {} food", creature.id);
+            assert_eq!(creature.num_rotations, recount.rotations, "creature {} rotations", creature.id);
+            assert_eq!(creature.num_kills, recount.kills, "creature {} kills", creature.id);
+            assert_eq!(creature.num_offspring, recount.offspring, "creature {} offspring", creature.id);
+
+            // Offspring that found no room next to their parent are counted but never born
+            assert!(recount.births <= recount.offspring);
+        }
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let mut params = EnvironmentParams::with_size(8, 12);
+        params.num_start_creatures = 1;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.view_distance = 3;
+        params.view_energy_cost = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 14);
+        assert_eq!(env.creatures[0].view_distance, 3);
+
+        // Creature at (3, 10) facing up, with food 4 spaces ahead
+        let old_pos = env.creatures[0].position;
+        env.positions[old_pos.x][old_pos.y] = SpaceStates::BlankSpace;
+        env.creatures[0].set_position(3, 10);
+        env.creatures[0].orientation = CreatureOrientation::Up;
+        env.creatures[0].energy = DEFAULT_MIN_REPRODUCE_ENERGY;
+        env.positions[3][10] = SpaceStates::CreatureSpace(env.creatures[0].id);
+        env.add_food_space(CreaturePosition {x : 3, y : 6});
+
+        // Out of range of the param, in range of a longer gene
+        env.update_creature_vision();
+        assert!(!env.creatures[0].vision_state.obj_in_view);
+        env.creatures[0].view_distance = 4;
+        env.update_creature_vision();
+        assert!(env.creatures[0].vision_state.obj_in_view && env.creatures[0].vision_state.dist == 4);
+
+        // Each step costs the view cost per space of the gene, with fractions carried over to the next step
+        let mut free = env.clone();
+        env.params.view_energy_cost = 0.625;     // 2.5 energy per step
+        for env in [&mut env, &mut free] {
+            for _ in 0..2 {
+                env.creatures[0].pending_action = Some(CreatureActions::Stay);
+                env.creatures[0].thinking_steps_left = 1;
+                env.advance_step();
+            }
+        }
+        assert_eq!(env.creatures[0].energy + 5, free.creatures[0].energy);
+
+        // Offspring get the param's view distance, unless it evolves
+        let offspring = run_seeded(&mut Some(StdRng::seed_from_u64(14)), || env.create_offspring(0, 1));
+        assert_eq!(offspring[0].view_distance, 3);
+        env.params.evolve_view_distance = true;
+        env.params.mutation_prob = 1.0;
+        let offspring = run_seeded(&mut Some(StdRng::seed_from_u64(14)), || env.create_offspring(0, 20));
+        assert!(offspring.iter().all(|c| c.view_distance == 3 || c.view_distance == 5));
+        assert!(offspring.iter().any(|c| c.view_distance == 3) && offspring.iter().any(|c| c.view_distance == 5));
+    }
+
+    #[test]
+    fn test_large_bodies() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.enable_large_bodies = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+
+        // A large creature facing up gets its tail right behind it
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(3, 3);
+        creature.is_large = true;
+        env.add_creature(creature);
+        assert!(env.creatures[0].tail == Some(CreaturePosition {x : 3, y : 4}));
+        assert_eq!(env.positions[3][4], SpaceStates::CreatureSpace(0));
+
+        // Moving forwards pulls the tail along and costs double
+        let start_energy = env.creatures[0].energy;
+        env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+        env.creatures[0].thinking_steps_left = 1;
+        env.advance_step();
+        assert!(env.creatures[0].tail == Some(CreaturePosition {x : 3, y : 3}));
+        assert_eq!(env.positions[3][4], SpaceStates::BlankSpace);
+        assert_eq!(env.creatures[0].energy, start_energy - DEFAULT_MOVE_ENERGY_COST * LARGE_BODY_MULTIPLIER);
+        assert_eq!(env.num_creatures, 1);
+        assert_eq!(env.creatures[0].get_max_energy(), MAX_POSSIBLE_ENERGY * LARGE_BODY_MULTIPLIER);
+
+        // In a full run every creature stays on its own two spaces (or one, if it's squeezed in)
+        params.num_start_creatures = 20;
+        params.initial_large_fraction = 0.5;
+        params.env_x_size = 16;
+        params.env_y_size = 16;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        for _ in 0..30 {
+            env.advance_step();
+        }
+        assert!(env.creatures.iter().any(|c| c.tail.is_some()));
+        for creature in env.creatures.iter() {
+            assert_eq!(env.positions[creature.position.x][creature.position.y], SpaceStates::CreatureSpace(creature.id));
+            if let Some(tail) = creature.tail {
+                assert!(creature.is_large);
+                assert_eq!(env.positions[tail.x][tail.y], SpaceStates::CreatureSpace(creature.id));
+            }
+        }
+        let num_creature_spaces = env.positions.iter().flatten().filter(|s| matches!(s, SpaceStates::CreatureSpace(_))).count();
+        assert_eq!(num_creature_spaces, env.creatures.len() + env.get_num_tails());
+        assert_eq!(env.repair_after_load().num_board_fixes, 0);
+    }
 
+    #[test]
+    fn test_large_body_own_tail() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.view_energy_cost = 0.0;
+        params.enable_large_bodies = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        for (x, y) in [(2, 3), (3, 2), (4, 3)] {
+            env.add_wall_space(CreaturePosition {x : x, y : y});
+        }
+
+        // A large creature facing up with walls on three sides turns around twice. With no room behind it the tail is
+        // pulled in rather than being left in front of it
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(3, 3);
+        creature.is_large = true;
+        env.add_creature(creature);
+        assert!(env.creatures[0].tail == Some(CreaturePosition {x : 3, y : 4}));
+        for _ in 0..2 {
+            env.creatures[0].pending_action = Some(CreatureActions::RotateCW);
+            env.creatures[0].thinking_steps_left = 1;
+            env.advance_step();
+        }
+        assert_eq!(env.creatures[0].orientation, CreatureOrientation::Down);
+        assert!(env.creatures[0].tail.is_none());
+        assert_eq!(env.positions[3][4], SpaceStates::BlankSpace);
+
+        // Even with its tail in front of it (e.g. from an old save), the creature doesn't see or kill itself
+        env.positions[3][4] = SpaceStates::CreatureSpace(0);
+        env.creatures[0].tail = Some(CreaturePosition {x : 3, y : 4});
+        env.update_creature_vision();
+        assert!(env.creatures[0].vision_state.space_type != SpaceStates::CreatureSpace(0));
+        env.creatures[0].vision_state.obj_in_view = true;
+        env.creatures[0].vision_state.dist = 1;
+        env.creatures[0].vision_state.space_type = SpaceStates::CreatureSpace(0);
+        assert_eq!(env.preview_action(0, CreatureActions::Kill), PreviewResult::NoEffect);
+        let start_energy = env.creatures[0].energy;
+        env.creatures[0].pending_action = Some(CreatureActions::Kill);
+        env.creatures[0].thinking_steps_left = 1;
+        env.advance_step();
+        assert!(!env.creatures[0].is_dead());
+        assert!(env.creatures[0].energy < start_energy);
+    }
+
+    #[test]
+    fn test_elevation_move_cost() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.enable_elevation = true;
+        params.elevation_energy_cost = 10.0;
+        params.high_ground_view_bonus = 4;
+        let mut env = EnvironmentV1::new_seeded(&params, 6);
+        assert!(env.has_elevation());
+
+        // A single steep hill one space up from the creature
+        env.elevation = vec![vec![0.0; 8]; 8];
+        env.adjust_elevation(CreaturePosition {x : 3, y : 2}, 1.0);
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(3, 3);
+        env.add_creature(creature);
+        assert_eq!(env.get_high_ground_view_bonus(CreaturePosition {x : 3, y : 2}), 4);
+        assert_eq!(env.get_high_ground_view_bonus(CreaturePosition {x : 3, y : 3}), 0);
+
+        // Force a move up the hill and check it cost the move plus the climb
+        let start_energy = env.creatures[0].energy;
+        env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+        env.creatures[0].thinking_steps_left = 1;
+        env.advance_step();
+        assert!(env.creatures[0].position == CreaturePosition {x : 3, y : 2});
+        assert_eq!(env.creatures[0].energy, start_energy - DEFAULT_MOVE_ENERGY_COST - 10);
+    }
+
+    #[test]
+    fn test_river_carries_food() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.enable_river = true;
+        params.river_flow_strength = 1.0;
+        params.river_source_food = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 8);
+        assert!(env.river.iter().all(|pos| env.positions[pos.x][pos.y] == SpaceStates::BlankSpace));
+
+        // Food dropped at the source ends up piled at the mouth
+        let (source, mouth) = (env.river[0], *env.river.last().unwrap());
+        env.add_food_space(source);
+        for _ in 0..env.river.len() {
+            env.update_river();
+        }
+        assert_eq!(env.positions[source.x][source.y], SpaceStates::BlankSpace);
+        assert_eq!(env.positions[mouth.x][mouth.y], SpaceStates::FoodSpace);
+    }
+
+    #[test]
+    fn test_swimming() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.avg_new_food_per_day = 0.0;
+        params.num_start_walls = 0;
+        params.enable_swimming = true;
+        let mut env = EnvironmentV1::new_seeded(&params, 4);
+
+        // A single space of water right in front of the creature
+        let (bank, water) = (CreaturePosition {x : 3, y : 3}, CreaturePosition {x : 3, y : 2});
+        env.river = vec![water];
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(bank.x, bank.y);
+        env.add_creature(creature);
+
+        // Can't get in without being able to swim
+        env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+        env.creatures[0].thinking_steps_left = 1;
+        env.advance_step();
+        assert!(env.creatures[0].position == bank);
+
+        // Swimmers can, but it costs extra
+        env.creatures[0].can_swim = true;
+        env.creatures[0].pending_action = Some(CreatureActions::MoveForwards);
+        env.creatures[0].thinking_steps_left = 1;
+        let start_energy = env.creatures[0].energy;
+        env.advance_step();
+        assert!(env.creatures[0].position == water);
+        assert!(start_energy - env.creatures[0].energy >= DEFAULT_SWIM_ENERGY_COST);
+        assert_eq!(env.swimmer_count_history.back(), Some(&1));
+    }
+
+    #[test]
+    fn test_wall_map() {
+        let mut params = EnvironmentParams::with_size(12, 10);
+        params.num_start_walls = 30;
+        let env = EnvironmentV1::new_seeded(&params, 9);
+        let wall_map = env.get_wall_map();
+        assert_eq!(wall_map.num_walls(), params.num_start_walls);
+
+        // A new env built from the map has the same walls, with every creature still on a blank space
+        let mut copy_params = EnvironmentParams::new();
+        copy_params.num_start_creatures = 10;
+        copy_params.num_start_food = 10;
+        let copy = EnvironmentV1::new_from_wall_map(&copy_params, &wall_map);
+        assert_eq!((copy.params.env_x_size, copy.params.env_y_size), (12, 10));
+        for x in 0..12 {
+            for y in 0..10 {
+                assert_eq!(copy.positions[x][y] == SpaceStates::WallSpace, env.positions[x][y] == SpaceStates::WallSpace);
+            }
+        }
+        assert!(copy.creatures.iter().all(|c| copy.positions[c.position.x][c.position.y] == SpaceStates::CreatureSpace(c.id)));
+
+        // Maps for a different size board are rejected
+        let mut other = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(8, 8), 9);
+        assert!(other.apply_wall_map(&wall_map).is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_wall_image() {
+        let mut params = EnvironmentParams::with_size(8, 6);
+        params.num_start_creatures = 4;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 10);
+        let creature_ids : Vec<usize> = env.creatures.iter().map(|c| c.id).collect();
+
+        // Image at twice the board size: a dark column at x = 2, a dark space at (5, 4) and a transparent dark space
+        // at (6, 1) that shouldn't become a wall
+        let img = image::RgbaImage::from_fn(16, 12, |x, y| {
+            return match (x / 2, y / 2) {
+                (2, _) | (5, 4) => image::Rgba([0, 0, 0, 255]),
+                (6, 1) => image::Rgba([0, 0, 0, 0]),
+                _ => image::Rgba([255, 255, 255, 255]),
+            };
+        });
+        env.apply_wall_image(&image::DynamicImage::ImageRgba8(img)).unwrap();
+
+        let mut walls : Vec<(usize, usize)> = Vec::new();
+        for x in 0..8 {
+            for y in 0..6 {
+                if env.positions[x][y] == SpaceStates::WallSpace {
+                    walls.push((x, y));
+                }
+            }
+        }
+        let mut expected : Vec<(usize, usize)> = (0..6).map(|y| (2, y)).collect();
+        expected.push((5, 4));
+        assert_eq!(walls, expected);
+        assert_eq!(env.num_walls, 7);
+        assert_eq!(env.get_wall_map().num_walls(), 7);
+
+        // Every creature is still around and off the walls
+        assert_eq!(env.creatures.iter().map(|c| c.id).collect::<Vec<usize>>(), creature_ids);
+        for creature in env.creatures.iter() {
+            assert_eq!(env.positions[creature.position.x][creature.position.y], SpaceStates::CreatureSpace(creature.id));
+        }
+
+        // An image is scaled to whatever size the board is
+        let mut small = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(4, 3), 10);
+        let img = image::RgbaImage::from_fn(16, 12, |x, _y| if x < 4 {image::Rgba([0, 0, 0, 255])} else {image::Rgba([255, 255, 255, 255])});
+        small.apply_wall_image(&image::DynamicImage::ImageRgba8(img)).unwrap();
+        assert_eq!(small.num_walls, 3);
+        assert!((0..3).all(|y| small.positions[0][y] == SpaceStates::WallSpace));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 5;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 12);
+        env.add_wall_space(CreaturePosition {x : 0, y : 0});
+        env.add_food_space(CreaturePosition {x : 9, y : 9});
+
+        // Growing around the middle shifts everything by half the growth
+        env.resize(14, 12, ResizeAnchor::Center);
+        assert_eq!((env.params.env_x_size, env.params.env_y_size), (14, 12));
+        assert_eq!(env.positions[2][1], SpaceStates::WallSpace);
+        assert_eq!(env.positions[11][10], SpaceStates::FoodSpace);
+        assert_eq!(env.creatures.len(), 5);
+
+        // Cropping keeps the top left, and creatures that were cut off are moved back onto the board
+        env.resize(4, 4, ResizeAnchor::TopLeft);
+        assert_eq!(env.positions[2][1], SpaceStates::WallSpace);
+        assert_eq!(env.num_food, 0);
+        assert_eq!(env.creatures.len(), 5);
+        assert!(env.creatures.iter().all(|c| env.positions[c.position.x][c.position.y] == SpaceStates::CreatureSpace(c.id)));
+        assert_eq!(env.num_blank, 16 - 1 - 5);
+    }
+
+    #[test]
+    fn test_resized_env_steps() {
+        // A crowded board that's been running for a while, cropped so small that some creatures are dropped
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 60;
+        let mut env = EnvironmentV1::new_seeded(&params, 8);
+        for _ in 0..5 {
+            env.advance_step();
+        }
+        let num_creatures = env.creatures.len();
+        env.resize(5, 5, ResizeAnchor::Center);
+        assert!(env.creatures.len() < num_creatures);
+        assert_vision_up_to_date(&env);
+        for _ in 0..5 {
+            assert_step_kills_neighbours(&mut env);
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut params = EnvironmentParams::with_size(6, 5);
+        params.num_start_creatures = 4;
+        params.num_start_food = 3;
+        params.num_start_walls = 0;
+        let first = EnvironmentV1::new_seeded(&params, 1);
+        let second = EnvironmentV1::new_seeded(&params, 2);
+
+        let merged = EnvironmentV1::new_merged(&first, &second, MergeDirection::Horizontal, MergeSeam::Wall);
+        assert_eq!((merged.params.env_x_size, merged.params.env_y_size), (13, 5));
+        assert!((0..5).all(|y| merged.positions[6][y] == SpaceStates::WallSpace));
+        assert_eq!(merged.creatures.len(), 8);
+        assert_eq!(merged.num_food, 6);
+
+        // Every creature has its own ID, and the second board's creatures are on the right side of the wall
+        let mut ids : Vec<usize> = merged.creatures.iter().map(|c| c.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 8);
+        assert!(merged.creatures[4..].iter().all(|c| c.position.x > 6 && c.lineage_id >= 4));
+
+        // A corridor leaves a gap in the middle of the seam
+        let merged = EnvironmentV1::new_merged(&first, &second, MergeDirection::Vertical, MergeSeam::Corridor);
+        assert_eq!((merged.params.env_x_size, merged.params.env_y_size), (6, 11));
+        assert_eq!(merged.num_walls, 6 - MERGE_CORRIDOR_WIDTH);
+    }
+
+    /// Check every creature sees what it would if it looked again now
+    fn assert_vision_up_to_date(env : &EnvironmentV1) {
+        let mut fresh = env.clone();
+        fresh.update_creature_vision();
+        for (creature, fresh_creature) in env.creatures.iter().zip(fresh.creatures.iter()) {
+            assert!(creature.vision_state == fresh_creature.vision_state, "Creature {} sees something that isn't there", creature.id);
+        }
+    }
+
+    /// Step the environment and check every kill was of a creature right next to its killer
+    fn assert_step_kills_neighbours(env : &mut EnvironmentV1) {
+        // Killers can be killed later in the same step, so remember where everyone started (killing doesn't move them)
+        let start_positions : HashMap<usize, CreaturePosition> = env.creatures.iter().map(|c| (c.id, c.position)).collect();
+        env.events.take_all();
+        env.advance_step();
+        for event in env.events.take_all() {
+            if let EnvEvent::Kill {killer_id, position, ..} = event {
+                let killer = start_positions[&killer_id];
+                assert_eq!(killer.x.abs_diff(position.x) + killer.y.abs_diff(position.y), 1, "Creature {} killed from across the board", killer_id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_merged_env_steps() {
+        // Two boards that have been running for a while, so their creatures have seen each other
+        let params = EnvironmentParams::with_size(20, 20);
+        let mut first = EnvironmentV1::new_seeded(&params, 21);
+        let mut second = EnvironmentV1::new_seeded(&params, 22);
+        for _ in 0..20 {
+            first.advance_step();
+            second.advance_step();
+        }
+
+        let mut merged = EnvironmentV1::new_merged(&first, &second, MergeDirection::Horizontal, MergeSeam::Open);
+        assert_vision_up_to_date(&merged);
+        for _ in 0..5 {
+            assert_step_kills_neighbours(&mut merged);
+        }
+    }
+
+    #[test]
+    fn test_import_creatures() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 3;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+
+        // Creatures from an env with different inputs
+        let mut other_params = params;
+        other_params.enable_hearing_input = !params.enable_hearing_input;
+        let other = EnvironmentV1::new_seeded(&other_params, 4);
+
+        let left_half = Some((CreaturePosition {x : 0, y : 0}, CreaturePosition {x : 4, y : 9}));
+        let report = env.import_creatures(other.creatures.clone(), &CreatureImportOptions {adapt_brains : false, region : left_half});
+        assert_eq!(report, CreatureImportReport {num_imported : 0, num_adapted : 0, num_rejected : 3});
+
+        let report = env.import_creatures(other.creatures.clone(), &CreatureImportOptions {adapt_brains : true, region : left_half});
+        assert_eq!(report, CreatureImportReport {num_imported : 3, num_adapted : 3, num_rejected : 0});
+        assert_eq!(env.creatures.len(), 6);
+        for creature in env.creatures[3..].iter() {
+            assert!(creature.id >= 3 && creature.position.x <= 4 && creature.brain.is_valid());
+            assert!(creature.has_io(&params.get_enabled_creature_inputs(), &params.get_enabled_creature_actions()));
+        }
+
+        // The imports can think in their new home
+        env.advance_step();
+    }
+
+    #[test]
+    fn test_imported_creatures_step() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        params.avg_new_food_per_day = 0.0;
+
+        // Two creatures facing each other somewhere else, so each one sees the other's old ID
+        let mut other = EnvironmentV1::new_seeded(&params, 4);
+        for (id, y, orientation) in [(0, 3, CreatureOrientation::Down), (1, 4, CreatureOrientation::Up)] {
+            let mut creature = run_seeded(&mut Some(StdRng::seed_from_u64(id as u64)), || CreatureV1::new(id, &CreatureParams::new()));
+            creature.set_position(3, y);
+            creature.set_orientation(orientation);
+            other.add_creature(creature);
+        }
+        other.update_creature_vision();
+        assert!(other.creatures[0].vision_state.space_type == SpaceStates::CreatureSpace(1));
+
+        // Imported into a board where those IDs belong to other creatures, the imports only see (and kill) what's really there
+        params.num_start_creatures = 3;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        let options = CreatureImportOptions {adapt_brains : true, region : None};
+        let report = run_seeded(&mut Some(StdRng::seed_from_u64(5)), || env.import_creatures(other.creatures.clone(), &options));
+        assert_eq!(report.num_imported, 2);
+        assert_vision_up_to_date(&env);
+        for creature in env.creatures[3..].iter_mut() {
+            creature.pending_action = Some(CreatureActions::Kill);
+            creature.thinking_steps_left = 1;
+        }
+        assert_step_kills_neighbours(&mut env);
+    }
+
+    #[test]
+    fn test_edit_creature() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 2;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        let id = env.creatures[0].id;
+
+        let edit = CreatureEdit {energy : Some(usize::MAX), orientation : Some(CreatureOrientation::Left), color : Some([1, 2, 3]), lineage_id : Some(42), ..Default::default()};
+        env.edit_creature(id, &edit).unwrap();
+        let creature = env.get_creature(id).unwrap();
+        assert_eq!(creature.energy, MAX_POSSIBLE_ENERGY);
+        assert_eq!(creature.orientation, CreatureOrientation::Left);
+        assert_eq!(creature.color.get_as_vec(), [1, 2, 3]);
+        assert_eq!(creature.lineage_id, 42);
+        assert!(env.edit_creature(usize::MAX, &edit).is_err());
+
+        // Brain tweaks stay in range
+        let weight = BrainParam::Weight {layer : 0, to : 0, from : 0};
+        assert_eq!(env.set_creature_brain_param(id, weight, 1e9), Ok(Brain::get_param_range().1));
+        assert_eq!(env.get_creature(id).unwrap().brain.get_param(weight), Some(Brain::get_param_range().1));
+        assert!(env.set_creature_brain_param(id, BrainParam::Bias {layer : 99, node : 0}, 0.0).is_err());
+
+        // Taking all of its energy starves it through the normal death path
+        env.edit_creature(id, &CreatureEdit {energy : Some(0), ..Default::default()}).unwrap();
+        env.advance_step();
+        assert!(env.get_creature(id).is_none());
+        assert_eq!(env.creatures.len(), env.num_creatures);
+    }
+
+    #[test]
+    fn test_summary_report() {
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 6;
+        let mut env = EnvironmentV1::new_seeded(&params, 6);
+        for _ in 0..5 {
+            env.advance_step();
+        }
+        let report = env.summary_report();
+        assert!(report.contains("- Duration: 5 steps") && report.contains("- Outcome: still alive"));
+        assert!(report.contains("## Top Lineages") && report.contains("| MoveForwards |"));
+        assert!(env.stats.peak_population >= 6);
+
+        // Wiping everyone out with a plague gets blamed on the plague
+        env.apply_catastrophe(CatastropheKind::Plague, 1.0);
+        assert!(env.summary_report().contains("extinct. A plague killed the last"));
+    }
+
+    #[test]
+    fn test_decals() {
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 5;
+        params.avg_new_food_per_day = 0.0;
+        let mut env = EnvironmentV1::new_seeded(&params, 2);
+
+        // A killed creature leaves a fight decal on a blank space, which fades after FIGHT_DECAL_PERSISTENCE_STEPS
+        let victim = env.creatures[0].clone();
+        env.creatures[0].kill();
+        env.advance_step();
+        let pos = victim.position;
+        assert!(env.positions[pos.x][pos.y] != SpaceStates::CreatureSpace(victim.id));
+        assert_eq!(env.get_snapshot().get_decal_at(pos.x, pos.y), Some(DecalKind::Fight));
+        for _ in 0..=FIGHT_DECAL_PERSISTENCE_STEPS {
+            env.advance_step();
+        }
+        assert!(!matches!(env.decals[pos.x][pos.y], Some(Decal {kind : DecalKind::Fight, ..})));
+
+        // Fight spaces in older saves are read as blank spaces
+        #[cfg(feature = "json")]
+        {
+            let json = r#"[["BlankSpace", {"FightSpace": 3}], ["FoodSpace", {"CreatureSpace": 7}]]"#;
+            let positions = deserialize_positions(&mut serde_json::Deserializer::from_str(json)).unwrap();
+            assert_eq!(positions, vec![vec![SpaceStates::BlankSpace, SpaceStates::BlankSpace], vec![SpaceStates::FoodSpace, SpaceStates::CreatureSpace(7)]]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_creatures_to_json() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.num_start_creatures = 10;
+        let env = EnvironmentV1::new_seeded(&params, 4);
+        let ids : Vec<usize> = env.creatures.iter().take(3).map(|c| c.id).collect();
+
+        // Only the picked creatures are saved, with their genomes, and they can be imported into another board
+        let saved = EnvironmentV1::parse_json(&env.creatures_to_json(&ids), "test").unwrap();
+        assert_eq!(saved.creatures.iter().map(|c| c.id).collect::<Vec<usize>>(), ids);
+        assert_eq!(saved.creatures[0].brain.get_genome(), env.creatures[0].brain.get_genome());
+        assert_eq!(saved.num_creatures, 3);
+        let mut other = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 5);
+        let report = other.import_creatures(saved.creatures, &CreatureImportOptions {adapt_brains : false, region : None});
+        assert_eq!(report.num_imported, 3);
+    }
+
+    #[test]
+    fn test_decal_vision() {
+        let mut params = EnvironmentParams::with_size(8, 8);
+        params.num_start_creatures = 0;
+        params.num_start_food = 0;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        let mut creature = CreatureV1::new(0, &CreatureParams::new());
+        creature.set_position(3, 6);
+        env.add_creature(creature);
+        env.add_decal(CreaturePosition {x : 3, y : 3}, DecalKind::Fight, FIGHT_DECAL_PERSISTENCE_STEPS);
+        env.territory = vec![vec![TerritoryMark {owner : 99, strength : 1.0}; 8]; 8];
+
+        // Decals and territory are invisible unless decal vision is on
+        env.update_creature_vision();
+        assert!(!env.creatures[0].vision_state.obj_in_view);
+
+        // The fight decal is seen in red, and hides the territory behind it
+        env.params.enable_decal_vision = true;
+        env.update_creature_vision();
+        let vision = env.creatures[0].vision_state;
+        assert!(vision.obj_in_view && vision.dist == 3 && vision.color.get_as_vec() == FIGHT_DECAL_VISION_COLOR);
+
+        // Without the decal, the closest space of another lineage's territory is seen
+        env.decals[3][3] = None;
+        env.update_creature_vision();
+        let vision = env.creatures[0].vision_state;
+        assert!(vision.obj_in_view && vision.dist == 1 && vision.color.get_as_vec() == TERRITORY_VISION_COLOR);
+        env.territory = vec![vec![TerritoryMark {owner : env.creatures[0].lineage_id, strength : 1.0}; 8]; 8];
+        env.update_creature_vision();
+        assert!(!env.creatures[0].vision_state.obj_in_view);
+    }
+
+    #[test]
+    fn test_lineage_extinction() {
+        let mut params = EnvironmentParams::with_size(12, 12);
+        params.num_start_creatures = 5;
+        let mut env = EnvironmentV1::new_seeded(&params, 8);
+        let groups = get_group_stats(&env.creatures, GroupBy::Tribe);
+        assert_eq!(groups.iter().map(|g| g.num_creatures).sum::<usize>(), env.creatures.len());
+
+        // Starving every member of the biggest tribe wipes it out
+        let lineage_id = groups[0].id;
+        let member_ids : Vec<usize> = env.creatures.iter().filter(|c| c.lineage_id == lineage_id).map(|c| c.id).collect();
+        for id in member_ids {
+            env.edit_creature(id, &CreatureEdit {energy : Some(0), ..Default::default()}).unwrap();
+        }
+        env.events.take_all();
+        env.advance_step();
+        assert!(env.events.take_all().contains(&EnvEvent::LineageExtinct {time_step : 0, lineage_id : lineage_id}));
+        assert!(get_group_stats(&env.creatures, GroupBy::Tribe).iter().all(|g| g.id != lineage_id));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_save_metadata() {
+        let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(10, 10), 6);
+        let _ = env.run_n_steps(5);
+        env.metadata.notes = String::from("first try");
+
+        // Saves record the step count and get an ID. A save made from a loaded run points back at the one it came from
+        let first = EnvironmentV1::from_json(&env.to_json()).unwrap();
+        assert_eq!(first.metadata.seed, Some(6));
+        assert_eq!(first.metadata.total_steps, 5);
+        assert_eq!(first.metadata.notes, "first try");
+        assert!(first.metadata.save_id.is_some() && first.metadata.parent_save_id.is_none());
+        let second = EnvironmentV1::from_json(&first.to_json()).unwrap();
+        assert_eq!(second.metadata.parent_save_id, first.metadata.save_id);
+        assert!(second.metadata.describe().contains("first try"));
+    }
+
+    #[test]
+    fn test_repair_after_load() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 3;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 7);
+
+        // Nothing to fix in a healthy environment
+        let hash = env.state_hash();
+        assert_eq!(env.repair_after_load().num_fixes(), 0);
+        assert_eq!(env.state_hash(), hash);
+
+        // Break it the ways a hand-edited save might be broken
+        let mut twin = env.creatures[0].clone();
+        twin.set_position(50, 50);
+        env.creatures.push(twin);
+        env.positions[0][0] = SpaceStates::CreatureSpace(999);
+        env.positions[9].pop();
+
+        let report = env.repair_after_load();
+        assert_eq!(report.num_ids_reissued, 1);
+        assert_eq!(report.num_relocated, 1);
+        assert_eq!(report.num_board_fixes, 2);
+        assert_eq!(report.num_dropped, 0);
+        assert!(report.describe().contains("1 duplicate creature IDs re-issued"));
+
+        // Everything lines up again
+        assert_eq!(env.creatures.len(), 4);
+        for creature in env.creatures.iter() {
+            assert_eq!(env.positions[creature.position.x][creature.position.y], SpaceStates::CreatureSpace(creature.id));
+        }
+        assert_eq!(env.positions.iter().flatten().filter(|s| matches!(s, SpaceStates::CreatureSpace(_))).count(), 4);
+        assert_eq!(env.repair_after_load().num_fixes(), 0);
+    }
+
+    #[test]
+    fn test_morton_code() {
+        assert_eq!(get_morton_code(0, 0), 0);
+        assert_eq!(get_morton_code(1, 0), 1);
+        assert_eq!(get_morton_code(0, 1), 2);
+        assert_eq!(get_morton_code(3, 3), 15);
+        assert_eq!(get_morton_code(2, 0), 4);
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let mut params = EnvironmentParams::new();
+        params.env_x_size = 16;
+        params.env_y_size = 16;
+        params.num_start_creatures = 10;
+        params.num_start_food = 10;
+        params.num_start_walls = 10;
+        let env = EnvironmentV1::new_rand(&params);
+
+        // Copies hash the same, even if their creatures have different histories
+        let mut env_copy = env.clone();
+        env_copy.creatures[0].energy_history.clear();
+        assert_eq!(env.state_hash(), env_copy.state_hash());
+
+        // Any change to the simulation state changes the hash
+        env_copy.creatures[0].energy += 1;
+        assert_ne!(env.state_hash(), env_copy.state_hash());
+    }
+
+    #[cfg(feature = "file_io")]
+    #[test]
+    fn test_save_file_checksum() {
+        let mut params = EnvironmentParams::new();
+        params.env_x_size = 16;
+        params.env_y_size = 16;
+        params.num_start_creatures = 5;
+        params.num_start_food = 10;
+        params.num_start_walls = 10;
+        let env = EnvironmentV1::new_rand(&params);
+        let json = env.to_json();
+        let filename = std::env::temp_dir().join(format!("evolution_sim_checksum_test_{}.json", std::process::id()));
+        let filename = filename.to_str().unwrap();
+
+        // An untouched save loads fine
+        std::fs::write(filename, &json).unwrap();
+        let loaded = EnvironmentV1::read_json_file(filename).unwrap();
+        assert_eq!(loaded.state_hash(), env.state_hash());
+
+        // Truncated and tampered saves are rejected
+        std::fs::write(filename, &json[..json.len() / 2]).unwrap();
+        assert!(EnvironmentV1::read_json_file(filename).is_err());
+        std::fs::write(filename, json.replacen("\"time_step\": 0", "\"time_step\": 1", 1)).unwrap();
+        assert!(EnvironmentV1::read_json_file(filename).is_err());
+
+        // Compact saves are smaller and still load
+        let options = SaveOptions {exclude_activations : true, exclude_creatures : false, round_weights : true};
+        let compact_json = env.to_json_with_options(&options);
+        assert!(compact_json.len() < json.len());
+        std::fs::write(filename, &compact_json).unwrap();
+        let mut loaded = EnvironmentV1::read_json_file(filename).unwrap();
+        loaded.advance_step();
+
+        let _ = std::fs::remove_file(filename);
+    }
+
+    #[cfg(feature = "file_io")]
+    #[test]
+    fn test_load_single_layer() {
+        let mut params = EnvironmentParams::with_size(10, 10);
+        params.num_start_creatures = 5;
+        params.num_start_walls = 0;
+        let mut env = EnvironmentV1::new_seeded(&params, 8);
+        let creature_ids : Vec<usize> = env.creatures.iter().map(|c| c.id).collect();
+        let covered = env.creatures[0].position;
+
+        // Wall layer from another save, with a wall right on top of one of the creatures
+        params.num_start_creatures = 0;
+        let mut wall_env = EnvironmentV1::new_seeded(&params, 9);
+        for y in 0..10 {
+            wall_env.add_wall_space(CreaturePosition {x : covered.x, y : y});
+        }
+        let filename = std::env::temp_dir().join(format!("evolution_sim_layer_test_{}.json", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        std::fs::write(filename, wall_env.to_json()).unwrap();
+
+        // Only the walls layer is read, so the main file is never opened
+        let load_ops = JsonEnvLoadParams {
+            load_all : false,
+            load_parameters : false,
+            load_creatures : false,
+            load_walls : true,
+            load_food : false,
+            parameters_file : None,
+            creatures_file : None,
+            walls_file : Some(String::from(filename)),
+            food_file : None,
+        };
+        let report = env.load_from_json("missing_main_file.json", &load_ops).unwrap();
+        let _ = std::fs::remove_file(filename);
+        assert_eq!(report.num_relocated, 1);
+        assert_eq!(report.num_dropped, 0);
+
+        // The walls came along and the covered creature moved next to them
+        for y in 0..10 {
+            assert_eq!(env.positions[covered.x][y], SpaceStates::WallSpace);
+        }
+        assert_eq!(env.creatures.iter().map(|c| c.id).collect::<Vec<usize>>(), creature_ids);
+        assert_eq!(env.creatures[0].position.x.abs_diff(covered.x), 1);
+        assert!(env.creatures[0].position.y.abs_diff(covered.y) <= 1);
+        for creature in env.creatures.iter() {
+            assert_eq!(env.positions[creature.position.x][creature.position.y], SpaceStates::CreatureSpace(creature.id));
+        }
+        env.advance_step();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_compact_save() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.num_start_creatures = 10;
+        let mut env = EnvironmentV1::new_seeded(&params, 12);
+        let _ = env.run_n_steps(5);
+        let full_json = env.to_json();
+
+        // Without activations the save is smaller, but the env it loads plays out exactly the same
+        let options = SaveOptions {exclude_activations : true, ..SaveOptions::default()};
+        let json = env.to_json_with_options(&options);
+        assert!(json.len() < full_json.len());
+        let mut loaded = EnvironmentV1::from_json(&json).unwrap();
+        assert_eq!(loaded.state_hash(), env.state_hash());
+        let mut original = env.clone();
+        original.rng = Some(StdRng::seed_from_u64(13));
+        loaded.rng = Some(StdRng::seed_from_u64(13));
+        for _ in 0..5 {
+            original.advance_step();
+            loaded.advance_step();
+            assert_eq!(loaded.state_hash(), original.state_hash());
+        }
+
+        // Rounded weights make it smaller still and only move the genomes by the rounding
+        let options = SaveOptions {round_weights : true, ..options};
+        let rounded_json = env.to_json_with_options(&options);
+        assert!(rounded_json.len() < json.len());
+        let loaded = EnvironmentV1::from_json(&rounded_json).unwrap();
+        assert_eq!(loaded.positions, env.positions);
+        for (loaded_creature, creature) in loaded.creatures.iter().zip(env.creatures.iter()) {
+            assert_eq!(loaded_creature.id, creature.id);
+            assert!(loaded_creature.brain.genome_distance(&creature.brain) <= 0.5 * 10f32.powi(-SAVE_WEIGHT_DECIMALS));
+        }
+
+        // Map-only saves keep everything but the creatures
+        let options = SaveOptions {exclude_creatures : true, ..options};
+        let map_json = env.to_json_with_options(&options);
+        assert!(map_json.len() < rounded_json.len());
+        let loaded = EnvironmentV1::from_json(&map_json).unwrap();
+        assert!(loaded.creatures.is_empty());
+        for (loaded_column, column) in loaded.positions.iter().zip(env.positions.iter()) {
+            for (loaded_space, space) in loaded_column.iter().zip(column.iter()) {
+                match space {
+                    SpaceStates::CreatureSpace(_) => assert_eq!(*loaded_space, SpaceStates::BlankSpace),
+                    _ => assert_eq!(loaded_space, space),
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_load_older_schema() {
+        // Saved by an earlier build of the same crate version, before many of the current params/creature fields existed.
+        // Its checksum is of the JSON it was saved with, so the fields that have been added since mustn't change it
+        let json = include_str!("../data/old_schema_save.json");
+        let mut env = EnvironmentV1::from_json(json).unwrap();
+        assert_eq!((env.params.env_x_size, env.params.env_y_size, env.time_step), (8, 8, 1));
+        assert!(!env.params.enable_decal_vision);
+        assert!(env.creatures.iter().all(|c| c.brain.is_valid()));
+        env.advance_step();
+
+        // Changing the values still fails the check, but reformatting the file doesn't
+        assert!(EnvironmentV1::from_json(&json.replacen("\"time_step\": 1", "\"time_step\": 2", 1)).is_err());
+        assert!(EnvironmentV1::from_json(&json.replace("\n", "\r\n  ")).is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+/** ===============================================================================
+ * File: events.rs
+ * Author: Scott Stack
+ * Description: Notable things that happen in the environment (births, deaths, kills, ...)
+ * are queued up as events so frontends can react to them without re-scanning the board
+ * ===============================================================================*/
+use crate::creature::*;
+use crate::catastrophes::CatastropheKind;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const MAX_PENDING_EVENTS : usize = 10_000;  // Max number of events waiting to be taken. The oldest are dropped after this
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Something notable that happened in the environment. In JSON, the kind of event is the `event` field
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum EnvEvent {
+    StepCompleted {time_step : usize, num_creatures : usize, num_food : usize, num_species : usize},
+    Birth {id : usize, parent_id : Option<usize>, position : CreaturePosition},
+    Death {id : usize, killed : bool, position : CreaturePosition},
+    Kill {killer_id : usize, victim_id : usize, position : CreaturePosition},
+    Extinction {time_step : usize},
+    LineageExtinct {time_step : usize, lineage_id : usize},     // The last living member of a tribe died
+    SpeciesExtinct {time_step : usize, species_id : usize},     // A species had no members left when species were re-clustered
+    Catastrophe {time_step : usize, kind : CatastropheKind, num_affected : usize},
+    Restart {time_step : usize, num_restarts : usize},         // The run policy started the sim over from the last run's creatures
+    Immigration {time_step : usize, id : usize, position : CreaturePosition}, // A new creature entered from the edge of the board
+}
+
+/// Events that happened since a frontend last took them (oldest first)
+#[derive(Clone, Default)]
+pub struct EventQueue {
+    pending : VecDeque<EnvEvent>,
+}
+
+/// Writes events to a file as JSON lines (one event per line) so other programs can follow a run by tailing the file
+#[cfg(feature = "file_io")]
+pub struct EventStreamWriter {
+    file : std::io::BufWriter<std::fs::File>,
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl EventQueue {
+
+    /// Create a new empty queue
+    pub fn new() -> EventQueue {
+        return EventQueue {
+            pending : VecDeque::new(),
+        };
+    }
+
+    /// Add an event to the queue. If nobody is taking events, the oldest are dropped
+    pub fn push(&mut self, event : EnvEvent) {
+        if self.pending.len() >= MAX_PENDING_EVENTS {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(event);
+    }
+
+    /// Take every pending event, leaving the queue empty
+    #[allow(dead_code)]
+    pub fn take_all(&mut self) -> Vec<EnvEvent> {
+        return self.pending.drain(..).collect();
+    }
+}
+
+impl EnvEvent {
+
+    /// Get the event as a single line of JSON (without the newline), e.g. `{"event":"Extinction","time_step":12}`
+    #[cfg(feature = "json")]
+    pub fn to_json_line(&self) -> String {
+        return serde_json::to_string(self).unwrap_or_default();
+    }
+}
+
+#[cfg(feature = "file_io")]
+impl EventStreamWriter {
+
+    /// Create (or overwrite) the event stream file. Returns the reason if it can't be created
+    pub fn create(filename : &str) -> Result<EventStreamWriter, String> {
+        return match std::fs::File::create(filename) {
+            Ok(file) => Ok(EventStreamWriter {file : std::io::BufWriter::new(file)}),
+            Err(e) => Err(format!("could not create event stream file {}. Error = {e}", filename)),
+        };
+    }
+
+    /// Write each event as a line of JSON, then flush so the lines show up for anyone tailing the file right away
+    pub fn write_events(&mut self, events : &[EnvEvent]) -> Result<(), String> {
+        use std::io::Write;
+        for event in events.iter() {
+            if let Err(e) = writeln!(self.file, "{}", event.to_json_line()) {
+                return Err(format!("could not write to the event stream. Error = {e}"));
+            }
+        }
+        return self.file.flush().map_err(|e| format!("could not write to the event stream. Error = {e}"));
+    }
+}
+
+
+#[cfg(test)]
+mod events_test {
+    use super::*;
+
+    #[test]
+    fn test_event_queue_drops_oldest() {
+        let mut queue = EventQueue::new();
+        for step in 0..(MAX_PENDING_EVENTS + 5) {
+            queue.push(EnvEvent::StepCompleted {time_step : step, num_creatures : 0, num_food : 0, num_species : 0});
+        }
+
+        let events = queue.take_all();
+        assert_eq!(events.len(), MAX_PENDING_EVENTS);
+        assert!(events[0] == EnvEvent::StepCompleted {time_step : 5, num_creatures : 0, num_food : 0, num_species : 0});
+        assert!(queue.take_all().is_empty());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_event_json_lines() {
+        let event = EnvEvent::Kill {killer_id : 1, victim_id : 2, position : CreaturePosition {x : 3, y : 4}};
+        let line = event.to_json_line();
+        assert_eq!(line, r#"{"event":"Kill","killer_id":1,"victim_id":2,"position":{"x":3,"y":4}}"#);
+        assert!(serde_json::from_str::<EnvEvent>(&line).unwrap() == event);
+    }
+}
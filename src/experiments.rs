@@ -0,0 +1,615 @@
+/** ===============================================================================
+ * File: experiments.rs
+ * Author: Scott Stack
+ * Description: Run policies for longer experiments. Decides when a simulation should
+ * be restarted and which creatures from the previous run seed the next one
+ * ===============================================================================*/
+use crate::creature::*;
+use crate::environment::*;
+use crate::events::EnvEvent;
+use crate::sim_rng::{sim_rng, run_seeded};
+use crate::stats::LifetimeRecord;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+#[cfg(feature = "file_io")]
+use std::fs::File;
+#[cfg(feature = "file_io")]
+use std::io::Write;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const DEFAULT_TOP_K : usize = 10;               // Default number of best creatures kept by top-K selection
+#[allow(dead_code)]
+pub const DEFAULT_TOURNAMENT_SIZE : usize = 4;      // Default number of creatures competing in each tournament
+pub const ALL_OBJECTIVES : [Objective; 4] = [Objective::Longevity, Objective::Offspring, Objective::KillsAvoided, Objective::AreaExplored];
+const PARETO_CSV_HEADER : &str = "label,longevity,offspring,kills_avoided,area_explored,on_front";
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// How parents for the next run are picked from the creatures of the previous run
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SelectionMethod {
+    Random,             // Every candidate is equally likely to be picked
+    TopK(usize),        // Only the K fittest candidates are used (round robin)
+    Tournament(usize),  // For each slot, pick N random candidates and keep the fittest
+}
+
+/// Measure used to rank creatures during selection
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FitnessMetric {
+    Lifespan,   // Age the creature reached
+    Offspring,  // Number of offspring the creature had
+}
+
+/// Things a run (or a single creature) can be scored on when there's more than one goal. Higher is better for all of them
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Objective {
+    Longevity,      // Age reached
+    Offspring,      // Number of offspring
+    KillsAvoided,   // 1 if the creature wasn't killed by another creature, 0 if it was (averaged over a run)
+    AreaExplored,   // Number of spaces moved
+}
+
+/// Score on each objective. For a run, each score is averaged over every creature that lived in it
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ObjectiveScores {
+    pub longevity : f32,
+    pub offspring : f32,
+    pub kills_avoided : f32,
+    pub area_explored : f32,
+}
+
+/// One run (or one setting of a sweep) in a Pareto report
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoEntry {
+    pub label : String,                 // What the entry is, e.g. "mutation_prob=0.05"
+    pub scores : ObjectiveScores,       // Its score on each objective
+    pub on_front : bool,                // True if no other entry is at least as good on every objective and better on one
+}
+
+/// Entries of a sweep with the ones on the Pareto front marked
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParetoReport {
+    pub entries : Vec<ParetoEntry>,     // Entries in the order they were added
+}
+
+/// Limits on how far a single run goes. Anything left as None isn't limited
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RunLimits {
+    pub max_steps : Option<usize>,              // Stop after this many steps
+    pub max_wall_clock : Option<Duration>,      // Stop after running for this long in real time
+    pub max_population : Option<usize>,         // Stop once this many creatures are alive (e.g. to catch runaway growth)
+}
+
+/// Why a run stopped
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StopReason {
+    Extinction,         // Every creature died
+    MaxSteps,           // RunLimits::max_steps was reached
+    MaxWallClock,       // RunLimits::max_wall_clock ran out
+    MaxPopulation,      // RunLimits::max_population was reached
+    Quit,               // The frontend (or its user) asked to stop
+}
+
+/// Describes when a simulation gets restarted and how the next run is seeded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunPolicy {
+    pub restart_on_extinction : bool,           // Restart as soon as every creature has died
+    pub restart_every_n_generations : usize,    // Restart once the population has advanced this many generations since the last restart. 0 = never
+    pub selection : SelectionMethod,            // How parents are picked for the next run
+    pub fitness : FitnessMetric,                // What "fittest" means for top-K/tournament selection
+    #[serde(default)]
+    pub limits : RunLimits,                     // Limits on each run (they start over when the sim is restarted)
+
+    #[serde(skip)]
+    run_start : Option<(usize, Instant)>,       // Time step and time the current run's limits are counted from (None = not started yet)
+    #[serde(skip)]
+    last_restart_generation : usize,            // Highest generation at the time of the last restart
+    #[serde(skip)]
+    pub num_restarts : usize,                   // Number of restarts performed so far
+}
+
+impl RunPolicy {
+
+    /// Policy that never restarts the simulation
+    pub fn new() -> RunPolicy {
+        return RunPolicy {
+            restart_on_extinction : false,
+            restart_every_n_generations : 0,
+            selection : SelectionMethod::Random,
+            fitness : FitnessMetric::Lifespan,
+            limits : RunLimits::default(),
+            run_start : None,
+            last_restart_generation : 0,
+            num_restarts : 0,
+        };
+    }
+
+    /// Check whether the environment should be restarted according to this policy
+    pub fn should_restart(&self, env : &EnvironmentV1) -> bool {
+        if env.creatures.is_empty() {
+            return self.restart_on_extinction;
+        }
+
+        if self.restart_every_n_generations > 0 {
+            let max_generation = env.creatures.iter().map(|c| c.generation).max().unwrap_or(0);
+            return max_generation >= self.last_restart_generation + self.restart_every_n_generations;
+        }
+
+        return false;
+    }
+
+    /// Restart the environment if the policy says so. Returns true if a restart happened
+    pub fn maybe_restart(&mut self, env : &mut EnvironmentV1) -> bool {
+        if !self.should_restart(env) {
+            return false;
+        }
+
+        *env = self.new_seeded_environment(env);
+        self.last_restart_generation = env.creatures.iter().map(|c| c.generation).max().unwrap_or(0);
+        self.num_restarts += 1;
+        self.run_start = None;
+        env.events.push(EnvEvent::Restart {time_step : env.time_step, num_restarts : self.num_restarts});
+        return true;
+    }
+
+    /// Start counting the limits over from the next `check_limits` call (e.g. after a new environment is created)
+    pub fn reset_limits(&mut self) {
+        self.run_start = None;
+    }
+
+    /// Check whether the current run should stop: every creature died or one of the limits was reached. Limits are
+    /// counted from the first call after the policy was created or last restarted the sim, so frontends that step
+    /// the sim themselves should call this after each step (after `maybe_restart`)
+    pub fn check_limits(&mut self, env : &EnvironmentV1) -> Option<StopReason> {
+        let (start_step, start_time) = *self.run_start.get_or_insert((env.time_step, Instant::now()));
+        if env.creatures.is_empty() {
+            return Some(StopReason::Extinction);
+        }
+        return self.limits.check(env, start_step, start_time);
+    }
+
+    /// Create a fresh environment (same parameters) whose creatures are mutated copies of parents selected from `env`.
+    /// Both living creatures and recently dead ones are candidates. If there are no candidates the new creatures are random.
+    /// If `env` is a seeded run, the new environment carries on from its generator so the run stays reproducible
+    pub fn new_seeded_environment(&self, env : &EnvironmentV1) -> EnvironmentV1 {
+        let mut rng = env.rng.clone();
+        let mut new_env = run_seeded(&mut rng, || self.new_seeded_environment_inner(env));
+        new_env.rng = rng;
+        return new_env;
+    }
+
+    /// Body of `new_seeded_environment`, run with the simulation RNG already set up
+    fn new_seeded_environment_inner(&self, env : &EnvironmentV1) -> EnvironmentV1 {
+        let mut new_env = EnvironmentV1::new_rand(&env.params);
+        new_env.time_step = env.time_step;
+        new_env.stats = env.stats.clone();
+        new_env.events = env.events.clone();
+
+        let candidates : Vec<&CreatureV1> = env.creatures.iter().chain(env.dead_creatures.iter()).collect();
+        let parent_idxs = self.select_parents(&candidates, new_env.creatures.len());
+
+        for (creature, parent_idx) in new_env.creatures.iter_mut().zip(parent_idxs) {
+            let parent = candidates[parent_idx];
+
+            // Keep the randomly picked spot/orientation of the placeholder creature, but take the parent's genes
+            let mut child = CreatureV1::new_offspring(creature.id, parent, &env.params.get_mutation_config(), env.params.creature_starting_energy);
+            child.set_position(creature.position.x, creature.position.y);
+            child.set_orientation(creature.orientation);
+            if env.params.evolve_view_distance {
+                child.mutate_view_distance(env.params.mutation_prob);
+            } else {
+                child.view_distance = creature.view_distance;
+            }
+            if env.params.enable_swimming {
+                child.mutate_swimming(env.params.mutation_prob);
+            }
+            *creature = child;
+        }
+
+        return new_env;
+    }
+
+    /// Pick `num_parents` parents (by index into `candidates`, repeats allowed) according to the selection method
+    pub fn select_parents(&self, candidates : &[&CreatureV1], num_parents : usize) -> Vec<usize> {
+        let mut rng = sim_rng();
+        let mut parents : Vec<usize> = Vec::with_capacity(num_parents);
+        if candidates.is_empty() {
+            return parents;
+        }
+
+        match self.selection {
+            SelectionMethod::Random => {
+                for _ in 0..num_parents {
+                    parents.push(rng.gen_range(0..candidates.len()));
+                }
+            },
+            SelectionMethod::TopK(k) => {
+                let mut ranked : Vec<usize> = (0..candidates.len()).collect();
+                ranked.sort_by_key(|idx| std::cmp::Reverse(self.get_fitness(candidates[*idx])));
+                ranked.truncate(k.max(1));
+                for slot in 0..num_parents {
+                    parents.push(ranked[slot % ranked.len()]);
+                }
+            },
+            SelectionMethod::Tournament(size) => {
+                for _ in 0..num_parents {
+                    let mut best_idx = rng.gen_range(0..candidates.len());
+                    for _ in 1..size.max(1) {
+                        let challenger_idx = rng.gen_range(0..candidates.len());
+                        if self.get_fitness(candidates[challenger_idx]) > self.get_fitness(candidates[best_idx]) {
+                            best_idx = challenger_idx;
+                        }
+                    }
+                    parents.push(best_idx);
+                }
+            },
+        }
+
+        return parents;
+    }
+
+    /// Get the fitness of a creature according to the policy's metric
+    pub fn get_fitness(&self, creature : &CreatureV1) -> usize {
+        return match self.fitness {
+            FitnessMetric::Lifespan => creature.age,
+            FitnessMetric::Offspring => creature.num_offspring,
+        };
+    }
+}
+impl Default for RunPolicy {
+    fn default() -> RunPolicy {
+        return RunPolicy::new();
+    }
+}
+
+impl SelectionMethod {
+    /// Parse a selection method from a string like "random", "top-k:10" or "tournament:4".
+    /// The number after the colon is optional
+    #[allow(dead_code)]
+    pub fn from_arg(text : &str) -> Option<SelectionMethod> {
+        let mut parts = text.splitn(2, ':');
+        let name = parts.next()?;
+        let arg = match parts.next() {
+            Some(num_str) => Some(num_str.parse::<usize>().ok()?),
+            None => None,
+        };
+
+        return match name {
+            "random" => Some(SelectionMethod::Random),
+            "top-k" => Some(SelectionMethod::TopK(arg.unwrap_or(DEFAULT_TOP_K))),
+            "tournament" => Some(SelectionMethod::Tournament(arg.unwrap_or(DEFAULT_TOURNAMENT_SIZE))),
+            _ => None,
+        };
+    }
+}
+
+impl FitnessMetric {
+    /// Parse a fitness metric from "lifespan" or "offspring"
+    #[allow(dead_code)]
+    pub fn from_arg(text : &str) -> Option<FitnessMetric> {
+        return match text {
+            "lifespan" => Some(FitnessMetric::Lifespan),
+            "offspring" => Some(FitnessMetric::Offspring),
+            _ => None,
+        };
+    }
+}
+
+impl Objective {
+    /// Parse an objective from "longevity", "offspring", "kills-avoided" or "area-explored"
+    #[allow(dead_code)]
+    pub fn from_arg(text : &str) -> Option<Objective> {
+        return ALL_OBJECTIVES.iter().copied().find(|objective| objective.get_name() == text);
+    }
+
+    /// Get the name of the objective as used on the command line
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            Objective::Longevity => "longevity",
+            Objective::Offspring => "offspring",
+            Objective::KillsAvoided => "kills-avoided",
+            Objective::AreaExplored => "area-explored",
+        };
+    }
+}
+
+impl ObjectiveScores {
+
+    /// Score a living creature
+    pub fn from_creature(creature : &CreatureV1) -> ObjectiveScores {
+        return ObjectiveScores {
+            longevity : creature.age as f32,
+            offspring : creature.num_offspring as f32,
+            kills_avoided : if creature.was_killed() { 0.0 } else { 1.0 },
+            area_explored : creature.distance_traveled as f32,
+        };
+    }
+
+    /// Score a creature that has died from its lifetime record
+    pub fn from_record(record : &LifetimeRecord) -> ObjectiveScores {
+        return ObjectiveScores {
+            longevity : record.lifespan as f32,
+            offspring : record.offspring as f32,
+            kills_avoided : if record.was_killed { 0.0 } else { 1.0 },
+            area_explored : record.distance_traveled as f32,
+        };
+    }
+
+    /// Score a run: the average over every creature that has died (that's still recorded) and every living creature
+    pub fn from_run(env : &EnvironmentV1) -> ObjectiveScores {
+        let dead = env.stats.lifetime_records.iter().map(ObjectiveScores::from_record);
+        let living = env.creatures.iter().map(ObjectiveScores::from_creature);
+        return ObjectiveScores::average(&dead.chain(living).collect::<Vec<ObjectiveScores>>());
+    }
+
+    /// Average a list of scores (e.g. from runs with different seeds). All zeros if the list is empty
+    pub fn average(scores : &[ObjectiveScores]) -> ObjectiveScores {
+        if scores.is_empty() {
+            return ObjectiveScores::default();
+        }
+        let num_scores = scores.len() as f32;
+        let avg = |get_value : fn(&ObjectiveScores) -> f32| scores.iter().map(get_value).sum::<f32>() / num_scores;
+        return ObjectiveScores {
+            longevity : avg(|s| s.longevity),
+            offspring : avg(|s| s.offspring),
+            kills_avoided : avg(|s| s.kills_avoided),
+            area_explored : avg(|s| s.area_explored),
+        };
+    }
+
+    /// Get the score on one objective
+    pub fn get(&self, objective : Objective) -> f32 {
+        return match objective {
+            Objective::Longevity => self.longevity,
+            Objective::Offspring => self.offspring,
+            Objective::KillsAvoided => self.kills_avoided,
+            Objective::AreaExplored => self.area_explored,
+        };
+    }
+
+    /// Returns true if these scores are at least as good as `other` on every objective and better on at least one
+    pub fn dominates(&self, other : &ObjectiveScores) -> bool {
+        let no_worse = ALL_OBJECTIVES.iter().all(|o| self.get(*o) >= other.get(*o));
+        let better = ALL_OBJECTIVES.iter().any(|o| self.get(*o) > other.get(*o));
+        return no_worse && better;
+    }
+}
+
+impl ParetoReport {
+
+    /// Create a report from (label, scores) pairs, marking which of them are on the Pareto front
+    pub fn new(results : Vec<(String, ObjectiveScores)>) -> ParetoReport {
+        let all_scores : Vec<ObjectiveScores> = results.iter().map(|(_, scores)| *scores).collect();
+        let entries = results.into_iter().map(|(label, scores)| ParetoEntry {
+            label : label,
+            scores : scores,
+            on_front : !all_scores.iter().any(|other| other.dominates(&scores)),
+        }).collect();
+        return ParetoReport {entries : entries};
+    }
+
+    /// Get the entries on the Pareto front
+    pub fn get_front(&self) -> Vec<&ParetoEntry> {
+        return self.entries.iter().filter(|entry| entry.on_front).collect();
+    }
+
+    /// Get every entry as CSV text (with a header line)
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(PARETO_CSV_HEADER);
+        csv.push('\n');
+        for entry in self.entries.iter() {
+            let s = &entry.scores;
+            csv.push_str(&format!("{},{},{},{},{},{}\n",
+                entry.label, s.longevity, s.offspring, s.kills_avoided, s.area_explored, entry.on_front));
+        }
+        return csv;
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Write the report to a CSV file
+    pub fn save_csv(&self, filename : &str) {
+        let mut csv_file = match File::create(filename) {
+            Ok(f) => f,
+            Err(e) => {
+                println!("Error: could not create file {}. Error {e}", filename);
+                return;
+            }
+        };
+
+        if let Err(e) = csv_file.write_all(self.to_csv().as_bytes()) {
+            println!("Error: could not write Pareto report to {}. Error {e}", filename);
+        }
+    }
+}
+
+impl RunLimits {
+
+    /// Limits that only stop the run after `max_steps` steps
+    pub fn with_max_steps(max_steps : usize) -> RunLimits {
+        return RunLimits {max_steps : Some(max_steps), ..Default::default()};
+    }
+
+    /// Check whether a run that started at `start_step` / `start_time` has reached any of the limits
+    pub fn check(&self, env : &EnvironmentV1, start_step : usize, start_time : Instant) -> Option<StopReason> {
+        if self.max_steps.is_some_and(|max_steps| env.time_step - start_step >= max_steps) {
+            return Some(StopReason::MaxSteps);
+        }
+        if self.max_population.is_some_and(|max_population| env.num_creatures >= max_population) {
+            return Some(StopReason::MaxPopulation);
+        }
+        if self.max_wall_clock.is_some_and(|max_wall_clock| start_time.elapsed() >= max_wall_clock) {
+            return Some(StopReason::MaxWallClock);
+        }
+        return None;
+    }
+}
+
+/// Run two copies of an environment made from the same params and `seed` in lockstep for up to `num_steps` steps,
+/// comparing their state hashes after every step. Returns the number of steps that matched, or the first time step
+/// the copies diverged at. Stops early if the creatures go extinct
+#[allow(dead_code)]
+pub fn audit_determinism(params : &EnvironmentParams, seed : u64, num_steps : usize) -> Result<usize, usize> {
+    let mut env_a = EnvironmentV1::new_seeded(params, seed);
+    let mut env_b = EnvironmentV1::new_seeded(params, seed);
+    if env_a.state_hash() != env_b.state_hash() {
+        return Err(env_a.time_step);
+    }
+
+    for step_num in 0..num_steps {
+        env_a.advance_step();
+        env_b.advance_step();
+        if env_a.state_hash() != env_b.state_hash() {
+            return Err(env_a.time_step);
+        }
+        if env_a.creatures.is_empty() {
+            return Ok(step_num + 1);
+        }
+    }
+    return Ok(num_steps);
+}
+
+
+#[cfg(test)]
+mod experiments_test {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_seeded_runs_are_deterministic() {
+        let mut params = EnvironmentParams::new();
+        params.env_x_size = 24;
+        params.env_y_size = 24;
+        params.num_start_creatures = 30;
+        params.num_start_food = 30;
+        params.num_start_walls = 10;
+        params.enable_speciation = true;
+        assert!(audit_determinism(&params, 7, 50).is_ok());
+
+        // Restarting a seeded run is reproducible too
+        let policy = RunPolicy::new();
+        let restarted_a = policy.new_seeded_environment(&EnvironmentV1::new_seeded(&params, 7));
+        let restarted_b = policy.new_seeded_environment(&EnvironmentV1::new_seeded(&params, 7));
+        assert_eq!(restarted_a.state_hash(), restarted_b.state_hash());
+
+        // Different seeds should give different runs
+        let env_a = EnvironmentV1::new_seeded(&params, 1);
+        let env_b = EnvironmentV1::new_seeded(&params, 2);
+        assert_ne!(env_a.state_hash(), env_b.state_hash());
+    }
+
+    #[test]
+    fn test_select_parents() {
+        // Candidate i is i*10 steps old and has 9-i offspring
+        let mut candidates : Vec<CreatureV1> = Vec::new();
+        for idx in 0..10 {
+            let mut creature = CreatureV1::new(idx, &CreatureParams::new());
+            creature.age = idx * 10;
+            creature.num_offspring = 9 - idx;
+            candidates.push(creature);
+        }
+        let candidates : Vec<&CreatureV1> = candidates.iter().collect();
+        let mut policy = RunPolicy::new();
+        let select = |policy : &RunPolicy, seed : u64| run_seeded(&mut Some(StdRng::seed_from_u64(seed)), || policy.select_parents(&candidates, 100));
+
+        let random = select(&policy, 5);
+        assert_eq!(random, select(&policy, 5));
+        assert!(random.iter().all(|idx| *idx < candidates.len()));
+        assert!((0..candidates.len()).all(|idx| random.contains(&idx)));
+
+        // Top-K goes round robin through the K fittest
+        policy.selection = SelectionMethod::TopK(3);
+        assert_eq!(&select(&policy, 5)[..6], &[9, 8, 7, 9, 8, 7]);
+        policy.fitness = FitnessMetric::Offspring;
+        assert_eq!(&select(&policy, 5)[..6], &[0, 1, 2, 0, 1, 2]);
+
+        // A tournament of one is the same as random selection. Bigger tournaments favor the fittest
+        policy.fitness = FitnessMetric::Lifespan;
+        policy.selection = SelectionMethod::Tournament(1);
+        assert_eq!(select(&policy, 5), random);
+        policy.selection = SelectionMethod::Tournament(4);
+        let tournament = select(&policy, 5);
+        assert_eq!(tournament, select(&policy, 5));
+        assert!(tournament.iter().sum::<usize>() > random.iter().sum::<usize>() + 150);
+        assert!(!tournament.contains(&0));
+    }
+
+    #[test]
+    fn test_should_restart() {
+        let params = EnvironmentParams::with_size(20, 20);
+        let mut env = EnvironmentV1::new_seeded(&params, 6);
+        let mut policy = RunPolicy::new();
+        assert!(!policy.should_restart(&env));
+
+        // Every N generations, counted from the last restart
+        policy.restart_every_n_generations = 3;
+        env.creatures[0].generation = 2;
+        assert!(!policy.should_restart(&env));
+        env.creatures[0].generation = 3;
+        assert!(policy.should_restart(&env));
+        policy.selection = SelectionMethod::TopK(1);
+        env.creatures[0].age = 1000;
+        assert!(policy.maybe_restart(&mut env));
+        assert_eq!(policy.num_restarts, 1);
+        assert_eq!(env.creatures.iter().map(|c| c.generation).max(), Some(4));
+        assert!(!policy.should_restart(&env));
+        env.creatures[0].generation = 7;
+        assert!(policy.should_restart(&env));
+
+        // On extinction, only if asked to
+        policy.restart_every_n_generations = 0;
+        env.creatures.clear();
+        assert!(!policy.should_restart(&env));
+        policy.restart_on_extinction = true;
+        assert!(policy.should_restart(&env));
+    }
+
+    #[test]
+    fn test_run_limits() {
+        let mut params = EnvironmentParams::with_size(20, 20);
+        params.num_start_creatures = 20;
+        let mut env = EnvironmentV1::new_seeded(&params, 4);
+        assert_eq!(env.run_with_limits(&RunLimits::with_max_steps(5)), StopReason::MaxSteps);
+        assert_eq!(env.time_step, 5);
+        assert_eq!(env.run_with_limits(&RunLimits {max_population : Some(1), ..Default::default()}), StopReason::MaxPopulation);
+        assert_eq!(env.run_with_limits(&RunLimits {max_wall_clock : Some(Duration::ZERO), ..Default::default()}), StopReason::MaxWallClock);
+        assert_eq!(env.time_step, 5);
+
+        // Policies count their limits from the start of each run
+        let mut policy = RunPolicy::new();
+        policy.limits = RunLimits::with_max_steps(2);
+        assert_eq!(policy.check_limits(&env), None);
+        env.advance_step();
+        env.advance_step();
+        assert_eq!(policy.check_limits(&env), Some(StopReason::MaxSteps));
+    }
+
+    #[test]
+    fn test_pareto_report() {
+        let scores = |longevity : f32, offspring : f32| ObjectiveScores {longevity : longevity, offspring : offspring, kills_avoided : 0.5, area_explored : 1.0};
+        let report = ParetoReport::new(vec![
+            (String::from("a"), scores(10.0, 1.0)),
+            (String::from("b"), scores(5.0, 3.0)),
+            (String::from("c"), scores(5.0, 1.0)),     // Worse than both a and b
+        ]);
+        let front : Vec<&str> = report.get_front().iter().map(|entry| entry.label.as_str()).collect();
+        assert_eq!(front, vec!["a", "b"]);
+        assert_eq!(report.to_csv().lines().nth(3), Some("c,5,1,0.5,1,false"));
+        assert_eq!(Objective::from_arg("kills-avoided"), Some(Objective::KillsAvoided));
+
+        // Run scores average over dead and living creatures
+        let mut params = EnvironmentParams::with_size(20, 20);
+        params.num_start_creatures = 20;
+        let mut env = EnvironmentV1::new_seeded(&params, 3);
+        let _ = env.run_n_steps(30);
+        let run_scores = ObjectiveScores::from_run(&env);
+        assert!(run_scores.longevity > 0.0);
+        assert!((0.0..=1.0).contains(&run_scores.kills_avoided));
+    }
+}
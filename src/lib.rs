@@ -0,0 +1,88 @@
+//! Simulation core for the evolution sim, shared by the GUI and console frontends. Frontends build an
+//! [`environment::EnvironmentParams`], create an [`environment::EnvironmentV1`] from it and step it, drawing
+//! from [`snapshot::EnvSnapshot`]s and reacting to [`events::EnvEvent`]s as they go.
+//!
+//! The simulation itself only needs `rand` and the `serde` derives. Everything else is behind features so the
+//! core can be built for smaller targets with `default-features = false`:
+//! * `json`: converting environments and creatures to and from JSON (pulls in `serde_json`)
+//! * `file_io`: loading saves from files, writing stats files and watching params files (implies `json`)
+//! * `image`: importing walls from images and exporting PNG snapshots
+//!
+//! Run a small seeded environment headless for a while:
+//!
+//! ```
+//! use evolution_sim::environment::*;
+//!
+//! let mut params = EnvironmentParams::with_size(20, 20);
+//! params.enable_hearing_input = true;
+//! let mut env = EnvironmentV1::new_seeded(&params, 42);
+//! for _ in 0..50 {
+//!     env.advance_step();
+//! }
+//! assert_eq!(env.time_step, 50);
+//! ```
+//!
+//! Save it and load it back (needs the `json` feature):
+//!
+//! ```
+//! # use evolution_sim::environment::*;
+//! # #[cfg(feature = "json")] {
+//! # let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(20, 20), 42);
+//! let json = env.to_json();
+//! let mut loaded = EnvironmentV1::from_json(&json).unwrap();
+//! assert_eq!(loaded.state_hash(), env.state_hash());
+//!
+//! // Unseeded environments can keep running from where the save left off
+//! loaded.advance_step();
+//! # }
+//! ```
+//!
+//! Inspect the creatures:
+//!
+//! ```
+//! # use evolution_sim::environment::*;
+//! # let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(20, 20), 42);
+//! # let _ = env.run_n_steps(10);
+//! for creature in env.creatures.iter() {
+//!     println!("Creature {} at ({}, {}) has {} energy", creature.id, creature.position.x, creature.position.y, creature.energy);
+//! }
+//! if let Some(creature) = env.creatures.first() {
+//!     let same_creature = env.get_creature(creature.id).unwrap();
+//!     println!("{}", same_creature.brain.describe());
+//! }
+//!
+//! // What happened while the env ran (births, deaths, ...)
+//! let events = env.events.take_all();
+//! assert!(!events.is_empty());
+//! ```
+pub mod linalg;
+pub mod sim_rng;
+pub mod neural_net;
+pub mod creature;
+pub mod analysis;
+pub mod environment;
+pub mod experiments;
+pub mod stats;
+pub mod events;
+pub mod snapshot;
+pub mod snapshot_delta;
+pub mod replay;
+pub mod time_travel;
+pub mod param_docs;
+#[cfg(feature = "file_io")]
+pub mod params_watch;
+pub mod catastrophes;
+pub mod terrain;
+pub mod wall_map;
+pub mod commands;
+pub mod render;
+
+/// The types most frontends need, so they can be pulled in with a single `use evolution_sim::prelude::*;`
+pub mod prelude {
+    pub use crate::environment::{EnvironmentParams, EnvironmentV1, EnvErrors, SpaceStates, DecalKind};
+    pub use crate::creature::{CreatureV1, CreaturePosition, CreatureOrientation, CreatureColor};
+    pub use crate::snapshot::{EnvSnapshot, SnapshotCell, CreatureRenderInfo};
+    pub use crate::events::EnvEvent;
+    pub use crate::experiments::RunPolicy;
+    pub use crate::render::Renderer;
+}
@@ -4,6 +4,7 @@
 * Description: Implements some simple linear algebra types and methods
 * ===============================================================================
 */
+use crate::sim_rng::sim_rng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 
@@ -40,7 +41,7 @@ where
 
     /// Return a matrix initialized with random data
     pub fn random(nrows : usize, ncols : usize, min_val : T, max_val : T) -> Matrix<T> {
-        let mut rng = rand::thread_rng();
+        let mut rng = sim_rng();
         let mut temp_mat = Matrix::<T> {
             data : vec![T::default(); nrows * ncols],
             nrows : nrows,
@@ -72,6 +73,14 @@ where
         self.data[row * self.ncols + col] = val;
     }
 
+    /// Get a mutable reference to a single entry in the matrix, or None if the row/col is out of range
+    pub fn get_mut(&mut self, row : usize, col : usize) -> Option<&mut T> {
+        if (row >= self.nrows) || (col >= self.ncols) {
+            return None;
+        }
+        return self.data.get_mut(row * self.ncols + col);
+    }
+
     /// Get number of rows
     pub fn get_nrows(&self) -> usize {
         return self.nrows;
@@ -4,15 +4,195 @@
  * Author: Scott Stack
  * Description: main application entry point for console target version of the program
  * ===============================================================================*/
-mod linalg;
-mod neural_net;
-mod creature;
-mod environment;
 mod env_console;
-use crate::environment::*;
+use evolution_sim::experiments::*;
+use evolution_sim::render::SpeedGovernor;
+use evolution_sim::param_docs::get_params_help;
+
+const DEFAULT_AUDIT_SEED : u64 = 0;     // Seed used by --determinism-audit and --record-replay if --seed isn't given
+const DEFAULT_REPLAY_STEPS : usize = 1000; // Steps recorded by --record-replay if --replay-steps isn't given
 
 /// Main function for command line sim visualization version
+/// Usage: console [--snapshot-png <file>] [--restart-on-extinction] [--restart-every <generations>]
+///                [--selection <random|top-k[:K]|tournament[:N]>] [--fitness <lifespan|offspring>]
+///                [--color-by-species] [--lifetime-csv <file>] [--brain-dot <file>] [--report <file>]
+///                [--command-socket <path>] [--events-out <file>] [--watch-params <file>] [--governor <min rate>:<max rate>] [--max-steps <steps>] [--max-seconds <seconds>] [--max-population <creatures>]
+///                [--timelapse-dir <dir> [--timelapse-every <steps>] [--timelapse-stats]]
+///        console --help-params
+///        console --determinism-audit <steps> [--seed <seed>]
+///        console --record-replay <replay file> --save <save file> [--replay-steps <steps>] [--seed <seed>]
+///        console --verify-replay <replay file> --save <save file>
 fn main() {
-    env_console::run_console_demo_mode();
+    let args : Vec<String> = std::env::args().collect();
+
+    // Just list what every param does (handy when writing a params file for --watch-params)
+    if args.iter().any(|arg| arg == "--help-params") {
+        print!("{}", get_params_help());
+        return;
+    }
+
+    let color_by_species = args.iter().any(|arg| arg == "--color-by-species");
+    let command_socket = get_arg_value(&args, "--command-socket");
+    let outputs = env_console::DemoOutputs {
+        snapshot_file : get_arg_value(&args, "--snapshot-png"),
+        lifetime_csv_file : get_arg_value(&args, "--lifetime-csv"),
+        brain_dot_file : get_arg_value(&args, "--brain-dot"),
+        report_file : get_arg_value(&args, "--report").unwrap_or(String::from(env_console::DEFAULT_REPORT_FILE)),
+        events_file : get_arg_value(&args, "--events-out"),
+    };
+
+    // Determinism audit mode just checks that seeded runs repeat exactly. Exits with an error code if they don't
+    if let Some(num_steps) = get_arg_value(&args, "--determinism-audit") {
+        let num_steps = match num_steps.parse::<usize>() {
+            Ok(num_steps) => num_steps,
+            Err(_) => {
+                println!("Error: invalid number of steps for --determinism-audit: {}", num_steps);
+                std::process::exit(2);
+            }
+        };
+        if !env_console::run_determinism_audit(num_steps, get_seed(&args)) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Replay modes record a seeded run next to the save it starts from, or re-simulate one to check it still matches.
+    // Exits with an error code if the replay can't be recorded or doesn't match
+    let record_replay_file = get_arg_value(&args, "--record-replay");
+    let verify_replay_file = get_arg_value(&args, "--verify-replay");
+    if record_replay_file.is_some() || verify_replay_file.is_some() {
+        let save_file = match get_arg_value(&args, "--save") {
+            Some(save_file) => save_file,
+            None => {
+                println!("Error: replays need the save they start from (--save <file>)");
+                std::process::exit(2);
+            }
+        };
+        let passed = match (record_replay_file, verify_replay_file) {
+            (Some(replay_file), _) => {
+                let num_steps = match get_arg_value(&args, "--replay-steps").map(|s| s.parse::<usize>()) {
+                    Some(Ok(num_steps)) => num_steps,
+                    Some(Err(_)) => {
+                        println!("Error: invalid number of steps for --replay-steps");
+                        std::process::exit(2);
+                    }
+                    None => DEFAULT_REPLAY_STEPS,
+                };
+                env_console::record_replay(&replay_file, &save_file, num_steps, get_seed(&args))
+            },
+            (None, Some(replay_file)) => env_console::run_replay_verification(&replay_file, &save_file),
+            (None, None) => unreachable!(),
+        };
+        if !passed {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // Build the restart policy from the command line
+    let mut run_policy = RunPolicy::new();
+    run_policy.restart_on_extinction = args.iter().any(|arg| arg == "--restart-on-extinction");
+    if let Some(num_gens) = get_arg_value(&args, "--restart-every") {
+        match num_gens.parse::<usize>() {
+            Ok(num_gens) => run_policy.restart_every_n_generations = num_gens,
+            Err(_) => {
+                println!("Error: invalid number of generations for --restart-every: {}", num_gens);
+                return;
+            }
+        }
+    }
+    if let Some(selection) = get_arg_value(&args, "--selection") {
+        match SelectionMethod::from_arg(&selection) {
+            Some(method) => run_policy.selection = method,
+            None => {
+                println!("Error: invalid selection method: {}", selection);
+                return;
+            }
+        }
+    }
+    if let Some(fitness) = get_arg_value(&args, "--fitness") {
+        match FitnessMetric::from_arg(&fitness) {
+            Some(metric) => run_policy.fitness = metric,
+            None => {
+                println!("Error: invalid fitness metric: {}", fitness);
+                return;
+            }
+        }
+    }
+
+    // Limits on each run (they start over when the run policy restarts the sim)
+    for (flag, limit) in [("--max-steps", &mut run_policy.limits.max_steps), ("--max-population", &mut run_policy.limits.max_population)] {
+        if let Some(val) = get_arg_value(&args, flag) {
+            match val.parse::<usize>() {
+                Ok(val) => *limit = Some(val),
+                Err(_) => {
+                    println!("Error: invalid value for {}: {}", flag, val);
+                    return;
+                }
+            }
+        }
+    }
+    if let Some(seconds) = get_arg_value(&args, "--max-seconds") {
+        match seconds.parse::<f64>().ok().and_then(|s| std::time::Duration::try_from_secs_f64(s).ok()) {
+            Some(duration) => run_policy.limits.max_wall_clock = Some(duration),
+            None => {
+                println!("Error: invalid number of seconds for --max-seconds: {}", seconds);
+                return;
+            }
+        }
+    }
+
+    // Time-lapse frames are only exported if a directory is given
+    let mut timelapse : Option<env_console::TimelapseOptions> = None;
+    if let Some(dir) = get_arg_value(&args, "--timelapse-dir") {
+        let every_n_steps = match get_arg_value(&args, "--timelapse-every").map(|s| s.parse::<usize>()) {
+            Some(Ok(every_n_steps)) if every_n_steps > 0 => every_n_steps,
+            Some(_) => {
+                println!("Error: invalid number of steps for --timelapse-every");
+                return;
+            }
+            None => env_console::DEFAULT_TIMELAPSE_INTERVAL,
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            println!("Error: could not create time-lapse directory {}. Error {e}", dir);
+            return;
+        }
+        timelapse = Some(env_console::TimelapseOptions {
+            dir : dir,
+            every_n_steps : every_n_steps,
+            stats_footer : args.iter().any(|arg| arg == "--timelapse-stats"),
+        });
+    }
+
+    let params_file = get_arg_value(&args, "--watch-params");
+    let mut governor : Option<SpeedGovernor> = None;
+    if let Some(rates) = get_arg_value(&args, "--governor") {
+        match SpeedGovernor::from_arg(&rates) {
+            Some(parsed) => governor = Some(parsed),
+            None => {
+                println!("Error: invalid --governor: {} (expected <min steps/sec>:<max steps/sec>)", rates);
+                return;
+            }
+        }
+    }
+    env_console::run_console_demo_mode(outputs, timelapse, command_socket, params_file, governor, run_policy, color_by_species);
+}
+
+/// Get the seed given with --seed (or the default one). Exits if it isn't a valid number
+fn get_seed(args : &[String]) -> u64 {
+    return match get_arg_value(args, "--seed").map(|s| s.parse::<u64>()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => {
+            println!("Error: invalid --seed");
+            std::process::exit(2);
+        }
+        None => DEFAULT_AUDIT_SEED,
+    };
+}
+
+/// Get the value following a `--flag` style command line argument, if it was specified
+fn get_arg_value(args : &[String], flag : &str) -> Option<String> {
+    let flag_idx = args.iter().position(|arg| arg == flag)?;
+    return args.get(flag_idx + 1).cloned();
 }
 
@@ -8,10 +8,8 @@
  *  - generate walls in more interesting way (connected walls)
  *  - allow saving individual creatures
  * ===============================================================================*/
-mod linalg;
-mod neural_net;
-mod creature;
-mod environment;
+mod sounds;
+mod effects;
 mod env_macroquad;
 use macroquad::prelude::next_frame;
 
@@ -21,6 +19,26 @@ use macroquad::prelude::next_frame;
 async fn main() {
     let mut m_env = env_macroquad::EnvMacroquad::new();
 
+    // The speed governor steps slower when few creatures are alive and faster during booms
+    if std::env::args().any(|arg| arg == "--speed-governor") {
+        m_env.enable_speed_governor();
+    }
+
+    // Display only mode just shows the board until the creatures die out
+    if std::env::args().any(|arg| arg == "--display-only") {
+        m_env.run_display_only_mode().await;
+        return;
+    }
+
+    // Kiosk mode for unattended touchscreens
+    if std::env::args().any(|arg| arg == "--kiosk") {
+        m_env.enable_kiosk_mode();
+    }
+
+    // Sound effects (only with the `audio` feature)
+    #[cfg(feature = "audio")]
+    m_env.load_sounds().await;
+
     // Start the visualization
     loop {
         m_env.main_loop_interactive_mode();
@@ -5,9 +5,16 @@
  * creatures in the environment
  * ===============================================================================*/
 use crate::linalg::*;
+use crate::sim_rng::sim_rng;
 use rand::Rng;
 use num;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Mutation operator tuning
+const GAUSSIAN_MUTATION_STD_FRACTION : f32 = 0.1;   // Std deviation of gaussian perturbations as a fraction of the [min, max] value range
+const LAYER_SCALE_MIN_FACTOR : f32 = 0.5;           // Smallest factor a layer can be scaled by in a single mutation
+const LAYER_SCALE_MAX_FACTOR : f32 = 1.5;           // Largest factor a layer can be scaled by in a single mutation
 
 
 /// Possible error types to be returned from neural network functions
@@ -16,6 +23,78 @@ pub enum NeuralNetErrors {
     OutputActivationError,    // Error finding the max activation value of an output neuron after evaluating network
 }
 
+/// Different ways a single weight/bias (or whole layer) can be mutated
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum MutationOperator {
+    GaussianPerturb,    // Add gaussian noise to the current value
+    SignFlip,           // Negate the current value
+    Reset,              // Replace with a new uniformly random value
+    Swap,               // Swap values with another random entry in the same layer
+    LayerScale,         // Scale every weight/bias in the layer by a random factor
+}
+
+/// Relative weights of each mutation operator. When an entry is chosen for mutation, the operator
+/// applied is picked randomly in proportion to these weights
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct MutationMix {
+    pub gaussian : f32,     // Weight of MutationOperator::GaussianPerturb
+    pub sign_flip : f32,    // Weight of MutationOperator::SignFlip
+    pub reset : f32,        // Weight of MutationOperator::Reset
+    pub swap : f32,         // Weight of MutationOperator::Swap
+    pub layer_scale : f32,  // Weight of MutationOperator::LayerScale
+}
+
+impl MutationMix {
+    /// Mix that only re-randomizes values (the original mutation behavior)
+    pub const RESET_ONLY : MutationMix = MutationMix {
+        gaussian : 0.0,
+        sign_flip : 0.0,
+        reset : 1.0,
+        swap : 0.0,
+        layer_scale : 0.0,
+    };
+
+    /// Randomly pick an operator according to the weights. Returns None if all weights are 0
+    pub fn choose<R: Rng>(&self, rng : &mut R) -> Option<MutationOperator> {
+        let choices = [
+            (MutationOperator::GaussianPerturb, self.gaussian),
+            (MutationOperator::SignFlip, self.sign_flip),
+            (MutationOperator::Reset, self.reset),
+            (MutationOperator::Swap, self.swap),
+            (MutationOperator::LayerScale, self.layer_scale),
+        ];
+        let total : f32 = choices.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut pick = rng.gen::<f32>() * total;
+        for (op, weight) in choices {
+            let weight = weight.max(0.0);
+            if weight > 0.0 && pick < weight {
+                return Some(op);
+            }
+            pick -= weight;
+        }
+
+        // Floating point rounding can leave us just past the end, so fall back to the last non-zero operator
+        return choices.iter().rev().find(|(_, weight)| *weight > 0.0).map(|(op, _)| *op);
+    }
+}
+
+impl Default for MutationMix {
+    fn default() -> Self {
+        return MutationMix::RESET_ONLY;
+    }
+}
+
+impl fmt::Display for MutationMix {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "gaussian={} sign_flip={} reset={} swap={} layer_scale={}",
+            self.gaussian, self.sign_flip, self.reset, self.swap, self.layer_scale);
+    }
+}
+
 /// Implements a simple generic neural network for use as a brain for creatures
 /// The generic represents the underlying type that network values/weights/biases
 /// will use (f32, isize, etc...)
@@ -30,7 +109,9 @@ pub struct NeuralNet<T> {
     // biases - List of vectors (nx1 matrix types) where each index into the vector is the layers
     pub biases : Vec<Matrix<T>>,
 
-    // Activations of each neuron n the network. Matrix is an nx1 vector
+    // Activations of each neuron n the network. Matrix is an nx1 vector. These aren't needed to restore
+    // a network (see `reset_activations`), so compact saves leave them out
+    #[serde(default)]
     pub activations : Vec<Matrix<T>>,
 }
 
@@ -46,6 +127,8 @@ T: Copy,
 T: std::cmp::PartialOrd,
 T: rand::distributions::uniform::SampleUniform,
 T: num::Zero,
+T: num::NumCast,
+T: std::ops::Neg<Output = T>,
 T: for<'a> Deserialize<'a>,
 T: Serialize,
 {
@@ -107,19 +190,162 @@ T: Serialize,
         return max_act_node;
     }
 
+    /// Evaluate the network for one set of input node values and return the activations of every layer (input layer first).
+    /// This doesn't touch the stored activations, so it can be used to probe a network
+    pub fn evaluate_layers(&self, inputs : &[T]) -> Vec<Vec<T>> {
+        let mut layer_vals = Matrix::<T>::new(inputs.len(), 1);
+        layer_vals.set_from_arr(inputs);
+        let mut layers : Vec<Vec<T>> = vec![inputs.to_vec()];
+        for layer_num in 0..(self.num_layers - 1) {
+            layer_vals = self.weights[layer_num].mult(&layer_vals).add(&self.biases[layer_num]);
+            for i in 0..layer_vals.get_nrows() {
+                layer_vals.set(i, 0, NeuralNet::relu(layer_vals.get(i, 0)));
+            }
+            layers.push((0..layer_vals.get_nrows()).map(|i| layer_vals.get(i, 0)).collect());
+        }
+        return layers;
+    }
+
+    /// Root-mean-square difference between every weight/bias of this network and `other`.
+    /// Returns None if the two networks don't have the same shape
+    pub fn distance(&self, other : &NeuralNet<T>) -> Option<f32> {
+        if self.num_layers != other.num_layers {
+            return None;
+        }
+
+        let mut sum_sq : f32 = 0.0;
+        let mut num_vals : usize = 0;
+        let mats = self.weights.iter().zip(other.weights.iter()).chain(self.biases.iter().zip(other.biases.iter()));
+        for (mat, other_mat) in mats {
+            if mat.get_nrows() != other_mat.get_nrows() || mat.get_ncols() != other_mat.get_ncols() {
+                return None;
+            }
+            for row in 0..mat.get_nrows() {
+                for col in 0..mat.get_ncols() {
+                    let diff = num::cast::<T, f32>(mat.get(row, col))? - num::cast::<T, f32>(other_mat.get(row, col))?;
+                    sum_sq += diff * diff;
+                    num_vals += 1;
+                }
+            }
+        }
+
+        if num_vals == 0 {
+            return Some(0.0);
+        }
+        return Some((sum_sq / num_vals as f32).sqrt());
+    }
+
+    /// Get the current value of an input node
+    pub fn get_input_node(&self, input_node_idx : usize) -> T {
+        return self.activations[0].get(input_node_idx, 0);
+    }
+
+    /// Get the activation of every output node from the last evaluation
+    pub fn get_output_activations(&self) -> Vec<T> {
+        let output_layer = &self.activations[self.num_layers - 1];
+        return (0..output_layer.get_nrows()).map(|i| output_layer.get(i, 0)).collect();
+    }
+
+    /// Get the number of connections (weights) in the network. One evaluation does one multiply per connection
+    pub fn get_num_connections(&self) -> usize {
+        return self.weights.iter().map(|w| w.get_nrows() * w.get_ncols()).sum();
+    }
+
+    /// Returns true if the weights and biases of every layer line up with the layers around them
+    /// (e.g. false for a network from a hand-edited or corrupted file)
+    pub fn is_well_formed(&self) -> bool {
+        if self.num_layers < 2 || self.weights.len() != self.num_layers - 1 || self.biases.len() != self.weights.len() {
+            return false;
+        }
+        for (layer_num, layer_weights) in self.weights.iter().enumerate() {
+            let bias = &self.biases[layer_num];
+            if bias.get_nrows() != layer_weights.get_nrows() || bias.get_ncols() != 1 {
+                return false;
+            }
+            if layer_num > 0 && layer_weights.get_ncols() != self.weights[layer_num - 1].get_nrows() {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    /// Get the number of neurons in each layer (input layer first)
+    pub fn get_layer_sizes(&self) -> Vec<usize> {
+        let mut layer_sizes : Vec<usize> = Vec::with_capacity(self.num_layers);
+        if let Some(first_weights) = self.weights.first() {
+            layer_sizes.push(first_weights.get_ncols());
+        }
+        for layer_weights in self.weights.iter() {
+            layer_sizes.push(layer_weights.get_nrows());
+        }
+        return layer_sizes;
+    }
+
+    /// Get the Frobenius norm (root of the sum of squares) of the weights between each pair of layers
+    pub fn get_weight_norms(&self) -> Vec<f32> {
+        return self.weights.iter().map(|mat| {
+            let mut sum_sq : f32 = 0.0;
+            for row in 0..mat.get_nrows() {
+                for col in 0..mat.get_ncols() {
+                    let val = num::cast::<T, f32>(mat.get(row, col)).unwrap_or(0.0);
+                    sum_sq += val * val;
+                }
+            }
+            sum_sq.sqrt()
+        }).collect();
+    }
+
+    /// Get how strongly each input neuron feeds each output neuron, ignoring biases and the activation function.
+    /// This is the product of all weight matrices, so entry (output, input) sums the weight products of every path between them
+    pub fn get_pathway_strengths(&self) -> Option<Matrix<T>> {
+        let mut pathways = self.weights.first()?.clone();
+        for layer_weights in self.weights.iter().skip(1) {
+            pathways = layer_weights.mult(&pathways);
+        }
+        return Some(pathways);
+    }
+
+    /// Reset every neuron's activation to zero. Also (re)creates the activation vectors, e.g. if the
+    /// network was loaded from a save that didn't include them
+    pub fn reset_activations(&mut self) {
+        self.activations.clear();
+        if let Some(first_weights) = self.weights.first() {
+            self.activations.push(Matrix::new(first_weights.get_ncols(), 1));
+        }
+        for layer_weights in self.weights.iter() {
+            self.activations.push(Matrix::new(layer_weights.get_nrows(), 1));
+        }
+    }
+
+    /// Get a mutable reference to the weight connecting neuron `from` in layer `layer` to neuron `to` in layer `layer + 1`.
+    /// Returns None if there's no such connection
+    pub fn get_weight_mut(&mut self, layer : usize, to : usize, from : usize) -> Option<&mut T> {
+        return self.weights.get_mut(layer)?.get_mut(to, from);
+    }
+
+    /// Get a mutable reference to the bias of neuron `node` in layer `layer + 1` (the input layer has no biases).
+    /// Returns None if there's no such neuron
+    pub fn get_bias_mut(&mut self, layer : usize, node : usize) -> Option<&mut T> {
+        return self.biases.get_mut(layer)?.get_mut(node, 0);
+    }
+
     /// Set value of specified input node
     pub fn set_input_node(&mut self, input_node_idx : usize, val : T) {
         self.activations[0].set(input_node_idx, 0, val);
     }
 
 
-    /// Apply random mutation to every weight/bias with probability of mutation `mutation_prob` 
-    /// to a value between val_min and val_max
-    pub fn apply_rand_mutations(&mut self, mutation_prob : f32, val_min : T, val_max : T) {
-        let mut rng = rand::thread_rng();
+    /// Mutate every weight/bias with probability of mutation `mutation_prob`. The operator used for each
+    /// mutation is picked from `mix`. Mutated values are kept between val_min and val_max.
+    /// `magnitude` scales how far each mutation moves a value (1.0 = full strength)
+    pub fn apply_mutations(&mut self, mutation_prob : f32, mix : &MutationMix, magnitude : f32, val_min : T, val_max : T) {
+        let mut rng = sim_rng();
 
         // apply mutations to biases in each layer
         for layer in 0..(self.num_layers - 1) {
+            // A layer is scaled at most once per pass, no matter how many of its entries picked LayerScale
+            let mut scale_layer = false;
+
             // Apply mutations to biases
             let num_nodes = self.biases[layer].get_nrows();
             let num_nodes_next = self.weights[layer].get_ncols();
@@ -128,18 +354,96 @@ T: Serialize,
                 // Mutate biases 
                 // If random number [0,1) is less than mutation_prob, entry should be mutated!
                 if rng.gen::<f32>() <= mutation_prob {
-                    // Set to new random value
-                    self.biases[layer].set(i, 0, rng.gen_range(val_min..=val_max));
+                    match mix.choose(&mut rng) {
+                        Some(MutationOperator::LayerScale) => scale_layer = true,
+                        Some(op) => NeuralNet::mutate_entry(&mut self.biases[layer], i, 0, op, magnitude, (val_min, val_max), &mut rng),
+                        None => {},
+                    }
                 }
 
                 // Mutate weights
                 for j in 0..num_nodes_next {
                     if rng.gen::<f32>() <= mutation_prob {
-                        // Set to new random value
-                        self.weights[layer].set(i, j, rng.gen_range(val_min..=val_max));
+                        match mix.choose(&mut rng) {
+                            Some(MutationOperator::LayerScale) => scale_layer = true,
+                            Some(op) => NeuralNet::mutate_entry(&mut self.weights[layer], i, j, op, magnitude, (val_min, val_max), &mut rng),
+                            None => {},
+                        }
                     }
                 }
             }
+
+            if scale_layer {
+                let factor = 1.0 + (rng.gen_range(LAYER_SCALE_MIN_FACTOR..=LAYER_SCALE_MAX_FACTOR) - 1.0) * magnitude;
+                NeuralNet::scale_matrix(&mut self.weights[layer], factor, val_min, val_max);
+                NeuralNet::scale_matrix(&mut self.biases[layer], factor, val_min, val_max);
+            }
+        }
+    }
+
+    /// Apply a single per-entry mutation operator to entry (row, col) of `mat`. `bounds` is the (min, max)
+    /// range values are kept within
+    fn mutate_entry<R: Rng>(mat : &mut Matrix<T>, row : usize, col : usize, op : MutationOperator, magnitude : f32, bounds : (T, T), rng : &mut R) {
+        let (val_min, val_max) = bounds;
+        let cur_val = mat.get(row, col);
+        match op {
+            MutationOperator::GaussianPerturb => {
+                let range : f32 = num::cast::<T, f32>(val_max - val_min).unwrap_or(0.0);
+                let noise = standard_normal_sample(rng) * range * GAUSSIAN_MUTATION_STD_FRACTION * magnitude;
+                let new_val = num::cast::<T, f32>(cur_val)
+                    .and_then(|val| num::cast::<f32, T>(val + noise))
+                    .unwrap_or(cur_val);
+                mat.set(row, col, NeuralNet::clamp(new_val, val_min, val_max));
+            },
+            MutationOperator::SignFlip => {
+                mat.set(row, col, NeuralNet::clamp(-cur_val, val_min, val_max));
+            },
+            MutationOperator::Reset => {
+                // A weakened reset only moves part of the way towards the new random value
+                let rand_val = rng.gen_range(val_min..=val_max);
+                if magnitude >= 1.0 {
+                    mat.set(row, col, rand_val);
+                } else {
+                    let new_val = match (num::cast::<T, f32>(cur_val), num::cast::<T, f32>(rand_val)) {
+                        (Some(cur), Some(target)) => num::cast::<f32, T>(cur + (target - cur) * magnitude).unwrap_or(cur_val),
+                        _ => rand_val,
+                    };
+                    mat.set(row, col, NeuralNet::clamp(new_val, val_min, val_max));
+                }
+            },
+            MutationOperator::Swap => {
+                let other_row = rng.gen_range(0..mat.get_nrows());
+                let other_col = rng.gen_range(0..mat.get_ncols());
+                let other_val = mat.get(other_row, other_col);
+                mat.set(other_row, other_col, cur_val);
+                mat.set(row, col, other_val);
+            },
+            // Whole-layer operators are handled by the caller
+            MutationOperator::LayerScale => {},
+        }
+    }
+
+    /// Scale every entry in `mat` by `factor`, keeping them between val_min and val_max
+    fn scale_matrix(mat : &mut Matrix<T>, factor : f32, val_min : T, val_max : T) {
+        for row in 0..mat.get_nrows() {
+            for col in 0..mat.get_ncols() {
+                let cur_val = mat.get(row, col);
+                let new_val = num::cast::<T, f32>(cur_val)
+                    .and_then(|val| num::cast::<f32, T>(val * factor))
+                    .unwrap_or(cur_val);
+                mat.set(row, col, NeuralNet::clamp(new_val, val_min, val_max));
+            }
+        }
+    }
+
+    /// Keep a value within [min, max]
+    fn clamp(val : T, min : T, max : T) -> T {
+        if val < min {
+            return min;
+        } else if val > max {
+            return max;
+        } else {
+            return val;
         }
     }
 
@@ -156,11 +460,36 @@ T: Serialize,
 }
 
 
+/// Draw a sample from the standard normal distribution (Box-Muller transform)
+pub fn standard_normal_sample<R: Rng>(rng : &mut R) -> f32 {
+    let u1 : f32 = 1.0 - rng.gen::<f32>(); // (0, 1] so the log is defined
+    let u2 : f32 = rng.gen::<f32>();
+    return (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+}
+
 
 #[cfg(test)]
 mod neuralnet_test {
     use super::*;
 
+    #[test]
+    fn test_distance() {
+        let nn_a = NeuralNet::<f32>::new(&vec![3, 4, 2], -10.0, 10.0);
+        let nn_b = NeuralNet::<f32>::new(&vec![3, 4, 2], -10.0, 10.0);
+        assert_eq!(nn_a.distance(&nn_a), Some(0.0));
+        assert_eq!(nn_a.distance(&nn_b), nn_b.distance(&nn_a));
+        assert!(nn_a.distance(&nn_b).unwrap() > 0.0);
+
+        // RMS over every weight and bias: 12 + 8 weights and 4 + 2 biases, one of them 2.6 apart
+        let mut nn_c = nn_a.clone();
+        nn_c.weights[1].set(0, 0, nn_a.weights[1].get(0, 0) + 2.6);
+        assert!((nn_a.distance(&nn_c).unwrap() - (2.6f32 * 2.6 / 26.0).sqrt()).abs() < 1e-5);
+
+        // Different shapes can't be compared
+        assert_eq!(nn_a.distance(&NeuralNet::<f32>::new(&vec![3, 5, 2], -10.0, 10.0)), None);
+        assert_eq!(nn_a.distance(&NeuralNet::<f32>::new(&vec![3, 4, 4, 2], -10.0, 10.0)), None);
+    }
+
     #[test]
     fn test_neuralnet_init() {
         let layer_sizes = vec![5, 4, 4, 4, 8];
@@ -188,5 +517,58 @@ mod neuralnet_test {
         assert!(res < layer_sizes[layer_sizes.len()-1]);
         println!("Output layer activated {}", res);
 
+        // Probing the network with the same inputs gives the same outputs
+        assert_eq!(nn.evaluate_layers(&input_neuron_vals).last().unwrap(), &nn.get_output_activations());
+    }
+
+    #[test]
+    fn test_neuralnet_mutation_mix() {
+        let layer_sizes = vec![3, 4, 2];
+        let mut nn = NeuralNet::<f32>::new(&layer_sizes, -100.0, 100.0);
+        let orig = nn.clone();
+
+        // Mutating every entry with only sign flips should negate the whole network
+        let mix = MutationMix { gaussian : 0.0, sign_flip : 1.0, reset : 0.0, swap : 0.0, layer_scale : 0.0 };
+        nn.apply_mutations(1.0, &mix, 1.0, -100.0, 100.0);
+        for layer in 0..(layer_sizes.len() - 1) {
+            assert_eq!(nn.weights[layer].get(0, 0), -orig.weights[layer].get(0, 0));
+            assert_eq!(nn.biases[layer].get(1, 0), -orig.biases[layer].get(1, 0));
+        }
+
+        // A mix with no weight shouldn't change anything
+        let mut nn2 = orig.clone();
+        let empty_mix = MutationMix { gaussian : 0.0, sign_flip : 0.0, reset : 0.0, swap : 0.0, layer_scale : 0.0 };
+        nn2.apply_mutations(1.0, &empty_mix, 1.0, -100.0, 100.0);
+        assert_eq!(nn2.weights[0].get(1, 1), orig.weights[0].get(1, 1));
+    }
+
+    #[test]
+    fn test_neuralnet_summary() {
+        let mut nn = NeuralNet::<f32>::new(&vec![2, 2, 1], -1.0, 1.0);
+        nn.weights[0].set_from_arr(&[1.0, 0.0, 2.0, 3.0]);
+        nn.weights[1].set_from_arr(&[4.0, 0.0]);
+
+        assert_eq!(nn.get_layer_sizes(), vec![2, 2, 1]);
+        assert_eq!(nn.get_weight_norms()[1], 4.0);
+
+        // Only the path through the first hidden neuron has any weight
+        let pathways = nn.get_pathway_strengths().unwrap();
+        assert_eq!(pathways.get(0, 0), 4.0);
+        assert_eq!(pathways.get(0, 1), 0.0);
+    }
+
+    #[test]
+    fn test_neuralnet_weight_accessors() {
+        let mut nn = NeuralNet::<f32>::new(&vec![2, 3, 1], -1.0, 1.0);
+        *nn.get_weight_mut(1, 0, 2).unwrap() = 5.0;
+        *nn.get_bias_mut(0, 2).unwrap() = -5.0;
+        assert_eq!(nn.weights[1].get(0, 2), 5.0);
+        assert_eq!(nn.biases[0].get(2, 0), -5.0);
+
+        // Nothing outside the network can be reached
+        assert!(nn.get_weight_mut(1, 1, 0).is_none());
+        assert!(nn.get_weight_mut(0, 0, 2).is_none());
+        assert!(nn.get_weight_mut(2, 0, 0).is_none());
+        assert!(nn.get_bias_mut(1, 1).is_none());
     }
 }
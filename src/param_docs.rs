@@ -0,0 +1,213 @@
+/** ===============================================================================
+ * File: param_docs.rs
+ * Author: Scott Stack
+ * Description: Human readable descriptions, units and sensible ranges for every environment
+ * and creature param (and the run policy settings in the GUI param panel). Used for the GUI
+ * param panel tooltips and slider ranges, and the console's --help-params
+ * ===============================================================================*/
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const CATASTROPHE_RATE_RANGE : (f32, f32) = (0.0, 10.0);         // Sensible range of a catastrophe's rate (average number per 1000 steps)
+pub const CATASTROPHE_MAGNITUDE_RANGE : (f32, f32) = (0.0, 1.0);     // Range of a catastrophe's magnitude (chance of each thing being hit)
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Documentation for a single param
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParamDoc {
+    pub name : &'static str,            // Name of the field (as it appears in saves and params files)
+    pub description : &'static str,     // What the param does
+    pub units : &'static str,           // What the value is measured in ("" for on/off switches, options and plain numbers)
+    pub range : Option<(f32, f32)>,     // Smallest and largest values that make sense (None for on/off switches, options and catastrophes)
+}
+
+/// Shorthand for building the tables below
+const fn doc(name : &'static str, description : &'static str, units : &'static str, range : Option<(f32, f32)>) -> ParamDoc {
+    return ParamDoc {name : name, description : description, units : units, range : range};
+}
+
+/// Every field of `EnvironmentParams`, in the order they're declared
+pub const ENVIRONMENT_PARAM_DOCS : [ParamDoc; 71] = [
+    doc("env_x_size", "Width of the board", "spaces", Some((8.0, 256.0))),
+    doc("env_y_size", "Height of the board", "spaces", Some((8.0, 256.0))),
+    doc("num_start_creatures", "Number of creatures the sim starts with", "creatures", Some((0.0, 2000.0))),
+    doc("num_start_food", "Number of food pieces on the board at the start", "food pieces", Some((0.0, 4000.0))),
+    doc("num_start_walls", "Number of wall spaces on the board", "spaces", Some((0.0, 4000.0))),
+    doc("energy_per_food_piece", "Energy a creature gets for eating a piece of food", "energy", Some((0.0, 200.0))),
+    doc("energy_per_kill", "Energy a creature gets for killing another creature", "energy", Some((0.0, 200.0))),
+    doc("max_offspring_per_reproduce", "Most offspring a single reproduction can produce", "creatures", Some((1.0, 10.0))),
+    doc("mutation_prob", "Chance of each weight/bias in an offspring's brain mutating", "probability", Some((0.0, 1.0))),
+    doc("mutation_mix", "Relative weights of each kind of mutation (gaussian, sign flip, reset, swap, layer scale)", "relative weight", Some((0.0, 10.0))),
+    doc("mutation_schedule", "How mutation strength changes over a lineage: Constant, AnnealByGeneration or SelfAdaptive", "", None),
+    doc("mutation_anneal_rate", "With AnnealByGeneration, mutation strength halves every 1/rate generations", "per generation", Some((0.0, 1.0))),
+    doc("offspring_energy_fraction", "Fraction of the parent's energy each offspring gets. 0 = offspring get the starting energy instead", "fraction", Some((0.0, 1.0))),
+    doc("gestation_steps", "Steps between reproducing and the offspring being born. 0 = born right away", "steps", Some((0.0, 50.0))),
+    doc("gestation_upkeep_energy", "Extra energy a pregnant creature pays every step", "energy per step", Some((0.0, 10.0))),
+    doc("avg_new_food_per_day", "Average number of food pieces added to the board every step", "food pieces per step", Some((0.0, 20.0))),
+    doc("creature_repro_energy_cost", "Energy a creature spends to reproduce", "energy", Some((0.0, 200.0))),
+    doc("creature_starting_energy", "Energy each new creature starts with", "energy", Some((1.0, 200.0))),
+    doc("enable_position_inputs", "Creatures sense their x/y position on the board", "", None),
+    doc("enable_wall_distance_input", "Creatures sense the distance to the nearest wall in front of them", "", None),
+    doc("enable_move_blocked_input", "Creatures sense whether their last move was blocked", "", None),
+    doc("one_hot_categorical_inputs", "Last action and orientation are sensed as one neuron per possible value instead of a single number", "", None),
+    doc("enable_hearing_input", "Creatures sense how many creatures and food pieces are within the hearing radius", "", None),
+    doc("enable_territory", "Creatures can mark territory for their lineage and sense other lineages' territory", "", None),
+    doc("enable_rest", "Creatures get a Rest action that recovers a little energy, but makes them cheaper to kill", "", None),
+    doc("enable_tribe_vision", "Creatures sense whether a nearby creature of the same lineage sees food or a threat", "", None),
+    doc("enable_decal_vision", "Creatures see fights, births, deaths and other lineages' territory as colored objects, so they can learn to avoid danger", "", None),
+    doc("enable_beacon", "Place a beacon on the board that creatures can sense and get energy for reaching", "", None),
+    doc("energy_per_beacon_reach", "Energy a creature gets for reaching the beacon", "energy", Some((0.0, 200.0))),
+    doc("enable_novelty", "Creatures with unusual behavior get a chance at an extra offspring", "", None),
+    doc("novelty_descriptor", "Behavior compared for novelty search: ActionDistribution or Visitation", "", None),
+    doc("novelty_weight", "Chance of an extra offspring for the most novel creature (less for the rest)", "probability", Some((0.0, 1.0))),
+    doc("enable_speciation", "Periodically group creatures into species by how similar their brains are", "", None),
+    doc("speciation_threshold", "Largest genome distance from a species representative that still counts as the same species", "genome distance", Some((0.0, 50.0))),
+    doc("speciation_interval", "Steps between re-grouping creatures into species", "steps", Some((1.0, 200.0))),
+    doc("creature_order", "Order creatures take their turns in each step: Insertion, Random or Morton", "", None),
+    doc("view_distance", "How far ahead creatures can see (starting value if it evolves)", "spaces", Some((1.0, 20.0))),
+    doc("evolve_view_distance", "Each creature has its own view distance that mutates when it reproduces", "", None),
+    doc("view_energy_cost", "Energy creatures pay every step for each space they can see", "energy per space per step", Some((0.0, 1.0))),
+    doc("enable_metabolic_cost", "Creatures pay a small energy cost every step for thinking, even if they stay put", "", None),
+    doc("metabolic_energy_cost", "Energy creatures pay every step for each neuron in their brain", "energy per neuron per step", Some((0.0, 0.2))),
+    doc("hearing_radius", "How far creatures can hear in any direction", "spaces", Some((1.0, 20.0))),
+    doc("brain_compute_budget", "Brain connections a creature can evaluate per step. Bigger brains take several steps to decide. 0 = unlimited", "connections per step", Some((0.0, 200.0))),
+    doc("action_delay_steps", "Steps between a creature deciding on an action and carrying it out. 0 = right away", "steps", Some((0.0, 5.0))),
+    doc("territory_decay", "Fraction of a territory mark's strength that fades every step", "fraction per step", Some((0.0, 0.2))),
+    doc("tribe_vision_radius", "How far apart creatures of the same lineage can share what they see", "spaces", Some((1.0, 20.0))),
+    doc("season_length", "Steps in a full seasonal cycle. 0 = no seasons", "steps", Some((0.0, 1000.0))),
+    doc("mating_season_start", "Point in the seasonal cycle that mating season starts", "fraction of a season", Some((0.0, 1.0))),
+    doc("mating_season_length", "How much of the seasonal cycle creatures can reproduce in. 1 = all year", "fraction of a season", Some((0.0, 1.0))),
+    doc("food_die_off", "Random food die-offs. Rate is the average number per 1000 steps (0 to 10), magnitude the chance of each food piece being lost (0 to 1)", "", None),
+    doc("plague", "Random plagues. Rate is the average number per 1000 steps (0 to 10), magnitude the chance of each creature being killed (0 to 1)", "", None),
+    doc("wall_collapse", "Random wall collapses. Rate is the average number per 1000 steps (0 to 10), magnitude the chance of each wall space collapsing (0 to 1)", "", None),
+    doc("enable_disease", "Creatures can catch a contagious disease from their neighbors", "", None),
+    doc("initial_infection_fraction", "Fraction of the starting creatures that start out sick", "fraction", Some((0.0, 1.0))),
+    doc("infection_prob", "Chance each step of catching the disease from each sick neighbor", "probability", Some((0.0, 1.0))),
+    doc("infection_duration", "Steps an infection lasts. Creatures that survive it become immune", "steps", Some((1.0, 100.0))),
+    doc("infection_energy_drain", "Energy a sick creature loses every step", "energy per step", Some((0.0, 10.0))),
+    doc("enable_elevation", "Give the board hills. Climbing costs extra energy and going downhill costs less", "", None),
+    doc("elevation_feature_size", "Rough width of the biggest hills", "spaces", Some((2.0, 50.0))),
+    doc("elevation_energy_cost", "Extra energy it takes to climb from the lowest point to the highest", "energy", Some((0.0, 20.0))),
+    doc("high_ground_view_bonus", "Extra spaces creatures can see from the highest point. 0 = off", "spaces", Some((0.0, 10.0))),
+    doc("enable_river", "Run a river down the board that carries food downstream", "", None),
+    doc("river_flow_strength", "Chance each step that food on the river drifts one space downstream", "probability", Some((0.0, 1.0))),
+    doc("river_source_food", "Average number of food pieces that wash in at the river's source every step", "food pieces per step", Some((0.0, 2.0))),
+    doc("enable_swimming", "The river blocks creatures that can't swim. Swimming is inherited and can mutate", "", None),
+    doc("initial_swimmer_fraction", "Fraction of the starting creatures that can swim", "fraction", Some((0.0, 1.0))),
+    doc("enable_large_bodies", "Some creatures have bodies two spaces long, with more energy storage and a higher move cost", "", None),
+    doc("initial_large_fraction", "Fraction of the starting creatures that have large bodies", "fraction", Some((0.0, 1.0))),
+    doc("swim_energy_cost", "Extra energy it takes to move into water", "energy", Some((0.0, 20.0))),
+    doc("immigration_rate", "Average number of immigrants arriving from the edges of the board every step. 0 = none", "creatures per step", Some((0.0, 2.0))),
+    doc("immigrant_source", "Where immigrants' brains come from: Random or HallOfFame", "", None),
+];
+
+/// Every field of `CreatureParams`, in the order they're declared
+pub const CREATURE_PARAM_DOCS : [ParamDoc; 8] = [
+    doc("reproduce_energy_cost", "Energy a creature spends to reproduce", "energy", Some((0.0, 200.0))),
+    doc("move_energy_cost", "Energy a creature spends to move one space", "energy", Some((0.0, 20.0))),
+    doc("rotate_energy_cost", "Energy a creature spends to turn", "energy", Some((0.0, 20.0))),
+    doc("kill_energy_cost", "Energy a creature spends on a kill action", "energy", Some((0.0, 20.0))),
+    doc("starting_energy", "Energy the creature started with", "energy", Some((1.0, 200.0))),
+    doc("rest_energy_gain", "Energy a creature recovers by resting for a step", "energy", Some((0.0, 20.0))),
+    doc("kill_resting_energy_cost", "Energy a creature spends killing a creature that's resting", "energy", Some((0.0, 20.0))),
+    doc("mark_territory_energy_cost", "Energy a creature spends marking territory", "energy", Some((0.0, 20.0))),
+];
+
+/// Run policy settings shown in the GUI param panel (see `experiments::RunPolicy`). These aren't part of a save
+pub const RUN_POLICY_PARAM_DOCS : [ParamDoc; 6] = [
+    doc("restart_on_extinction", "Start a new run from the best creatures so far when every creature has died", "", None),
+    doc("restart_every_n_generations", "Start a new run from the best creatures every N generations. 0 = never", "generations", Some((0.0, 100.0))),
+    doc("max_steps_per_run", "Stop the sim after this many steps of a run. 0 = no limit", "steps", Some((0.0, 10000.0))),
+    doc("selection_method", "How the parents of a new run are picked: Random, Top K or Tournament", "", None),
+    doc("selection_size", "K for top K selection, or the number of creatures in each tournament", "creatures", Some((1.0, 50.0))),
+    doc("fitness_metric", "What makes a creature fitter when picking parents: Lifespan or Offspring", "", None),
+];
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl ParamDoc {
+
+    /// Get the units and range as text, e.g. "energy, 0 to 200"
+    pub fn get_units_and_range(&self) -> String {
+        let range = match self.range {
+            Some((min, max)) => format!("{} to {}", min, max),
+            None => String::new(),
+        };
+        return [self.units, range.as_str()].iter().filter(|s| !s.is_empty()).cloned().collect::<Vec<&str>>().join(", ");
+    }
+
+    /// Get the doc as a line of help text, e.g. "  env_x_size  Width of the board (spaces, 8 to 256)"
+    pub fn to_help_line(&self, name_width : usize) -> String {
+        let units_and_range = self.get_units_and_range();
+        if units_and_range.is_empty() {
+            return format!("  {:name_width$}  {}", self.name, self.description);
+        }
+        return format!("  {:name_width$}  {} ({})", self.name, self.description, units_and_range);
+    }
+}
+
+/// Get the doc for the environment, creature or run policy param called `name`, if there is one
+pub fn get_param_doc(name : &str) -> Option<&'static ParamDoc> {
+    return ENVIRONMENT_PARAM_DOCS.iter().chain(CREATURE_PARAM_DOCS.iter()).chain(RUN_POLICY_PARAM_DOCS.iter()).find(|doc| doc.name == name);
+}
+
+/// Get help text listing every environment and creature param
+pub fn get_params_help() -> String {
+    let name_width = ENVIRONMENT_PARAM_DOCS.iter().chain(CREATURE_PARAM_DOCS.iter()).map(|doc| doc.name.len()).max().unwrap_or(0);
+    let mut help = String::from("Environment params:\n");
+    for doc in ENVIRONMENT_PARAM_DOCS.iter() {
+        help += &doc.to_help_line(name_width);
+        help += "\n";
+    }
+    help += "\nCreature params:\n";
+    for doc in CREATURE_PARAM_DOCS.iter() {
+        help += &doc.to_help_line(name_width);
+        help += "\n";
+    }
+    return help;
+}
+
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod param_docs_test {
+    use super::*;
+    use crate::environment::EnvironmentParams;
+    use crate::creature::CreatureParams;
+
+    /// Check `docs` covers exactly the fields of `params`, and that every numeric default is in range
+    fn check_docs(docs : &[ParamDoc], params : serde_json::Value) {
+        let fields = params.as_object().unwrap();
+        let mut names : Vec<&str> = docs.iter().map(|doc| doc.name).collect();
+        names.sort();
+        assert_eq!(names, fields.keys().map(|key| key.as_str()).collect::<Vec<&str>>());
+        for doc in docs.iter() {
+            if let (Some((min, max)), Some(value)) = (doc.range, fields[doc.name].as_f64()) {
+                assert!(min as f64 <= value && value <= max as f64, "{} default {} is out of range", doc.name, value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_param_documented() {
+        check_docs(&ENVIRONMENT_PARAM_DOCS, serde_json::to_value(EnvironmentParams::new()).unwrap());
+        check_docs(&CREATURE_PARAM_DOCS, serde_json::to_value(CreatureParams::new()).unwrap());
+        assert_eq!(get_param_doc("env_x_size").unwrap().get_units_and_range(), "spaces, 8 to 256");
+        assert!(get_params_help().contains("Creature params:"));
+
+        // Run policy settings don't clash with environment or creature params
+        for doc in RUN_POLICY_PARAM_DOCS.iter() {
+            assert!(get_param_doc(doc.name) == Some(doc), "{} is documented twice", doc.name);
+        }
+        assert_eq!(get_param_doc("selection_size").unwrap().range, Some((1.0, 50.0)));
+    }
+}
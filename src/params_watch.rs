@@ -0,0 +1,117 @@
+/** ===============================================================================
+ * File: params_watch.rs
+ * Author: Scott Stack
+ * Description: Watches a params file and applies the params that are safe to change to a
+ * running environment whenever the file changes, so long headless runs can be tuned live
+ * by editing the file
+ * ===============================================================================*/
+use crate::environment::*;
+use std::time::SystemTime;
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Watches a JSON file of environment params (the same format as the "params" of a save). The file only needs the
+/// params being tuned, e.g. `{"avg_new_food_per_day": 3.5, "mutation_prob": 0.02}`. Anything left out keeps its current value
+pub struct ParamsWatcher {
+    pub filename : String,                          // File being watched
+    last_seen : Option<(SystemTime, u64)>,          // Modification time and length of the file when it was last read (None = not read yet)
+    reported_missing : bool,                        // The file couldn't be found and that's already been reported
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl ParamsWatcher {
+
+    /// Start watching `filename`. The file is read on the first call to `poll`, so its params get applied from the start
+    pub fn new(filename : &str) -> ParamsWatcher {
+        return ParamsWatcher {
+            filename : filename.to_string(),
+            last_seen : None,
+            reported_missing : false,
+        };
+    }
+
+    /// Check whether the file has changed since it was last read. If it has, apply its live params (see
+    /// `EnvironmentParams::copy_live_params`) to `env`. Returns None if the file hasn't changed, otherwise the names
+    /// of the params that changed or the reason the file couldn't be applied
+    pub fn poll(&mut self, env : &mut EnvironmentV1) -> Option<Result<Vec<&'static str>, String>> {
+        let metadata = match std::fs::metadata(&self.filename) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                if self.reported_missing {
+                    return None;
+                }
+                self.reported_missing = true;
+                self.last_seen = None;
+                return Some(Err(format!("could not read {}. Error {e}", self.filename)));
+            },
+        };
+        self.reported_missing = false;
+        let seen = (metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len());
+        if self.last_seen == Some(seen) {
+            return None;
+        }
+        self.last_seen = Some(seen);
+
+        let contents = match std::fs::read_to_string(&self.filename) {
+            Ok(contents) => contents,
+            Err(e) => return Some(Err(format!("could not read {}. Error {e}", self.filename))),
+        };
+        return Some(get_updated_params(&env.params, &contents).map(|new_params| env.params.copy_live_params(&new_params)));
+    }
+}
+
+/// Get a copy of `params` with every param given in the JSON object `contents` changed to the value in it
+fn get_updated_params(params : &EnvironmentParams, contents : &str) -> Result<EnvironmentParams, String> {
+    let changes = match serde_json::from_str::<serde_json::Value>(contents) {
+        Ok(serde_json::Value::Object(changes)) => changes,
+        Ok(_) => return Err(String::from("params file should hold a JSON object")),
+        Err(e) => return Err(format!("params file isn't valid JSON. Error {e}")),
+    };
+
+    let mut merged = match serde_json::to_value(params) {
+        Ok(serde_json::Value::Object(merged)) => merged,
+        _ => return Err(String::from("could not convert the current params to JSON")),
+    };
+    merged.extend(changes);
+    return match serde_json::from_value::<EnvironmentParams>(serde_json::Value::Object(merged)) {
+        Ok(new_params) => Ok(new_params),
+        Err(e) => Err(format!("invalid params. Error {e}")),
+    };
+}
+
+
+#[cfg(test)]
+mod params_watch_test {
+    use super::*;
+
+    #[test]
+    fn test_params_watcher() {
+        let filename = std::env::temp_dir().join(format!("params_watch_test_{}.json", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(16, 16), 2);
+        let mut watcher = ParamsWatcher::new(filename);
+        assert!(watcher.poll(&mut env).unwrap().is_err());
+        assert_eq!(watcher.poll(&mut env), None);
+
+        // Only the live params given in the file change
+        std::fs::write(filename, r#"{"avg_new_food_per_day": 7.5, "env_x_size": 99}"#).unwrap();
+        assert_eq!(watcher.poll(&mut env), Some(Ok(vec!["avg_new_food_per_day"])));
+        assert_eq!(env.params.avg_new_food_per_day, 7.5);
+        assert_eq!(env.params.env_x_size, 16);
+        assert_eq!(watcher.poll(&mut env), None);
+
+        // Changes are picked up again after the file is edited, and bad edits leave the params alone
+        std::fs::write(filename, r#"{"mutation_prob": "lots"}"#).unwrap();
+        assert!(watcher.poll(&mut env).unwrap().is_err());
+        std::fs::write(filename, r#"{"mutation_prob": 0.5, "avg_new_food_per_day": 7.5}"#).unwrap();
+        assert_eq!(watcher.poll(&mut env), Some(Ok(vec!["mutation_prob"])));
+        let _ = std::fs::remove_file(filename);
+    }
+}
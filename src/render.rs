@@ -0,0 +1,207 @@
+/** ===============================================================================
+ * File: render.rs
+ * Author: Scott Stack
+ * Description: Common interface for frontends that show a running simulation, plus the
+ * loop that steps an environment and hands each snapshot to one of them
+ * ===============================================================================*/
+use crate::environment::*;
+use crate::commands::*;
+use crate::experiments::{RunLimits, StopReason};
+use crate::snapshot::EnvSnapshot;
+use std::time::Duration;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const DEFAULT_GOVERNOR_MIN_RATE : f32 = 2.0;            // Steps per second the governor slows down to when few creatures are left
+pub const DEFAULT_GOVERNOR_MAX_RATE : f32 = 30.0;           // Steps per second the governor speeds up to during population booms
+pub const DEFAULT_GOVERNOR_LOW_POPULATION : usize = 10;     // At or below this many creatures the governor runs at its min rate
+pub const DEFAULT_GOVERNOR_HIGH_POPULATION : usize = 300;   // At or above this many creatures the governor runs at its max rate
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// A way of showing a running simulation (GUI window, console, ...). Renderers only ever see snapshots of the board
+/// and ask for changes through commands, so the same loop (see `run_frame`) can drive any of them
+pub trait Renderer {
+
+    /// Get ready to show the board in `snapshot` (the first one of the run)
+    fn init(&mut self, _snapshot : &EnvSnapshot) {}
+
+    /// Show the board
+    fn render_snapshot(&mut self, snapshot : &EnvSnapshot);
+
+    /// Get the commands the user gave since the last call (pause, quit, ...)
+    fn handle_input(&mut self) -> Vec<SimCommand> {
+        return Vec::new();
+    }
+
+    /// Show the reply to a command returned by `handle_input`
+    fn show_reply(&mut self, _reply : &str) {}
+
+    /// Clean up once the run is over
+    fn shutdown(&mut self) {}
+}
+
+/// Picks how fast a display steps the simulation from the number of living creatures, so unattended displays slow
+/// down when only a few creatures are left (so what each one does can be followed) and speed up during booms
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpeedGovernor {
+    pub min_rate : f32,                 // Steps per second at or below `low_population` creatures
+    pub max_rate : f32,                 // Steps per second at or above `high_population` creatures
+    pub low_population : usize,         // Population the rate starts rising from
+    pub high_population : usize,        // Population the rate stops rising at
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl SpeedGovernor {
+
+    /// Create a governor that goes between `min_rate` and `max_rate` steps per second over the default population range
+    pub fn new(min_rate : f32, max_rate : f32) -> SpeedGovernor {
+        return SpeedGovernor {
+            min_rate : min_rate,
+            max_rate : max_rate,
+            low_population : DEFAULT_GOVERNOR_LOW_POPULATION,
+            high_population : DEFAULT_GOVERNOR_HIGH_POPULATION,
+        };
+    }
+
+    /// Parse a governor from "<min rate>:<max rate>" (in steps per second), e.g. "2:30"
+    #[allow(dead_code)]
+    pub fn from_arg(text : &str) -> Option<SpeedGovernor> {
+        let (min_rate, max_rate) = text.split_once(':')?;
+        let min_rate = min_rate.parse::<f32>().ok().filter(|rate| *rate > 0.0)?;
+        let max_rate = max_rate.parse::<f32>().ok().filter(|rate| *rate >= min_rate)?;
+        return Some(SpeedGovernor::new(min_rate, max_rate));
+    }
+
+    /// Get the number of steps per second to run at with `population` living creatures. Goes linearly from the
+    /// min rate to the max rate between the low and high populations
+    pub fn get_rate(&self, population : usize) -> f32 {
+        if self.high_population <= self.low_population {
+            return if population > self.low_population { self.max_rate } else { self.min_rate };
+        }
+        let fraction = (population.saturating_sub(self.low_population) as f32 / (self.high_population - self.low_population) as f32).min(1.0);
+        return self.min_rate + (self.max_rate - self.min_rate) * fraction;
+    }
+
+    /// Get the time to wait between steps with `population` living creatures
+    pub fn get_step_interval(&self, population : usize) -> Duration {
+        return Duration::from_secs_f32(1.0 / self.get_rate(population).max(f32::EPSILON));
+    }
+}
+
+/// Run one frame of a renderer: carry out its commands, step the environment (unless paused) and render the board.
+/// Returns false once the run is over (the renderer quit or every creature died)
+pub fn run_frame(env : &mut EnvironmentV1, renderer : &mut dyn Renderer, state : &mut CommandState) -> bool {
+    for command in renderer.handle_input() {
+        let reply = command.execute(env, state);
+        renderer.show_reply(&reply);
+    }
+    if state.quit {
+        return false;
+    }
+
+    if !state.paused {
+        env.advance_step();
+    }
+    renderer.render_snapshot(&env.get_snapshot());
+    return !env.creatures.is_empty();
+}
+
+/// Run an environment with a renderer until the run is over or one of the `limits` is reached, waiting `frame_delay`
+/// between frames. Returns why it stopped
+pub fn run_renderer(env : &mut EnvironmentV1, renderer : &mut dyn Renderer, limits : &RunLimits, frame_delay : std::time::Duration) -> StopReason {
+    let mut state = CommandState::default();
+    let start_step = env.time_step;
+    let start_time = std::time::Instant::now();
+    renderer.init(&env.get_snapshot());
+    let reason = loop {
+        if !run_frame(env, renderer, &mut state) {
+            break if state.quit { StopReason::Quit } else { StopReason::Extinction };
+        }
+        if let Some(reason) = limits.check(env, start_step, start_time) {
+            break reason;
+        }
+        std::thread::sleep(frame_delay);
+    };
+    renderer.shutdown();
+    return reason;
+}
+
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+
+    /// Counts what it's asked to do and quits after a few frames
+    #[derive(Default)]
+    struct CountingRenderer {
+        num_inits : usize,
+        num_frames : usize,
+        num_shutdowns : usize,
+        replies : Vec<String>,
+    }
+
+    impl Renderer for CountingRenderer {
+        fn init(&mut self, _snapshot : &EnvSnapshot) {
+            self.num_inits += 1;
+        }
+        fn render_snapshot(&mut self, _snapshot : &EnvSnapshot) {
+            self.num_frames += 1;
+        }
+        fn handle_input(&mut self) -> Vec<SimCommand> {
+            return match self.num_frames {
+                2 => vec![SimCommand::Pause],
+                4 => vec![SimCommand::Quit],
+                _ => Vec::new(),
+            };
+        }
+        fn show_reply(&mut self, reply : &str) {
+            self.replies.push(reply.to_string());
+        }
+        fn shutdown(&mut self) {
+            self.num_shutdowns += 1;
+        }
+    }
+
+    #[test]
+    fn test_run_renderer() {
+        let mut env = EnvironmentV1::new_seeded(&EnvironmentParams::with_size(10, 10), 3);
+        let mut renderer = CountingRenderer::default();
+        let reason = run_renderer(&mut env, &mut renderer, &RunLimits::with_max_steps(100), std::time::Duration::ZERO);
+        assert_eq!(reason, StopReason::Quit);
+
+        // Two steps, then paused for two frames and quit before the fifth
+        assert_eq!((renderer.num_inits, renderer.num_frames, renderer.num_shutdowns), (1, 4, 1));
+        assert_eq!(env.time_step, 2);
+        assert_eq!(renderer.replies.len(), 2);
+
+        // Step limits stop the run too
+        let reason = run_renderer(&mut env, &mut CountingRenderer {num_frames : 5, ..Default::default()}, &RunLimits::with_max_steps(3), std::time::Duration::ZERO);
+        assert_eq!(reason, StopReason::MaxSteps);
+        assert_eq!(env.time_step, 5);
+    }
+
+    #[test]
+    fn test_speed_governor() {
+        let governor = SpeedGovernor::from_arg("2:30").unwrap();
+        assert_eq!(governor.get_rate(0), 2.0);
+        assert_eq!(governor.get_rate(DEFAULT_GOVERNOR_LOW_POPULATION), 2.0);
+        assert_eq!(governor.get_rate(DEFAULT_GOVERNOR_HIGH_POPULATION * 2), 30.0);
+        let mid_population = (DEFAULT_GOVERNOR_LOW_POPULATION + DEFAULT_GOVERNOR_HIGH_POPULATION) / 2;
+        assert!((governor.get_rate(mid_population) - 16.0).abs() < 0.1);
+        assert_eq!(governor.get_step_interval(0), Duration::from_millis(500));
+
+        assert!(SpeedGovernor::from_arg("30:2").is_none());
+        assert!(SpeedGovernor::from_arg("0:2").is_none());
+        assert!(SpeedGovernor::from_arg("fast").is_none());
+    }
+}
@@ -0,0 +1,183 @@
+/** ===============================================================================
+ * File: replay.rs
+ * Author: Scott Stack
+ * Description: Replays of seeded runs (the seed plus the snapshot delta of every step) and
+ * re-simulating them from the save they started from to check they still play back the same
+ * ===============================================================================*/
+use crate::environment::*;
+use crate::snapshot_delta::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const REPLAY_HEADER : &[u8] = b"EVREPLAY";  // First bytes of every replay file
+pub const REPLAY_FORMAT_VERSION : u8 = 1;       // Byte after the header. Bumped whenever the encoding changes
+pub const REPLAY_EXTENSION : &str = ".replay";  // File extension replays are saved with
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// A recorded run. It starts from a saved environment (kept separately) and is reproducible from `seed`
+#[derive(Clone, PartialEq)]
+pub struct Replay {
+    pub crate_version : String,         // Version of evolution_sim that recorded the replay
+    pub seed : u64,                     // Seed the sim RNG was set to at the start of the run
+    pub start_hash : u64,               // State hash of the environment the run started from
+    pub end_hash : u64,                 // State hash after the last step
+    pub deltas : Vec<SnapshotDelta>,    // Changes to the board made by each step, in order
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl Replay {
+
+    /// Record `num_steps` steps of `env` (fewer if every creature dies), seeding its RNG with `seed` first.
+    /// `env` should be in the state it was saved in, so the run can be replayed from the save
+    pub fn record(env : &mut EnvironmentV1, seed : u64, num_steps : usize) -> Replay {
+        env.rng = Some(StdRng::seed_from_u64(seed));
+        let start_hash = env.state_hash();
+
+        let mut deltas : Vec<SnapshotDelta> = Vec::with_capacity(num_steps);
+        let mut prev = env.get_snapshot();
+        while deltas.len() < num_steps && !env.creatures.is_empty() {
+            env.advance_step();
+            let next = env.get_snapshot();
+            deltas.push(SnapshotDelta::new(&prev, &next));
+            prev = next;
+        }
+
+        return Replay {
+            crate_version : env!("CARGO_PKG_VERSION").to_string(),
+            seed : seed,
+            start_hash : start_hash,
+            end_hash : env.state_hash(),
+            deltas : deltas,
+        };
+    }
+
+    /// Re-simulate the replay from `env` (the save it started from) and check every step makes the same changes
+    /// as the recorded ones. Returns the number of steps checked, or the reason the replay doesn't match
+    pub fn verify(&self, env : &mut EnvironmentV1) -> Result<usize, String> {
+        if env.state_hash() != self.start_hash {
+            if self.crate_version != env!("CARGO_PKG_VERSION") {
+                return Err(format!("Save doesn't match the start of the replay. The replay was recorded by version {} (this is version {}), so they may be incompatible",
+                    self.crate_version, env!("CARGO_PKG_VERSION")));
+            }
+            return Err(String::from("Save doesn't match the start of the replay"));
+        }
+
+        env.rng = Some(StdRng::seed_from_u64(self.seed));
+        let mut prev = env.get_snapshot();
+        for recorded in self.deltas.iter() {
+            env.advance_step();
+            let next = env.get_snapshot();
+            if SnapshotDelta::new(&prev, &next) != *recorded {
+                return Err(format!("Re-simulated step {} doesn't match the replay", env.time_step));
+            }
+            prev = next;
+        }
+
+        if env.state_hash() != self.end_hash {
+            return Err(format!("State after the last step ({}) doesn't match the replay", env.time_step));
+        }
+        return Ok(self.deltas.len());
+    }
+
+    /// Encode the replay as bytes: the header and format version, the recording's version, seed and hashes, then
+    /// each delta (see `SnapshotDelta::to_bytes`) prefixed with its length
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes : Vec<u8> = REPLAY_HEADER.to_vec();
+        bytes.push(REPLAY_FORMAT_VERSION);
+        write_varint(&mut bytes, self.crate_version.len());
+        bytes.extend_from_slice(self.crate_version.as_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.start_hash.to_le_bytes());
+        bytes.extend_from_slice(&self.end_hash.to_le_bytes());
+
+        write_varint(&mut bytes, self.deltas.len());
+        for delta in self.deltas.iter() {
+            let delta_bytes = delta.to_bytes();
+            write_varint(&mut bytes, delta_bytes.len());
+            bytes.extend_from_slice(&delta_bytes);
+        }
+        return bytes;
+    }
+
+    /// Decode a replay written by `to_bytes`. Returns the reason if the bytes aren't a valid replay
+    pub fn from_bytes(bytes : &[u8]) -> Result<Replay, String> {
+        if !bytes.starts_with(REPLAY_HEADER) {
+            return Err(String::from("Not a replay file"));
+        }
+        let mut reader = ByteReader::new(&bytes[REPLAY_HEADER.len()..]);
+        let version = reader.read_u8()?;
+        if version != REPLAY_FORMAT_VERSION {
+            return Err(format!("Unsupported replay format version {} (expected {})", version, REPLAY_FORMAT_VERSION));
+        }
+
+        let version_len = reader.read_len()?;
+        let crate_version = match std::str::from_utf8(reader.read_bytes(version_len)?) {
+            Ok(crate_version) => crate_version.to_string(),
+            Err(_) => return Err(String::from("Replay version isn't valid text")),
+        };
+        let seed = reader.read_u64()?;
+        let start_hash = reader.read_u64()?;
+        let end_hash = reader.read_u64()?;
+
+        let mut deltas : Vec<SnapshotDelta> = Vec::new();
+        for step_num in 0..reader.read_len()? {
+            let delta_len = reader.read_len()?;
+            match SnapshotDelta::from_bytes(reader.read_bytes(delta_len)?) {
+                Ok(delta) => deltas.push(delta),
+                Err(msg) => return Err(format!("Step {} of the replay is invalid: {}", step_num + 1, msg)),
+            }
+        }
+        if !reader.is_done() {
+            return Err(String::from("Replay has extra bytes at the end"));
+        }
+
+        return Ok(Replay {
+            crate_version : crate_version,
+            seed : seed,
+            start_hash : start_hash,
+            end_hash : end_hash,
+            deltas : deltas,
+        });
+    }
+}
+
+
+#[cfg(test)]
+mod replay_test {
+    use super::*;
+
+    #[test]
+    fn test_replay_verifies() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.num_start_creatures = 20;
+        let start = EnvironmentV1::new_seeded(&params, 4);
+
+        let mut recorded_env = start.clone();
+        let replay = Replay::from_bytes(&Replay::record(&mut recorded_env, 9, 30).to_bytes()).unwrap();
+        assert!(!replay.deltas.is_empty());
+        assert_eq!(replay.verify(&mut start.clone()), Ok(replay.deltas.len()));
+
+        // A different seed (or a different starting state) gives a different run
+        let mut wrong_seed = replay.clone();
+        wrong_seed.seed = 10;
+        assert!(wrong_seed.verify(&mut start.clone()).is_err());
+        let mut moved_on = start.clone();
+        moved_on.advance_step();
+        assert!(replay.verify(&mut moved_on).is_err());
+
+        assert!(Replay::from_bytes(b"EVREPLAY").is_err());
+        assert!(Replay::from_bytes(&replay.to_bytes()[..20]).is_err());
+    }
+}
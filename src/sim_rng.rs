@@ -0,0 +1,95 @@
+/** ===============================================================================
+ * File: sim_rng.rs
+ * Author: Scott Stack
+ * Description: Random number generator used by the simulation. Normally this is just
+ * the thread RNG, but an environment with a seed swaps its own generator in while it
+ * steps so seeded runs can be reproduced exactly
+ * ===============================================================================*/
+use rand::rngs::StdRng;
+use rand::RngCore;
+use std::cell::RefCell;
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+thread_local! {
+    static SEEDED_RNG : RefCell<Option<StdRng>> = const { RefCell::new(None) }; // Generator installed by `run_seeded` (None = use the thread RNG)
+}
+
+/// Handle to the simulation RNG. Every call is forwarded to the seeded generator if one is installed,
+/// otherwise to the thread RNG
+pub struct SimRng;
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+/// Get a handle to the simulation RNG. Use this instead of `rand::thread_rng()` anywhere the sim makes random choices
+pub fn sim_rng() -> SimRng {
+    return SimRng;
+}
+
+/// Run `f` with `rng` installed as the simulation RNG, then hand the (advanced) generator back.
+/// If `rng` is None, whatever generator is already in use stays in use
+pub fn run_seeded<T>(rng : &mut Option<StdRng>, f : impl FnOnce() -> T) -> T {
+    if rng.is_none() {
+        return f();
+    }
+
+    let prev_rng = SEEDED_RNG.with(|cell| cell.replace(rng.take()));
+    let result = f();
+    *rng = SEEDED_RNG.with(|cell| cell.replace(prev_rng));
+    return result;
+}
+
+/// Call `f` with whichever generator is currently in use
+fn with_rng<T>(f : impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    return SEEDED_RNG.with(|cell| match cell.borrow_mut().as_mut() {
+        Some(rng) => f(rng),
+        None => f(&mut rand::thread_rng()),
+    });
+}
+
+impl RngCore for SimRng {
+    fn next_u32(&mut self) -> u32 {
+        return with_rng(|rng| rng.next_u32());
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        return with_rng(|rng| rng.next_u64());
+    }
+
+    fn fill_bytes(&mut self, dest : &mut [u8]) {
+        with_rng(|rng| rng.fill_bytes(dest));
+    }
+
+    fn try_fill_bytes(&mut self, dest : &mut [u8]) -> Result<(), rand::Error> {
+        return with_rng(|rng| rng.try_fill_bytes(dest));
+    }
+}
+
+
+#[cfg(test)]
+mod sim_rng_test {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let draw = || (0..10).map(|_| sim_rng().gen_range(0..1000)).collect::<Vec<u32>>();
+
+        let mut rng_a = Some(StdRng::seed_from_u64(42));
+        let mut rng_b = Some(StdRng::seed_from_u64(42));
+        let first_a = run_seeded(&mut rng_a, draw);
+        let first_b = run_seeded(&mut rng_b, draw);
+        assert_eq!(first_a, first_b);
+
+        // The generator picks up where it left off
+        let second_a = run_seeded(&mut rng_a, draw);
+        assert_ne!(first_a, second_a);
+        assert_eq!(second_a, run_seeded(&mut rng_b, draw));
+    }
+}
@@ -0,0 +1,244 @@
+/** ===============================================================================
+ * File: snapshot.rs
+ * Author: Scott Stack
+ * Description: Read-only snapshot of everything a renderer needs to draw the board.
+ * Frontends draw from this instead of reaching into the environment's internals
+ * ===============================================================================*/
+use crate::creature::*;
+use crate::environment::*;
+use crate::analysis::GridCounts;
+use crate::terrain::get_hillshade;
+use std::collections::HashMap;
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Contents of a single board cell as seen by a renderer
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SnapshotCell {
+    Blank,
+    Creature(usize),    // Argument is the index into `EnvSnapshot::creatures`
+    Food,
+    Wall,
+}
+
+/// Which cells a density field counts
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DensitySource {
+    Creatures,
+    Food,
+    CreaturesAndFood,
+}
+
+/// The parts of a creature needed to draw it
+#[derive(Copy, Clone, PartialEq)]
+pub struct CreatureRenderInfo {
+    pub id : usize,                         // ID of the creature
+    pub position : CreaturePosition,        // Where the creature is on the board
+    pub orientation : CreatureOrientation,  // Direction the creature is facing
+    pub color : CreatureColor,              // Inherited color of the creature
+    pub species_id : usize,                 // Species of the creature (0 if speciation is off)
+    pub infected : bool,                    // True if the creature is sick
+}
+
+/// Read-only view of the board at one time step
+#[derive(Clone, PartialEq)]
+pub struct EnvSnapshot {
+    #[allow(dead_code)]
+    pub time_step : usize,                  // Time step the snapshot was taken at
+    pub x_size : usize,                     // Width of the board in spaces
+    pub y_size : usize,                     // Height of the board in spaces
+    pub creatures : Vec<CreatureRenderInfo>,// Every creature on the board
+    pub beacon : Option<CreaturePosition>,  // Position of the beacon (if enabled)
+    pub(crate) cells : Vec<SnapshotCell>,   // Board cells, indexed by `x * y_size + y`
+    pub(crate) territory : Vec<TerritoryMark>, // Territory marks, indexed like `cells` (empty if territory is disabled)
+    pub(crate) hillshade : Vec<f32>,        // How brightly lit each cell's terrain is [0, 1], indexed like `cells` (empty without elevation)
+    pub(crate) river : Vec<bool>,           // True for cells the river runs through, indexed like `cells` (empty without a river)
+    pub(crate) decals : Vec<Option<DecalKind>>, // Decal on each cell (if any), indexed like `cells`
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl EnvSnapshot {
+
+    /// Take a snapshot of the current state of an environment
+    pub fn new(env : &EnvironmentV1) -> EnvSnapshot {
+        let x_size = env.params.env_x_size;
+        let y_size = env.params.env_y_size;
+
+        let creatures : Vec<CreatureRenderInfo> = env.creatures.iter().map(|c| CreatureRenderInfo {
+            id : c.id,
+            position : c.position,
+            orientation : c.orientation,
+            color : c.color,
+            species_id : c.species_id,
+            infected : c.is_infected(),
+        }).collect();
+        let creature_idxs : HashMap<usize, usize> = creatures.iter().enumerate().map(|(idx, c)| (c.id, idx)).collect();
+
+        let mut cells : Vec<SnapshotCell> = Vec::with_capacity(x_size * y_size);
+        for column in env.positions.iter() {
+            for space in column.iter() {
+                cells.push(match space {
+                    SpaceStates::BlankSpace => SnapshotCell::Blank,
+                    SpaceStates::CreatureSpace(id) => match creature_idxs.get(id) {
+                        Some(idx) => SnapshotCell::Creature(*idx),
+                        None => SnapshotCell::Blank,
+                    },
+                    SpaceStates::FoodSpace => SnapshotCell::Food,
+                    SpaceStates::WallSpace => SnapshotCell::Wall,
+                });
+            }
+        }
+
+        let mut hillshade : Vec<f32> = Vec::new();
+        if env.has_elevation() {
+            hillshade = (0..x_size).flat_map(|x| (0..y_size).map(move |y| (x, y))).map(|(x, y)| get_hillshade(&env.elevation, x, y)).collect();
+        }
+
+        let mut river : Vec<bool> = Vec::new();
+        if !env.river.is_empty() {
+            river = vec![false; x_size * y_size];
+            for pos in env.river.iter() {
+                river[pos.x * y_size + pos.y] = true;
+            }
+        }
+
+        let mut decals : Vec<Option<DecalKind>> = vec![None; x_size * y_size];
+        if !env.decals.is_empty() {
+            decals = env.decals.iter().flatten().map(|decal| decal.map(|d| d.kind)).collect();
+        }
+
+        return EnvSnapshot {
+            time_step : env.time_step,
+            x_size : x_size,
+            y_size : y_size,
+            creatures : creatures,
+            beacon : env.beacon,
+            cells : cells,
+            territory : env.territory.iter().flatten().cloned().collect(),
+            hillshade : hillshade,
+            river : river,
+            decals : decals,
+        };
+    }
+
+    /// Create a snapshot of an empty board (all blank, no creatures, terrain, river or decals) of the given size
+    pub fn new_blank(x_size : usize, y_size : usize) -> EnvSnapshot {
+        return EnvSnapshot {
+            time_step : 0,
+            x_size : x_size,
+            y_size : y_size,
+            creatures : Vec::new(),
+            beacon : None,
+            cells : vec![SnapshotCell::Blank; x_size * y_size],
+            territory : Vec::new(),
+            hillshade : Vec::new(),
+            river : Vec::new(),
+            decals : vec![None; x_size * y_size],
+        };
+    }
+
+    /// Get the contents of the cell at (x, y)
+    pub fn get_cell(&self, x : usize, y : usize) -> SnapshotCell {
+        return self.cells[x * self.y_size + y];
+    }
+
+    /// Get the creature at (x, y), if there is one
+    #[allow(dead_code)]
+    pub fn get_creature_at(&self, x : usize, y : usize) -> Option<&CreatureRenderInfo> {
+        return match self.get_cell(x, y) {
+            SnapshotCell::Creature(idx) => self.creatures.get(idx),
+            _ => None,
+        };
+    }
+
+    /// Get a smoothed density field of the cells counted by `source`. Each cell's value is the fraction of
+    /// cells in the (2 * radius + 1) square around it that are counted, so values go from 0 to 1. Indexed by `x * y_size + y`
+    #[allow(dead_code)]
+    pub fn get_density_field(&self, source : DensitySource, radius : usize) -> Vec<f32> {
+        let counted = |cell : SnapshotCell| matches!((source, cell),
+            (DensitySource::Creatures, SnapshotCell::Creature(_)) |
+            (DensitySource::Food, SnapshotCell::Food) |
+            (DensitySource::CreaturesAndFood, SnapshotCell::Creature(_) | SnapshotCell::Food));
+
+        let counts = GridCounts::new(self.x_size, self.y_size, |x, y| counted(self.get_cell(x, y)));
+        let mut density : Vec<f32> = Vec::with_capacity(self.x_size * self.y_size);
+        for x in 0..self.x_size {
+            for y in 0..self.y_size {
+                let (count, window_size) = counts.count_in_radius(x, y, radius);
+                density.push(count as f32 / window_size as f32);
+            }
+        }
+        return density;
+    }
+
+    /// Get the territory mark on the cell at (x, y), if it's been marked
+    #[allow(dead_code)]
+    pub fn get_territory_at(&self, x : usize, y : usize) -> Option<TerritoryMark> {
+        return self.territory.get(x * self.y_size + y).filter(|mark| mark.strength > 0.0).cloned();
+    }
+
+    /// Get how brightly lit the terrain at (x, y) is [0, 1], if the board has elevation
+    #[allow(dead_code)]
+    pub fn get_hillshade_at(&self, x : usize, y : usize) -> Option<f32> {
+        return self.hillshade.get(x * self.y_size + y).cloned();
+    }
+
+    /// Get the decal left on the cell at (x, y), if there is one
+    pub fn get_decal_at(&self, x : usize, y : usize) -> Option<DecalKind> {
+        return self.decals.get(x * self.y_size + y).cloned().flatten();
+    }
+
+    /// Returns true if the river runs through the cell at (x, y)
+    pub fn is_river_at(&self, x : usize, y : usize) -> bool {
+        return self.river.get(x * self.y_size + y).cloned().unwrap_or(false);
+    }
+
+    /// Returns true if the beacon is in the (blank) cell at (x, y)
+    pub fn is_beacon_at(&self, x : usize, y : usize) -> bool {
+        return self.beacon == Some(CreaturePosition {x : x, y : y}) && self.get_cell(x, y) == SnapshotCell::Blank;
+    }
+}
+
+
+#[cfg(test)]
+mod snapshot_test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_matches_env() {
+        let mut params = EnvironmentParams::new();
+        params.env_x_size = 16;
+        params.env_y_size = 12;
+        params.num_start_creatures = 10;
+        params.num_start_food = 10;
+        params.num_start_walls = 10;
+        let env = EnvironmentV1::new_rand(&params);
+        let snapshot = EnvSnapshot::new(&env);
+
+        assert_eq!(snapshot.creatures.len(), env.creatures.len());
+        for creature in env.creatures.iter() {
+            let info = snapshot.get_creature_at(creature.position.x, creature.position.y).unwrap();
+            assert_eq!(info.id, creature.id);
+        }
+        for x in 0..params.env_x_size {
+            for y in 0..params.env_y_size {
+                assert_eq!(snapshot.get_cell(x, y) == SnapshotCell::Wall, env.positions[x][y] == SpaceStates::WallSpace);
+            }
+        }
+
+        // With a radius covering the whole board every cell sees the overall food density
+        let density = snapshot.get_density_field(DensitySource::Food, 16);
+        let num_food = env.positions.iter().flatten().filter(|s| **s == SpaceStates::FoodSpace).count();
+        let food_fraction = num_food as f32 / (params.env_x_size * params.env_y_size) as f32;
+        assert!(density.iter().all(|d| (d - food_fraction).abs() < 1e-6));
+        assert!(snapshot.get_density_field(DensitySource::Creatures, 0).iter().all(|d| *d == 0.0 || *d == 1.0));
+    }
+}
@@ -0,0 +1,593 @@
+/** ===============================================================================
+ * File: snapshot_delta.rs
+ * Author: Scott Stack
+ * Description: Compact per-step changes between two board snapshots (changed cells and
+ * creatures), with a small binary encoding, for streaming boards to remote viewers and replays
+ * ===============================================================================*/
+use crate::creature::*;
+use crate::environment::{TerritoryMark, DecalKind};
+use crate::snapshot::*;
+use std::collections::{HashMap, HashSet};
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const DELTA_FORMAT_VERSION : u8 = 2;    // First byte of every encoded delta. Bumped whenever the encoding changes
+pub const MAX_DELTA_BOARD_CELLS : usize = 1 << 24;  // Biggest board (in cells) a decoded delta can have, so a bad delta can't make `apply` allocate a huge board
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Contents of a board cell in a delta. Creatures are given by ID rather than by index into the creature list,
+/// so creatures joining/leaving the list don't change every other creature's cells
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeltaCell {
+    Blank,
+    Creature(usize),    // Argument is the creature ID
+    Food,
+    Wall,
+}
+
+/// Everything that changed between two snapshots. Applying it to the first snapshot (see `apply`) gives the second.
+/// A delta from a blank board (see `new_keyframe`) holds the whole snapshot, so a stream can start from one
+#[derive(Clone, PartialEq)]
+pub struct SnapshotDelta {
+    pub time_step : usize,                          // Time step of the new snapshot
+    pub x_size : usize,                             // Board size of the new snapshot. Cells left out of a resized board are blank
+    pub y_size : usize,
+    pub cells : Vec<(usize, DeltaCell)>,            // (cell index, new contents) of every changed cell, in index order
+    pub changed_creatures : Vec<CreatureRenderInfo>,// New creatures and creatures whose render info changed
+    pub removed_creatures : Vec<usize>,             // IDs of creatures that are gone
+    pub creature_order : Vec<usize>,                // IDs of every creature in the new order. Empty if the order is the usual one (see `apply`)
+    pub beacon : Option<CreaturePosition>,          // Position of the beacon in the new snapshot
+    pub territory : Vec<(usize, TerritoryMark)>,    // (cell index, new mark) of every changed territory mark, in index order
+    pub hillshade : Option<Vec<f32>>,               // Full hillshade layer if it changed (None = unchanged)
+    pub river : Option<Vec<bool>>,                  // Full river layer if it changed (None = unchanged)
+    pub decals : Vec<(usize, Option<DecalKind>)>,   // (cell index, new decal) of every changed decal, in index order
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl SnapshotDelta {
+
+    /// Find everything that changed from `prev` to `next`
+    pub fn new(prev : &EnvSnapshot, next : &EnvSnapshot) -> SnapshotDelta {
+        let same_size = prev.x_size == next.x_size && prev.y_size == next.y_size;
+
+        // Cells (compared by creature ID). Every cell of a resized board is sent
+        let prev_cells = get_delta_cells(prev);
+        let next_cells = get_delta_cells(next);
+        let cells : Vec<(usize, DeltaCell)> = next_cells.iter().enumerate()
+            .filter(|(idx, cell)| !same_size || prev_cells[*idx] != **cell)
+            .map(|(idx, cell)| (idx, *cell)).collect();
+
+        // Creatures
+        let prev_creatures : HashMap<usize, &CreatureRenderInfo> = prev.creatures.iter().map(|c| (c.id, c)).collect();
+        let next_ids : HashSet<usize> = next.creatures.iter().map(|c| c.id).collect();
+        let changed_creatures : Vec<CreatureRenderInfo> = next.creatures.iter().filter(|c| prev_creatures.get(&c.id) != Some(c)).copied().collect();
+        let removed_creatures : Vec<usize> = prev.creatures.iter().map(|c| c.id).filter(|id| !next_ids.contains(id)).collect();
+        let new_ids : Vec<usize> = next.creatures.iter().map(|c| c.id).filter(|id| !prev_creatures.contains_key(id)).collect();
+        let next_order : Vec<usize> = next.creatures.iter().map(|c| c.id).collect();
+        let creature_order = if get_usual_order(prev, &removed_creatures, &new_ids) == next_order { Vec::new() } else { next_order };
+
+        // Territory marks. A board without territory has no marks at all
+        let blank_mark = TerritoryMark::default();
+        let get_mark = |snapshot : &EnvSnapshot, idx : usize| if same_size { snapshot.territory.get(idx).copied().unwrap_or(blank_mark) } else { blank_mark };
+        let territory : Vec<(usize, TerritoryMark)> = next.territory.iter().enumerate()
+            .filter(|(idx, mark)| get_mark(prev, *idx) != **mark || !same_size)
+            .map(|(idx, mark)| (idx, *mark)).collect();
+
+        // Decals. A resized board starts with none
+        let decals : Vec<(usize, Option<DecalKind>)> = next.decals.iter().enumerate()
+            .filter(|(idx, decal)| if same_size { prev.decals.get(*idx) != Some(*decal) } else { decal.is_some() })
+            .map(|(idx, decal)| (idx, *decal)).collect();
+
+        return SnapshotDelta {
+            time_step : next.time_step,
+            x_size : next.x_size,
+            y_size : next.y_size,
+            cells : cells,
+            changed_creatures : changed_creatures,
+            removed_creatures : removed_creatures,
+            creature_order : creature_order,
+            beacon : next.beacon,
+            territory : territory,
+            hillshade : if prev.hillshade != next.hillshade { Some(next.hillshade.clone()) } else { None },
+            river : if prev.river != next.river { Some(next.river.clone()) } else { None },
+            decals : decals,
+        };
+    }
+
+    /// Make a delta that holds the whole snapshot (a delta from a blank board of the same size)
+    pub fn new_keyframe(snapshot : &EnvSnapshot) -> SnapshotDelta {
+        return SnapshotDelta::new(&EnvSnapshot::new_blank(snapshot.x_size, snapshot.y_size), snapshot);
+    }
+
+    /// Apply the changes to `prev` (the snapshot the delta was made from) to get the new snapshot.
+    /// Unless `creature_order` says otherwise, the creatures keep their order from `prev`, with new creatures at the end
+    pub fn apply(&self, prev : &EnvSnapshot) -> EnvSnapshot {
+        let same_size = prev.x_size == self.x_size && prev.y_size == self.y_size;
+        let num_cells = self.x_size * self.y_size;
+
+        // Creatures
+        let mut creatures_by_id : HashMap<usize, CreatureRenderInfo> = prev.creatures.iter().map(|c| (c.id, *c)).collect();
+        for id in self.removed_creatures.iter() {
+            creatures_by_id.remove(id);
+        }
+        let new_ids : Vec<usize> = self.changed_creatures.iter().map(|c| c.id).filter(|id| !creatures_by_id.contains_key(id)).collect();
+        for creature in self.changed_creatures.iter() {
+            creatures_by_id.insert(creature.id, *creature);
+        }
+        let order = if self.creature_order.is_empty() { get_usual_order(prev, &self.removed_creatures, &new_ids) } else { self.creature_order.clone() };
+        let creatures : Vec<CreatureRenderInfo> = order.iter().filter_map(|id| creatures_by_id.get(id)).copied().collect();
+        let creature_idxs : HashMap<usize, usize> = creatures.iter().enumerate().map(|(idx, c)| (c.id, idx)).collect();
+
+        // Cells are updated by creature ID, then turned back into indices into the new creature list
+        let mut delta_cells = if same_size { get_delta_cells(prev) } else { vec![DeltaCell::Blank; num_cells] };
+        for (idx, cell) in self.cells.iter() {
+            if let Some(delta_cell) = delta_cells.get_mut(*idx) {
+                *delta_cell = *cell;
+            }
+        }
+        let cells : Vec<SnapshotCell> = delta_cells.iter().map(|cell| match cell {
+            DeltaCell::Blank => SnapshotCell::Blank,
+            DeltaCell::Creature(id) => match creature_idxs.get(id) {
+                Some(idx) => SnapshotCell::Creature(*idx),
+                None => SnapshotCell::Blank,
+            },
+            DeltaCell::Food => SnapshotCell::Food,
+            DeltaCell::Wall => SnapshotCell::Wall,
+        }).collect();
+
+        // Territory only exists on boards that have it, so a delta with no marks on a board without them stays empty
+        let mut territory = if same_size { prev.territory.clone() } else { Vec::new() };
+        if !self.territory.is_empty() && territory.len() != num_cells {
+            territory = vec![TerritoryMark::default(); num_cells];
+        }
+        for (idx, mark) in self.territory.iter() {
+            if let Some(territory_mark) = territory.get_mut(*idx) {
+                *territory_mark = *mark;
+            }
+        }
+
+        let mut decals = if same_size { prev.decals.clone() } else { vec![None; num_cells] };
+        for (idx, decal) in self.decals.iter() {
+            if let Some(cell_decal) = decals.get_mut(*idx) {
+                *cell_decal = *decal;
+            }
+        }
+
+        return EnvSnapshot {
+            time_step : self.time_step,
+            x_size : self.x_size,
+            y_size : self.y_size,
+            creatures : creatures,
+            beacon : self.beacon,
+            cells : cells,
+            territory : territory,
+            hillshade : self.hillshade.clone().unwrap_or_else(|| prev.hillshade.clone()),
+            river : self.river.clone().unwrap_or_else(|| prev.river.clone()),
+            decals : decals,
+        };
+    }
+
+    /// Encode the delta as compact bytes. Numbers are written as variable length integers (7 bits per byte) and
+    /// cell/territory indices as the gap from the previous one, so small changes take only a few bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes : Vec<u8> = vec![DELTA_FORMAT_VERSION];
+        write_varint(&mut bytes, self.time_step);
+        write_varint(&mut bytes, self.x_size);
+        write_varint(&mut bytes, self.y_size);
+
+        write_varint(&mut bytes, self.cells.len());
+        let mut prev_idx : usize = 0;
+        for (idx, cell) in self.cells.iter() {
+            write_varint(&mut bytes, idx - prev_idx);
+            prev_idx = *idx;
+            match cell {
+                DeltaCell::Blank => bytes.push(0),
+                DeltaCell::Creature(id) => {
+                    bytes.push(1);
+                    write_varint(&mut bytes, *id);
+                },
+                DeltaCell::Food => bytes.push(2),
+                DeltaCell::Wall => bytes.push(3),
+            }
+        }
+
+        write_varint(&mut bytes, self.changed_creatures.len());
+        for creature in self.changed_creatures.iter() {
+            write_varint(&mut bytes, creature.id);
+            write_varint(&mut bytes, creature.position.x);
+            write_varint(&mut bytes, creature.position.y);
+            bytes.push(match creature.orientation {
+                CreatureOrientation::Up => 0,
+                CreatureOrientation::Down => 1,
+                CreatureOrientation::Left => 2,
+                CreatureOrientation::Right => 3,
+            });
+            bytes.extend_from_slice(&creature.color.get_as_vec());
+            write_varint(&mut bytes, creature.species_id);
+            bytes.push(creature.infected as u8);
+        }
+
+        for ids in [&self.removed_creatures, &self.creature_order] {
+            write_varint(&mut bytes, ids.len());
+            for id in ids.iter() {
+                write_varint(&mut bytes, *id);
+            }
+        }
+
+        match self.beacon {
+            Some(pos) => {
+                bytes.push(1);
+                write_varint(&mut bytes, pos.x);
+                write_varint(&mut bytes, pos.y);
+            },
+            None => bytes.push(0),
+        }
+
+        write_varint(&mut bytes, self.territory.len());
+        let mut prev_idx : usize = 0;
+        for (idx, mark) in self.territory.iter() {
+            write_varint(&mut bytes, idx - prev_idx);
+            prev_idx = *idx;
+            write_varint(&mut bytes, mark.owner);
+            bytes.extend_from_slice(&mark.strength.to_le_bytes());
+        }
+
+        match &self.hillshade {
+            Some(hillshade) => {
+                bytes.push(1);
+                write_varint(&mut bytes, hillshade.len());
+                for shade in hillshade.iter() {
+                    bytes.extend_from_slice(&shade.to_le_bytes());
+                }
+            },
+            None => bytes.push(0),
+        }
+
+        // River cells are packed 8 to a byte
+        match &self.river {
+            Some(river) => {
+                bytes.push(1);
+                write_varint(&mut bytes, river.len());
+                for chunk in river.chunks(8) {
+                    bytes.push(chunk.iter().enumerate().fold(0, |byte, (bit, is_river)| byte | ((*is_river as u8) << bit)));
+                }
+            },
+            None => bytes.push(0),
+        }
+
+        write_varint(&mut bytes, self.decals.len());
+        let mut prev_idx : usize = 0;
+        for (idx, decal) in self.decals.iter() {
+            write_varint(&mut bytes, idx - prev_idx);
+            prev_idx = *idx;
+            bytes.push(match decal {
+                None => 0,
+                Some(DecalKind::Fight) => 1,
+                Some(DecalKind::Birth) => 2,
+                Some(DecalKind::Death) => 3,
+            });
+        }
+        return bytes;
+    }
+
+    /// Decode a delta written by `to_bytes`. Returns the reason if the bytes aren't a valid delta
+    pub fn from_bytes(bytes : &[u8]) -> Result<SnapshotDelta, String> {
+        let mut reader = ByteReader::new(bytes);
+        let version = reader.read_u8()?;
+        if version != DELTA_FORMAT_VERSION {
+            return Err(format!("Unsupported delta format version {} (expected {})", version, DELTA_FORMAT_VERSION));
+        }
+        let time_step = reader.read_varint()?;
+        let x_size = reader.read_varint()?;
+        let y_size = reader.read_varint()?;
+        let num_cells = match x_size.checked_mul(y_size) {
+            Some(num_cells) if num_cells <= MAX_DELTA_BOARD_CELLS => num_cells,
+            _ => return Err(format!("Board size {}x{} is too big", x_size, y_size)),
+        };
+
+        let mut cells : Vec<(usize, DeltaCell)> = Vec::new();
+        let mut idx : usize = 0;
+        for _ in 0..reader.read_len()? {
+            idx = idx.saturating_add(reader.read_varint()?);
+            if idx >= num_cells {
+                return Err(format!("Cell {} is off the {}x{} board", idx, x_size, y_size));
+            }
+            let cell = match reader.read_u8()? {
+                0 => DeltaCell::Blank,
+                1 => DeltaCell::Creature(reader.read_varint()?),
+                2 => DeltaCell::Food,
+                3 => DeltaCell::Wall,
+                tag => return Err(format!("Unknown cell type {}", tag)),
+            };
+            cells.push((idx, cell));
+        }
+
+        let mut changed_creatures : Vec<CreatureRenderInfo> = Vec::new();
+        for _ in 0..reader.read_len()? {
+            let id = reader.read_varint()?;
+            let position = CreaturePosition {x : reader.read_varint()?, y : reader.read_varint()?};
+            let orientation = match reader.read_u8()? {
+                0 => CreatureOrientation::Up,
+                1 => CreatureOrientation::Down,
+                2 => CreatureOrientation::Left,
+                3 => CreatureOrientation::Right,
+                val => return Err(format!("Unknown orientation {}", val)),
+            };
+            let color = CreatureColor::new_from_vec([reader.read_u8()?, reader.read_u8()?, reader.read_u8()?]);
+            let species_id = reader.read_varint()?;
+            let infected = reader.read_u8()? != 0;
+            changed_creatures.push(CreatureRenderInfo {id : id, position : position, orientation : orientation, color : color, species_id : species_id, infected : infected});
+        }
+
+        let mut id_lists : [Vec<usize>; 2] = [Vec::new(), Vec::new()];
+        for ids in id_lists.iter_mut() {
+            for _ in 0..reader.read_len()? {
+                ids.push(reader.read_varint()?);
+            }
+        }
+        let [removed_creatures, creature_order] = id_lists;
+
+        let beacon = match reader.read_u8()? {
+            0 => None,
+            _ => Some(CreaturePosition {x : reader.read_varint()?, y : reader.read_varint()?}),
+        };
+
+        let mut territory : Vec<(usize, TerritoryMark)> = Vec::new();
+        let mut idx : usize = 0;
+        for _ in 0..reader.read_len()? {
+            idx = idx.saturating_add(reader.read_varint()?);
+            if idx >= num_cells {
+                return Err(format!("Territory mark {} is off the {}x{} board", idx, x_size, y_size));
+            }
+            territory.push((idx, TerritoryMark {owner : reader.read_varint()?, strength : reader.read_f32()?}));
+        }
+
+        let hillshade = match reader.read_u8()? {
+            0 => None,
+            _ => {
+                let len = check_layer_len("Hillshade", reader.read_len()?, num_cells)?;
+                Some((0..len).map(|_| reader.read_f32()).collect::<Result<Vec<f32>, String>>()?)
+            },
+        };
+
+        let river = match reader.read_u8()? {
+            0 => None,
+            _ => {
+                // Packed 8 cells to a byte, so the length can be more than the number of bytes left
+                let len = check_layer_len("River", reader.read_varint()?, num_cells)?;
+                let mut river : Vec<bool> = Vec::with_capacity(len);
+                for _ in 0..len.div_ceil(8) {
+                    let byte = reader.read_u8()?;
+                    river.extend((0..8).map(|bit| byte & (1 << bit) != 0));
+                }
+                river.truncate(len);
+                Some(river)
+            },
+        };
+
+        let mut decals : Vec<(usize, Option<DecalKind>)> = Vec::new();
+        let mut idx : usize = 0;
+        for _ in 0..reader.read_len()? {
+            idx = idx.saturating_add(reader.read_varint()?);
+            if idx >= num_cells {
+                return Err(format!("Decal {} is off the {}x{} board", idx, x_size, y_size));
+            }
+            let decal = match reader.read_u8()? {
+                0 => None,
+                1 => Some(DecalKind::Fight),
+                2 => Some(DecalKind::Birth),
+                3 => Some(DecalKind::Death),
+                tag => return Err(format!("Unknown decal type {}", tag)),
+            };
+            decals.push((idx, decal));
+        }
+
+        if reader.pos != bytes.len() {
+            return Err(format!("{} extra bytes after the delta", bytes.len() - reader.pos));
+        }
+        return Ok(SnapshotDelta {
+            time_step : time_step,
+            x_size : x_size,
+            y_size : y_size,
+            cells : cells,
+            changed_creatures : changed_creatures,
+            removed_creatures : removed_creatures,
+            creature_order : creature_order,
+            beacon : beacon,
+            territory : territory,
+            hillshade : hillshade,
+            river : river,
+            decals : decals,
+        });
+    }
+}
+
+/// Reads the pieces of an encoded delta (or anything else written with `write_varint`) in order
+pub(crate) struct ByteReader<'a> {
+    bytes : &'a [u8],
+    pos : usize,        // Index of the next byte to read
+}
+
+impl<'a> ByteReader<'a> {
+
+    /// Start reading from the beginning of `bytes`
+    pub(crate) fn new(bytes : &'a [u8]) -> ByteReader<'a> {
+        return ByteReader {bytes : bytes, pos : 0};
+    }
+
+    /// Returns true once every byte has been read
+    pub(crate) fn is_done(&self) -> bool {
+        return self.pos >= self.bytes.len();
+    }
+
+    /// Read a single byte
+    pub(crate) fn read_u8(&mut self) -> Result<u8, String> {
+        let byte = *self.bytes.get(self.pos).ok_or("Delta ends early")?;
+        self.pos += 1;
+        return Ok(byte);
+    }
+
+    /// Read a variable length integer written by `write_varint`
+    pub(crate) fn read_varint(&mut self) -> Result<usize, String> {
+        let mut val : usize = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.read_u8()?;
+            val |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(val);
+            }
+        }
+        return Err(String::from("Number in delta is too big"));
+    }
+
+    /// Read the length of a list. Lists can't be longer than the bytes left, which stops a corrupted length from
+    /// making the decoder allocate a huge list
+    pub(crate) fn read_len(&mut self) -> Result<usize, String> {
+        let len = self.read_varint()?;
+        if len > self.bytes.len() - self.pos {
+            return Err(format!("List of {} entries doesn't fit in the delta", len));
+        }
+        return Ok(len);
+    }
+
+    /// Read a little endian f32
+    fn read_f32(&mut self) -> Result<f32, String> {
+        let bytes = [self.read_u8()?, self.read_u8()?, self.read_u8()?, self.read_u8()?];
+        return Ok(f32::from_le_bytes(bytes));
+    }
+
+    /// Read a little endian u64
+    pub(crate) fn read_u64(&mut self) -> Result<u64, String> {
+        return Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()));
+    }
+
+    /// Read the next `len` bytes as they are
+    pub(crate) fn read_bytes(&mut self, len : usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() - self.pos {
+            return Err(String::from("Delta ends early"));
+        }
+        self.pos += len;
+        return Ok(&self.bytes[self.pos - len..self.pos]);
+    }
+}
+
+/// Write a number 7 bits at a time (least significant first), with the top bit of each byte set if more follow
+pub(crate) fn write_varint(bytes : &mut Vec<u8>, mut val : usize) {
+    while val >= 0x80 {
+        bytes.push((val & 0x7f) as u8 | 0x80);
+        val >>= 7;
+    }
+    bytes.push(val as u8);
+}
+
+/// Get the cells of a snapshot with creatures given by ID
+fn get_delta_cells(snapshot : &EnvSnapshot) -> Vec<DeltaCell> {
+    return snapshot.cells.iter().map(|cell| match cell {
+        SnapshotCell::Blank => DeltaCell::Blank,
+        SnapshotCell::Creature(idx) => DeltaCell::Creature(snapshot.creatures[*idx].id),
+        SnapshotCell::Food => DeltaCell::Food,
+        SnapshotCell::Wall => DeltaCell::Wall,
+    }).collect();
+}
+
+/// Get the creature order a delta uses when it doesn't give one: the creatures left from `prev` in the same order,
+/// followed by the new ones
+fn get_usual_order(prev : &EnvSnapshot, removed_ids : &[usize], new_ids : &[usize]) -> Vec<usize> {
+    let removed : HashSet<usize> = removed_ids.iter().copied().collect();
+    let mut order : Vec<usize> = prev.creatures.iter().map(|c| c.id).filter(|id| !removed.contains(id)).collect();
+    order.extend_from_slice(new_ids);
+    return order;
+}
+
+/// Check that a full board layer (hillshade/river) has one entry per cell, or none if the board doesn't have it
+fn check_layer_len(name : &str, len : usize, num_cells : usize) -> Result<usize, String> {
+    if len != 0 && len != num_cells {
+        return Err(format!("{} layer has {} cells but the board has {}", name, len, num_cells));
+    }
+    return Ok(len);
+}
+
+
+#[cfg(test)]
+mod snapshot_delta_test {
+    use super::*;
+    use crate::environment::*;
+
+    #[test]
+    fn test_deltas_rebuild_snapshots() {
+        let mut params = EnvironmentParams::with_size(16, 12);
+        params.enable_territory = true;
+        params.enable_elevation = true;
+        params.enable_river = true;
+        params.creature_order = CreatureOrder::Random;
+        let mut env = EnvironmentV1::new_seeded(&params, 9);
+
+        // Stream a keyframe followed by a delta per step, going through the byte encoding each time
+        let mut prev = env.get_snapshot();
+        let mut rebuilt = SnapshotDelta::new_keyframe(&prev).apply(&EnvSnapshot::new_blank(16, 12));
+        assert!(rebuilt == prev);
+        for _ in 0..20 {
+            env.advance_step();
+            let next = env.get_snapshot();
+            let delta = SnapshotDelta::new(&prev, &next);
+            let bytes = delta.to_bytes();
+            assert!(SnapshotDelta::from_bytes(&bytes).unwrap() == delta);
+
+            rebuilt = SnapshotDelta::from_bytes(&bytes).unwrap().apply(&rebuilt);
+            assert!(rebuilt == next);
+            prev = next;
+        }
+
+        // Resizing the board is just a bigger delta
+        env.resize(20, 10, ResizeAnchor::TopLeft);
+        let next = env.get_snapshot();
+        assert!(SnapshotDelta::new(&prev, &next).apply(&prev) == next);
+
+        // Nothing changing costs next to nothing
+        assert!(SnapshotDelta::new(&next, &next).to_bytes().len() < 20);
+        assert!(SnapshotDelta::from_bytes(&[DELTA_FORMAT_VERSION, 1]).is_err());
+        assert!(SnapshotDelta::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_checks_sizes() {
+        let mut params = EnvironmentParams::with_size(16, 12);
+        params.enable_elevation = true;
+        params.enable_river = true;
+        let env = EnvironmentV1::new_seeded(&params, 9);
+        let snapshot = env.get_snapshot();
+
+        // A keyframe goes through the byte encoding, including a river with more cells than bytes left after it
+        let keyframe = SnapshotDelta::new_keyframe(&snapshot);
+        assert!(SnapshotDelta::from_bytes(&keyframe.to_bytes()).unwrap() == keyframe);
+
+        // Layers that don't match the board are rejected
+        let mut bad = keyframe.clone();
+        bad.hillshade = Some(vec![0.5; 10]);
+        assert!(SnapshotDelta::from_bytes(&bad.to_bytes()).is_err());
+        bad = keyframe.clone();
+        bad.river = Some(vec![true; 16 * 12 + 1]);
+        assert!(SnapshotDelta::from_bytes(&bad.to_bytes()).is_err());
+        bad.river = Some(Vec::new());
+        assert!(SnapshotDelta::from_bytes(&bad.to_bytes()).is_ok());
+
+        // So are boards too big to build
+        let empty = SnapshotDelta::new(&snapshot, &snapshot);
+        for (x_size, y_size) in [(1 << 20, 1 << 20), (usize::MAX, 2)] {
+            bad = empty.clone();
+            bad.x_size = x_size;
+            bad.y_size = y_size;
+            assert!(SnapshotDelta::from_bytes(&bad.to_bytes()).is_err());
+        }
+    }
+}
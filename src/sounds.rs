@@ -0,0 +1,161 @@
+/** ===============================================================================
+ * File: sounds.rs
+ * Author: Scott Stack
+ * Description: Sound effects for the GUI. The effects are tiny synthesized tones so there
+ * are no audio assets to ship. Playback needs the `audio` feature
+ * ===============================================================================*/
+#[cfg(feature = "audio")]
+use evolution_sim::events::*;
+#[cfg(feature = "audio")]
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const SOUND_SAMPLE_RATE : u32 = 22050;  // Samples per second of the synthesized effects
+
+/// Shape of a synthesized effect. The pitch slides from `start_freq` to `end_freq` while the volume decays
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ToneSpec {
+    pub start_freq : f32,   // Pitch at the start of the tone (Hz)
+    pub end_freq : f32,     // Pitch at the end of the tone (Hz)
+    pub duration_s : f32,   // Length of the tone in seconds
+    pub amplitude : f32,    // Peak volume (0-1)
+    pub decay : f32,        // How fast the volume dies off (bigger = shorter)
+}
+
+#[allow(dead_code)]
+pub const TICK_TONE : ToneSpec = ToneSpec {start_freq : 1800.0, end_freq : 1800.0, duration_s : 0.015, amplitude : 0.08, decay : 300.0};
+#[allow(dead_code)]
+pub const BIRTH_TONE : ToneSpec = ToneSpec {start_freq : 900.0, end_freq : 1600.0, duration_s : 0.08, amplitude : 0.3, decay : 30.0};
+#[allow(dead_code)]
+pub const KILL_TONE : ToneSpec = ToneSpec {start_freq : 110.0, end_freq : 50.0, duration_s : 0.2, amplitude : 0.6, decay : 15.0};
+#[allow(dead_code)]
+pub const EXTINCTION_TONE : ToneSpec = ToneSpec {start_freq : 440.0, end_freq : 220.0, duration_s : 1.2, amplitude : 0.4, decay : 2.0};
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// One loaded sound per kind of event
+#[cfg(feature = "audio")]
+pub struct SoundBank {
+    tick : Sound,
+    birth : Sound,
+    kill : Sound,
+    extinction : Sound,
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+/// Synthesize a tone as 16-bit mono samples
+#[allow(dead_code)]
+pub fn synth_tone(tone : &ToneSpec) -> Vec<i16> {
+    let num_samples = (tone.duration_s * SOUND_SAMPLE_RATE as f32) as usize;
+    let mut samples : Vec<i16> = Vec::with_capacity(num_samples);
+    let mut phase : f32 = 0.0;
+    for sample_idx in 0..num_samples {
+        let t = sample_idx as f32 / SOUND_SAMPLE_RATE as f32;
+        let freq = tone.start_freq + (tone.end_freq - tone.start_freq) * (sample_idx as f32 / num_samples as f32);
+        phase += 2.0 * std::f32::consts::PI * freq / SOUND_SAMPLE_RATE as f32;
+        let envelope = tone.amplitude * (-tone.decay * t).exp();
+        samples.push((phase.sin() * envelope * i16::MAX as f32) as i16);
+    }
+    return samples;
+}
+
+/// Wrap 16-bit mono samples in a WAV file
+#[allow(dead_code)]
+pub fn to_wav_bytes(samples : &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav : Vec<u8> = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    // Format chunk: PCM, 1 channel, 16 bits per sample
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SOUND_SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SOUND_SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    return wav;
+}
+
+#[cfg(feature = "audio")]
+impl SoundBank {
+
+    /// Synthesize and load every effect. Returns None if the audio backend couldn't load them
+    pub async fn load() -> Option<SoundBank> {
+        let mut sounds : Vec<Sound> = Vec::new();
+        for tone in [TICK_TONE, BIRTH_TONE, KILL_TONE, EXTINCTION_TONE] {
+            match load_sound_from_bytes(&to_wav_bytes(&synth_tone(&tone))).await {
+                Ok(sound) => sounds.push(sound),
+                Err(e) => {
+                    println!("Error: could not load sound effects. Error {:?}", e);
+                    return None;
+                }
+            }
+        }
+
+        let extinction = sounds.pop().unwrap();
+        let kill = sounds.pop().unwrap();
+        let birth = sounds.pop().unwrap();
+        let tick = sounds.pop().unwrap();
+        return Some(SoundBank {
+            tick : tick,
+            birth : birth,
+            kill : kill,
+            extinction : extinction,
+        });
+    }
+
+    /// Play the effects for a batch of events. Each kind of effect plays at most once per batch
+    /// so a step with lots of births doesn't turn into a wall of noise
+    pub fn play_for_events(&self, events : &[EnvEvent], volume : f32) {
+        if events.iter().any(|e| matches!(e, EnvEvent::StepCompleted {..})) {
+            play_sound(&self.tick, PlaySoundParams {looped : false, volume : volume});
+        }
+        if events.iter().any(|e| matches!(e, EnvEvent::Birth {..})) {
+            play_sound(&self.birth, PlaySoundParams {looped : false, volume : volume});
+        }
+        if events.iter().any(|e| matches!(e, EnvEvent::Kill {..})) {
+            play_sound(&self.kill, PlaySoundParams {looped : false, volume : volume});
+        }
+        if events.iter().any(|e| matches!(e, EnvEvent::Extinction {..})) {
+            play_sound(&self.extinction, PlaySoundParams {looped : false, volume : volume});
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod sounds_test {
+    use super::*;
+
+    #[test]
+    fn test_wav_bytes() {
+        let samples = synth_tone(&BIRTH_TONE);
+        assert_eq!(samples.len(), (BIRTH_TONE.duration_s * SOUND_SAMPLE_RATE as f32) as usize);
+        assert!(samples.iter().any(|s| *s != 0));
+
+        let wav = to_wav_bytes(&samples);
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[36..40], b"data");
+    }
+}
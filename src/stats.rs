@@ -0,0 +1,406 @@
+/** ===============================================================================
+ * File: stats.rs
+ * Author: Scott Stack
+ * Description: Records statistics about the simulation while it runs, such as a
+ * summary of every creature's life, so runs can be analyzed after the fact
+ * ===============================================================================*/
+use crate::creature::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "file_io")]
+use std::fs::File;
+#[cfg(feature = "file_io")]
+use std::io::Write;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const LIFETIME_RECORDS_MAX_LEN : usize = 1_000_000;    // Max number of lifetime records kept in memory. The oldest are dropped after this
+pub const LEADERBOARD_LEN : usize = 5;                      // Number of living creatures kept on each leaderboard
+pub const COHORT_HISTORY_LEN : usize = 10_000;              // Max number of steps of history kept for each cohort. The oldest are dropped after this
+const LIFETIME_CSV_HEADER : &str = "id,parent_id,generation,species_id,birth_step,death_step,lifespan,was_killed,kills,offspring,distance_traveled,food_eaten,rotations";
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Summary of a single creature's life, recorded when it dies
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifetimeRecord {
+    pub id : usize,                     // ID of the creature
+    pub parent_id : Option<usize>,      // ID of the parent (None for randomly generated creatures)
+    pub generation : usize,             // Number of ancestors back to the first generation
+    pub species_id : usize,             // Species at the time of death
+    pub birth_step : usize,             // Time step the creature was born in
+    pub death_step : usize,             // Time step the creature died in
+    pub lifespan : usize,               // Age at death
+    pub was_killed : bool,              // True if another creature killed it, false if it starved
+    pub kills : usize,                  // Number of creatures it killed
+    pub offspring : usize,              // Number of offspring it had
+    pub distance_traveled : usize,      // Number of spaces it moved
+    pub food_eaten : usize,             // Number of food pieces it ate
+    pub rotations : usize,              // Number of times it rotated
+}
+
+/// Averages over all recorded lifetimes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LifetimeSummary {
+    pub num_records : usize,            // Number of lifetimes the averages are taken over
+    pub avg_lifespan : f32,
+    pub avg_kills : f32,
+    pub avg_offspring : f32,
+    pub avg_distance_traveled : f32,
+    pub avg_food_eaten : f32,
+    pub avg_rotations : f32,
+}
+
+/// A living creature's place on a leaderboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeaderboardEntry {
+    pub id : usize,                     // ID of the creature
+    pub value : usize,                  // What it's ranked by (age, number of offspring, ...)
+}
+
+/// A creature that immigrated onto the board, founding its own lineage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImmigrantRecord {
+    pub lineage_id : usize,             // Lineage the immigrant founded (its own ID)
+    pub arrival_step : usize,           // Time step it arrived in
+}
+
+/// How the lineage founded by an immigrant is doing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImmigrantLineageStats {
+    pub lineage_id : usize,             // Lineage the immigrant founded
+    pub arrival_step : usize,           // Time step the immigrant arrived in
+    pub num_alive : usize,              // Members of the lineage (the immigrant and its descendants) alive now
+    pub num_dead : usize,               // Members of the lineage that have died
+}
+
+/// Totals and averages over a group of living creatures (e.g. ones selected on the board)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GroupSummary {
+    pub num_creatures : usize,          // Number of creatures in the group
+    pub num_lineages : usize,           // Number of different lineages (tribes) in the group
+    pub avg_age : f32,
+    pub avg_energy : f32,
+    pub avg_generation : f32,
+    pub total_kills : usize,
+    pub total_offspring : usize,
+}
+
+/// How a cohort was doing at the end of one step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CohortSample {
+    pub time_step : usize,              // Time step the sample was taken at the end of
+    pub num_members_alive : usize,      // Original members still alive
+    pub num_descendants_alive : usize,  // Descendants of the members (born after the cohort was tagged) alive
+    pub avg_energy : f32,               // Average energy of the living members and descendants
+}
+
+/// A named group of creatures tagged by hand (e.g. a cluster selected on the board), followed over time along with
+/// every descendant born after it was tagged
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cohort {
+    pub name : String,                  // Name the cohort was tagged with
+    pub tagged_step : usize,            // Time step the cohort was tagged in
+    pub member_ids : Vec<usize>,        // IDs of the original members
+    pub family_ids : HashSet<usize>,    // IDs of the members and all of their descendants so far
+    pub history : VecDeque<CohortSample>, // One sample per step since it was tagged (oldest first)
+}
+
+/// Collects statistics while a simulation runs
+#[derive(Debug, Clone, Default)]
+pub struct StatsRecorder {
+    pub lifetime_records : VecDeque<LifetimeRecord>,   // Summary of every creature that has died (oldest first)
+    pub dead_action_counts : [usize; NUM_CREATURE_ACTIONS], // Number of times creatures that have died took each action (indexed by `CreatureActions as usize`)
+    pub lineage_deaths : HashMap<usize, usize>,         // Number of creatures that have died in each lineage (by lineage ID)
+    pub peak_population : usize,                        // Most creatures alive at once
+    pub peak_population_step : usize,                   // Time step the peak population was first reached in
+    pub last_plague : Option<(usize, usize)>,           // Time step and number of victims of the last plague that killed anything
+    pub oldest_creatures : Vec<LeaderboardEntry>,       // Oldest living creatures at the end of the last step (oldest first)
+    pub most_offspring : Vec<LeaderboardEntry>,         // Living creatures with the most offspring at the end of the last step (most first)
+    pub immigrants : Vec<ImmigrantRecord>,              // Every creature that immigrated onto the board (oldest first)
+    pub cohorts : Vec<Cohort>,                          // Cohorts being followed (oldest first)
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl StatsRecorder {
+
+    /// Create a new empty recorder
+    pub fn new() -> StatsRecorder {
+        return StatsRecorder {
+            lifetime_records : VecDeque::new(),
+            dead_action_counts : [0; NUM_CREATURE_ACTIONS],
+            lineage_deaths : HashMap::new(),
+            peak_population : 0,
+            peak_population_step : 0,
+            last_plague : None,
+            oldest_creatures : Vec::new(),
+            most_offspring : Vec::new(),
+            immigrants : Vec::new(),
+            cohorts : Vec::new(),
+        };
+    }
+
+    /// Record the number of creatures alive in time step `time_step`
+    pub fn record_population(&mut self, num_creatures : usize, time_step : usize) {
+        if num_creatures > self.peak_population {
+            self.peak_population = num_creatures;
+            self.peak_population_step = time_step;
+        }
+    }
+
+    /// Record a plague that killed `num_victims` creatures in time step `time_step`
+    pub fn record_plague(&mut self, num_victims : usize, time_step : usize) {
+        if num_victims > 0 {
+            self.last_plague = Some((time_step, num_victims));
+        }
+    }
+
+    /// Record the top LEADERBOARD_LEN living creatures by age and by number of offspring. Ties go to the lowest ID
+    pub fn record_leaders(&mut self, creatures : &[CreatureV1]) {
+        let get_top = |get_value : fn(&CreatureV1) -> usize| {
+            let mut entries : Vec<LeaderboardEntry> = creatures.iter().map(|c| LeaderboardEntry {id : c.id, value : get_value(c)}).collect();
+            entries.sort_by(|a, b| b.value.cmp(&a.value).then(a.id.cmp(&b.id)));
+            entries.truncate(LEADERBOARD_LEN);
+            return entries;
+        };
+        self.oldest_creatures = get_top(|c| c.age);
+        self.most_offspring = get_top(|c| c.num_offspring);
+    }
+
+    /// Record a creature (founding lineage `lineage_id`) immigrating onto the board in time step `time_step`
+    pub fn record_immigrant(&mut self, lineage_id : usize, time_step : usize) {
+        self.immigrants.push(ImmigrantRecord {lineage_id : lineage_id, arrival_step : time_step});
+    }
+
+    /// Get how the lineage of every immigrant is doing, given the creatures alive now. Most living members first
+    /// (ties go to the earliest arrival)
+    pub fn get_immigrant_lineage_stats(&self, creatures : &[CreatureV1]) -> Vec<ImmigrantLineageStats> {
+        let mut num_alive : HashMap<usize, usize> = HashMap::new();
+        for creature in creatures.iter() {
+            *num_alive.entry(creature.lineage_id).or_insert(0) += 1;
+        }
+        let mut lineages : Vec<ImmigrantLineageStats> = self.immigrants.iter().map(|record| ImmigrantLineageStats {
+            lineage_id : record.lineage_id,
+            arrival_step : record.arrival_step,
+            num_alive : num_alive.get(&record.lineage_id).cloned().unwrap_or(0),
+            num_dead : self.lineage_deaths.get(&record.lineage_id).cloned().unwrap_or(0),
+        }).collect();
+        lineages.sort_by(|a, b| b.num_alive.cmp(&a.num_alive).then(a.arrival_step.cmp(&b.arrival_step)));
+        return lineages;
+    }
+
+    /// Start following `creatures` as a cohort called `name`, tagged in time step `time_step`
+    pub fn add_cohort(&mut self, name : &str, creatures : &[&CreatureV1], time_step : usize) {
+        let member_ids : Vec<usize> = creatures.iter().map(|c| c.id).collect();
+        self.cohorts.push(Cohort {
+            name : name.to_string(),
+            tagged_step : time_step,
+            family_ids : member_ids.iter().cloned().collect(),
+            member_ids : member_ids,
+            history : VecDeque::new(),
+        });
+    }
+
+    /// Record how every cohort is doing at the end of time step `time_step`. Creatures born to a member (or a
+    /// descendant) since the last step join the cohort's family
+    pub fn record_cohorts(&mut self, creatures : &[CreatureV1], time_step : usize) {
+        for cohort in self.cohorts.iter_mut() {
+            for creature in creatures.iter() {
+                if creature.parent_id.is_some_and(|parent_id| cohort.family_ids.contains(&parent_id)) {
+                    cohort.family_ids.insert(creature.id);
+                }
+            }
+
+            let family : Vec<&CreatureV1> = creatures.iter().filter(|c| cohort.family_ids.contains(&c.id)).collect();
+            let num_members_alive = family.iter().filter(|c| cohort.member_ids.contains(&c.id)).count();
+            if cohort.history.len() >= COHORT_HISTORY_LEN {
+                cohort.history.pop_front();
+            }
+            cohort.history.push_back(CohortSample {
+                time_step : time_step,
+                num_members_alive : num_members_alive,
+                num_descendants_alive : family.len() - num_members_alive,
+                avg_energy : summarize_group(&family).avg_energy,
+            });
+        }
+    }
+
+    /// Record the life of a creature that died in time step `death_step`
+    pub fn record_death(&mut self, creature : &CreatureV1, death_step : usize) {
+        for (total, count) in self.dead_action_counts.iter_mut().zip(creature.action_counts.iter()) {
+            *total += count;
+        }
+        *self.lineage_deaths.entry(creature.lineage_id).or_insert(0) += 1;
+
+        if self.lifetime_records.len() >= LIFETIME_RECORDS_MAX_LEN {
+            self.lifetime_records.pop_front();
+        }
+
+        self.lifetime_records.push_back(LifetimeRecord {
+            id : creature.id,
+            parent_id : creature.parent_id,
+            generation : creature.generation,
+            species_id : creature.species_id,
+            birth_step : death_step.saturating_sub(creature.age),
+            death_step : death_step,
+            lifespan : creature.age,
+            was_killed : creature.was_killed(),
+            kills : creature.num_kills,
+            offspring : creature.num_offspring,
+            distance_traveled : creature.distance_traveled,
+            food_eaten : creature.food_eaten,
+            rotations : creature.num_rotations,
+        });
+    }
+
+    /// Average the lifetime counters over every recorded creature. Returns None if nothing has died yet
+    pub fn summarize_lifetimes(&self) -> Option<LifetimeSummary> {
+        if self.lifetime_records.is_empty() {
+            return None;
+        }
+
+        let num_records = self.lifetime_records.len();
+        let avg = |get_value : fn(&LifetimeRecord) -> usize| {
+            self.lifetime_records.iter().map(get_value).sum::<usize>() as f32 / num_records as f32
+        };
+        return Some(LifetimeSummary {
+            num_records : num_records,
+            avg_lifespan : avg(|r| r.lifespan),
+            avg_kills : avg(|r| r.kills),
+            avg_offspring : avg(|r| r.offspring),
+            avg_distance_traveled : avg(|r| r.distance_traveled),
+            avg_food_eaten : avg(|r| r.food_eaten),
+            avg_rotations : avg(|r| r.rotations),
+        });
+    }
+
+    /// Get all lifetime records as CSV text (with a header line)
+    pub fn lifetimes_to_csv(&self) -> String {
+        let mut csv = String::from(LIFETIME_CSV_HEADER);
+        csv.push('\n');
+        for record in self.lifetime_records.iter() {
+            let parent_id = match record.parent_id {
+                Some(parent_id) => parent_id.to_string(),
+                None => String::new(),
+            };
+            csv.push_str(&format!("{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                record.id, parent_id, record.generation, record.species_id, record.birth_step, record.death_step,
+                record.lifespan, record.was_killed, record.kills, record.offspring, record.distance_traveled, record.food_eaten, record.rotations));
+        }
+        return csv;
+    }
+
+    #[cfg(feature = "file_io")]
+    /// Write all lifetime records to a CSV file
+    pub fn save_lifetimes_csv(&self, filename : &str) {
+        let mut csv_file = match File::create(filename) {
+            Ok(f) => f,
+            Err(e) => {
+                println!("Error: could not create file {}. Error {e}", filename);
+                return;
+            }
+        };
+
+        if let Err(e) = csv_file.write_all(self.lifetimes_to_csv().as_bytes()) {
+            println!("Error: could not write lifetime records to {}. Error {e}", filename);
+        }
+    }
+}
+
+/// Get totals and averages over a group of living creatures
+pub fn summarize_group(creatures : &[&CreatureV1]) -> GroupSummary {
+    if creatures.is_empty() {
+        return GroupSummary::default();
+    }
+    let num_creatures = creatures.len();
+    let avg = |get_value : fn(&CreatureV1) -> usize| {
+        creatures.iter().map(|c| get_value(c)).sum::<usize>() as f32 / num_creatures as f32
+    };
+    return GroupSummary {
+        num_creatures : num_creatures,
+        num_lineages : creatures.iter().map(|c| c.lineage_id).collect::<HashSet<usize>>().len(),
+        avg_age : avg(|c| c.age),
+        avg_energy : avg(|c| c.energy),
+        avg_generation : avg(|c| c.generation),
+        total_kills : creatures.iter().map(|c| c.num_kills).sum(),
+        total_offspring : creatures.iter().map(|c| c.num_offspring).sum(),
+    };
+}
+
+
+#[cfg(test)]
+mod stats_test {
+    use super::*;
+
+    #[test]
+    fn test_lifetimes_to_csv() {
+        let mut recorder = StatsRecorder::new();
+        let mut creature = CreatureV1::new(7, &CreatureParams::new());
+        creature.age = 30;
+        creature.food_eaten = 2;
+        recorder.record_death(&creature, 100);
+
+        let csv = recorder.lifetimes_to_csv();
+        let lines : Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], LIFETIME_CSV_HEADER);
+        assert_eq!(lines[1], "7,,0,0,70,100,30,false,0,0,0,2,0");
+
+        let summary = recorder.summarize_lifetimes().unwrap();
+        assert_eq!(summary.num_records, 1);
+        assert_eq!(summary.avg_lifespan, 30.0);
+        assert_eq!(summary.avg_food_eaten, 2.0);
+    }
+
+    #[test]
+    fn test_cohorts() {
+        let mut creatures : Vec<CreatureV1> = (0..4).map(|id| CreatureV1::new(id, &CreatureParams::new())).collect();
+        let mut recorder = StatsRecorder::new();
+        recorder.add_cohort("cluster", &[&creatures[0], &creatures[1]], 10);
+        let summary = summarize_group(&creatures.iter().collect::<Vec<&CreatureV1>>());
+        assert_eq!((summary.num_creatures, summary.num_lineages), (4, 4));
+
+        // Children (and grandchildren) of members join the family, other creatures don't
+        recorder.record_cohorts(&creatures, 11);
+        for (id, parent_id) in [(4, 0), (5, 2)] {
+            let mut child = CreatureV1::new(id, &CreatureParams::new());
+            child.parent_id = Some(parent_id);
+            creatures.push(child);
+        }
+        recorder.record_cohorts(&creatures, 12);
+        let mut grandchild = CreatureV1::new(6, &CreatureParams::new());
+        grandchild.parent_id = Some(4);
+        creatures.remove(0);
+        creatures.push(grandchild);
+        recorder.record_cohorts(&creatures, 13);
+
+        let cohort = &recorder.cohorts[0];
+        let samples : Vec<(usize, usize, usize)> = cohort.history.iter().map(|s| (s.time_step, s.num_members_alive, s.num_descendants_alive)).collect();
+        assert_eq!(samples, vec![(11, 2, 0), (12, 2, 1), (13, 1, 2)]);
+        assert_eq!(cohort.family_ids.len(), 4);
+    }
+
+    #[test]
+    fn test_record_leaders() {
+        let mut creatures : Vec<CreatureV1> = (0..8).map(|id| CreatureV1::new(id, &CreatureParams::new())).collect();
+        for creature in creatures.iter_mut() {
+            creature.age = creature.id * 10;
+            creature.num_offspring = creature.id % 3;
+        }
+        let mut recorder = StatsRecorder::new();
+        recorder.record_leaders(&creatures);
+
+        let oldest : Vec<usize> = recorder.oldest_creatures.iter().map(|e| e.id).collect();
+        assert_eq!(oldest, vec![7, 6, 5, 4, 3]);
+        let most_offspring : Vec<(usize, usize)> = recorder.most_offspring.iter().map(|e| (e.id, e.value)).collect();
+        assert_eq!(most_offspring, vec![(2, 2), (5, 2), (1, 1), (4, 1), (7, 1)]);
+    }
+}
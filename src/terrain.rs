@@ -0,0 +1,147 @@
+/** ===============================================================================
+ * File: terrain.rs
+ * Author: Scott Stack
+ * Description: Generates the elevation layer of the board from smooth random noise,
+ * and shades it so hills and valleys can be seen when it's drawn
+ * ===============================================================================*/
+use crate::creature::CreaturePosition;
+use crate::sim_rng::sim_rng;
+use rand::Rng;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const ELEVATION_NOISE_OCTAVES : usize = 3;          // Number of layers of noise added together. Each one has half the feature size (and half the height) of the last
+pub const HILLSHADE_LIGHT_DIR : (f32, f32) = (-std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2); // Direction the light shines from (up and to the left, like most maps)
+pub const HILLSHADE_SLOPE_SCALE : f32 = 8.0;            // Exaggerates slopes when shading so gentle hills are still visible
+pub const RIVER_MEANDER_PROB : f32 = 0.4;               // Chance at each row that a generated river shifts a space sideways
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+/// Generate a smooth random height map in [0, 1], indexed by `[x][y]`. `feature_size` is the rough width (in spaces)
+/// of the biggest hills. Built from a few octaves of value noise: random heights on a coarse grid, smoothly interpolated
+pub fn generate_elevation(x_size : usize, y_size : usize, feature_size : usize) -> Vec<Vec<f32>> {
+    let mut elevation = vec![vec![0.0; y_size]; x_size];
+    let mut amplitude : f32 = 1.0;
+    let mut total_amplitude : f32 = 0.0;
+    let mut cell_size = feature_size.max(1);
+
+    for _octave in 0..ELEVATION_NOISE_OCTAVES {
+        add_value_noise(&mut elevation, cell_size, amplitude);
+        total_amplitude += amplitude;
+        amplitude /= 2.0;
+        cell_size = (cell_size / 2).max(1);
+    }
+
+    for height in elevation.iter_mut().flatten() {
+        *height /= total_amplitude;
+    }
+    return elevation;
+}
+
+/// Add one octave of value noise with lattice points every `cell_size` spaces and heights up to `amplitude`
+fn add_value_noise(elevation : &mut [Vec<f32>], cell_size : usize, amplitude : f32) {
+    let mut rng = sim_rng();
+    let x_size = elevation.len();
+    let y_size = elevation.first().map_or(0, |column| column.len());
+    let lattice_x = x_size / cell_size + 2;
+    let lattice_y = y_size / cell_size + 2;
+    let lattice : Vec<Vec<f32>> = (0..lattice_x).map(|_| (0..lattice_y).map(|_| rng.gen::<f32>()).collect()).collect();
+
+    // Smoothstep keeps the slope continuous across lattice cells so there are no creases
+    let smooth = |t : f32| t * t * (3.0 - 2.0 * t);
+    for (x, column) in elevation.iter_mut().enumerate() {
+        for (y, height) in column.iter_mut().enumerate() {
+            let (cell_x, cell_y) = (x / cell_size, y / cell_size);
+            let tx = smooth((x % cell_size) as f32 / cell_size as f32);
+            let ty = smooth((y % cell_size) as f32 / cell_size as f32);
+            let top = lattice[cell_x][cell_y] + (lattice[cell_x + 1][cell_y] - lattice[cell_x][cell_y]) * tx;
+            let bottom = lattice[cell_x][cell_y + 1] + (lattice[cell_x + 1][cell_y + 1] - lattice[cell_x][cell_y + 1]) * tx;
+            *height += amplitude * (top + (bottom - top) * ty);
+        }
+    }
+}
+
+/// Generate a river that meanders from a random spot on the top edge of the board down to the bottom edge.
+/// Returned in order from the source to the mouth. Each space is next to the one before it, so the river
+/// flows from each space into the next one in the list
+pub fn generate_river(x_size : usize, y_size : usize) -> Vec<CreaturePosition> {
+    let mut rng = sim_rng();
+    let mut river : Vec<CreaturePosition> = Vec::with_capacity(y_size * 2);
+    if x_size == 0 {
+        return river;
+    }
+
+    let mut x = rng.gen_range(0..x_size);
+    for y in 0..y_size {
+        river.push(CreaturePosition {x : x, y : y});
+
+        // Sometimes wander a space sideways before flowing down to the next row
+        if rng.gen::<f32>() < RIVER_MEANDER_PROB {
+            let next_x = if rng.gen::<bool>() { x + 1 } else { x.wrapping_sub(1) };
+            if next_x < x_size {
+                x = next_x;
+                river.push(CreaturePosition {x : x, y : y});
+            }
+        }
+    }
+    return river;
+}
+
+/// Get how brightly lit the space at (x, y) is [0, 1] given the height map (indexed by `[x][y]`). Slopes facing
+/// the light are bright, slopes facing away are dark and flat ground is in between
+pub fn get_hillshade(elevation : &[Vec<f32>], x : usize, y : usize) -> f32 {
+    let x_size = elevation.len();
+    let y_size = elevation[0].len();
+    let height = |x : usize, y : usize| elevation[x.min(x_size - 1)][y.min(y_size - 1)];
+
+    // Slope from the neighbors on either side (one-sided at the edges)
+    let dx = (height(x + 1, y) - height(x.saturating_sub(1), y)) * HILLSHADE_SLOPE_SCALE;
+    let dy = (height(x, y + 1) - height(x, y.saturating_sub(1))) * HILLSHADE_SLOPE_SCALE;
+
+    // Surface normal is (-dx, -dy, 1). Light comes in at 45 degrees from HILLSHADE_LIGHT_DIR
+    let normal_len = (dx * dx + dy * dy + 1.0).sqrt();
+    let (light_x, light_y) = HILLSHADE_LIGHT_DIR;
+    let lit = (-dx * light_x - dy * light_y + 1.0) / (normal_len * std::f32::consts::SQRT_2);
+    return lit.clamp(0.0, 1.0);
+}
+
+
+#[cfg(test)]
+mod terrain_test {
+    use super::*;
+
+    #[test]
+    fn test_generate_elevation() {
+        let elevation = generate_elevation(30, 20, 8);
+        assert_eq!((elevation.len(), elevation[0].len()), (30, 20));
+        assert!(elevation.iter().flatten().all(|h| (0.0..=1.0).contains(h)));
+
+        // Neighboring spaces are close in height
+        for x in 1..30 {
+            assert!((elevation[x][5] - elevation[x - 1][5]).abs() < 0.5);
+        }
+
+        // Slopes facing the light (rising to the right, so facing left) are brighter than flat ground, slopes facing away are darker
+        let facing_light : Vec<Vec<f32>> = (0..5).map(|x| vec![x as f32 / 40.0; 5]).collect();
+        let facing_away : Vec<Vec<f32>> = (0..5).map(|x| vec![1.0 - x as f32 / 40.0; 5]).collect();
+        let flat = vec![vec![0.5; 5]; 5];
+        assert!(get_hillshade(&facing_light, 2, 2) > get_hillshade(&flat, 2, 2));
+        assert!(get_hillshade(&facing_away, 2, 2) < get_hillshade(&flat, 2, 2));
+    }
+
+    #[test]
+    fn test_generate_river() {
+        let river = generate_river(20, 15);
+        assert_eq!(river.first().unwrap().y, 0);
+        assert_eq!(river.last().unwrap().y, 14);
+        for pair in river.windows(2) {
+            assert_eq!(pair[0].x.abs_diff(pair[1].x) + pair[0].y.abs_diff(pair[1].y), 1);
+            assert!(pair[1].x < 20);
+        }
+    }
+}
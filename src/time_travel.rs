@@ -0,0 +1,159 @@
+/** ===============================================================================
+ * File: time_travel.rs
+ * Author: Scott Stack
+ * Description: Developer tool that keeps the full state of an environment at every step of a
+ * short run, so questions like "when did creature X's energy drop below 5?" can be answered
+ * by searching back through the run instead of adding prints and re-running it
+ * ===============================================================================*/
+use crate::environment::*;
+use crate::creature::*;
+use std::collections::VecDeque;
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const DEFAULT_TIME_TRAVEL_MAX_STEPS : usize = 500;   // Default number of steps kept. Every step is a full copy of the environment, so keep runs short
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Full copies of an environment at each recorded step, oldest first. Once `max_steps` states are kept, the oldest is dropped
+pub struct TimeTravelDebugger {
+    pub max_steps : usize,                  // Most states kept at once
+    states : VecDeque<EnvironmentV1>,       // Recorded states, oldest first. Each one is at a different time step
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl TimeTravelDebugger {
+
+    /// Create a debugger that keeps the last `max_steps` recorded states
+    pub fn new(max_steps : usize) -> TimeTravelDebugger {
+        return TimeTravelDebugger {
+            max_steps : max_steps.max(1),
+            states : VecDeque::new(),
+        };
+    }
+
+    /// Keep a copy of the current state of `env`
+    pub fn record(&mut self, env : &EnvironmentV1) {
+        if self.states.len() >= self.max_steps {
+            self.states.pop_front();
+        }
+        self.states.push_back(env.clone());
+    }
+
+    /// Record the current state of `env`, then step it `num_steps` times recording each step (fewer if every creature dies)
+    pub fn run(&mut self, env : &mut EnvironmentV1, num_steps : usize) {
+        self.record(env);
+        for _ in 0..num_steps {
+            if env.creatures.is_empty() {
+                break;
+            }
+            env.advance_step();
+            self.record(env);
+        }
+    }
+
+    /// Get the time steps that have been recorded (oldest first)
+    pub fn get_recorded_steps(&self) -> Vec<usize> {
+        return self.states.iter().map(|state| state.time_step).collect();
+    }
+
+    /// Get the recorded state at `time_step`, if it's still kept
+    pub fn get_state(&self, time_step : usize) -> Option<&EnvironmentV1> {
+        return self.states.iter().find(|state| state.time_step == time_step);
+    }
+
+    /// Get a copy of the state at `time_step` that can be run again from there (e.g. with extra debug prints)
+    pub fn rewind(&self, time_step : usize) -> Option<EnvironmentV1> {
+        return self.get_state(time_step).cloned();
+    }
+
+    /// Get the first recorded time step where `condition` holds for the whole environment
+    pub fn first_step_where(&self, condition : impl Fn(&EnvironmentV1) -> bool) -> Option<usize> {
+        return self.states.iter().find(|state| condition(state)).map(|state| state.time_step);
+    }
+
+    /// Get the first recorded time step where creature `creature_id` is alive and `condition` holds for it,
+    /// e.g. `first_step_where_creature(7, |c| c.energy < 5)`
+    pub fn first_step_where_creature(&self, creature_id : usize, condition : impl Fn(&CreatureV1) -> bool) -> Option<usize> {
+        return self.first_step_where(|state| state.get_creature(creature_id).is_some_and(&condition));
+    }
+
+    /// Get the first recorded time step where the space at `position` held `space` (e.g. when a wall first showed up there)
+    pub fn first_step_with_space(&self, position : CreaturePosition, space : SpaceStates) -> Option<usize> {
+        return self.first_step_where(|state| state.positions.get(position.x).and_then(|column| column.get(position.y)) == Some(&space));
+    }
+
+    /// Get the first recorded time step where some creature saw the space at `position` (it was the closest thing in view)
+    pub fn first_step_seen(&self, position : CreaturePosition) -> Option<usize> {
+        return self.first_step_where(|state| state.creatures.iter().any(|c| get_seen_position(c) == Some(position)));
+    }
+
+    /// Get every recorded state of creature `creature_id` as (time step, creature), oldest first
+    pub fn get_creature_history(&self, creature_id : usize) -> Vec<(usize, &CreatureV1)> {
+        return self.states.iter().filter_map(|state| state.get_creature(creature_id).map(|c| (state.time_step, c))).collect();
+    }
+}
+
+/// Get the position of the closest thing in a creature's view (if anything is in view)
+fn get_seen_position(creature : &CreatureV1) -> Option<CreaturePosition> {
+    if !creature.vision_state.obj_in_view {
+        return None;
+    }
+    let (x, y, dist) = (creature.position.x, creature.position.y, creature.vision_state.dist);
+    return match creature.orientation {
+        CreatureOrientation::Up => y.checked_sub(dist).map(|y| CreaturePosition {x : x, y : y}),
+        CreatureOrientation::Down => Some(CreaturePosition {x : x, y : y + dist}),
+        CreatureOrientation::Left => x.checked_sub(dist).map(|x| CreaturePosition {x : x, y : y}),
+        CreatureOrientation::Right => Some(CreaturePosition {x : x + dist, y : y}),
+    };
+}
+
+
+#[cfg(test)]
+mod time_travel_test {
+    use super::*;
+
+    #[test]
+    fn test_time_travel_queries() {
+        let mut params = EnvironmentParams::with_size(16, 16);
+        params.num_start_creatures = 20;
+        let mut env = EnvironmentV1::new_seeded(&params, 5);
+        let mut debugger = TimeTravelDebugger::new(DEFAULT_TIME_TRAVEL_MAX_STEPS);
+        debugger.run(&mut env, 20);
+        assert_eq!(debugger.get_recorded_steps(), (0..=20).collect::<Vec<usize>>());
+
+        // Creature queries agree with the creature's own history
+        let creature_id = env.creatures[0].id;
+        let history = debugger.get_creature_history(creature_id);
+        assert!(!history.is_empty());
+        let first_low = debugger.first_step_where_creature(creature_id, |c| c.energy < DEFAULT_ENERGY_LEVEL);
+        assert_eq!(first_low, history.iter().find(|(_, c)| c.energy < DEFAULT_ENERGY_LEVEL).map(|(step, _)| *step));
+
+        // Rewinding and re-running gives the same run
+        let mut rewound = debugger.rewind(10).unwrap();
+        let _ = rewound.run_n_steps(10);
+        assert_eq!(rewound.state_hash(), env.state_hash());
+
+        // Only the newest states are kept
+        let mut short = TimeTravelDebugger::new(5);
+        short.run(&mut env, 10);
+        assert_eq!(short.get_recorded_steps(), (26..=30).collect::<Vec<usize>>());
+        let wall = env.positions.iter().flatten().position(|s| *s == SpaceStates::WallSpace).unwrap();
+        let wall_pos = CreaturePosition {x : wall / 16, y : wall % 16};
+        assert_eq!(short.first_step_with_space(wall_pos, SpaceStates::WallSpace), Some(26));
+        assert!(short.get_state(20).is_none());
+
+        // Whatever a creature sees was seen no later than that step
+        let seer = env.creatures.iter().find(|c| c.vision_state.obj_in_view).unwrap();
+        assert!(short.first_step_seen(get_seen_position(seer).unwrap()).is_some_and(|step| step <= 30));
+    }
+}
@@ -0,0 +1,136 @@
+/** ===============================================================================
+ * File: wall_map.rs
+ * Author: Scott Stack
+ * Description: Compact map format that only stores the board size and where the walls
+ * are (run-length encoded), for sharing map layouts without a whole environment save
+ * ===============================================================================*/
+use crate::environment::SpaceStates;
+use serde::{Deserialize, Serialize};
+
+
+//===============================================================================
+// CONSTANTS
+//===============================================================================
+pub const WALL_MAP_HEADER : &str = "WALLMAP";           // First word of a wall map file, followed by the board size
+pub const WALL_MAP_EXTENSION : &str = ".walls";         // File extension wall maps are saved with
+
+
+//===============================================================================
+// DATA
+//===============================================================================
+
+/// Board size plus which spaces are walls. The walls are stored as run lengths of alternating blank and wall
+/// spaces (always starting with blank, so the first run can be 0), going through the spaces in `x * y_size + y` order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WallMap {
+    pub x_size : usize,     // Width of the board in spaces
+    pub y_size : usize,     // Height of the board in spaces
+    pub runs : Vec<usize>,  // Run lengths of blank, wall, blank, wall... spaces. Adds up to x_size * y_size
+}
+
+
+//===============================================================================
+// FUNCTIONS
+//===============================================================================
+
+impl WallMap {
+
+    /// Encode a board of the given size. `is_wall(x, y)` says whether each space is a wall
+    pub fn new(x_size : usize, y_size : usize, is_wall : impl Fn(usize, usize) -> bool) -> WallMap {
+        let mut runs : Vec<usize> = vec![0];
+        let mut in_wall = false;
+        for x in 0..x_size {
+            for y in 0..y_size {
+                if is_wall(x, y) != in_wall {
+                    in_wall = !in_wall;
+                    runs.push(0);
+                }
+                *runs.last_mut().unwrap() += 1;
+            }
+        }
+        return WallMap {x_size : x_size, y_size : y_size, runs : runs};
+    }
+
+    /// Encode the walls out of a full board (indexed by `[x][y]`)
+    pub fn from_positions(positions : &[Vec<SpaceStates>]) -> WallMap {
+        let y_size = positions.first().map_or(0, |column| column.len());
+        return WallMap::new(positions.len(), y_size, |x, y| positions[x][y] == SpaceStates::WallSpace);
+    }
+
+    /// Decode the walls into a grid indexed by `[x][y]` where true is a wall
+    pub fn to_wall_grid(&self) -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![false; self.y_size]; self.x_size];
+        let mut space_idx : usize = 0;
+        for (run_idx, run) in self.runs.iter().enumerate() {
+            let is_wall = run_idx % 2 == 1;
+            for _ in 0..*run {
+                if is_wall {
+                    grid[space_idx / self.y_size][space_idx % self.y_size] = true;
+                }
+                space_idx += 1;
+            }
+        }
+        return grid;
+    }
+
+    /// Get the number of wall spaces on the map
+    pub fn num_walls(&self) -> usize {
+        return self.runs.iter().skip(1).step_by(2).sum();
+    }
+
+    /// Write the map as text: a header line with the size, then the run lengths on one line
+    pub fn to_text(&self) -> String {
+        let runs : Vec<String> = self.runs.iter().map(|run| run.to_string()).collect();
+        return format!("{} {} {}\n{}\n", WALL_MAP_HEADER, self.x_size, self.y_size, runs.join(" "));
+    }
+
+    /// Read a map written by `to_text`. Returns the reason if the text isn't a valid wall map
+    pub fn from_text(text : &str) -> Result<WallMap, String> {
+        let mut words = text.split_whitespace();
+        if words.next() != Some(WALL_MAP_HEADER) {
+            return Err(format!("Wall map doesn't start with {}", WALL_MAP_HEADER));
+        }
+
+        let mut numbers : Vec<usize> = Vec::new();
+        for word in words {
+            match word.parse::<usize>() {
+                Ok(val) => numbers.push(val),
+                Err(e) => return Err(format!("Bad number '{}' in wall map ({e})", word)),
+            }
+        }
+        if numbers.len() < 3 {
+            return Err(String::from("Wall map is missing its size or walls"));
+        }
+
+        let map = WallMap {x_size : numbers[0], y_size : numbers[1], runs : numbers[2..].to_vec()};
+        let num_spaces : usize = map.runs.iter().sum();
+        if num_spaces != map.x_size * map.y_size {
+            return Err(format!("Wall map covers {} spaces but the board has {}", num_spaces, map.x_size * map.y_size));
+        }
+        return Ok(map);
+    }
+}
+
+
+#[cfg(test)]
+mod wall_map_test {
+    use super::*;
+
+    #[test]
+    fn test_wall_map_round_trip() {
+        let is_wall = |x : usize, y : usize| x == 2 || (x == 0 && y == 0);
+        let map = WallMap::new(4, 3, is_wall);
+        assert_eq!(map.runs, vec![0, 1, 5, 3, 3]);
+        assert_eq!(map.num_walls(), 4);
+
+        for (x, column) in map.to_wall_grid().iter().enumerate() {
+            for (y, wall) in column.iter().enumerate() {
+                assert_eq!(*wall, is_wall(x, y));
+            }
+        }
+
+        assert_eq!(WallMap::from_text(&map.to_text()).unwrap(), map);
+        assert!(WallMap::from_text("WALLMAP 4 3\n1 2").is_err());
+        assert!(WallMap::from_text("{\"params\": 1}").is_err());
+    }
+}